@@ -0,0 +1,525 @@
+//! Stampa dell'AST, per il debug del parser e i test di riferimento.
+//!
+//! Il JSON è la serializzazione serde dei nodi. Le S-espressioni sono più
+//! compatte: ogni struttura diventa `(Nome ...)`, il `kind` dei nodi compare
+//! senza etichetta, gli id come `#n`, gli identificatori come `nome@posizione`
+//! e le posizioni come `@riga:colonna-riga:colonna`. I campi assenti, vuoti o
+//! falsi sono omessi.
+
+use std::fmt;
+
+use serde::ser::{self, Serialize};
+use serde_json::Value;
+
+use super::Program;
+
+/// Larghezza oltre la quale una S-espressione va a capo
+const WIDTH: usize = 100;
+
+/// Formato della stampa dell'AST
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DumpFormat {
+    /// S-espressioni indentate
+    #[default]
+    SExpr,
+    /// JSON indentato
+    Json,
+}
+
+/// Stampa `program` nel formato richiesto. Senza `spans` le posizioni sono
+/// omesse, così l'uscita non cambia quando cambiano solo spazi e righe vuote.
+pub fn dump(program: &Program, format: DumpFormat, spans: bool) -> String {
+    match format {
+        DumpFormat::SExpr => {
+            let serializer = SExprSerializer {
+                spans,
+                raw_strings: false,
+            };
+            let sexpr = program.serialize(serializer).expect("l'AST è sempre serializzabile");
+            let mut out = String::new();
+            if let Some(sexpr) = sexpr {
+                sexpr.write(&mut out, 0);
+            }
+            out.push('\n');
+            out
+        },
+        DumpFormat::Json => {
+            let mut value = serde_json::to_value(program).expect("l'AST è sempre serializzabile");
+            if !spans {
+                remove_spans(&mut value);
+            }
+            let mut out = serde_json::to_string_pretty(&value).expect("l'AST è sempre serializzabile");
+            out.push('\n');
+            out
+        },
+    }
+}
+
+fn remove_spans(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            fields.remove("span");
+            fields.values_mut().for_each(remove_spans);
+        },
+        Value::Array(items) => items.iter_mut().for_each(remove_spans),
+        _ => {},
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SExpr {
+    Atom(String),
+    List(Vec<SExpr>),
+    /// Sequenza senza nome, inserita direttamente nella lista che la contiene
+    Seq(Vec<SExpr>),
+}
+
+fn atom(text: impl fmt::Display) -> SExpr {
+    SExpr::Atom(text.to_string())
+}
+
+/// `(nome valore)`, con gli elementi di una sequenza inseriti direttamente
+fn tagged(name: &str, value: Option<SExpr>) -> SExpr {
+    let mut items = vec![atom(name)];
+    match value {
+        Some(SExpr::Seq(values)) => items.extend(values),
+        Some(value) => items.push(value),
+        None => {},
+    }
+    SExpr::List(items)
+}
+
+impl SExpr {
+    fn write_inline(&self, out: &mut String) {
+        match self {
+            SExpr::Atom(text) => out.push_str(text),
+            SExpr::List(items) | SExpr::Seq(items) => {
+                out.push('(');
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        out.push(' ');
+                    }
+                    item.write_inline(out);
+                }
+                out.push(')');
+            },
+        }
+    }
+    
+    /// Scrive l'espressione su una riga se entra in [`WIDTH`], altrimenti un
+    /// elemento per riga; gli atomi iniziali restano sulla riga dell'apertura
+    fn write(&self, out: &mut String, indent: usize) {
+        let mut line = String::new();
+        self.write_inline(&mut line);
+        let items = match self {
+            SExpr::List(items) | SExpr::Seq(items) if indent + line.len() > WIDTH => items,
+            _ => {
+                out.push_str(&line);
+                return;
+            },
+        };
+        
+        let head = items.iter().take_while(|item| matches!(item, SExpr::Atom(_))).count().max(1);
+        out.push('(');
+        for (index, item) in items.iter().enumerate() {
+            if index < head {
+                if index > 0 {
+                    out.push(' ');
+                }
+                item.write(out, indent + 1);
+            } else {
+                out.push('\n');
+                out.push_str(&" ".repeat(indent + 2));
+                item.write(out, indent + 2);
+            }
+        }
+        out.push(')');
+    }
+}
+
+/// Serializza i nodi in S-espressioni; `None` indica un valore omesso
+#[derive(Clone, Copy)]
+struct SExprSerializer {
+    spans: bool,
+    /// Stringhe senza virgolette, per i nomi degli identificatori
+    raw_strings: bool,
+}
+
+type Output = Result<Option<SExpr>, fmt::Error>;
+
+impl ser::Serializer for SExprSerializer {
+    type Ok = Option<SExpr>;
+    type Error = fmt::Error;
+    type SerializeSeq = SeqBuilder;
+    type SerializeTuple = SeqBuilder;
+    type SerializeTupleStruct = SeqBuilder;
+    type SerializeTupleVariant = SeqBuilder;
+    type SerializeMap = SeqBuilder;
+    type SerializeStruct = StructBuilder;
+    type SerializeStructVariant = StructBuilder;
+    
+    fn serialize_bool(self, v: bool) -> Output {
+        Ok(v.then(|| atom(v)))
+    }
+    
+    fn serialize_i8(self, v: i8) -> Output {
+        Ok(Some(atom(v)))
+    }
+    
+    fn serialize_i16(self, v: i16) -> Output {
+        Ok(Some(atom(v)))
+    }
+    
+    fn serialize_i32(self, v: i32) -> Output {
+        Ok(Some(atom(v)))
+    }
+    
+    fn serialize_i64(self, v: i64) -> Output {
+        Ok(Some(atom(v)))
+    }
+    
+    fn serialize_u8(self, v: u8) -> Output {
+        Ok(Some(atom(v)))
+    }
+    
+    fn serialize_u16(self, v: u16) -> Output {
+        Ok(Some(atom(v)))
+    }
+    
+    fn serialize_u32(self, v: u32) -> Output {
+        Ok(Some(atom(v)))
+    }
+    
+    fn serialize_u64(self, v: u64) -> Output {
+        Ok(Some(atom(v)))
+    }
+    
+    fn serialize_f32(self, v: f32) -> Output {
+        Ok(Some(atom(v)))
+    }
+    
+    fn serialize_f64(self, v: f64) -> Output {
+        Ok(Some(atom(v)))
+    }
+    
+    fn serialize_char(self, v: char) -> Output {
+        Ok(Some(atom(format!("{:?}", v))))
+    }
+    
+    fn serialize_str(self, v: &str) -> Output {
+        match self.raw_strings {
+            true => Ok(Some(atom(v))),
+            false => Ok(Some(atom(format!("{:?}", v)))),
+        }
+    }
+    
+    fn serialize_bytes(self, v: &[u8]) -> Output {
+        Ok(Some(SExpr::Seq(v.iter().map(atom).collect())))
+    }
+    
+    fn serialize_none(self) -> Output {
+        Ok(None)
+    }
+    
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Output {
+        value.serialize(self)
+    }
+    
+    fn serialize_unit(self) -> Output {
+        Ok(None)
+    }
+    
+    fn serialize_unit_struct(self, name: &'static str) -> Output {
+        Ok(Some(atom(name)))
+    }
+    
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Output {
+        Ok(Some(atom(variant)))
+    }
+    
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Output {
+        value.serialize(self)
+    }
+    
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Output {
+        Ok(Some(tagged(variant, value.serialize(self)?)))
+    }
+    
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqBuilder, fmt::Error> {
+        Ok(SeqBuilder::new(self, None, len.unwrap_or(0)))
+    }
+    
+    fn serialize_tuple(self, len: usize) -> Result<SeqBuilder, fmt::Error> {
+        Ok(SeqBuilder::new(self, None, len))
+    }
+    
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<SeqBuilder, fmt::Error> {
+        Ok(SeqBuilder::new(self, Some(name), len))
+    }
+    
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqBuilder, fmt::Error> {
+        Ok(SeqBuilder::new(self, Some(variant), len))
+    }
+    
+    fn serialize_map(self, len: Option<usize>) -> Result<SeqBuilder, fmt::Error> {
+        Ok(SeqBuilder::new(self, None, len.unwrap_or(0)))
+    }
+    
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<StructBuilder, fmt::Error> {
+        Ok(StructBuilder {
+            serializer: self,
+            name,
+            fields: Vec::with_capacity(len),
+        })
+    }
+    
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StructBuilder, fmt::Error> {
+        self.serialize_struct(variant, len)
+    }
+}
+
+/// Sequenze, tuple e mappe; queste ultime come liste di coppie `(chiave valore)`
+struct SeqBuilder {
+    serializer: SExprSerializer,
+    name: Option<&'static str>,
+    items: Vec<SExpr>,
+    key: Option<SExpr>,
+}
+
+impl SeqBuilder {
+    fn new(serializer: SExprSerializer, name: Option<&'static str>, len: usize) -> Self {
+        Self {
+            serializer,
+            name,
+            items: Vec::with_capacity(len),
+            key: None,
+        }
+    }
+    
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), fmt::Error> {
+        // Una sequenza annidata resta una lista a sé
+        let item = match value.serialize(self.serializer)? {
+            Some(SExpr::Seq(items)) | Some(SExpr::List(items)) => SExpr::List(items),
+            Some(item) => item,
+            None => SExpr::List(Vec::new()),
+        };
+        self.items.push(item);
+        Ok(())
+    }
+    
+    fn finish(self) -> Output {
+        match self.name {
+            Some(name) => Ok(Some(tagged(name, Some(SExpr::Seq(self.items))))),
+            None if self.items.is_empty() => Ok(None),
+            None => Ok(Some(SExpr::Seq(self.items))),
+        }
+    }
+}
+
+impl ser::SerializeSeq for SeqBuilder {
+    type Ok = Option<SExpr>;
+    type Error = fmt::Error;
+    
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), fmt::Error> {
+        self.push(value)
+    }
+    
+    fn end(self) -> Output {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqBuilder {
+    type Ok = Option<SExpr>;
+    type Error = fmt::Error;
+    
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), fmt::Error> {
+        self.push(value)
+    }
+    
+    fn end(self) -> Output {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqBuilder {
+    type Ok = Option<SExpr>;
+    type Error = fmt::Error;
+    
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), fmt::Error> {
+        self.push(value)
+    }
+    
+    fn end(self) -> Output {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqBuilder {
+    type Ok = Option<SExpr>;
+    type Error = fmt::Error;
+    
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), fmt::Error> {
+        self.push(value)
+    }
+    
+    fn end(self) -> Output {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for SeqBuilder {
+    type Ok = Option<SExpr>;
+    type Error = fmt::Error;
+    
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), fmt::Error> {
+        self.key = key.serialize(self.serializer)?;
+        Ok(())
+    }
+    
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), fmt::Error> {
+        let key = self.key.take().unwrap_or_else(|| SExpr::List(Vec::new()));
+        let mut entry = vec![key];
+        entry.extend(value.serialize(self.serializer)?);
+        self.items.push(SExpr::List(entry));
+        Ok(())
+    }
+    
+    fn end(self) -> Output {
+        self.finish()
+    }
+}
+
+/// Strutture e varianti con campi, compresi `Span`, `Position` e `Ident`
+/// che hanno una forma abbreviata
+struct StructBuilder {
+    serializer: SExprSerializer,
+    name: &'static str,
+    fields: Vec<(&'static str, SExpr)>,
+}
+
+impl StructBuilder {
+    fn field(&self, key: &str) -> String {
+        match self.fields.iter().find(|(name, _)| *name == key) {
+            Some((_, SExpr::Atom(text))) => text.clone(),
+            _ => String::new(),
+        }
+    }
+    
+    fn finish(self) -> Output {
+        let spans = self.serializer.spans;
+        match self.name {
+            "Position" => Ok(Some(atom(format!("{}:{}", self.field("line"), self.field("column"))))),
+            "Span" if spans => Ok(Some(atom(format!("@{}-{}", self.field("start"), self.field("end"))))),
+            "Span" => Ok(None),
+            "Ident" => Ok(Some(atom(format!("{}{}", self.field("name"), self.field("span"))))),
+            name => {
+                let mut items = vec![atom(name)];
+                for (key, value) in self.fields {
+                    items.push(match (key, value) {
+                        ("id", SExpr::Atom(id)) => atom(format!("#{}", id)),
+                        ("kind" | "name" | "span", value) => value,
+                        (key, SExpr::Seq(values)) => tagged(key, Some(SExpr::Seq(values))),
+                        (key, value) => SExpr::List(vec![atom(key), value]),
+                    });
+                }
+                Ok(Some(SExpr::List(items)))
+            },
+        }
+    }
+}
+
+impl ser::SerializeStruct for StructBuilder {
+    type Ok = Option<SExpr>;
+    type Error = fmt::Error;
+    
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), fmt::Error> {
+        let serializer = SExprSerializer {
+            raw_strings: self.name == "Ident" && key == "name",
+            ..self.serializer
+        };
+        if let Some(value) = value.serialize(serializer)? {
+            self.fields.push((key, value));
+        }
+        Ok(())
+    }
+    
+    fn end(self) -> Output {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for StructBuilder {
+    type Ok = Option<SExpr>;
+    type Error = fmt::Error;
+    
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), fmt::Error> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+    
+    fn end(self) -> Output {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    
+    #[test]
+    fn test_sexpr_dump() {
+        let program = parse("let total = add(1, \"a\")\n").unwrap();
+        let expected = "\
+(Program
+  (body
+    (Stmt #5
+      (Var
+        (VarDecl Let
+          (pattern (Pattern #0 (Binding total)))
+          (init
+            (Expr #4
+              (Call
+                (callee (Expr #1 (Ident add)))
+                (args (Expr #2 (Literal (Number 1))) (Expr #3 (Literal (String \"a\"))))))))))))
+";
+        assert_eq!(dump(&program, DumpFormat::SExpr, false), expected);
+        
+        let dumped = dump(&program, DumpFormat::SExpr, true);
+        assert!(dumped.contains("(pattern (Pattern #0 (Binding total@1:5-1:10) @1:5-1:10))"));
+        assert!(dumped.ends_with("      @1:1-1:24))\n  @1:1-2:1)\n"));
+    }
+    
+    #[test]
+    fn test_dump_without_spans_ignores_layout() {
+        let compact = parse("fn f(x)\n  return x + 1\n").unwrap();
+        let spaced = parse("\n\nfn   f( x )\n\n    return x+1\n").unwrap();
+        for format in [DumpFormat::SExpr, DumpFormat::Json] {
+            assert_eq!(dump(&compact, format, false), dump(&spaced, format, false));
+            assert_ne!(dump(&compact, format, true), dump(&spaced, format, true));
+        }
+        
+        let json: Value = serde_json::from_str(&dump(&compact, DumpFormat::Json, true)).unwrap();
+        assert_eq!(json["body"][0]["span"]["start"]["line"], 1);
+        let restored: Program = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, compact);
+    }
+}
@@ -0,0 +1,540 @@
+//! Trasformazione dell'AST per valore.
+//!
+//! Un [`Fold`] riceve ogni nodo per valore e restituisce il nodo che lo
+//! sostituisce. L'implementazione predefinita di ogni `fold_*` ricostruisce il
+//! nodo trasformandone i figli con la funzione `fold_*` omonima del modulo.
+//! [`Fold::fold_stmts`] riceve intere sequenze di istruzioni, quindi può anche
+//! aggiungerne o toglierne.
+
+use super::*;
+
+pub trait Fold: Sized {
+    fn fold_program(&mut self, program: Program) -> Program {
+        fold_program(self, program)
+    }
+    
+    /// Istruzioni del programma o di un blocco
+    fn fold_stmts(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+        fold_stmts(self, stmts)
+    }
+    
+    fn fold_block(&mut self, block: Block) -> Block {
+        fold_block(self, block)
+    }
+    
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+        fold_stmt(self, stmt)
+    }
+    
+    fn fold_function(&mut self, function: FunctionDecl) -> FunctionDecl {
+        fold_function(self, function)
+    }
+    
+    fn fold_param(&mut self, param: Param) -> Param {
+        fold_param(self, param)
+    }
+    
+    fn fold_type_param(&mut self, param: TypeParam) -> TypeParam {
+        fold_type_param(self, param)
+    }
+    
+    fn fold_decorator(&mut self, decorator: Decorator) -> Decorator {
+        fold_decorator(self, decorator)
+    }
+    
+    fn fold_class_member(&mut self, member: ClassMember) -> ClassMember {
+        fold_class_member(self, member)
+    }
+    
+    fn fold_interface_member(&mut self, member: InterfaceMember) -> InterfaceMember {
+        fold_interface_member(self, member)
+    }
+    
+    fn fold_enum_variant(&mut self, variant: EnumVariant) -> EnumVariant {
+        fold_enum_variant(self, variant)
+    }
+    
+    fn fold_catch_clause(&mut self, catch: CatchClause) -> CatchClause {
+        fold_catch_clause(self, catch)
+    }
+    
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        fold_expr(self, expr)
+    }
+    
+    fn fold_property(&mut self, property: Property) -> Property {
+        fold_property(self, property)
+    }
+    
+    fn fold_lambda(&mut self, lambda: Lambda) -> Lambda {
+        fold_lambda(self, lambda)
+    }
+    
+    fn fold_match_arm(&mut self, arm: MatchArm) -> MatchArm {
+        fold_match_arm(self, arm)
+    }
+    
+    fn fold_pattern(&mut self, pattern: Pattern) -> Pattern {
+        fold_pattern(self, pattern)
+    }
+    
+    fn fold_type(&mut self, ty: TypeExpr) -> TypeExpr {
+        fold_type(self, ty)
+    }
+}
+
+fn fold_all<T>(items: Vec<T>, fold: impl FnMut(T) -> T) -> Vec<T> {
+    items.into_iter().map(fold).collect()
+}
+
+/// Trasforma il contenuto di `item` riusandone l'allocazione
+fn fold_boxed<T>(mut item: Box<T>, fold: impl FnOnce(T) -> T) -> Box<T> {
+    *item = fold(*item);
+    item
+}
+
+pub fn fold_program<F: Fold>(folder: &mut F, program: Program) -> Program {
+    Program {
+        body: folder.fold_stmts(program.body),
+        span: program.span,
+    }
+}
+
+pub fn fold_stmts<F: Fold>(folder: &mut F, stmts: Vec<Stmt>) -> Vec<Stmt> {
+    fold_all(stmts, |stmt| folder.fold_stmt(stmt))
+}
+
+pub fn fold_block<F: Fold>(folder: &mut F, block: Block) -> Block {
+    Block {
+        stmts: folder.fold_stmts(block.stmts),
+        span: block.span,
+    }
+}
+
+pub fn fold_stmt<F: Fold>(folder: &mut F, stmt: Stmt) -> Stmt {
+    let kind = match stmt.kind {
+        StmtKind::Expr(expr) => StmtKind::Expr(folder.fold_expr(expr)),
+        StmtKind::Throw(expr) => StmtKind::Throw(folder.fold_expr(expr)),
+        StmtKind::Var(decl) => StmtKind::Var(VarDecl {
+            pattern: folder.fold_pattern(decl.pattern),
+            ty: decl.ty.map(|ty| folder.fold_type(ty)),
+            init: decl.init.map(|init| folder.fold_expr(init)),
+            ..decl
+        }),
+        StmtKind::Function(function) => StmtKind::Function(folder.fold_function(function)),
+        StmtKind::Class(class) => StmtKind::Class(ClassDecl {
+            decorators: fold_all(class.decorators, |d| folder.fold_decorator(d)),
+            type_params: fold_all(class.type_params, |p| folder.fold_type_param(p)),
+            extends: class.extends.map(|ty| folder.fold_type(ty)),
+            implements: fold_all(class.implements, |ty| folder.fold_type(ty)),
+            members: fold_all(class.members, |m| folder.fold_class_member(m)),
+            ..class
+        }),
+        StmtKind::Interface(interface) => StmtKind::Interface(InterfaceDecl {
+            type_params: fold_all(interface.type_params, |p| folder.fold_type_param(p)),
+            extends: fold_all(interface.extends, |ty| folder.fold_type(ty)),
+            members: fold_all(interface.members, |m| folder.fold_interface_member(m)),
+            ..interface
+        }),
+        StmtKind::TypeAlias(alias) => StmtKind::TypeAlias(TypeAlias {
+            type_params: fold_all(alias.type_params, |p| folder.fold_type_param(p)),
+            ty: folder.fold_type(alias.ty),
+            ..alias
+        }),
+        StmtKind::Enum(decl) => StmtKind::Enum(EnumDecl {
+            type_params: fold_all(decl.type_params, |p| folder.fold_type_param(p)),
+            variants: fold_all(decl.variants, |v| folder.fold_enum_variant(v)),
+            ..decl
+        }),
+        StmtKind::If(stmt) => StmtKind::If(IfStmt {
+            condition: folder.fold_expr(stmt.condition),
+            then_branch: folder.fold_block(stmt.then_branch),
+            else_branch: stmt.else_branch.map(|branch| match branch {
+                ElseBranch::If(else_if) => ElseBranch::If(fold_boxed(else_if, |s| folder.fold_stmt(s))),
+                ElseBranch::Block(block) => ElseBranch::Block(folder.fold_block(block)),
+            }),
+        }),
+        StmtKind::For(stmt) => StmtKind::For(ForStmt {
+            pattern: folder.fold_pattern(stmt.pattern),
+            iterable: folder.fold_expr(stmt.iterable),
+            body: folder.fold_block(stmt.body),
+        }),
+        StmtKind::While(stmt) => StmtKind::While(WhileStmt {
+            condition: folder.fold_expr(stmt.condition),
+            body: folder.fold_block(stmt.body),
+        }),
+        StmtKind::Return(value) => StmtKind::Return(value.map(|value| folder.fold_expr(value))),
+        StmtKind::Try(stmt) => StmtKind::Try(TryStmt {
+            body: folder.fold_block(stmt.body),
+            catches: fold_all(stmt.catches, |c| folder.fold_catch_clause(c)),
+            finally: stmt.finally.map(|block| folder.fold_block(block)),
+        }),
+        StmtKind::Export(ExportDecl::Declaration(inner)) => {
+            StmtKind::Export(ExportDecl::Declaration(fold_boxed(inner, |s| folder.fold_stmt(s))))
+        },
+        StmtKind::Export(ExportDecl::Default(inner)) => {
+            StmtKind::Export(ExportDecl::Default(fold_boxed(inner, |s| folder.fold_stmt(s))))
+        },
+        StmtKind::Pragma(pragma) => StmtKind::Pragma(Pragma {
+            name: pragma.name,
+            args: fold_all(pragma.args, |arg| folder.fold_expr(arg)),
+        }),
+        StmtKind::Decorators(decorators) => StmtKind::Decorators(fold_all(decorators, |d| folder.fold_decorator(d))),
+        kind @ (StmtKind::Import(_)
+        | StmtKind::Export(_)
+        | StmtKind::Break
+        | StmtKind::Continue
+        | StmtKind::Error) => kind,
+    };
+    Stmt { kind, ..stmt }
+}
+
+pub fn fold_function<F: Fold>(folder: &mut F, function: FunctionDecl) -> FunctionDecl {
+    FunctionDecl {
+        decorators: fold_all(function.decorators, |d| folder.fold_decorator(d)),
+        type_params: fold_all(function.type_params, |p| folder.fold_type_param(p)),
+        params: fold_all(function.params, |p| folder.fold_param(p)),
+        return_type: function.return_type.map(|ty| folder.fold_type(ty)),
+        body: folder.fold_block(function.body),
+        ..function
+    }
+}
+
+pub fn fold_param<F: Fold>(folder: &mut F, param: Param) -> Param {
+    Param {
+        pattern: folder.fold_pattern(param.pattern),
+        ty: param.ty.map(|ty| folder.fold_type(ty)),
+        default: param.default.map(|default| folder.fold_expr(default)),
+        ..param
+    }
+}
+
+pub fn fold_type_param<F: Fold>(folder: &mut F, param: TypeParam) -> TypeParam {
+    TypeParam {
+        bound: param.bound.map(|bound| folder.fold_type(bound)),
+        ..param
+    }
+}
+
+pub fn fold_decorator<F: Fold>(folder: &mut F, decorator: Decorator) -> Decorator {
+    Decorator {
+        args: decorator.args.map(|args| fold_all(args, |arg| folder.fold_expr(arg))),
+        ..decorator
+    }
+}
+
+pub fn fold_class_member<F: Fold>(folder: &mut F, member: ClassMember) -> ClassMember {
+    let kind = match member.kind {
+        ClassMemberKind::Field { name, ty, init } => ClassMemberKind::Field {
+            name,
+            ty: ty.map(|ty| folder.fold_type(ty)),
+            init: init.map(|init| folder.fold_expr(init)),
+        },
+        ClassMemberKind::Constructor { params, body } => ClassMemberKind::Constructor {
+            params: fold_all(params, |p| folder.fold_param(p)),
+            body: folder.fold_block(body),
+        },
+        ClassMemberKind::Method(method) => ClassMemberKind::Method(folder.fold_function(method)),
+    };
+    ClassMember {
+        decorators: fold_all(member.decorators, |d| folder.fold_decorator(d)),
+        kind,
+        ..member
+    }
+}
+
+pub fn fold_interface_member<F: Fold>(folder: &mut F, member: InterfaceMember) -> InterfaceMember {
+    let kind = match member.kind {
+        InterfaceMemberKind::Property(ty) => InterfaceMemberKind::Property(folder.fold_type(ty)),
+        InterfaceMemberKind::Method {
+            type_params,
+            params,
+            return_type,
+        } => InterfaceMemberKind::Method {
+            type_params: fold_all(type_params, |p| folder.fold_type_param(p)),
+            params: fold_all(params, |p| folder.fold_param(p)),
+            return_type: return_type.map(|ty| folder.fold_type(ty)),
+        },
+    };
+    InterfaceMember { kind, ..member }
+}
+
+pub fn fold_enum_variant<F: Fold>(folder: &mut F, variant: EnumVariant) -> EnumVariant {
+    EnumVariant {
+        payload: fold_all(variant.payload, |ty| folder.fold_type(ty)),
+        ..variant
+    }
+}
+
+pub fn fold_catch_clause<F: Fold>(folder: &mut F, catch: CatchClause) -> CatchClause {
+    CatchClause {
+        ty: catch.ty.map(|ty| folder.fold_type(ty)),
+        body: folder.fold_block(catch.body),
+        ..catch
+    }
+}
+
+pub fn fold_expr<F: Fold>(folder: &mut F, expr: Expr) -> Expr {
+    let kind = match expr.kind {
+        ExprKind::Array(elements) => ExprKind::Array(fold_all(elements, |e| folder.fold_expr(e))),
+        ExprKind::Object(properties) => ExprKind::Object(fold_all(properties, |p| folder.fold_property(p))),
+        ExprKind::Spread(inner) => ExprKind::Spread(fold_boxed(inner, |e| folder.fold_expr(e))),
+        ExprKind::NullCheck(inner) => ExprKind::NullCheck(fold_boxed(inner, |e| folder.fold_expr(e))),
+        ExprKind::Unary { op, operand } => ExprKind::Unary {
+            op,
+            operand: fold_boxed(operand, |e| folder.fold_expr(e)),
+        },
+        ExprKind::Binary { op, left, right } => ExprKind::Binary {
+            op,
+            left: fold_boxed(left, |e| folder.fold_expr(e)),
+            right: fold_boxed(right, |e| folder.fold_expr(e)),
+        },
+        ExprKind::Assign { op, target, value } => ExprKind::Assign {
+            op,
+            target: fold_boxed(target, |e| folder.fold_expr(e)),
+            value: fold_boxed(value, |e| folder.fold_expr(e)),
+        },
+        ExprKind::Call {
+            callee,
+            type_args,
+            args,
+            optional,
+        } => ExprKind::Call {
+            callee: fold_boxed(callee, |e| folder.fold_expr(e)),
+            type_args: fold_all(type_args, |ty| folder.fold_type(ty)),
+            args: fold_all(args, |arg| folder.fold_expr(arg)),
+            optional,
+        },
+        ExprKind::Member {
+            object,
+            property,
+            optional,
+        } => ExprKind::Member {
+            object: fold_boxed(object, |e| folder.fold_expr(e)),
+            property,
+            optional,
+        },
+        ExprKind::Index { object, index, optional } => ExprKind::Index {
+            object: fold_boxed(object, |e| folder.fold_expr(e)),
+            index: fold_boxed(index, |e| folder.fold_expr(e)),
+            optional,
+        },
+        ExprKind::New {
+            callee,
+            type_args,
+            args,
+        } => ExprKind::New {
+            callee: fold_boxed(callee, |e| folder.fold_expr(e)),
+            type_args: fold_all(type_args, |ty| folder.fold_type(ty)),
+            args: fold_all(args, |arg| folder.fold_expr(arg)),
+        },
+        ExprKind::Is { expr, ty } => ExprKind::Is {
+            expr: fold_boxed(expr, |e| folder.fold_expr(e)),
+            ty: folder.fold_type(ty),
+        },
+        ExprKind::As { expr, ty } => ExprKind::As {
+            expr: fold_boxed(expr, |e| folder.fold_expr(e)),
+            ty: folder.fold_type(ty),
+        },
+        ExprKind::Lambda(lambda) => ExprKind::Lambda(folder.fold_lambda(lambda)),
+        ExprKind::Match { scrutinee, arms } => ExprKind::Match {
+            scrutinee: fold_boxed(scrutinee, |e| folder.fold_expr(e)),
+            arms: fold_all(arms, |arm| folder.fold_match_arm(arm)),
+        },
+        ExprKind::Yield { argument, delegate } => ExprKind::Yield {
+            argument: argument.map(|argument| fold_boxed(argument, |e| folder.fold_expr(e))),
+            delegate,
+        },
+        ExprKind::Template(template) => ExprKind::Template(fold_template(folder, template)),
+        ExprKind::TaggedTemplate { tag, template } => ExprKind::TaggedTemplate {
+            tag: fold_boxed(tag, |e| folder.fold_expr(e)),
+            template: fold_template(folder, template),
+        },
+        kind @ (ExprKind::Literal(_) | ExprKind::Ident(_) | ExprKind::This | ExprKind::Super | ExprKind::Error) => {
+            kind
+        },
+    };
+    Expr { kind, ..expr }
+}
+
+fn fold_template<F: Fold>(folder: &mut F, template: Template) -> Template {
+    Template {
+        values: fold_all(template.values, |e| folder.fold_expr(e)),
+        ..template
+    }
+}
+
+pub fn fold_property<F: Fold>(folder: &mut F, property: Property) -> Property {
+    let kind = match property.kind {
+        PropertyKind::KeyValue { key, value } => PropertyKind::KeyValue {
+            key: match key {
+                PropertyKey::Computed(key) => PropertyKey::Computed(fold_boxed(key, |e| folder.fold_expr(e))),
+                key => key,
+            },
+            value: folder.fold_expr(value),
+        },
+        PropertyKind::Spread(value) => PropertyKind::Spread(folder.fold_expr(value)),
+        kind @ PropertyKind::Shorthand(_) => kind,
+    };
+    Property { kind, ..property }
+}
+
+pub fn fold_lambda<F: Fold>(folder: &mut F, lambda: Lambda) -> Lambda {
+    Lambda {
+        params: fold_all(lambda.params, |p| folder.fold_param(p)),
+        return_type: lambda.return_type.map(|ty| folder.fold_type(ty)),
+        body: match lambda.body {
+            LambdaBody::Expr(body) => LambdaBody::Expr(fold_boxed(body, |e| folder.fold_expr(e))),
+            LambdaBody::Block(body) => LambdaBody::Block(folder.fold_block(body)),
+        },
+    }
+}
+
+pub fn fold_match_arm<F: Fold>(folder: &mut F, arm: MatchArm) -> MatchArm {
+    MatchArm {
+        pattern: folder.fold_pattern(arm.pattern),
+        guard: arm.guard.map(|guard| folder.fold_expr(guard)),
+        body: folder.fold_block(arm.body),
+        ..arm
+    }
+}
+
+pub fn fold_pattern<F: Fold>(folder: &mut F, pattern: Pattern) -> Pattern {
+    let kind = match pattern.kind {
+        PatternKind::Array(patterns) => PatternKind::Array(fold_all(patterns, |p| folder.fold_pattern(p))),
+        PatternKind::Object { fields, rest } => PatternKind::Object {
+            fields: fold_all(fields, |field| ObjectPatternField {
+                pattern: folder.fold_pattern(field.pattern),
+                ..field
+            }),
+            rest,
+        },
+        PatternKind::Rest(inner) => PatternKind::Rest(fold_boxed(inner, |p| folder.fold_pattern(p))),
+        PatternKind::Default { pattern, default } => PatternKind::Default {
+            pattern: fold_boxed(pattern, |p| folder.fold_pattern(p)),
+            default: fold_boxed(default, |e| folder.fold_expr(e)),
+        },
+        PatternKind::Variant { enum_name, name, fields } => PatternKind::Variant {
+            enum_name,
+            name,
+            fields: fold_all(fields, |p| folder.fold_pattern(p)),
+        },
+        PatternKind::TypeTest { binding, ty } => PatternKind::TypeTest {
+            binding,
+            ty: folder.fold_type(ty),
+        },
+        PatternKind::Or(patterns) => PatternKind::Or(fold_all(patterns, |p| folder.fold_pattern(p))),
+        kind @ (PatternKind::Wildcard | PatternKind::Binding(_) | PatternKind::Literal(_)) => kind,
+    };
+    Pattern { kind, ..pattern }
+}
+
+pub fn fold_type<F: Fold>(folder: &mut F, ty: TypeExpr) -> TypeExpr {
+    let kind = match ty.kind {
+        TypeExprKind::Named { name, args } => TypeExprKind::Named {
+            name,
+            args: fold_all(args, |ty| folder.fold_type(ty)),
+        },
+        TypeExprKind::Array(inner) => TypeExprKind::Array(fold_boxed(inner, |ty| folder.fold_type(ty))),
+        TypeExprKind::Tuple(types) => TypeExprKind::Tuple(fold_all(types, |ty| folder.fold_type(ty))),
+        TypeExprKind::Function { params, return_type } => TypeExprKind::Function {
+            params: fold_all(params, |param| FunctionTypeParam {
+                ty: folder.fold_type(param.ty),
+                ..param
+            }),
+            return_type: fold_boxed(return_type, |ty| folder.fold_type(ty)),
+        },
+        TypeExprKind::Object(members) => TypeExprKind::Object(fold_all(members, |member| ObjectTypeMember {
+            ty: member.ty.map(|ty| folder.fold_type(ty)),
+            ..member
+        })),
+        TypeExprKind::Union(types) => TypeExprKind::Union(fold_all(types, |ty| folder.fold_type(ty))),
+        TypeExprKind::Intersection(types) => TypeExprKind::Intersection(fold_all(types, |ty| folder.fold_type(ty))),
+        TypeExprKind::Nullable(inner) => TypeExprKind::Nullable(fold_boxed(inner, |ty| folder.fold_type(ty))),
+    };
+    TypeExpr { kind, ..ty }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::visit::{walk_expr, walk_pattern, Visitor};
+    use crate::parser::parse;
+    
+    /// Raddoppia ogni letterale numerico e toglie le istruzioni `pass()`
+    struct Doubler;
+    
+    impl Fold for Doubler {
+        fn fold_stmts(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+            let is_pass = |stmt: &Stmt| match &stmt.kind {
+                StmtKind::Expr(Expr { kind: ExprKind::Call { callee, .. }, .. }) => {
+                    matches!(&callee.kind, ExprKind::Ident(name) if name.name == "pass")
+                },
+                _ => false,
+            };
+            stmts.into_iter().filter(|stmt| !is_pass(stmt)).map(|stmt| self.fold_stmt(stmt)).collect()
+        }
+        
+        fn fold_expr(&mut self, expr: Expr) -> Expr {
+            match expr.kind {
+                ExprKind::Literal(Literal::Number(n)) => Expr {
+                    kind: ExprKind::Literal(Literal::Number(n * 2.0)),
+                    ..expr
+                },
+                _ => fold_expr(self, expr),
+            }
+        }
+    }
+    
+    /// Numeri delle espressioni e dei pattern, in ordine di sorgente
+    #[derive(Default)]
+    struct Numbers {
+        exprs: Vec<f64>,
+        patterns: Vec<f64>,
+    }
+    
+    impl<'ast> Visitor<'ast> for Numbers {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if let ExprKind::Literal(Literal::Number(n)) = expr.kind {
+                self.exprs.push(n);
+            }
+            walk_expr(self, expr);
+        }
+        
+        fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+            if let PatternKind::Literal(Literal::Number(n)) = pattern.kind {
+                self.patterns.push(n);
+            }
+            walk_pattern(self, pattern);
+        }
+    }
+    
+    #[test]
+    fn test_fold_rewrites_nested_nodes() {
+        let source = "\
+fn f(x = 1)
+  pass()
+  let g = (y) -> [y, 2]
+  if x
+    pass()
+  return match x
+    case 3: { key: 4 }
+";
+        let program = Doubler.fold_program(parse(source).unwrap());
+        
+        let StmtKind::Function(function) = &program.body[0].kind else {
+            panic!("attesa una funzione");
+        };
+        assert_eq!(function.body.stmts.len(), 3);
+        let StmtKind::If(stmt) = &function.body.stmts[1].kind else {
+            panic!("atteso un `if`");
+        };
+        assert!(stmt.then_branch.stmts.is_empty());
+        
+        // I pattern non sono espressioni: il `3` del `case` resta invariato
+        let mut numbers = Numbers::default();
+        numbers.visit_program(&program);
+        assert_eq!(numbers.exprs, vec![2.0, 4.0, 8.0]);
+        assert_eq!(numbers.patterns, vec![3.0]);
+    }
+}
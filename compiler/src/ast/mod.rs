@@ -0,0 +1,861 @@
+//! Abstract Syntax Tree di NovaScript.
+//!
+//! Ogni nodo porta lo `Span` del sorgente da cui proviene e i nodi principali
+//! (espressioni, istruzioni, pattern e tipi) hanno anche un `NodeId` univoco
+//! all'interno del programma. Le analisi successive (risoluzione dei nomi,
+//! controllo dei tipi) associano i loro risultati ai `NodeId` in tabelle
+//! separate, senza modificare l'albero.
+
+pub mod dump;
+pub mod fold;
+pub mod renumber;
+pub mod visit;
+pub mod visit_mut;
+
+pub use dump::{dump, DumpFormat};
+pub use fold::Fold;
+pub use renumber::Renumber;
+pub use visit::{walk_with_path, AstPath, Node, PathVisitor, Visitor};
+pub use visit_mut::VisitorMut;
+
+use crate::lexer::Span;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Identificatore univoco di un nodo dell'AST
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NodeId(pub u32);
+
+impl NodeId {
+    /// Id segnaposto per nodi creati fuori dal parser, prima della numerazione
+    pub const DUMMY: NodeId = NodeId(u32::MAX);
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Generatore di `NodeId` crescenti
+#[derive(Debug, Clone, Default)]
+pub struct NodeIdGen {
+    next: u32,
+}
+
+impl NodeIdGen {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Restituisce un id mai restituito prima da questo generatore
+    pub fn fresh(&mut self) -> NodeId {
+        let id = NodeId(self.next);
+        self.next += 1;
+        id
+    }
+    
+    /// Numero di id generati finora
+    pub fn count(&self) -> u32 {
+        self.next
+    }
+}
+
+/// Nome scritto nel sorgente
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ident {
+    pub name: String,
+    pub span: Span,
+}
+
+impl Ident {
+    pub fn new(name: &str, span: Span) -> Self {
+        Self {
+            name: name.to_string(),
+            span,
+        }
+    }
+}
+
+impl fmt::Display for Ident {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Programma: il contenuto di un file sorgente
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Program {
+    pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
+/// Sequenza di istruzioni delimitata da INDENT e DEDENT
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Block {
+    pub stmts: Vec<Stmt>,
+    pub span: Span,
+}
+
+// ---------------------------------------------------------------------------
+// Istruzioni
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stmt {
+    pub id: NodeId,
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+impl Stmt {
+    pub fn new(id: NodeId, kind: StmtKind, span: Span) -> Self {
+        Self { id, kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StmtKind {
+    /// Espressione usata come istruzione, comprese le assegnazioni
+    Expr(Expr),
+    /// `let` o `const`
+    Var(VarDecl),
+    Function(FunctionDecl),
+    Class(ClassDecl),
+    Interface(InterfaceDecl),
+    TypeAlias(TypeAlias),
+    Enum(EnumDecl),
+    If(IfStmt),
+    For(ForStmt),
+    While(WhileStmt),
+    Break,
+    Continue,
+    Return(Option<Expr>),
+    Throw(Expr),
+    Try(TryStmt),
+    Import(ImportDecl),
+    Export(ExportDecl),
+    /// Direttiva del compilatore, ad esempio `#pragma optimize("speed")`
+    Pragma(Pragma),
+    /// Decoratori separati da una riga vuota da ciò che segue: valgono per il
+    /// blocco che li contiene, come `@deny(style)` in testa al file
+    Decorators(Vec<Decorator>),
+    /// Istruzione non valida, già segnalata dal parser
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VarKind {
+    Let,
+    Const,
+}
+
+/// `let pattern: tipo = valore`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VarDecl {
+    pub kind: VarKind,
+    pub pattern: Pattern,
+    pub ty: Option<TypeExpr>,
+    pub init: Option<Expr>,
+}
+
+/// Dichiarazione di funzione, di metodo o di generatore (`fn*`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionDecl {
+    pub decorators: Vec<Decorator>,
+    pub name: Ident,
+    pub type_params: Vec<TypeParam>,
+    pub params: Vec<Param>,
+    pub return_type: Option<TypeExpr>,
+    pub body: Block,
+    pub is_generator: bool,
+}
+
+/// Parametro di una funzione o di una lambda
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Param {
+    pub pattern: Pattern,
+    pub ty: Option<TypeExpr>,
+    pub default: Option<Expr>,
+    /// Parametro rest (`...args`)
+    pub rest: bool,
+    pub span: Span,
+}
+
+/// Parametro di tipo generico, con un vincolo opzionale (`T extends Comparable`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeParam {
+    pub id: NodeId,
+    pub name: Ident,
+    pub bound: Option<TypeExpr>,
+    pub span: Span,
+}
+
+/// Decoratore applicato a una dichiarazione (`@deprecated("...")`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Decorator {
+    pub name: Ident,
+    /// Argomenti tra parentesi; `None` se il decoratore non ha parentesi
+    pub args: Option<Vec<Expr>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassDecl {
+    pub decorators: Vec<Decorator>,
+    pub name: Ident,
+    pub type_params: Vec<TypeParam>,
+    pub extends: Option<TypeExpr>,
+    pub implements: Vec<TypeExpr>,
+    pub members: Vec<ClassMember>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassMember {
+    pub id: NodeId,
+    pub decorators: Vec<Decorator>,
+    pub is_static: bool,
+    pub kind: ClassMemberKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClassMemberKind {
+    Field {
+        name: Ident,
+        ty: Option<TypeExpr>,
+        init: Option<Expr>,
+    },
+    Constructor {
+        params: Vec<Param>,
+        body: Block,
+    },
+    Method(FunctionDecl),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceDecl {
+    pub name: Ident,
+    pub type_params: Vec<TypeParam>,
+    pub extends: Vec<TypeExpr>,
+    pub members: Vec<InterfaceMember>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceMember {
+    pub name: Ident,
+    /// Proprietà opzionale (`nome?: tipo`)
+    pub optional: bool,
+    pub kind: InterfaceMemberKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InterfaceMemberKind {
+    Property(TypeExpr),
+    Method {
+        type_params: Vec<TypeParam>,
+        params: Vec<Param>,
+        return_type: Option<TypeExpr>,
+    },
+}
+
+/// `type Nome<T> = tipo`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeAlias {
+    pub name: Ident,
+    pub type_params: Vec<TypeParam>,
+    pub ty: TypeExpr,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumDecl {
+    pub name: Ident,
+    pub type_params: Vec<TypeParam>,
+    pub variants: Vec<EnumVariant>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumVariant {
+    pub id: NodeId,
+    pub name: Ident,
+    /// Tipi dei valori trasportati dalla variante, ad esempio `Some(T)`
+    pub payload: Vec<TypeExpr>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IfStmt {
+    pub condition: Expr,
+    pub then_branch: Block,
+    pub else_branch: Option<ElseBranch>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ElseBranch {
+    /// `else if ...`: contiene un'istruzione `If`
+    If(Box<Stmt>),
+    Block(Block),
+}
+
+/// `for pattern in iterabile`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForStmt {
+    pub pattern: Pattern,
+    pub iterable: Expr,
+    pub body: Block,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhileStmt {
+    pub condition: Expr,
+    pub body: Block,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TryStmt {
+    pub body: Block,
+    pub catches: Vec<CatchClause>,
+    pub finally: Option<Block>,
+}
+
+/// `catch e: Tipo`; senza nome né tipo cattura qualsiasi valore
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatchClause {
+    pub id: NodeId,
+    pub binding: Option<Ident>,
+    pub ty: Option<TypeExpr>,
+    pub body: Block,
+    pub span: Span,
+}
+
+/// `import nome, * as modulo from "modulo"`, `import nome, { a, b as c } from "modulo"`
+/// oppure `import "modulo"`, che esegue il modulo senza importare nomi
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportDecl {
+    /// Valore esportato con `export default`
+    pub default: Option<ImportName>,
+    /// `* as nome`: oggetto con tutte le esportazioni del modulo
+    pub namespace: Option<ImportName>,
+    pub named: Vec<ImportSpecifier>,
+    pub source: StringLit,
+}
+
+impl ImportDecl {
+    /// Import che esegue il modulo senza introdurre nomi
+    pub fn is_side_effect(&self) -> bool {
+        self.default.is_none() && self.namespace.is_none() && self.named.is_empty()
+    }
+    
+    /// Nomi locali introdotti dall'import, ognuno con l'id del nodo che lo dichiara
+    pub fn bindings(&self) -> impl Iterator<Item = (NodeId, &Ident)> {
+        let names = self.default.iter().chain(&self.namespace).map(|name| (name.id, &name.name));
+        names.chain(self.named.iter().map(|specifier| (specifier.id, specifier.renamed())))
+    }
+}
+
+/// Nome locale di un import default o di `* as nome`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportName {
+    pub id: NodeId,
+    pub name: Ident,
+}
+
+/// `nome` oppure `nome as alias`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportSpecifier {
+    pub id: NodeId,
+    pub name: Ident,
+    pub alias: Option<Ident>,
+    pub span: Span,
+}
+
+impl ImportSpecifier {
+    /// Nome dopo `as`, se presente: il nome locale di un import oppure il
+    /// nome esportato di un export
+    pub fn renamed(&self) -> &Ident {
+        self.alias.as_ref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ExportDecl {
+    /// `export` seguito da una dichiarazione
+    Declaration(Box<Stmt>),
+    /// `export default` seguito da una funzione, una classe o un'espressione
+    Default(Box<Stmt>),
+    /// `export { a, b as c }`, oppure `export { a } from "modulo"` che
+    /// riesporta i nomi di un altro modulo
+    Named {
+        specifiers: Vec<ImportSpecifier>,
+        source: Option<StringLit>,
+    },
+    /// `export * from "modulo"` oppure `export * as nome from "modulo"`
+    All {
+        alias: Option<Ident>,
+        source: StringLit,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pragma {
+    pub name: Ident,
+    pub args: Vec<Expr>,
+}
+
+/// Stringa letterale con il suo intervallo, usata dove serve solo il testo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StringLit {
+    pub value: String,
+    pub span: Span,
+}
+
+// ---------------------------------------------------------------------------
+// Espressioni
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Expr {
+    pub id: NodeId,
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn new(id: NodeId, kind: ExprKind, span: Span) -> Self {
+        Self { id, kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ExprKind {
+    Literal(Literal),
+    /// Riferimento a una variabile
+    Ident(Ident),
+    This,
+    Super,
+    Array(Vec<Expr>),
+    Object(Vec<Property>),
+    /// `...espressione` in array, oggetti e argomenti di chiamata
+    Spread(Box<Expr>),
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    Assign {
+        op: AssignOp,
+        target: Box<Expr>,
+        value: Box<Expr>,
+    },
+    Call {
+        callee: Box<Expr>,
+        /// Argomenti di tipo espliciti (`f<T>(x)`)
+        type_args: Vec<TypeExpr>,
+        args: Vec<Expr>,
+        /// Chiamata opzionale (`f?.()`)
+        optional: bool,
+    },
+    Member {
+        object: Box<Expr>,
+        property: Ident,
+        /// Accesso opzionale (`a?.b`)
+        optional: bool,
+    },
+    Index {
+        object: Box<Expr>,
+        index: Box<Expr>,
+        /// Accesso opzionale (`a?.[i]`)
+        optional: bool,
+    },
+    New {
+        callee: Box<Expr>,
+        type_args: Vec<TypeExpr>,
+        args: Vec<Expr>,
+    },
+    /// Verifica di tipo (`valore is Tipo`)
+    Is {
+        expr: Box<Expr>,
+        ty: TypeExpr,
+    },
+    /// Cast di tipo (`valore as Tipo`)
+    As {
+        expr: Box<Expr>,
+        ty: TypeExpr,
+    },
+    /// Verifica di non nullità (`nome?`)
+    NullCheck(Box<Expr>),
+    /// Template con interpolazioni: `` `ciao ${nome}` ``
+    Template(Template),
+    /// Template preceduto da una funzione tag: `` sql`select ${id}` ``
+    TaggedTemplate {
+        tag: Box<Expr>,
+        template: Template,
+    },
+    Lambda(Lambda),
+    Match {
+        scrutinee: Box<Expr>,
+        arms: Vec<MatchArm>,
+    },
+    /// `yield valore` oppure `yield* iterabile`
+    Yield {
+        argument: Option<Box<Expr>>,
+        delegate: bool,
+    },
+    /// Espressione non valida, già segnalata dal parser. Le analisi successive
+    /// la accettano ovunque senza produrre altri errori.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Literal {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Null,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Property {
+    pub kind: PropertyKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PropertyKind {
+    /// `chiave: valore`
+    KeyValue {
+        key: PropertyKey,
+        value: Expr,
+    },
+    /// `{ nome }`, equivalente a `{ nome: nome }`
+    Shorthand(Ident),
+    /// `{ ...oggetto }`
+    Spread(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PropertyKey {
+    Ident(Ident),
+    String(StringLit),
+    /// Chiave calcolata (`[espressione]: valore`)
+    Computed(Box<Expr>),
+}
+
+/// Parti di un template. I tratti di testo, con le sequenze di escape già
+/// risolte, sono sempre uno in più dei valori interpolati: il valore `i` sta
+/// tra `strings[i]` e `strings[i + 1]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    pub strings: Vec<StringLit>,
+    pub values: Vec<Expr>,
+}
+
+/// Funzione anonima: `(a, b) -> a * b`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lambda {
+    pub params: Vec<Param>,
+    pub return_type: Option<TypeExpr>,
+    pub body: LambdaBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LambdaBody {
+    Expr(Box<Expr>),
+    Block(Block),
+}
+
+/// `case pattern if guardia:` seguito dal corpo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchArm {
+    pub id: NodeId,
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Block,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnaryOp {
+    /// `-`
+    Neg,
+    /// `+`
+    Plus,
+    /// `!`
+    Not,
+    /// `~`
+    BitNot,
+}
+
+impl UnaryOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnaryOp::Neg => "-",
+            UnaryOp::Plus => "+",
+            UnaryOp::Not => "!",
+            UnaryOp::BitNot => "~",
+        }
+    }
+}
+
+impl fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Eq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    And,
+    Or,
+    /// `??`
+    Coalesce,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+}
+
+impl BinaryOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Pow => "**",
+            BinaryOp::Eq => "==",
+            BinaryOp::NotEq => "!=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessEq => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterEq => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+            BinaryOp::Coalesce => "??",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+        }
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssignOp {
+    Assign,
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl AssignOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssignOp::Assign => "=",
+            AssignOp::Add => "+=",
+            AssignOp::Sub => "-=",
+            AssignOp::Mul => "*=",
+            AssignOp::Div => "/=",
+        }
+    }
+    
+    /// Operatore binario equivalente per le assegnazioni composte
+    pub fn binary_op(&self) -> Option<BinaryOp> {
+        match self {
+            AssignOp::Assign => None,
+            AssignOp::Add => Some(BinaryOp::Add),
+            AssignOp::Sub => Some(BinaryOp::Sub),
+            AssignOp::Mul => Some(BinaryOp::Mul),
+            AssignOp::Div => Some(BinaryOp::Div),
+        }
+    }
+}
+
+impl fmt::Display for AssignOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pattern
+// ---------------------------------------------------------------------------
+
+/// Pattern usato nelle dichiarazioni, nei parametri, nei `for` e nei `case`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pattern {
+    pub id: NodeId,
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+impl Pattern {
+    pub fn new(id: NodeId, kind: PatternKind, span: Span) -> Self {
+        Self { id, kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PatternKind {
+    /// `_`
+    Wildcard,
+    /// Introduce una variabile
+    Binding(Ident),
+    Literal(Literal),
+    /// `[a, b, ...resto]`, usato anche per le tuple
+    Array(Vec<Pattern>),
+    /// `{ nome, eta: anni, ...resto }`
+    Object {
+        fields: Vec<ObjectPatternField>,
+        rest: Option<Ident>,
+    },
+    /// `...resto` come ultimo elemento di un pattern array
+    Rest(Box<Pattern>),
+    /// `nome = valore`: valore usato quando l'elemento destrutturato manca
+    Default {
+        pattern: Box<Pattern>,
+        default: Box<Expr>,
+    },
+    /// Variante di un enum, eventualmente qualificata: `Some(x)`, `Shape.Circle(r)`.
+    /// Una variante senza valori né qualificazione, come `None`, è scritta come
+    /// un `Binding` e viene distinta durante la risoluzione dei nomi.
+    Variant {
+        enum_name: Option<Ident>,
+        name: Ident,
+        fields: Vec<Pattern>,
+    },
+    /// `is Tipo` oppure `nome is Tipo`
+    TypeTest {
+        binding: Option<Ident>,
+        ty: TypeExpr,
+    },
+    /// Alternative `a | b`: il pattern corrisponde se ne corrisponde una
+    Or(Vec<Pattern>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectPatternField {
+    pub key: Ident,
+    /// Pattern del valore. La forma abbreviata `{ nome }` equivale a
+    /// `{ nome: nome }` e ha come pattern il `Binding` di `nome`.
+    pub pattern: Pattern,
+    pub span: Span,
+}
+
+// ---------------------------------------------------------------------------
+// Tipi
+// ---------------------------------------------------------------------------
+
+/// Annotazione di tipo scritta nel sorgente
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeExpr {
+    pub id: NodeId,
+    pub kind: TypeExprKind,
+    pub span: Span,
+}
+
+impl TypeExpr {
+    pub fn new(id: NodeId, kind: TypeExprKind, span: Span) -> Self {
+        Self { id, kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TypeExprKind {
+    /// Tipo con nome, eventualmente generico: `number`, `Map<K, V>`
+    Named {
+        name: Ident,
+        args: Vec<TypeExpr>,
+    },
+    /// `T[]`
+    Array(Box<TypeExpr>),
+    /// `[string, number]`
+    Tuple(Vec<TypeExpr>),
+    /// `(x: number) -> number`
+    Function {
+        params: Vec<FunctionTypeParam>,
+        return_type: Box<TypeExpr>,
+    },
+    /// `{ name: string, age?: number }`
+    Object(Vec<ObjectTypeMember>),
+    /// `A | B`
+    Union(Vec<TypeExpr>),
+    /// `A & B`
+    Intersection(Vec<TypeExpr>),
+    /// `T?`
+    Nullable(Box<TypeExpr>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionTypeParam {
+    pub name: Option<Ident>,
+    pub ty: TypeExpr,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectTypeMember {
+    pub name: Ident,
+    pub ty: Option<TypeExpr>,
+    pub optional: bool,
+    pub span: Span,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Position;
+    
+    #[test]
+    fn test_node_ids_are_unique() {
+        let mut ids = NodeIdGen::new();
+        let span = Span::point(Position::new(1, 1, 0));
+        
+        let one = Expr::new(ids.fresh(), ExprKind::Literal(Literal::Number(1.0)), span);
+        let two = Expr::new(ids.fresh(), ExprKind::Literal(Literal::Number(2.0)), span);
+        let sum = Expr::new(
+            ids.fresh(),
+            ExprKind::Binary {
+                op: BinaryOp::Add,
+                left: Box::new(one),
+                right: Box::new(two),
+            },
+            span,
+        );
+        
+        let ExprKind::Binary { left, right, .. } = &sum.kind else {
+            unreachable!();
+        };
+        assert_ne!(left.id, right.id);
+        assert_ne!(sum.id, left.id);
+        assert_eq!(ids.count(), 3);
+        assert_eq!(AssignOp::Add.binary_op(), Some(BinaryOp::Add));
+    }
+}
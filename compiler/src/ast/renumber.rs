@@ -0,0 +1,191 @@
+//! Id nuovi per i nodi di un albero.
+//!
+//! Un passo che inserisce nell'albero la copia di un nodo già presente, come
+//! il tipo di una clausola `catch` usato sia nella dichiarazione della
+//! variabile sia nel controllo `is`, deve dare alla copia id mai usati: le
+//! tabelle indicizzate per `NodeId` non distinguerebbero altrimenti i due
+//! nodi.
+
+use super::visit_mut::{
+    walk_catch_clause, walk_class_member, walk_enum_variant, walk_expr, walk_match_arm, walk_pattern, walk_stmt,
+    walk_type, walk_type_param, VisitorMut,
+};
+use super::{
+    CatchClause, ClassMember, EnumVariant, ExportDecl, Expr, MatchArm, NodeId, NodeIdGen, Pattern, Stmt, StmtKind,
+    TypeExpr, TypeParam,
+};
+
+/// Assegna un id nuovo a ogni nodo visitato, figli compresi
+pub struct Renumber<'a> {
+    pub ids: &'a mut NodeIdGen,
+}
+
+impl Renumber<'_> {
+    /// Copia di `expr` con id nuovi
+    pub fn expr(&mut self, expr: &Expr) -> Expr {
+        let mut copy = expr.clone();
+        self.visit_expr(&mut copy);
+        copy
+    }
+    
+    /// Copia di `ty` con id nuovi
+    pub fn ty(&mut self, ty: &TypeExpr) -> TypeExpr {
+        let mut copy = ty.clone();
+        self.visit_type(&mut copy);
+        copy
+    }
+}
+
+impl VisitorMut for Renumber<'_> {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        stmt.id = self.ids.fresh();
+        for id in name_ids_mut(stmt) {
+            *id = self.ids.fresh();
+        }
+        walk_stmt(self, stmt);
+    }
+    
+    fn visit_type_param(&mut self, param: &mut TypeParam) {
+        param.id = self.ids.fresh();
+        walk_type_param(self, param);
+    }
+    
+    fn visit_class_member(&mut self, member: &mut ClassMember) {
+        member.id = self.ids.fresh();
+        walk_class_member(self, member);
+    }
+    
+    fn visit_enum_variant(&mut self, variant: &mut EnumVariant) {
+        variant.id = self.ids.fresh();
+        walk_enum_variant(self, variant);
+    }
+    
+    fn visit_catch_clause(&mut self, catch: &mut CatchClause) {
+        catch.id = self.ids.fresh();
+        walk_catch_clause(self, catch);
+    }
+    
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        expr.id = self.ids.fresh();
+        walk_expr(self, expr);
+    }
+    
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        arm.id = self.ids.fresh();
+        walk_match_arm(self, arm);
+    }
+    
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        pattern.id = self.ids.fresh();
+        walk_pattern(self, pattern);
+    }
+    
+    fn visit_type(&mut self, ty: &mut TypeExpr) {
+        ty.id = self.ids.fresh();
+        walk_type(self, ty);
+    }
+}
+
+/// Id dei nomi dichiarati da un `import` e dei nomi di un `export { ... }`,
+/// che non sono nodi visitati
+fn name_ids_mut(stmt: &mut Stmt) -> Vec<&mut NodeId> {
+    match &mut stmt.kind {
+        StmtKind::Import(decl) => {
+            let names = decl.default.iter_mut().chain(&mut decl.namespace).map(|name| &mut name.id);
+            names.chain(decl.named.iter_mut().map(|specifier| &mut specifier.id)).collect()
+        },
+        StmtKind::Export(ExportDecl::Named { specifiers, .. }) => specifiers.iter_mut().map(|specifier| &mut specifier.id).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Id di tutti i nodi del programma, per i test dei passi di abbassamento
+#[cfg(test)]
+pub(crate) fn all_ids(program: &super::Program) -> Vec<NodeId> {
+    use super::visit::{self, Visitor};
+    
+    struct Ids(Vec<NodeId>);
+    
+    impl<'ast> Visitor<'ast> for Ids {
+        fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+            self.0.push(stmt.id);
+            match &stmt.kind {
+                StmtKind::Import(decl) => self.0.extend(decl.bindings().map(|(id, _)| id)),
+                StmtKind::Export(ExportDecl::Named { specifiers, .. }) => {
+                    self.0.extend(specifiers.iter().map(|specifier| specifier.id));
+                },
+                _ => {},
+            }
+            visit::walk_stmt(self, stmt);
+        }
+        
+        fn visit_type_param(&mut self, param: &'ast TypeParam) {
+            self.0.push(param.id);
+            visit::walk_type_param(self, param);
+        }
+        
+        fn visit_class_member(&mut self, member: &'ast ClassMember) {
+            self.0.push(member.id);
+            visit::walk_class_member(self, member);
+        }
+        
+        fn visit_enum_variant(&mut self, variant: &'ast EnumVariant) {
+            self.0.push(variant.id);
+            visit::walk_enum_variant(self, variant);
+        }
+        
+        fn visit_catch_clause(&mut self, catch: &'ast CatchClause) {
+            self.0.push(catch.id);
+            visit::walk_catch_clause(self, catch);
+        }
+        
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            self.0.push(expr.id);
+            visit::walk_expr(self, expr);
+        }
+        
+        fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+            self.0.push(arm.id);
+            visit::walk_match_arm(self, arm);
+        }
+        
+        fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+            self.0.push(pattern.id);
+            visit::walk_pattern(self, pattern);
+        }
+        
+        fn visit_type(&mut self, ty: &'ast TypeExpr) {
+            self.0.push(ty.id);
+            visit::walk_type(self, ty);
+        }
+    }
+    
+    let mut ids = Ids(Vec::new());
+    ids.visit_program(program);
+    ids.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_with_node_ids;
+    use std::collections::HashSet;
+    
+    #[test]
+    fn test_copies_get_fresh_ids() {
+        let (mut program, mut ids) = parse_with_node_ids("let m: Map<string, (x: number) -> T[]> = a\nlet n = b\n").unwrap();
+        let parsed = ids.count();
+        let StmtKind::Var(decl) = &program.body[0].kind else {
+            panic!("attesa una dichiarazione");
+        };
+        let copy = Renumber { ids: &mut ids }.ty(decl.ty.as_ref().unwrap());
+        let StmtKind::Var(decl) = &mut program.body[1].kind else {
+            panic!("attesa una dichiarazione");
+        };
+        decl.ty = Some(copy);
+        
+        let all = all_ids(&program);
+        assert_eq!(all.iter().collect::<HashSet<_>>().len(), all.len());
+        assert_eq!(ids.count() - parsed, 6);
+    }
+}
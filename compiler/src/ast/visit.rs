@@ -0,0 +1,670 @@
+//! Visita in sola lettura dell'AST.
+//!
+//! Un [`Visitor`] ridefinisce solo i metodi dei nodi che gli interessano:
+//! l'implementazione predefinita di ogni `visit_*` chiama la funzione `walk_*`
+//! corrispondente, che visita i figli del nodo in ordine di sorgente. Un
+//! metodo ridefinito chiama `walk_*` per continuare la visita nei figli, o la
+//! omette per saltarli.
+//!
+//! Con [`walk_with_path`] la visita mantiene anche il percorso dalla radice
+//! al nodo corrente, da cui si ricava ad esempio la funzione che contiene
+//! un'espressione.
+
+use super::*;
+
+pub trait Visitor<'ast>: Sized {
+    fn visit_program(&mut self, program: &'ast Program) {
+        walk_program(self, program);
+    }
+    
+    fn visit_block(&mut self, block: &'ast Block) {
+        walk_block(self, block);
+    }
+    
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        walk_stmt(self, stmt);
+    }
+    
+    fn visit_function(&mut self, function: &'ast FunctionDecl) {
+        walk_function(self, function);
+    }
+    
+    fn visit_param(&mut self, param: &'ast Param) {
+        walk_param(self, param);
+    }
+    
+    fn visit_type_param(&mut self, param: &'ast TypeParam) {
+        walk_type_param(self, param);
+    }
+    
+    fn visit_decorator(&mut self, decorator: &'ast Decorator) {
+        walk_decorator(self, decorator);
+    }
+    
+    fn visit_class_member(&mut self, member: &'ast ClassMember) {
+        walk_class_member(self, member);
+    }
+    
+    fn visit_interface_member(&mut self, member: &'ast InterfaceMember) {
+        walk_interface_member(self, member);
+    }
+    
+    fn visit_enum_variant(&mut self, variant: &'ast EnumVariant) {
+        walk_enum_variant(self, variant);
+    }
+    
+    fn visit_catch_clause(&mut self, catch: &'ast CatchClause) {
+        walk_catch_clause(self, catch);
+    }
+    
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        walk_expr(self, expr);
+    }
+    
+    fn visit_property(&mut self, property: &'ast Property) {
+        walk_property(self, property);
+    }
+    
+    fn visit_lambda(&mut self, lambda: &'ast Lambda) {
+        walk_lambda(self, lambda);
+    }
+    
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        walk_match_arm(self, arm);
+    }
+    
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+        walk_pattern(self, pattern);
+    }
+    
+    fn visit_type(&mut self, ty: &'ast TypeExpr) {
+        walk_type(self, ty);
+    }
+}
+
+pub fn walk_program<'ast, V: Visitor<'ast>>(visitor: &mut V, program: &'ast Program) {
+    for stmt in &program.body {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_block<'ast, V: Visitor<'ast>>(visitor: &mut V, block: &'ast Block) {
+    for stmt in &block.stmts {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt<'ast, V: Visitor<'ast>>(visitor: &mut V, stmt: &'ast Stmt) {
+    match &stmt.kind {
+        StmtKind::Expr(expr) | StmtKind::Throw(expr) => visitor.visit_expr(expr),
+        StmtKind::Var(decl) => {
+            visitor.visit_pattern(&decl.pattern);
+            if let Some(ty) = &decl.ty {
+                visitor.visit_type(ty);
+            }
+            if let Some(init) = &decl.init {
+                visitor.visit_expr(init);
+            }
+        },
+        StmtKind::Function(function) => visitor.visit_function(function),
+        StmtKind::Class(class) => {
+            for decorator in &class.decorators {
+                visitor.visit_decorator(decorator);
+            }
+            for param in &class.type_params {
+                visitor.visit_type_param(param);
+            }
+            for ty in class.extends.iter().chain(&class.implements) {
+                visitor.visit_type(ty);
+            }
+            for member in &class.members {
+                visitor.visit_class_member(member);
+            }
+        },
+        StmtKind::Interface(interface) => {
+            for param in &interface.type_params {
+                visitor.visit_type_param(param);
+            }
+            for ty in &interface.extends {
+                visitor.visit_type(ty);
+            }
+            for member in &interface.members {
+                visitor.visit_interface_member(member);
+            }
+        },
+        StmtKind::TypeAlias(alias) => {
+            for param in &alias.type_params {
+                visitor.visit_type_param(param);
+            }
+            visitor.visit_type(&alias.ty);
+        },
+        StmtKind::Enum(decl) => {
+            for param in &decl.type_params {
+                visitor.visit_type_param(param);
+            }
+            for variant in &decl.variants {
+                visitor.visit_enum_variant(variant);
+            }
+        },
+        StmtKind::If(stmt) => {
+            visitor.visit_expr(&stmt.condition);
+            visitor.visit_block(&stmt.then_branch);
+            match &stmt.else_branch {
+                Some(ElseBranch::If(else_if)) => visitor.visit_stmt(else_if),
+                Some(ElseBranch::Block(block)) => visitor.visit_block(block),
+                None => {},
+            }
+        },
+        StmtKind::For(stmt) => {
+            visitor.visit_pattern(&stmt.pattern);
+            visitor.visit_expr(&stmt.iterable);
+            visitor.visit_block(&stmt.body);
+        },
+        StmtKind::While(stmt) => {
+            visitor.visit_expr(&stmt.condition);
+            visitor.visit_block(&stmt.body);
+        },
+        StmtKind::Return(value) => {
+            if let Some(value) = value {
+                visitor.visit_expr(value);
+            }
+        },
+        StmtKind::Try(stmt) => {
+            visitor.visit_block(&stmt.body);
+            for catch in &stmt.catches {
+                visitor.visit_catch_clause(catch);
+            }
+            if let Some(finally) = &stmt.finally {
+                visitor.visit_block(finally);
+            }
+        },
+        StmtKind::Export(ExportDecl::Declaration(inner) | ExportDecl::Default(inner)) => visitor.visit_stmt(inner),
+        StmtKind::Pragma(pragma) => {
+            for arg in &pragma.args {
+                visitor.visit_expr(arg);
+            }
+        },
+        StmtKind::Decorators(decorators) => {
+            for decorator in decorators {
+                visitor.visit_decorator(decorator);
+            }
+        },
+        StmtKind::Import(_)
+        | StmtKind::Export(ExportDecl::Named { .. } | ExportDecl::All { .. })
+        | StmtKind::Break
+        | StmtKind::Continue
+        | StmtKind::Error => {},
+    }
+}
+
+pub fn walk_function<'ast, V: Visitor<'ast>>(visitor: &mut V, function: &'ast FunctionDecl) {
+    for decorator in &function.decorators {
+        visitor.visit_decorator(decorator);
+    }
+    for param in &function.type_params {
+        visitor.visit_type_param(param);
+    }
+    for param in &function.params {
+        visitor.visit_param(param);
+    }
+    if let Some(ty) = &function.return_type {
+        visitor.visit_type(ty);
+    }
+    visitor.visit_block(&function.body);
+}
+
+pub fn walk_param<'ast, V: Visitor<'ast>>(visitor: &mut V, param: &'ast Param) {
+    visitor.visit_pattern(&param.pattern);
+    if let Some(ty) = &param.ty {
+        visitor.visit_type(ty);
+    }
+    if let Some(default) = &param.default {
+        visitor.visit_expr(default);
+    }
+}
+
+pub fn walk_type_param<'ast, V: Visitor<'ast>>(visitor: &mut V, param: &'ast TypeParam) {
+    if let Some(bound) = &param.bound {
+        visitor.visit_type(bound);
+    }
+}
+
+pub fn walk_decorator<'ast, V: Visitor<'ast>>(visitor: &mut V, decorator: &'ast Decorator) {
+    for arg in decorator.args.iter().flatten() {
+        visitor.visit_expr(arg);
+    }
+}
+
+pub fn walk_class_member<'ast, V: Visitor<'ast>>(visitor: &mut V, member: &'ast ClassMember) {
+    for decorator in &member.decorators {
+        visitor.visit_decorator(decorator);
+    }
+    match &member.kind {
+        ClassMemberKind::Field { ty, init, .. } => {
+            if let Some(ty) = ty {
+                visitor.visit_type(ty);
+            }
+            if let Some(init) = init {
+                visitor.visit_expr(init);
+            }
+        },
+        ClassMemberKind::Constructor { params, body } => {
+            for param in params {
+                visitor.visit_param(param);
+            }
+            visitor.visit_block(body);
+        },
+        ClassMemberKind::Method(method) => visitor.visit_function(method),
+    }
+}
+
+pub fn walk_interface_member<'ast, V: Visitor<'ast>>(visitor: &mut V, member: &'ast InterfaceMember) {
+    match &member.kind {
+        InterfaceMemberKind::Property(ty) => visitor.visit_type(ty),
+        InterfaceMemberKind::Method {
+            type_params,
+            params,
+            return_type,
+        } => {
+            for param in type_params {
+                visitor.visit_type_param(param);
+            }
+            for param in params {
+                visitor.visit_param(param);
+            }
+            if let Some(ty) = return_type {
+                visitor.visit_type(ty);
+            }
+        },
+    }
+}
+
+pub fn walk_enum_variant<'ast, V: Visitor<'ast>>(visitor: &mut V, variant: &'ast EnumVariant) {
+    for ty in &variant.payload {
+        visitor.visit_type(ty);
+    }
+}
+
+pub fn walk_catch_clause<'ast, V: Visitor<'ast>>(visitor: &mut V, catch: &'ast CatchClause) {
+    if let Some(ty) = &catch.ty {
+        visitor.visit_type(ty);
+    }
+    visitor.visit_block(&catch.body);
+}
+
+pub fn walk_expr<'ast, V: Visitor<'ast>>(visitor: &mut V, expr: &'ast Expr) {
+    match &expr.kind {
+        ExprKind::Array(elements) => {
+            for element in elements {
+                visitor.visit_expr(element);
+            }
+        },
+        ExprKind::Object(properties) => {
+            for property in properties {
+                visitor.visit_property(property);
+            }
+        },
+        ExprKind::Spread(inner) | ExprKind::NullCheck(inner) => visitor.visit_expr(inner),
+        ExprKind::Unary { operand, .. } => visitor.visit_expr(operand),
+        ExprKind::Binary { left, right, .. } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        },
+        ExprKind::Assign { target, value, .. } => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        },
+        ExprKind::Call {
+            callee,
+            type_args,
+            args,
+            ..
+        }
+        | ExprKind::New {
+            callee,
+            type_args,
+            args,
+        } => {
+            visitor.visit_expr(callee);
+            for ty in type_args {
+                visitor.visit_type(ty);
+            }
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        },
+        ExprKind::Member { object, .. } => visitor.visit_expr(object),
+        ExprKind::Index { object, index, .. } => {
+            visitor.visit_expr(object);
+            visitor.visit_expr(index);
+        },
+        ExprKind::Is { expr, ty } | ExprKind::As { expr, ty } => {
+            visitor.visit_expr(expr);
+            visitor.visit_type(ty);
+        },
+        ExprKind::Lambda(lambda) => visitor.visit_lambda(lambda),
+        ExprKind::Match { scrutinee, arms } => {
+            visitor.visit_expr(scrutinee);
+            for arm in arms {
+                visitor.visit_match_arm(arm);
+            }
+        },
+        ExprKind::Yield { argument, .. } => {
+            if let Some(argument) = argument {
+                visitor.visit_expr(argument);
+            }
+        },
+        ExprKind::Template(template) => {
+            for value in &template.values {
+                visitor.visit_expr(value);
+            }
+        },
+        ExprKind::TaggedTemplate { tag, template } => {
+            visitor.visit_expr(tag);
+            for value in &template.values {
+                visitor.visit_expr(value);
+            }
+        },
+        ExprKind::Literal(_) | ExprKind::Ident(_) | ExprKind::This | ExprKind::Super | ExprKind::Error => {},
+    }
+}
+
+pub fn walk_property<'ast, V: Visitor<'ast>>(visitor: &mut V, property: &'ast Property) {
+    match &property.kind {
+        PropertyKind::KeyValue { key, value } => {
+            if let PropertyKey::Computed(key) = key {
+                visitor.visit_expr(key);
+            }
+            visitor.visit_expr(value);
+        },
+        PropertyKind::Spread(value) => visitor.visit_expr(value),
+        PropertyKind::Shorthand(_) => {},
+    }
+}
+
+pub fn walk_lambda<'ast, V: Visitor<'ast>>(visitor: &mut V, lambda: &'ast Lambda) {
+    for param in &lambda.params {
+        visitor.visit_param(param);
+    }
+    if let Some(ty) = &lambda.return_type {
+        visitor.visit_type(ty);
+    }
+    match &lambda.body {
+        LambdaBody::Expr(body) => visitor.visit_expr(body),
+        LambdaBody::Block(body) => visitor.visit_block(body),
+    }
+}
+
+pub fn walk_match_arm<'ast, V: Visitor<'ast>>(visitor: &mut V, arm: &'ast MatchArm) {
+    visitor.visit_pattern(&arm.pattern);
+    if let Some(guard) = &arm.guard {
+        visitor.visit_expr(guard);
+    }
+    visitor.visit_block(&arm.body);
+}
+
+pub fn walk_pattern<'ast, V: Visitor<'ast>>(visitor: &mut V, pattern: &'ast Pattern) {
+    match &pattern.kind {
+        PatternKind::Array(patterns) | PatternKind::Variant { fields: patterns, .. } | PatternKind::Or(patterns) => {
+            for pattern in patterns {
+                visitor.visit_pattern(pattern);
+            }
+        },
+        PatternKind::Object { fields, .. } => {
+            for pattern in fields.iter().map(|field| &field.pattern) {
+                visitor.visit_pattern(pattern);
+            }
+        },
+        PatternKind::Rest(pattern) => visitor.visit_pattern(pattern),
+        PatternKind::Default { pattern, default } => {
+            visitor.visit_pattern(pattern);
+            visitor.visit_expr(default);
+        },
+        PatternKind::TypeTest { ty, .. } => visitor.visit_type(ty),
+        PatternKind::Wildcard | PatternKind::Binding(_) | PatternKind::Literal(_) => {},
+    }
+}
+
+pub fn walk_type<'ast, V: Visitor<'ast>>(visitor: &mut V, ty: &'ast TypeExpr) {
+    match &ty.kind {
+        TypeExprKind::Named { args: types, .. }
+        | TypeExprKind::Tuple(types)
+        | TypeExprKind::Union(types)
+        | TypeExprKind::Intersection(types) => {
+            for ty in types {
+                visitor.visit_type(ty);
+            }
+        },
+        TypeExprKind::Array(inner) | TypeExprKind::Nullable(inner) => visitor.visit_type(inner),
+        TypeExprKind::Function { params, return_type } => {
+            for param in params {
+                visitor.visit_type(&param.ty);
+            }
+            visitor.visit_type(return_type);
+        },
+        TypeExprKind::Object(members) => {
+            for ty in members.iter().filter_map(|member| member.ty.as_ref()) {
+                visitor.visit_type(ty);
+            }
+        },
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Percorso dalla radice
+// ---------------------------------------------------------------------------
+
+/// Nodo dell'AST lungo un [`AstPath`]
+#[derive(Debug, Clone, Copy)]
+pub enum Node<'ast> {
+    Stmt(&'ast Stmt),
+    Block(&'ast Block),
+    Function(&'ast FunctionDecl),
+    Param(&'ast Param),
+    Decorator(&'ast Decorator),
+    ClassMember(&'ast ClassMember),
+    CatchClause(&'ast CatchClause),
+    Expr(&'ast Expr),
+    Lambda(&'ast Lambda),
+    MatchArm(&'ast MatchArm),
+    Pattern(&'ast Pattern),
+    Type(&'ast TypeExpr),
+}
+
+impl Node<'_> {
+    /// Vero per i nodi che hanno un corpo eseguito quando vengono chiamati:
+    /// funzioni, metodi, costruttori e lambda
+    pub fn is_function(&self) -> bool {
+        match self {
+            Node::Function(_) | Node::Lambda(_) => true,
+            Node::ClassMember(member) => matches!(member.kind, ClassMemberKind::Constructor { .. }),
+            _ => false,
+        }
+    }
+}
+
+/// Antenati del nodo visitato, dal più esterno al più interno
+#[derive(Debug, Clone, Default)]
+pub struct AstPath<'ast> {
+    nodes: Vec<Node<'ast>>,
+}
+
+impl<'ast> AstPath<'ast> {
+    /// Nodo che contiene direttamente quello visitato
+    pub fn parent(&self) -> Option<Node<'ast>> {
+        self.nodes.last().copied()
+    }
+    
+    /// Antenati dal più interno al più esterno
+    pub fn ancestors(&self) -> impl Iterator<Item = Node<'ast>> + '_ {
+        self.nodes.iter().rev().copied()
+    }
+    
+    /// Funzione, metodo, costruttore o lambda più interna che contiene il nodo
+    pub fn enclosing_function(&self) -> Option<Node<'ast>> {
+        self.ancestors().find(Node::is_function)
+    }
+    
+    /// Numero di antenati; zero per le istruzioni del programma
+    pub fn depth(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+/// Visita che riceve ogni nodo insieme al percorso dei suoi antenati
+pub trait PathVisitor<'ast> {
+    /// Chiamato entrando in `node`, prima dei suoi figli
+    fn enter(&mut self, node: Node<'ast>, path: &AstPath<'ast>);
+    
+    /// Chiamato dopo aver visitato i figli di `node`
+    fn leave(&mut self, _node: Node<'ast>, _path: &AstPath<'ast>) {}
+}
+
+/// Visita tutto il programma mantenendo il percorso dalla radice
+pub fn walk_with_path<'ast, V: PathVisitor<'ast>>(program: &'ast Program, visitor: &mut V) {
+    let mut walker = PathWalker {
+        path: AstPath::default(),
+        visitor,
+    };
+    walk_program(&mut walker, program);
+}
+
+struct PathWalker<'ast, 'v, V> {
+    path: AstPath<'ast>,
+    visitor: &'v mut V,
+}
+
+impl<'ast, V: PathVisitor<'ast>> PathWalker<'ast, '_, V> {
+    fn node(&mut self, node: Node<'ast>, walk: impl FnOnce(&mut Self)) {
+        self.visitor.enter(node, &self.path);
+        self.path.nodes.push(node);
+        walk(self);
+        self.path.nodes.pop();
+        self.visitor.leave(node, &self.path);
+    }
+}
+
+impl<'ast, V: PathVisitor<'ast>> Visitor<'ast> for PathWalker<'ast, '_, V> {
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        self.node(Node::Stmt(stmt), |this| walk_stmt(this, stmt));
+    }
+    
+    fn visit_block(&mut self, block: &'ast Block) {
+        self.node(Node::Block(block), |this| walk_block(this, block));
+    }
+    
+    fn visit_function(&mut self, function: &'ast FunctionDecl) {
+        self.node(Node::Function(function), |this| walk_function(this, function));
+    }
+    
+    fn visit_param(&mut self, param: &'ast Param) {
+        self.node(Node::Param(param), |this| walk_param(this, param));
+    }
+    
+    fn visit_decorator(&mut self, decorator: &'ast Decorator) {
+        self.node(Node::Decorator(decorator), |this| walk_decorator(this, decorator));
+    }
+    
+    fn visit_class_member(&mut self, member: &'ast ClassMember) {
+        self.node(Node::ClassMember(member), |this| walk_class_member(this, member));
+    }
+    
+    fn visit_catch_clause(&mut self, catch: &'ast CatchClause) {
+        self.node(Node::CatchClause(catch), |this| walk_catch_clause(this, catch));
+    }
+    
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        self.node(Node::Expr(expr), |this| walk_expr(this, expr));
+    }
+    
+    fn visit_lambda(&mut self, lambda: &'ast Lambda) {
+        self.node(Node::Lambda(lambda), |this| walk_lambda(this, lambda));
+    }
+    
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        self.node(Node::MatchArm(arm), |this| walk_match_arm(this, arm));
+    }
+    
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+        self.node(Node::Pattern(pattern), |this| walk_pattern(this, pattern));
+    }
+    
+    fn visit_type(&mut self, ty: &'ast TypeExpr) {
+        self.node(Node::Type(ty), |this| walk_type(this, ty));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    
+    #[test]
+    fn test_visitor_reaches_nested_nodes() {
+        struct Names(Vec<String>);
+        
+        impl<'ast> Visitor<'ast> for Names {
+            fn visit_expr(&mut self, expr: &'ast Expr) {
+                if let ExprKind::Ident(name) = &expr.kind {
+                    self.0.push(name.name.clone());
+                }
+                walk_expr(self, expr);
+            }
+        }
+        
+        let source = "\
+@logged(level)
+fn f(x = fallback)
+  let g = (y) -> match y
+    case n if n > limit: [n, ...rest]
+    default: { key: value }
+  return g(x)
+";
+        let mut names = Names(Vec::new());
+        names.visit_program(&parse(source).unwrap());
+        assert_eq!(names.0, vec!["level", "fallback", "y", "n", "limit", "n", "rest", "value", "g", "x"]);
+    }
+    
+    #[test]
+    fn test_enclosing_function() {
+        struct Enclosing(Vec<(String, Option<String>)>);
+        
+        impl<'ast> PathVisitor<'ast> for Enclosing {
+            fn enter(&mut self, node: Node<'ast>, path: &AstPath<'ast>) {
+                let Node::Expr(Expr { kind: ExprKind::Ident(name), .. }) = node else {
+                    return;
+                };
+                let function = path.enclosing_function().map(|function| match function {
+                    Node::Function(function) => function.name.name.clone(),
+                    Node::Lambda(_) => "lambda".to_string(),
+                    _ => "costruttore".to_string(),
+                });
+                self.0.push((name.name.clone(), function));
+            }
+        }
+        
+        let source = "\
+top()
+fn outer()
+  a()
+  let inner = () -> b()
+class C
+  constructor()
+    c()
+";
+        let mut enclosing = Enclosing(Vec::new());
+        walk_with_path(&parse(source).unwrap(), &mut enclosing);
+        
+        let expected = [
+            ("top", None),
+            ("a", Some("outer")),
+            ("b", Some("lambda")),
+            ("c", Some("costruttore")),
+        ];
+        let expected: Vec<(String, Option<String>)> = expected
+            .iter()
+            .map(|(name, function)| (name.to_string(), function.map(str::to_string)))
+            .collect();
+        assert_eq!(enclosing.0, expected);
+    }
+}
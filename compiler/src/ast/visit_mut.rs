@@ -0,0 +1,485 @@
+//! Visita con accesso in scrittura all'AST.
+//!
+//! Come [`Visitor`](super::visit::Visitor), ma ogni nodo è ricevuto come
+//! riferimento mutabile: una visita può modificare i nodi sul posto senza
+//! cambiarne la forma. Per sostituire un nodo con uno di tipo diverso o
+//! aggiungere istruzioni si usa [`Fold`](super::fold::Fold).
+
+use super::*;
+
+pub trait VisitorMut: Sized {
+    fn visit_program(&mut self, program: &mut Program) {
+        walk_program(self, program);
+    }
+    
+    fn visit_block(&mut self, block: &mut Block) {
+        walk_block(self, block);
+    }
+    
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt(self, stmt);
+    }
+    
+    fn visit_function(&mut self, function: &mut FunctionDecl) {
+        walk_function(self, function);
+    }
+    
+    fn visit_param(&mut self, param: &mut Param) {
+        walk_param(self, param);
+    }
+    
+    fn visit_type_param(&mut self, param: &mut TypeParam) {
+        walk_type_param(self, param);
+    }
+    
+    fn visit_decorator(&mut self, decorator: &mut Decorator) {
+        walk_decorator(self, decorator);
+    }
+    
+    fn visit_class_member(&mut self, member: &mut ClassMember) {
+        walk_class_member(self, member);
+    }
+    
+    fn visit_interface_member(&mut self, member: &mut InterfaceMember) {
+        walk_interface_member(self, member);
+    }
+    
+    fn visit_enum_variant(&mut self, variant: &mut EnumVariant) {
+        walk_enum_variant(self, variant);
+    }
+    
+    fn visit_catch_clause(&mut self, catch: &mut CatchClause) {
+        walk_catch_clause(self, catch);
+    }
+    
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr);
+    }
+    
+    fn visit_property(&mut self, property: &mut Property) {
+        walk_property(self, property);
+    }
+    
+    fn visit_lambda(&mut self, lambda: &mut Lambda) {
+        walk_lambda(self, lambda);
+    }
+    
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        walk_match_arm(self, arm);
+    }
+    
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        walk_pattern(self, pattern);
+    }
+    
+    fn visit_type(&mut self, ty: &mut TypeExpr) {
+        walk_type(self, ty);
+    }
+}
+
+pub fn walk_program<V: VisitorMut>(visitor: &mut V, program: &mut Program) {
+    for stmt in &mut program.body {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_block<V: VisitorMut>(visitor: &mut V, block: &mut Block) {
+    for stmt in &mut block.stmts {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt<V: VisitorMut>(visitor: &mut V, stmt: &mut Stmt) {
+    match &mut stmt.kind {
+        StmtKind::Expr(expr) | StmtKind::Throw(expr) => visitor.visit_expr(expr),
+        StmtKind::Var(decl) => {
+            visitor.visit_pattern(&mut decl.pattern);
+            if let Some(ty) = &mut decl.ty {
+                visitor.visit_type(ty);
+            }
+            if let Some(init) = &mut decl.init {
+                visitor.visit_expr(init);
+            }
+        },
+        StmtKind::Function(function) => visitor.visit_function(function),
+        StmtKind::Class(class) => {
+            for decorator in &mut class.decorators {
+                visitor.visit_decorator(decorator);
+            }
+            for param in &mut class.type_params {
+                visitor.visit_type_param(param);
+            }
+            for ty in class.extends.iter_mut().chain(&mut class.implements) {
+                visitor.visit_type(ty);
+            }
+            for member in &mut class.members {
+                visitor.visit_class_member(member);
+            }
+        },
+        StmtKind::Interface(interface) => {
+            for param in &mut interface.type_params {
+                visitor.visit_type_param(param);
+            }
+            for ty in &mut interface.extends {
+                visitor.visit_type(ty);
+            }
+            for member in &mut interface.members {
+                visitor.visit_interface_member(member);
+            }
+        },
+        StmtKind::TypeAlias(alias) => {
+            for param in &mut alias.type_params {
+                visitor.visit_type_param(param);
+            }
+            visitor.visit_type(&mut alias.ty);
+        },
+        StmtKind::Enum(decl) => {
+            for param in &mut decl.type_params {
+                visitor.visit_type_param(param);
+            }
+            for variant in &mut decl.variants {
+                visitor.visit_enum_variant(variant);
+            }
+        },
+        StmtKind::If(stmt) => {
+            visitor.visit_expr(&mut stmt.condition);
+            visitor.visit_block(&mut stmt.then_branch);
+            match &mut stmt.else_branch {
+                Some(ElseBranch::If(else_if)) => visitor.visit_stmt(else_if),
+                Some(ElseBranch::Block(block)) => visitor.visit_block(block),
+                None => {},
+            }
+        },
+        StmtKind::For(stmt) => {
+            visitor.visit_pattern(&mut stmt.pattern);
+            visitor.visit_expr(&mut stmt.iterable);
+            visitor.visit_block(&mut stmt.body);
+        },
+        StmtKind::While(stmt) => {
+            visitor.visit_expr(&mut stmt.condition);
+            visitor.visit_block(&mut stmt.body);
+        },
+        StmtKind::Return(value) => {
+            if let Some(value) = value {
+                visitor.visit_expr(value);
+            }
+        },
+        StmtKind::Try(stmt) => {
+            visitor.visit_block(&mut stmt.body);
+            for catch in &mut stmt.catches {
+                visitor.visit_catch_clause(catch);
+            }
+            if let Some(finally) = &mut stmt.finally {
+                visitor.visit_block(finally);
+            }
+        },
+        StmtKind::Export(ExportDecl::Declaration(inner) | ExportDecl::Default(inner)) => visitor.visit_stmt(inner),
+        StmtKind::Pragma(pragma) => {
+            for arg in &mut pragma.args {
+                visitor.visit_expr(arg);
+            }
+        },
+        StmtKind::Decorators(decorators) => {
+            for decorator in decorators {
+                visitor.visit_decorator(decorator);
+            }
+        },
+        StmtKind::Import(_)
+        | StmtKind::Export(ExportDecl::Named { .. } | ExportDecl::All { .. })
+        | StmtKind::Break
+        | StmtKind::Continue
+        | StmtKind::Error => {},
+    }
+}
+
+pub fn walk_function<V: VisitorMut>(visitor: &mut V, function: &mut FunctionDecl) {
+    for decorator in &mut function.decorators {
+        visitor.visit_decorator(decorator);
+    }
+    for param in &mut function.type_params {
+        visitor.visit_type_param(param);
+    }
+    for param in &mut function.params {
+        visitor.visit_param(param);
+    }
+    if let Some(ty) = &mut function.return_type {
+        visitor.visit_type(ty);
+    }
+    visitor.visit_block(&mut function.body);
+}
+
+pub fn walk_param<V: VisitorMut>(visitor: &mut V, param: &mut Param) {
+    visitor.visit_pattern(&mut param.pattern);
+    if let Some(ty) = &mut param.ty {
+        visitor.visit_type(ty);
+    }
+    if let Some(default) = &mut param.default {
+        visitor.visit_expr(default);
+    }
+}
+
+pub fn walk_type_param<V: VisitorMut>(visitor: &mut V, param: &mut TypeParam) {
+    if let Some(bound) = &mut param.bound {
+        visitor.visit_type(bound);
+    }
+}
+
+pub fn walk_decorator<V: VisitorMut>(visitor: &mut V, decorator: &mut Decorator) {
+    for arg in decorator.args.iter_mut().flatten() {
+        visitor.visit_expr(arg);
+    }
+}
+
+pub fn walk_class_member<V: VisitorMut>(visitor: &mut V, member: &mut ClassMember) {
+    for decorator in &mut member.decorators {
+        visitor.visit_decorator(decorator);
+    }
+    match &mut member.kind {
+        ClassMemberKind::Field { ty, init, .. } => {
+            if let Some(ty) = ty {
+                visitor.visit_type(ty);
+            }
+            if let Some(init) = init {
+                visitor.visit_expr(init);
+            }
+        },
+        ClassMemberKind::Constructor { params, body } => {
+            for param in params {
+                visitor.visit_param(param);
+            }
+            visitor.visit_block(body);
+        },
+        ClassMemberKind::Method(method) => visitor.visit_function(method),
+    }
+}
+
+pub fn walk_interface_member<V: VisitorMut>(visitor: &mut V, member: &mut InterfaceMember) {
+    match &mut member.kind {
+        InterfaceMemberKind::Property(ty) => visitor.visit_type(ty),
+        InterfaceMemberKind::Method {
+            type_params,
+            params,
+            return_type,
+        } => {
+            for param in type_params {
+                visitor.visit_type_param(param);
+            }
+            for param in params {
+                visitor.visit_param(param);
+            }
+            if let Some(ty) = return_type {
+                visitor.visit_type(ty);
+            }
+        },
+    }
+}
+
+pub fn walk_enum_variant<V: VisitorMut>(visitor: &mut V, variant: &mut EnumVariant) {
+    for ty in &mut variant.payload {
+        visitor.visit_type(ty);
+    }
+}
+
+pub fn walk_catch_clause<V: VisitorMut>(visitor: &mut V, catch: &mut CatchClause) {
+    if let Some(ty) = &mut catch.ty {
+        visitor.visit_type(ty);
+    }
+    visitor.visit_block(&mut catch.body);
+}
+
+pub fn walk_expr<V: VisitorMut>(visitor: &mut V, expr: &mut Expr) {
+    match &mut expr.kind {
+        ExprKind::Array(elements) => {
+            for element in elements {
+                visitor.visit_expr(element);
+            }
+        },
+        ExprKind::Object(properties) => {
+            for property in properties {
+                visitor.visit_property(property);
+            }
+        },
+        ExprKind::Spread(inner) | ExprKind::NullCheck(inner) => visitor.visit_expr(inner),
+        ExprKind::Unary { operand, .. } => visitor.visit_expr(operand),
+        ExprKind::Binary { left, right, .. } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        },
+        ExprKind::Assign { target, value, .. } => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        },
+        ExprKind::Call {
+            callee,
+            type_args,
+            args,
+            ..
+        }
+        | ExprKind::New {
+            callee,
+            type_args,
+            args,
+        } => {
+            visitor.visit_expr(callee);
+            for ty in type_args {
+                visitor.visit_type(ty);
+            }
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        },
+        ExprKind::Member { object, .. } => visitor.visit_expr(object),
+        ExprKind::Index { object, index, .. } => {
+            visitor.visit_expr(object);
+            visitor.visit_expr(index);
+        },
+        ExprKind::Is { expr, ty } | ExprKind::As { expr, ty } => {
+            visitor.visit_expr(expr);
+            visitor.visit_type(ty);
+        },
+        ExprKind::Lambda(lambda) => visitor.visit_lambda(lambda),
+        ExprKind::Match { scrutinee, arms } => {
+            visitor.visit_expr(scrutinee);
+            for arm in arms {
+                visitor.visit_match_arm(arm);
+            }
+        },
+        ExprKind::Yield { argument, .. } => {
+            if let Some(argument) = argument {
+                visitor.visit_expr(argument);
+            }
+        },
+        ExprKind::Template(template) => {
+            for value in &mut template.values {
+                visitor.visit_expr(value);
+            }
+        },
+        ExprKind::TaggedTemplate { tag, template } => {
+            visitor.visit_expr(tag);
+            for value in &mut template.values {
+                visitor.visit_expr(value);
+            }
+        },
+        ExprKind::Literal(_) | ExprKind::Ident(_) | ExprKind::This | ExprKind::Super | ExprKind::Error => {},
+    }
+}
+
+pub fn walk_property<V: VisitorMut>(visitor: &mut V, property: &mut Property) {
+    match &mut property.kind {
+        PropertyKind::KeyValue { key, value } => {
+            if let PropertyKey::Computed(key) = key {
+                visitor.visit_expr(key);
+            }
+            visitor.visit_expr(value);
+        },
+        PropertyKind::Spread(value) => visitor.visit_expr(value),
+        PropertyKind::Shorthand(_) => {},
+    }
+}
+
+pub fn walk_lambda<V: VisitorMut>(visitor: &mut V, lambda: &mut Lambda) {
+    for param in &mut lambda.params {
+        visitor.visit_param(param);
+    }
+    if let Some(ty) = &mut lambda.return_type {
+        visitor.visit_type(ty);
+    }
+    match &mut lambda.body {
+        LambdaBody::Expr(body) => visitor.visit_expr(body),
+        LambdaBody::Block(body) => visitor.visit_block(body),
+    }
+}
+
+pub fn walk_match_arm<V: VisitorMut>(visitor: &mut V, arm: &mut MatchArm) {
+    visitor.visit_pattern(&mut arm.pattern);
+    if let Some(guard) = &mut arm.guard {
+        visitor.visit_expr(guard);
+    }
+    visitor.visit_block(&mut arm.body);
+}
+
+pub fn walk_pattern<V: VisitorMut>(visitor: &mut V, pattern: &mut Pattern) {
+    match &mut pattern.kind {
+        PatternKind::Array(patterns) | PatternKind::Variant { fields: patterns, .. } | PatternKind::Or(patterns) => {
+            for pattern in patterns {
+                visitor.visit_pattern(pattern);
+            }
+        },
+        PatternKind::Object { fields, .. } => {
+            for pattern in fields.iter_mut().map(|field| &mut field.pattern) {
+                visitor.visit_pattern(pattern);
+            }
+        },
+        PatternKind::Rest(pattern) => visitor.visit_pattern(pattern),
+        PatternKind::Default { pattern, default } => {
+            visitor.visit_pattern(pattern);
+            visitor.visit_expr(default);
+        },
+        PatternKind::TypeTest { ty, .. } => visitor.visit_type(ty),
+        PatternKind::Wildcard | PatternKind::Binding(_) | PatternKind::Literal(_) => {},
+    }
+}
+
+pub fn walk_type<V: VisitorMut>(visitor: &mut V, ty: &mut TypeExpr) {
+    match &mut ty.kind {
+        TypeExprKind::Named { args: types, .. }
+        | TypeExprKind::Tuple(types)
+        | TypeExprKind::Union(types)
+        | TypeExprKind::Intersection(types) => {
+            for ty in types {
+                visitor.visit_type(ty);
+            }
+        },
+        TypeExprKind::Array(inner) | TypeExprKind::Nullable(inner) => visitor.visit_type(inner),
+        TypeExprKind::Function { params, return_type } => {
+            for param in params {
+                visitor.visit_type(&mut param.ty);
+            }
+            visitor.visit_type(return_type);
+        },
+        TypeExprKind::Object(members) => {
+            for ty in members.iter_mut().filter_map(|member| member.ty.as_mut()) {
+                visitor.visit_type(ty);
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    
+    #[test]
+    fn test_visitor_mut_renames_in_place() {
+        struct Rename;
+        
+        impl VisitorMut for Rename {
+            fn visit_expr(&mut self, expr: &mut Expr) {
+                if let ExprKind::Ident(name) = &mut expr.kind {
+                    name.name = name.name.to_uppercase();
+                }
+                walk_expr(self, expr);
+            }
+        }
+        
+        let source = "\
+fn f(x = a)
+  return (y) -> b
+";
+        let mut program = parse(source).unwrap();
+        Rename.visit_program(&mut program);
+        
+        let StmtKind::Function(function) = &program.body[0].kind else {
+            panic!("attesa una funzione");
+        };
+        let is_ident = |expr: &Expr, expected: &str| matches!(&expr.kind, ExprKind::Ident(name) if name.name == expected);
+        // I nomi dei pattern non sono espressioni e restano invariati
+        assert!(matches!(&function.params[0].pattern.kind, PatternKind::Binding(name) if name.name == "x"));
+        assert!(is_ident(function.params[0].default.as_ref().unwrap(), "A"));
+        let StmtKind::Return(Some(Expr { kind: ExprKind::Lambda(lambda), .. })) = &function.body.stmts[0].kind else {
+            panic!("attesa una lambda");
+        };
+        assert!(matches!(&lambda.body, LambdaBody::Expr(body) if is_ident(body, "B")));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::error::{Error, ErrorKind, Result};
+use crate::lint::{lint_source, LintLevel, LintLevels};
+use crate::utils::config::{ProjectConfig, CONFIG_FILE};
+use crate::utils::diagnostics::Level;
+use crate::utils::fix::apply_suggestions;
+use crate::utils::ice;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Numero massimo di passaggi di correzione per file. Ogni passaggio ricalcola
+/// le diagnostiche, così le correzioni scartate perché sovrapposte vengono
+/// riproposte sul sorgente aggiornato.
+const MAX_FIX_PASSES: usize = 10;
+
+/// Esegue il linter su un file o su tutti i file `.ns` di una directory.
+/// `overrides` contiene i livelli passati da riga di comando, che hanno la
+/// precedenza su decoratori e configurazione del progetto.
+pub fn execute(path: &str, fix: bool, overrides: Vec<(String, LintLevel)>) -> Result<()> {
+    let mut files = Vec::new();
+    collect_sources(Path::new(path), &mut files)?;
+    
+    let mut levels = LintLevels::new();
+    if let Some(config) = ProjectConfig::find(Path::new(path))? {
+        levels.set_config(config.lint_rules()?);
+    }
+    levels.set_command_line(overrides);
+    for selector in levels.unknown_selectors() {
+        println!("avviso: regola del linter sconosciuta: `{}`", selector);
+    }
+    
+    let mut errors = 0;
+    let mut warnings = 0;
+    
+    for file in &files {
+        let _stage = ice::enter_stage("lint", file);
+        let mut source = fs::read_to_string(file).map_err(|e| Error::io(e, file))?;
+        
+        if fix {
+            let mut applied = 0;
+            for _ in 0..MAX_FIX_PASSES {
+                let diagnostics = lint_source(&source, &levels);
+                let result = apply_suggestions(&source, diagnostics.suggestions());
+                if result.applied == 0 {
+                    break;
+                }
+                applied += result.applied;
+                source = result.source;
+            }
+            if applied > 0 {
+                fs::write(file, &source).map_err(|e| Error::io(e, file))?;
+                println!("{}: {} correzioni applicate", file.display(), applied);
+            }
+        }
+        
+        let diagnostics = lint_source(&source, &levels);
+        for diagnostic in diagnostics.into_sorted_vec() {
+            match diagnostic.level {
+                Level::Error => errors += 1,
+                Level::Warning => warnings += 1,
+            }
+            println!("{}: {}", file.display(), diagnostic);
+        }
+    }
+    
+    println!("{} errori, {} avvisi in {} file", errors, warnings, files.len());
+    
+    if errors > 0 {
+        return Err(Error::without_position(
+            ErrorKind::SyntaxError,
+            format!("Il linter ha trovato {} errori", errors),
+        ));
+    }
+    
+    Ok(())
+}
+
+/// Raccoglie ricorsivamente i file sorgente, ignorando le directory nascoste e
+/// la configurazione del progetto, che non è un modulo del programma
+fn collect_sources(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_file() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)
+        .and_then(|entries| entries.map(|entry| entry.map(|e| e.path())).collect())
+        .map_err(|e| Error::io(e, path))?;
+    entries.sort();
+    
+    for entry in entries {
+        let hidden = entry
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if entry.is_dir() {
+            collect_sources(&entry, files)?;
+        } else if entry.extension().is_some_and(|ext| ext == "ns") && !entry.ends_with(CONFIG_FILE) {
+            files.push(entry);
+        }
+    }
+    
+    Ok(())
+}
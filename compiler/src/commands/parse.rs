@@ -0,0 +1,25 @@
+use crate::ast::{dump, DumpFormat};
+use crate::error::{Error, ErrorKind, Result};
+use crate::parser::parse_with_errors;
+use std::fs;
+use std::path::Path;
+
+/// Stampa l'AST di un file. Anche in presenza di errori di sintassi l'albero
+/// viene stampato, con nodi `Error` al posto delle parti non valide, e gli
+/// errori sono elencati dopo.
+pub fn execute(file: &Path, format: DumpFormat, spans: bool) -> Result<()> {
+    let source = fs::read_to_string(file).map_err(|e| Error::io(e, file))?;
+    let (program, errors) = parse_with_errors(&source);
+    print!("{}", dump(&program, format, spans));
+    
+    if errors.is_empty() {
+        return Ok(());
+    }
+    for error in &errors {
+        eprintln!("{}: {}", file.display(), error);
+    }
+    Err(Error::without_position(
+        ErrorKind::SyntaxError,
+        format!("Trovati {} errori di sintassi", errors.len()),
+    ))
+}
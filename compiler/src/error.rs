@@ -1,9 +1,12 @@
-use crate::lexer::Position;
+use crate::lexer::{Position, Span};
+use crate::utils::diagnostics::Suggestion;
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 /// Tipi di errore del compilatore NovaScript
-#[derive(Debug, Error, PartialEq, Eq)]
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ErrorKind {
     #[error("Errore di sintassi")]
     SyntaxError,
@@ -24,21 +27,35 @@ pub enum ErrorKind {
     RuntimeError,
 }
 
-/// Errore del compilatore NovaScript
-#[derive(Debug)]
+/// Errore del compilatore NovaScript.
+///
+/// È clonabile e serializzabile, così un language server o una cache di build
+/// possono conservare e riproporre le diagnostiche. La causa, se presente, è
+/// esposta tramite `std::error::Error::source`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Error {
     pub kind: ErrorKind,
     pub message: String,
-    pub position: Option<Position>,
+    /// File in cui si è verificato l'errore
+    pub path: Option<PathBuf>,
+    /// Porzione del sorgente a cui si riferisce l'errore
+    pub span: Option<Span>,
+    /// Correzioni proposte per l'errore
+    pub suggestions: Vec<Suggestion>,
+    /// Errore che ha causato questo errore
+    pub source: Option<Box<Error>>,
 }
 
 impl Error {
-    /// Crea un nuovo errore
-    pub fn new(kind: ErrorKind, message: String, position: Position) -> Self {
+    /// Crea un nuovo errore relativo a una porzione del sorgente
+    pub fn new(kind: ErrorKind, message: String, span: Span) -> Self {
         Self {
             kind,
             message,
-            position: Some(position),
+            path: None,
+            span: Some(span),
+            suggestions: Vec::new(),
+            source: None,
         }
     }
     
@@ -47,31 +64,103 @@ impl Error {
         Self {
             kind,
             message,
-            position: None,
+            path: None,
+            span: None,
+            suggestions: Vec::new(),
+            source: None,
         }
     }
+    
+    /// Crea un errore di I/O che ricorda il file coinvolto. Non esiste una
+    /// conversione da `std::io::Error` per `?`, che perderebbe il percorso.
+    pub fn io(error: std::io::Error, path: &Path) -> Self {
+        Error::without_position(ErrorKind::IoError, error.to_string()).with_path(path)
+    }
+    
+    /// Imposta il file in cui si è verificato l'errore
+    pub fn with_path(mut self, path: &Path) -> Self {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+    
+    /// Imposta l'errore che ha causato questo errore
+    pub fn with_source(mut self, source: Error) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+    
+    /// Aggiunge una correzione proposta all'errore
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+    
+    /// Posizione di inizio dell'errore nel codice
+    pub fn position(&self) -> Option<Position> {
+        self.span.map(|span| span.start)
+    }
+    
+    /// Itera sulla catena delle cause, a partire dalla più vicina
+    pub fn causes(&self) -> impl Iterator<Item = &Error> {
+        std::iter::successors(self.source.as_deref(), |error| error.source.as_deref())
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &self.position {
-            Some(pos) => write!(f, "{} a {}:{}: {}", self.kind, pos.line, pos.column, self.message),
-            None => write!(f, "{}: {}", self.kind, self.message),
+        match (&self.path, &self.span) {
+            (Some(path), Some(span)) => write!(
+                f,
+                "{} a {}:{}:{}: {}",
+                self.kind,
+                path.display(),
+                span.start.line,
+                span.start.column,
+                self.message
+            ),
+            (Some(path), None) => write!(f, "{} in {}: {}", self.kind, path.display(), self.message),
+            (None, Some(span)) => write!(f, "{} a {}:{}: {}", self.kind, span.start.line, span.start.column, self.message),
+            (None, None) => write!(f, "{}: {}", self.kind, self.message),
         }
     }
 }
 
-impl std::error::Error for Error {}
-
-/// Conversione di errori I/O in errori NovaScript
-impl From<std::io::Error> for Error {
-    fn from(error: std::io::Error) -> Self {
-        Error::without_position(
-            ErrorKind::IoError,
-            format!("Errore I/O: {}", error),
-        )
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|error| error as &(dyn std::error::Error + 'static))
     }
 }
 
 /// Tipo Result specifico per NovaScript
-pub type Result<T> = std::result::Result<T, Error>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_io_error_keeps_path() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "file non trovato");
+        let error = Error::io(io, Path::new("src/main.ns"));
+        
+        assert_eq!(error.kind, ErrorKind::IoError);
+        assert_eq!(error.to_string(), "Errore di I/O in src/main.ns: file non trovato");
+    }
+    
+    #[test]
+    fn test_serialize_with_cause() {
+        let span = Span::new(Position::new(2, 5, 14), Position::new(2, 9, 18));
+        let cause = Error::new(ErrorKind::SyntaxError, "Stringa non terminata".to_string(), span);
+        let error = Error::without_position(ErrorKind::InternalError, "Compilazione fallita".to_string())
+            .with_path(Path::new("app.ns"))
+            .with_source(cause.clone());
+        
+        let json = serde_json::to_string(&error).unwrap();
+        let restored: Error = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, error);
+        
+        let source = std::error::Error::source(&restored).unwrap();
+        assert_eq!(source.to_string(), "Errore di sintassi a 2:5: Stringa non terminata");
+        assert_eq!(restored.causes().collect::<Vec<_>>(), vec![&cause]);
+    }
+}
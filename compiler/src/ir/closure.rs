@@ -0,0 +1,315 @@
+//! Conversione delle chiusure.
+//!
+//! Ogni lambda del programma diventa una [`Closure`] che elenca le variabili
+//! locali delle funzioni che la contengono usate nel suo corpo. Le variabili
+//! del modulo non sono catturate: restano accessibili come globali. Una
+//! lambda annidata che usa una variabile esterna la fa catturare anche alle
+//! lambda intermedie, che devono passarla verso l'interno.
+
+use std::collections::HashSet;
+
+use crate::ast::visit::{
+    walk_block, walk_catch_clause, walk_class_member, walk_expr, walk_function, walk_lambda, walk_match_arm,
+    walk_pattern, walk_program, walk_property, walk_stmt, Visitor,
+};
+use crate::ast::{
+    Block, CatchClause, ClassMember, ExportDecl, Expr, ExprKind, FunctionDecl, Ident, Lambda, MatchArm, NodeId,
+    Pattern, PatternKind, Program, Property, PropertyKind, Stmt, StmtKind,
+};
+use crate::lexer::Span;
+
+/// Lambda convertita in chiusura
+#[derive(Debug, Clone, PartialEq)]
+pub struct Closure {
+    /// Id dell'espressione `Lambda` da cui deriva
+    pub id: NodeId,
+    pub span: Span,
+    /// Variabili introdotte dai parametri, anche destrutturati
+    pub params: Vec<String>,
+    /// Variabili catturate, in ordine di primo uso
+    pub captures: Vec<Capture>,
+}
+
+/// Variabile esterna usata da una chiusura
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    pub name: String,
+    /// Primo uso della variabile nel corpo della chiusura
+    pub span: Span,
+}
+
+/// Converte tutte le lambda del programma, in ordine di apparizione
+pub fn lower_closures(program: &Program) -> Vec<Closure> {
+    let mut lowering = ClosureLowering {
+        scopes: vec![HashSet::new()],
+        active: Vec::new(),
+        closures: Vec::new(),
+    };
+    lowering.visit_program(program);
+    lowering.closures
+}
+
+struct ClosureLowering {
+    /// Nomi dichiarati in ogni scope; il primo è quello del modulo
+    scopes: Vec<HashSet<String>>,
+    /// Lambda in corso di visita: indice in `closures` e numero di scope
+    /// esterni alla lambda
+    active: Vec<(usize, usize)>,
+    closures: Vec<Closure>,
+}
+
+impl ClosureLowering {
+    fn declare(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string());
+        }
+    }
+    
+    /// Registra l'uso di un nome come cattura delle lambda che non lo dichiarano
+    fn use_name(&mut self, ident: &Ident) {
+        // I nomi del modulo e quelli mai dichiarati non vengono catturati
+        let Some(depth) = self.scopes.iter().rposition(|scope| scope.contains(&ident.name)) else {
+            return;
+        };
+        if depth == 0 {
+            return;
+        }
+        
+        for &(index, outer_scopes) in &self.active {
+            let captures = &mut self.closures[index].captures;
+            if depth < outer_scopes && !captures.iter().any(|c| c.name == ident.name) {
+                captures.push(Capture {
+                    name: ident.name.clone(),
+                    span: ident.span,
+                });
+            }
+        }
+    }
+    
+    fn scoped(&mut self, visit: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashSet::new());
+        visit(self);
+        self.scopes.pop();
+    }
+    
+    /// Dichiara i nomi del blocco prima di visitarlo, come il resolver: una
+    /// lambda può usare una variabile dichiarata dopo di lei, perché il suo
+    /// corpo viene eseguito più tardi
+    fn hoist(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            let mut stmt = stmt;
+            while let StmtKind::Export(ExportDecl::Declaration(inner) | ExportDecl::Default(inner)) = &stmt.kind {
+                stmt = inner;
+            }
+            match &stmt.kind {
+                StmtKind::Function(function) => self.declare(&function.name.name),
+                StmtKind::Class(class) => self.declare(&class.name.name),
+                StmtKind::Enum(decl) => self.declare(&decl.name.name),
+                StmtKind::Var(decl) => {
+                    let mut names = Vec::new();
+                    bindings(&decl.pattern, &mut names);
+                    for name in names {
+                        self.declare(&name.name);
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+    
+    fn lambda(&mut self, expr: &Expr, lambda: &Lambda) {
+        let mut params = Vec::new();
+        for param in &lambda.params {
+            bindings(&param.pattern, &mut params);
+        }
+        
+        let index = self.closures.len();
+        self.closures.push(Closure {
+            id: expr.id,
+            span: expr.span,
+            params: params.into_iter().map(|p| p.name.clone()).collect(),
+            captures: Vec::new(),
+        });
+        
+        self.active.push((index, self.scopes.len()));
+        self.scoped(|this| walk_lambda(this, lambda));
+        self.active.pop();
+    }
+}
+
+// Funzioni, membri, rami e lambda aprono uno scope. I decoratori sono visitati
+// al suo inizio, quando i parametri non sono ancora dichiarati.
+impl<'ast> Visitor<'ast> for ClosureLowering {
+    fn visit_program(&mut self, program: &'ast Program) {
+        self.hoist(&program.body);
+        walk_program(self, program);
+    }
+    
+    fn visit_block(&mut self, block: &'ast Block) {
+        self.scoped(|this| {
+            this.hoist(&block.stmts);
+            walk_block(this, block);
+        });
+    }
+    
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        match &stmt.kind {
+            StmtKind::For(stmt) => {
+                self.visit_expr(&stmt.iterable);
+                self.scoped(|this| {
+                    this.visit_pattern(&stmt.pattern);
+                    this.visit_block(&stmt.body);
+                });
+            },
+            StmtKind::Import(decl) => {
+                for (_, name) in decl.bindings() {
+                    self.declare(&name.name);
+                }
+            },
+            // Le riesportazioni (`from`) usano nomi di un altro modulo
+            StmtKind::Export(ExportDecl::Named { specifiers, source: None }) => {
+                for specifier in specifiers {
+                    self.use_name(&specifier.name);
+                }
+            },
+            // Il nome di una variabile è visibile già nell'inizializzatore, per
+            // le lambda ricorsive: il pattern è visitato prima del valore
+            _ => walk_stmt(self, stmt),
+        }
+    }
+    
+    fn visit_function(&mut self, function: &'ast FunctionDecl) {
+        self.scoped(|this| walk_function(this, function));
+    }
+    
+    fn visit_class_member(&mut self, member: &'ast ClassMember) {
+        self.scoped(|this| walk_class_member(this, member));
+    }
+    
+    fn visit_catch_clause(&mut self, catch: &'ast CatchClause) {
+        self.scoped(|this| {
+            if let Some(binding) = &catch.binding {
+                this.declare(&binding.name);
+            }
+            walk_catch_clause(this, catch);
+        });
+    }
+    
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        self.scoped(|this| walk_match_arm(this, arm));
+    }
+    
+    /// I pattern compaiono solo dove dichiarano variabili
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+        match &pattern.kind {
+            PatternKind::Binding(name) | PatternKind::TypeTest { binding: Some(name), .. } => self.declare(&name.name),
+            PatternKind::Object { rest: Some(rest), .. } => self.declare(&rest.name),
+            _ => {},
+        }
+        walk_pattern(self, pattern);
+    }
+    
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match &expr.kind {
+            ExprKind::Ident(ident) => self.use_name(ident),
+            ExprKind::Lambda(lambda) => self.lambda(expr, lambda),
+            _ => walk_expr(self, expr),
+        }
+    }
+    
+    fn visit_property(&mut self, property: &'ast Property) {
+        if let PropertyKind::Shorthand(name) = &property.kind {
+            self.use_name(name);
+        }
+        walk_property(self, property);
+    }
+}
+
+/// Nomi introdotti da un pattern, in ordine
+fn bindings<'a>(pattern: &'a Pattern, names: &mut Vec<&'a Ident>) {
+    match &pattern.kind {
+        PatternKind::Binding(name) | PatternKind::TypeTest { binding: Some(name), .. } => names.push(name),
+        PatternKind::Array(patterns) | PatternKind::Variant { fields: patterns, .. } => {
+            patterns.iter().for_each(|p| bindings(p, names));
+        },
+        // Le alternative introducono gli stessi nomi
+        PatternKind::Or(alternatives) => {
+            if let Some(first) = alternatives.first() {
+                bindings(first, names);
+            }
+        },
+        PatternKind::Object { fields, rest } => {
+            fields.iter().for_each(|field| bindings(&field.pattern, names));
+            names.extend(rest);
+        },
+        PatternKind::Rest(pattern) | PatternKind::Default { pattern, .. } => bindings(pattern, names),
+        PatternKind::Wildcard | PatternKind::Literal(_) | PatternKind::TypeTest { binding: None, .. } => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    
+    fn captures(closure: &Closure) -> Vec<&str> {
+        closure.captures.iter().map(|c| c.name.as_str()).collect()
+    }
+    
+    #[test]
+    fn test_captured_variables() {
+        let source = "\
+let base = 10
+fn outer(factor)
+  let offset = 1
+  let scale = (x) -> x * factor + offset + base
+  let nested = (a) ->
+    let local = a
+    return (b) -> b + local + factor
+  let fact = (n: number): number -> n * fact(n - 1)
+  let shadow = (offset, { factor }) -> offset + factor
+  return scale
+";
+        let closures = lower_closures(&parse(source).unwrap());
+        assert_eq!(closures.len(), 5);
+        
+        assert_eq!(closures[0].params, vec!["x"]);
+        assert_eq!(captures(&closures[0]), vec!["factor", "offset"]);
+        // La lambda intermedia cattura `factor` per passarlo a quella interna
+        assert_eq!(captures(&closures[1]), vec!["factor"]);
+        assert_eq!(captures(&closures[2]), vec!["local", "factor"]);
+        assert_eq!(captures(&closures[3]), vec!["fact"]);
+        assert_eq!(closures[4].params, vec!["offset", "factor"]);
+        assert!(closures[4].captures.is_empty());
+    }
+    
+    #[test]
+    fn test_lambda_in_call_arguments() {
+        let source = "\
+fn main(items, limit)
+  let kept = items.filter((item) ->
+    let size = item.size
+    return size < limit
+  )
+  items.forEach((item) -> print(item, kept))
+";
+        let closures = lower_closures(&parse(source).unwrap());
+        assert_eq!(closures.len(), 2);
+        assert_eq!(captures(&closures[0]), vec!["limit"]);
+        assert_eq!(captures(&closures[1]), vec!["kept"]);
+    }
+    
+    #[test]
+    fn test_variable_declared_after_the_lambda() {
+        let source = "\
+let b = 0
+fn outer()
+  let a = 1
+  let f = (x) -> x + a + b
+  let b = 2
+  return f
+";
+        let closures = lower_closures(&parse(source).unwrap());
+        assert_eq!(captures(&closures[0]), vec!["a", "b"]);
+    }
+}
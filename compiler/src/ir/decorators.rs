@@ -0,0 +1,210 @@
+//! Applicazione dei decoratori dell'utente.
+//!
+//! Un decoratore non predefinito è una funzione chiamata quando la
+//! dichiarazione viene definita. `@a @b(1) fn f` diventa la dichiarazione di
+//! `f` seguita da `f = a(b(1)(f))`: il decoratore più vicino è applicato per
+//! primo e il suo risultato sostituisce la funzione. I decoratori di metodi e
+//! campi ricevono la classe e il nome del membro subito dopo la dichiarazione
+//! della classe, prima di quelli della classe: `@d fn m` in `class C` diventa
+//! `d(C, "m")`.
+
+use crate::ast::visit_mut::{walk_block, walk_program, VisitorMut};
+use crate::ast::{
+    AssignOp, Block, ClassMember, ClassMemberKind, Decorator, ExportDecl, Expr, ExprKind, Ident, Literal, NodeIdGen,
+    Program, Renumber, Stmt, StmtKind,
+};
+use crate::lexer::Span;
+use crate::semantics::BuiltinDecorator;
+
+/// Inserisce dopo ogni dichiarazione decorata le chiamate ai suoi decoratori.
+/// I nodi creati prendono gli id da `ids`, che deve proseguire quello del parser.
+pub fn apply_decorators(program: &mut Program, ids: &mut NodeIdGen) {
+    DecoratorApplication { ids }.visit_program(program);
+}
+
+struct DecoratorApplication<'a> {
+    ids: &'a mut NodeIdGen,
+}
+
+impl VisitorMut for DecoratorApplication<'_> {
+    fn visit_program(&mut self, program: &mut Program) {
+        walk_program(self, program);
+        self.apply_in(&mut program.body);
+    }
+    
+    fn visit_block(&mut self, block: &mut Block) {
+        walk_block(self, block);
+        self.apply_in(&mut block.stmts);
+    }
+}
+
+impl DecoratorApplication<'_> {
+    fn apply_in(&mut self, stmts: &mut Vec<Stmt>) {
+        let mut index = 0;
+        while index < stmts.len() {
+            let applications = self.applications(&stmts[index]);
+            let count = applications.len();
+            stmts.splice(index + 1..index + 1, applications);
+            index += count + 1;
+        }
+    }
+    
+    /// Istruzioni che applicano i decoratori dell'utente di `stmt`
+    fn applications(&mut self, stmt: &Stmt) -> Vec<Stmt> {
+        let kind = match &stmt.kind {
+            StmtKind::Export(ExportDecl::Declaration(inner) | ExportDecl::Default(inner)) => &inner.kind,
+            kind => kind,
+        };
+        
+        match kind {
+            StmtKind::Function(function) => self.rebind(&function.name, &function.decorators).into_iter().collect(),
+            StmtKind::Class(class) => {
+                let mut stmts: Vec<Stmt> = class
+                    .members
+                    .iter()
+                    .flat_map(|member| self.member_applications(&class.name, member))
+                    .collect();
+                stmts.extend(self.rebind(&class.name, &class.decorators));
+                stmts
+            },
+            _ => Vec::new(),
+        }
+    }
+    
+    /// `nome = a(b(nome))`, se la dichiarazione ha decoratori dell'utente
+    fn rebind(&mut self, name: &Ident, decorators: &[Decorator]) -> Option<Stmt> {
+        let mut decorators = user_decorators(decorators).rev().peekable();
+        let span = decorators.peek()?.span;
+        
+        let mut value = self.expr(ExprKind::Ident(name.clone()), name.span);
+        for decorator in decorators {
+            value = self.call(decorator, vec![value]);
+        }
+        let assign = ExprKind::Assign {
+            op: AssignOp::Assign,
+            target: Box::new(self.expr(ExprKind::Ident(name.clone()), name.span)),
+            value: Box::new(value),
+        };
+        let assign = self.expr(assign, span);
+        Some(Stmt::new(self.ids.fresh(), StmtKind::Expr(assign), span))
+    }
+    
+    /// `decoratore(Classe, "membro")` per ogni decoratore dell'utente del membro
+    fn member_applications(&mut self, class: &Ident, member: &ClassMember) -> Vec<Stmt> {
+        let name = match &member.kind {
+            ClassMemberKind::Method(method) => &method.name,
+            ClassMemberKind::Field { name, .. } => name,
+            ClassMemberKind::Constructor { .. } => return Vec::new(),
+        };
+        
+        user_decorators(&member.decorators)
+            .rev()
+            .map(|decorator| {
+                let args = vec![
+                    self.expr(ExprKind::Ident(class.clone()), class.span),
+                    self.expr(ExprKind::Literal(Literal::String(name.name.clone())), name.span),
+                ];
+                let call = self.call(decorator, args);
+                let span = call.span;
+                Stmt::new(self.ids.fresh(), StmtKind::Expr(call), span)
+            })
+            .collect()
+    }
+    
+    /// Chiama il decoratore con `args`; `@d(x)` viene prima chiamato con i suoi argomenti
+    fn call(&mut self, decorator: &Decorator, args: Vec<Expr>) -> Expr {
+        let mut callee = self.expr(ExprKind::Ident(decorator.name.clone()), decorator.name.span);
+        if let Some(decorator_args) = &decorator.args {
+            // Gli argomenti restano anche sul decoratore: la copia ha id nuovi
+            let decorator_args = decorator_args.iter().map(|arg| Renumber { ids: self.ids }.expr(arg)).collect();
+            callee = self.call_expr(callee, decorator_args, decorator.span);
+        }
+        self.call_expr(callee, args, decorator.span)
+    }
+    
+    fn call_expr(&mut self, callee: Expr, args: Vec<Expr>, span: Span) -> Expr {
+        let kind = ExprKind::Call {
+            callee: Box::new(callee),
+            type_args: Vec::new(),
+            args,
+            optional: false,
+        };
+        self.expr(kind, span)
+    }
+    
+    fn expr(&mut self, kind: ExprKind, span: Span) -> Expr {
+        Expr::new(self.ids.fresh(), kind, span)
+    }
+}
+
+fn user_decorators(decorators: &[Decorator]) -> impl DoubleEndedIterator<Item = &Decorator> {
+    decorators.iter().filter(|d| BuiltinDecorator::from_name(&d.name.name).is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::renumber::all_ids;
+    use crate::parser::parse_with_node_ids;
+    use std::collections::HashSet;
+    
+    /// Scrive la chiamata generata, con argomenti e destinazioni semplici
+    fn render(expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Ident(name) => name.name.clone(),
+            ExprKind::Literal(Literal::String(value)) => format!("{:?}", value),
+            ExprKind::Literal(Literal::Number(value)) => value.to_string(),
+            ExprKind::Call { callee, args, .. } => {
+                let args: Vec<String> = args.iter().map(render).collect();
+                format!("{}({})", render(callee), args.join(", "))
+            },
+            ExprKind::Assign { target, value, .. } => format!("{} = {}", render(target), render(value)),
+            other => panic!("espressione non prevista: {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_user_decorators_are_applied() {
+        let source = "\
+@logged
+@retry(3, backoff(2))
+@deprecated
+fn fetch()
+  return 1
+
+@logged
+class Service
+  @observable
+  value = 1
+  
+  @inline
+  fn stop()
+    return 0
+";
+        let (mut program, mut ids) = parse_with_node_ids(source).unwrap();
+        let parsed = ids.count();
+        apply_decorators(&mut program, &mut ids);
+        
+        let generated: Vec<String> = program
+            .body
+            .iter()
+            .filter_map(|stmt| match &stmt.kind {
+                StmtKind::Expr(expr) => Some(render(expr)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(generated, vec![
+            "fetch = logged(retry(3, backoff(2))(fetch))",
+            "observable(Service, \"value\")",
+            "Service = logged(Service)",
+        ]);
+        assert!(matches!(program.body[1].kind, StmtKind::Expr(_)));
+        
+        // I nodi creati hanno id nuovi e distinti
+        let created: Vec<u32> = program.body.iter().filter(|stmt| stmt.id.0 >= parsed).map(|stmt| stmt.id.0).collect();
+        assert_eq!(created.len(), 3);
+        assert!(created.windows(2).all(|pair| pair[0] < pair[1] && pair[1] < ids.count()));
+        let all = all_ids(&program);
+        assert_eq!(all.iter().collect::<HashSet<_>>().len(), all.len(), "id ripetuti");
+    }
+}
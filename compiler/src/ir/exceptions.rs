@@ -0,0 +1,281 @@
+//! Abbassamento delle clausole `catch`.
+//!
+//! Un `try` con più clausole diventa un `try` con un'unica clausola senza
+//! tipo, che riceve il valore lanciato in una variabile `@error` e prova i
+//! tipi nell'ordine in cui sono scritti:
+//!
+//! ```text
+//! try                          try
+//!   f()                          f()
+//! catch e: NotFound            catch @error
+//!   a(e)              -->        if @error is NotFound
+//! catch e: Timeout                 let e: NotFound = @error
+//!   b()                            a(e)
+//! finally                        else
+//!   close()                        if @error is Timeout
+//!                                    b()
+//!                                  else
+//!                                    throw @error
+//!                              finally
+//!                                close()
+//! ```
+//!
+//! Senza una clausola che catturi qualsiasi valore l'eccezione viene
+//! rilanciata e risale al chiamante; il `finally` resta al suo posto e viene
+//! eseguito in ogni caso. Il `try` che ne risulta ha sempre la forma che la
+//! generazione del codice sa tradurre in WebAssembly, descritta nella sezione
+//! sulle eccezioni di `docs/compiler_architecture.md`.
+
+use crate::ast::visit_mut::{walk_stmt, VisitorMut};
+use crate::ast::{
+    Block, CatchClause, ElseBranch, Expr, ExprKind, Ident, IfStmt, NodeId, NodeIdGen, Pattern, PatternKind, Program,
+    Renumber, Stmt, StmtKind, TryStmt, TypeExpr, VarDecl, VarKind,
+};
+use crate::lexer::Span;
+use crate::semantics::exceptions::is_catch_all;
+
+/// Riduce ogni `try` a una sola clausola `catch` senza tipo. I nodi creati
+/// prendono gli id da `ids`, che deve proseguire quello del parser.
+pub fn lower_exceptions(program: &mut Program, ids: &mut NodeIdGen) {
+    ExceptionLowering { ids }.visit_program(program);
+}
+
+/// Come [`lower_exceptions`], per i `try` di un solo blocco
+pub(super) fn lower_exceptions_in(block: &mut Block, ids: &mut NodeIdGen) {
+    ExceptionLowering { ids }.visit_block(block);
+}
+
+struct ExceptionLowering<'a> {
+    ids: &'a mut NodeIdGen,
+}
+
+impl VisitorMut for ExceptionLowering<'_> {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt(self, stmt);
+        
+        if let StmtKind::Try(try_stmt) = &mut stmt.kind {
+            self.lower_try(try_stmt, stmt.id, stmt.span);
+        }
+    }
+}
+
+impl ExceptionLowering<'_> {
+    fn lower_try(&mut self, try_stmt: &mut TryStmt, id: NodeId, span: Span) {
+        let already_lowered = matches!(try_stmt.catches.as_slice(), [catch] if catch.ty.is_none());
+        if try_stmt.catches.is_empty() || already_lowered {
+            return;
+        }
+        
+        let error = Ident::new(&format!("@error{}", id.0), span);
+        
+        // Dall'ultima clausola alla prima: ognuna prova il suo tipo e altrimenti
+        // passa alle successive, fino a rilanciare l'eccezione
+        let throw = StmtKind::Throw(self.ident(&error));
+        let mut otherwise = vec![self.stmt(throw, span)];
+        for catch in std::mem::take(&mut try_stmt.catches).into_iter().rev() {
+            let catch_all = is_catch_all(&catch);
+            let mut body = Vec::with_capacity(catch.body.stmts.len() + 1);
+            if let Some(binding) = &catch.binding {
+                // Il tipo compare anche nel controllo `is`: la copia ha id nuovi
+                let ty = catch.ty.as_ref().map(|ty| Renumber { ids: self.ids }.ty(ty));
+                body.push(self.bind(binding, ty, &error));
+            }
+            body.extend(catch.body.stmts);
+            
+            otherwise = match catch.ty {
+                Some(ty) if !catch_all => {
+                    let condition = Expr::new(
+                        self.ids.fresh(),
+                        ExprKind::Is {
+                            expr: Box::new(self.ident(&error)),
+                            ty,
+                        },
+                        catch.span,
+                    );
+                    let if_stmt = IfStmt {
+                        condition,
+                        then_branch: Block {
+                            stmts: body,
+                            span: catch.body.span,
+                        },
+                        else_branch: Some(ElseBranch::Block(Block { stmts: otherwise, span })),
+                    };
+                    vec![self.stmt(StmtKind::If(if_stmt), catch.span)]
+                },
+                // Le clausole successive non possono essere raggiunte
+                _ => body,
+            };
+        }
+        
+        try_stmt.catches = vec![CatchClause {
+            id: self.ids.fresh(),
+            binding: Some(error),
+            ty: None,
+            body: Block { stmts: otherwise, span },
+            span,
+        }];
+    }
+    
+    /// `let nome: Tipo = @error`
+    fn bind(&mut self, binding: &Ident, ty: Option<TypeExpr>, error: &Ident) -> Stmt {
+        let decl = VarDecl {
+            kind: VarKind::Let,
+            pattern: Pattern {
+                id: self.ids.fresh(),
+                kind: PatternKind::Binding(binding.clone()),
+                span: binding.span,
+            },
+            ty,
+            init: Some(self.ident(error)),
+        };
+        self.stmt(StmtKind::Var(decl), binding.span)
+    }
+    
+    fn ident(&mut self, name: &Ident) -> Expr {
+        Expr::new(self.ids.fresh(), ExprKind::Ident(name.clone()), name.span)
+    }
+    
+    fn stmt(&mut self, kind: StmtKind, span: Span) -> Stmt {
+        Stmt::new(self.ids.fresh(), kind, span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::TypeExprKind;
+    use crate::ast::renumber::all_ids;
+    use crate::parser::parse_with_node_ids;
+    use std::collections::HashSet;
+    
+    /// Scrive le istruzioni abbassate, una per riga e indentate
+    fn render(stmts: &[Stmt], depth: usize, out: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+        for stmt in stmts {
+            match &stmt.kind {
+                StmtKind::Expr(Expr { kind: ExprKind::Call { callee, .. }, .. }) => {
+                    out.push(format!("{}{}()", indent, expr(callee)));
+                },
+                StmtKind::Var(decl) => {
+                    let PatternKind::Binding(name) = &decl.pattern.kind else {
+                        panic!("atteso un nome");
+                    };
+                    let ty = if decl.ty.is_some() { ": T" } else { "" };
+                    out.push(format!("{}let {}{} = {}", indent, name, ty, expr(decl.init.as_ref().unwrap())));
+                },
+                StmtKind::Throw(value) => out.push(format!("{}throw {}", indent, expr(value))),
+                StmtKind::If(if_stmt) => {
+                    out.push(format!("{}if {}", indent, expr(&if_stmt.condition)));
+                    render(&if_stmt.then_branch.stmts, depth + 1, out);
+                    let Some(ElseBranch::Block(block)) = &if_stmt.else_branch else {
+                        panic!("atteso un else");
+                    };
+                    out.push(format!("{}else", indent));
+                    render(&block.stmts, depth + 1, out);
+                },
+                StmtKind::Try(try_stmt) => {
+                    out.push(format!("{}try", indent));
+                    render(&try_stmt.body.stmts, depth + 1, out);
+                    for catch in &try_stmt.catches {
+                        out.push(format!("{}catch {}", indent, catch.binding.as_ref().unwrap()));
+                        render(&catch.body.stmts, depth + 1, out);
+                    }
+                    if let Some(finally) = &try_stmt.finally {
+                        out.push(format!("{}finally", indent));
+                        render(&finally.stmts, depth + 1, out);
+                    }
+                },
+                other => panic!("istruzione non prevista: {:?}", other),
+            }
+        }
+    }
+    
+    fn expr(expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Ident(name) => name.name.clone(),
+            ExprKind::Is { expr: value, ty } => match &ty.kind {
+                TypeExprKind::Named { name, .. } => format!("{} is {}", self::expr(value), name),
+                other => panic!("tipo non previsto: {:?}", other),
+            },
+            other => panic!("espressione non prevista: {:?}", other),
+        }
+    }
+    
+    fn lowered(source: &str) -> Vec<String> {
+        let (mut program, mut ids) = parse_with_node_ids(source).unwrap();
+        let parsed = ids.count();
+        lower_exceptions(&mut program, &mut ids);
+        
+        let all = all_ids(&program);
+        assert_eq!(all.iter().collect::<HashSet<_>>().len(), all.len(), "id ripetuti");
+        
+        // La clausola unica è un nodo nuovo, con un id mai usato dal parser
+        for stmt in &program.body {
+            if let StmtKind::Try(try_stmt) = &stmt.kind {
+                assert!((parsed..ids.count()).contains(&try_stmt.catches[0].id.0));
+            }
+        }
+        
+        let mut out = Vec::new();
+        render(&program.body, 0, &mut out);
+        out
+    }
+    
+    #[test]
+    fn test_typed_catches_are_tried_in_order() {
+        let source = "\
+try
+  f()
+catch e: NotFound
+  a()
+catch (t: Timeout)
+  b()
+finally
+  close()
+";
+        assert_eq!(lowered(source), vec![
+            "try",
+            "  f()",
+            "catch @error16",
+            "  if @error16 is NotFound",
+            "    let e: T = @error16",
+            "    a()",
+            "  else",
+            "    if @error16 is Timeout",
+            "      let t: T = @error16",
+            "      b()",
+            "    else",
+            "      throw @error16",
+            "finally",
+            "  close()",
+        ]);
+    }
+    
+    #[test]
+    fn test_catch_all_ends_the_chain() {
+        let source = "\
+try
+  try
+    f()
+  catch e: any
+    a()
+catch e: IoError
+  b()
+catch err
+  c()
+catch e: Other
+  d()
+";
+        let lowered = lowered(source);
+        assert_eq!(lowered[..6], ["try", "  try", "    f()", "  catch @error8", "    let e: T = @error8", "    a()"]);
+        assert_eq!(lowered[6..], [
+            "catch @error23",
+            "  if @error23 is IoError",
+            "    let e: T = @error23",
+            "    b()",
+            "  else",
+            "    let err = @error23",
+            "    c()",
+        ]);
+    }
+}
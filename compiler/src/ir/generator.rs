@@ -0,0 +1,2076 @@
+//! Abbassamento dei generatori in macchine a stati.
+//!
+//! Il corpo di un `fn*` viene eseguito a tratti: ogni `yield` sospende la
+//! funzione e la chiamata successiva a `next()` riprende subito dopo. Il passo
+//! trasforma il generatore in una funzione ordinaria che restituisce
+//! `@generator(passo)`: `passo` è una lambda che riceve il valore passato a
+//! `next()` ed esegue il corpo dallo stato salvato in `@state` fino al
+//! `yield` successivo, restituendo l'oggetto `{ value, done }`:
+//!
+//! ```text
+//! fn* count(n)                 fn count(n)
+//!   let i = 0                    let @state = 0
+//!   while i < n                  let i
+//!     yield i          -->       return @generator((@sent) ->
+//!     i += 1                       while true
+//!                                    match @state
+//!                                      case 0:
+//!                                        i = 0
+//!                                        @state = 1
+//!                                        continue
+//!                                      case 1:
+//!                                        if i < n
+//!                                          @state = 2
+//!                                        else
+//!                                          @state = 3
+//!                                        continue
+//!                                      case 2:
+//!                                        @state = 4
+//!                                        return { value: i, done: false }
+//!                                      case 3:
+//!                                        @state = -1
+//!                                        return { value: null, done: true }
+//!                                      case 4:
+//!                                        i += 1
+//!                                        @state = 1
+//!                                        continue
+//!                                      case _:
+//!                                        return { value: null, done: true }
+//!                                  )
+//! ```
+//!
+//! Le variabili del corpo diventano variabili della funzione esterna, catturate
+//! dalla lambda, e sopravvivono così ai `yield`; quelle con lo stesso nome in
+//! scope diversi ricevono nomi distinti (`x@1`). Le istruzioni senza `yield`
+//! restano intere dentro uno stato. Un'espressione che contiene `yield` viene
+//! divisa: gli operandi valutati prima del `yield` sono salvati in temporanei
+//! `@tN`, mentre `&&`, `||`, `??`, le catene opzionali e i `match` diventano
+//! salti tra stati. Il valore di un `yield` è il parametro `@sent` del passo e
+//! `yield*` ripete il protocollo degli iteratori sull'iterabile delegato.
+//!
+//! Un `try` che contiene `yield` diventa un `try` intorno al dispatch: il
+//! valore lanciato passa allo stato del gestore di chi l'ha lanciato. Un
+//! `finally` riceve in `@nextN` lo stato da cui proseguire, così `break`,
+//! `continue`, `return` ed eccezioni lo attraversano prima di uscire.
+//!
+//! Le lambda create in un ciclo diviso tra stati condividono le variabili del
+//! corpo del ciclo, che sono variabili del frame. Il risultato usa solo
+//! funzioni, chiusure, cicli e `match`, quindi la generazione del codice
+//! WebAssembly non ha casi propri per i generatori; il contratto di
+//! `@generator` è descritto in `docs/runtime.md`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::ast::fold::{fold_expr, fold_stmt, Fold};
+use crate::ast::visit::{self, Visitor};
+use crate::ast::visit_mut::{
+    walk_block, walk_catch_clause, walk_class_member, walk_expr, walk_function, walk_lambda, walk_match_arm,
+    walk_pattern, walk_property, walk_stmt, VisitorMut,
+};
+use crate::ast::{
+    AssignOp, BinaryOp, Block, CatchClause, ClassMember, ClassMemberKind, ElseBranch, Expr, ExprKind, FunctionDecl,
+    Ident, IfStmt, Lambda, LambdaBody, Literal, MatchArm, NodeId, NodeIdGen, Param, Pattern, PatternKind, Program,
+    Property, PropertyKey, PropertyKind, Renumber, Stmt, StmtKind, TryStmt, TypeExpr, UnaryOp, VarDecl, VarKind,
+    WhileStmt,
+};
+use crate::lexer::Span;
+
+use super::exceptions::lower_exceptions_in;
+use super::iteration::{lower_for_in_block, ITERATOR};
+
+/// Funzione di runtime che crea un generatore dalla lambda che ne esegue un passo
+const GENERATOR: &str = "@generator";
+/// Stato da cui riprende il passo successivo; `-1` a generatore terminato
+const STATE: &str = "@state";
+/// Parametro del passo: il valore passato a `next()`, che è il valore del
+/// `yield` da cui si riprende
+const SENT: &str = "@sent";
+/// Valore lanciato durante il passo, in attesa del suo gestore
+const THROWN: &str = "@thrown";
+/// Valore di un `return` che attraversa un `finally`
+const RESULT: &str = "@result";
+
+/// Trasforma ogni generatore del programma, compresi metodi e funzioni
+/// annidate, in una funzione che restituisce la sua macchina a stati. I nodi
+/// creati prendono gli id da `ids`, che deve proseguire quello del parser.
+pub fn lower_generators(program: &mut Program, ids: &mut NodeIdGen) {
+    let mut variants = Variants(HashSet::new());
+    variants.visit_program(program);
+    GeneratorLowering {
+        ids,
+        variants: &variants.0,
+    }
+    .visit_program(program);
+}
+
+/// Nomi delle varianti degli enum del programma
+struct Variants(HashSet<String>);
+
+impl<'ast> Visitor<'ast> for Variants {
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        if let StmtKind::Enum(decl) = &stmt.kind {
+            self.0.extend(decl.variants.iter().map(|variant| variant.name.name.clone()));
+        }
+        visit::walk_stmt(self, stmt);
+    }
+}
+
+struct GeneratorLowering<'a> {
+    ids: &'a mut NodeIdGen,
+    variants: &'a HashSet<String>,
+}
+
+impl VisitorMut for GeneratorLowering<'_> {
+    fn visit_function(&mut self, function: &mut FunctionDecl) {
+        // I generatori annidati sono abbassati prima di quello che li contiene
+        walk_function(self, function);
+        if function.is_generator {
+            self.lower(function);
+        }
+    }
+}
+
+impl GeneratorLowering<'_> {
+    fn lower(&mut self, function: &mut FunctionDecl) {
+        let span = function.body.span;
+        let mut body = std::mem::replace(&mut function.body, Block { stmts: Vec::new(), span });
+        // Da qui in poi un `try` ha al più una clausola senza tipo e non ci sono `for`
+        lower_exceptions_in(&mut body, self.ids);
+        lower_for_in_block(&mut body, self.ids);
+        
+        let mut params = HashSet::new();
+        for param in &mut function.params {
+            bindings_mut(&mut param.pattern, &mut |_, name| {
+                params.insert(name.name.clone());
+            });
+        }
+        let (slots, variant_patterns) = rename_frame(&mut body, params, self.variants, self.ids);
+        
+        let mut machine = Machine::new(self.ids, span, variant_patterns);
+        machine.lower_stmts(body.stmts);
+        // Alla fine del corpo il generatore termina senza valore
+        let end = machine.return_stmts(None);
+        machine.push_all(end);
+        function.body = machine.finish(slots);
+        function.is_generator = false;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Variabili del frame
+// ---------------------------------------------------------------------------
+
+/// Rinomina le variabili del corpo che diventano variabili del frame e
+/// restituisce i loro nomi, insieme ai pattern `Binding` che indicano una
+/// variante di enum
+fn rename_frame(
+    body: &mut Block,
+    params: HashSet<String>,
+    variants: &HashSet<String>,
+    ids: &mut NodeIdGen,
+) -> (Vec<String>, HashSet<NodeId>) {
+    // Una prima visita su una copia trova i nomi esterni usati nel corpo, che le
+    // variabili del frame non devono nascondere
+    let mut probe = Frame::new(ids, variants, params.clone());
+    probe.visit_block(&mut body.clone());
+    let mut taken = params;
+    taken.extend(probe.free);
+    
+    let mut frame = Frame::new(ids, variants, taken);
+    frame.visit_block(body);
+    (frame.slots, frame.variant_patterns)
+}
+
+struct Frame<'a> {
+    ids: &'a mut NodeIdGen,
+    variants: &'a HashSet<String>,
+    /// Per ogni scope, nome nel sorgente e nome dopo l'abbassamento
+    scopes: Vec<HashMap<String, String>>,
+    /// Nomi che una variabile del frame non può prendere: i parametri, i nomi
+    /// esterni usati nel corpo e le altre variabili del frame
+    taken: HashSet<String>,
+    /// Nomi usati nel corpo e dichiarati fuori
+    free: HashSet<String>,
+    slots: Vec<String>,
+    variant_patterns: HashSet<NodeId>,
+    /// Le dichiarazioni dello scope corrente diventano variabili del frame:
+    /// vale per il corpo e per i blocchi delle istruzioni divise tra stati
+    hoist: bool,
+}
+
+impl<'a> Frame<'a> {
+    fn new(ids: &'a mut NodeIdGen, variants: &'a HashSet<String>, taken: HashSet<String>) -> Self {
+        Self {
+            ids,
+            variants,
+            scopes: Vec::new(),
+            taken,
+            free: HashSet::new(),
+            slots: Vec::new(),
+            variant_patterns: HashSet::new(),
+            hoist: true,
+        }
+    }
+    
+    fn scoped(&mut self, hoist: bool, visit: impl FnOnce(&mut Self)) {
+        let enclosing = std::mem::replace(&mut self.hoist, hoist);
+        self.scopes.push(HashMap::new());
+        visit(self);
+        self.scopes.pop();
+        self.hoist = enclosing;
+    }
+    
+    /// Dichiara `name` nello scope corrente. Nel frame prende un nome libero e,
+    /// se è una variabile, uno slot; le funzioni e le classi sono spostate
+    /// nella funzione esterna con il loro nuovo nome.
+    fn declare(&mut self, name: &mut Ident, slot: bool) {
+        let original = name.name.clone();
+        if self.hoist {
+            let mut suffix = 0;
+            while self.taken.contains(&name.name) {
+                suffix += 1;
+                name.name = format!("{}@{}", original, suffix);
+            }
+            self.taken.insert(name.name.clone());
+            if slot {
+                self.slots.push(name.name.clone());
+            }
+        }
+        let renamed = name.name.clone();
+        self.scopes.last_mut().expect("scope del blocco").insert(original, renamed);
+    }
+    
+    fn lookup(&mut self, name: &mut Ident) {
+        match self.scopes.iter().rev().find_map(|scope| scope.get(&name.name)).cloned() {
+            Some(renamed) => name.name = renamed,
+            None => {
+                self.free.insert(name.name.clone());
+            },
+        }
+    }
+    
+    fn is_declared(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.contains_key(name))
+    }
+    
+    /// Dichiarazioni di un blocco, visibili dall'inizio del blocco come nel resolver
+    fn declare_stmt(&mut self, stmt: &mut Stmt) {
+        match &mut stmt.kind {
+            StmtKind::Var(decl) => self.declare_pattern(&mut decl.pattern, false),
+            StmtKind::Function(function) => self.declare(&mut function.name, false),
+            StmtKind::Class(class) => self.declare(&mut class.name, false),
+            StmtKind::Enum(decl) => self.declare(&mut decl.name, false),
+            _ => {},
+        }
+    }
+    
+    fn declare_pattern(&mut self, pattern: &mut Pattern, in_case: bool) {
+        match &mut pattern.kind {
+            // Come nel resolver, in un `case` il nome di una variante non dichiara niente
+            PatternKind::Binding(name)
+                if in_case && self.variants.contains(&name.name) && !self.is_declared(&name.name) =>
+            {
+                self.variant_patterns.insert(pattern.id);
+            },
+            PatternKind::Binding(name) | PatternKind::TypeTest { binding: Some(name), .. } => self.declare(name, true),
+            PatternKind::Object { fields, rest } => {
+                for field in fields {
+                    self.declare_pattern(&mut field.pattern, in_case);
+                }
+                if let Some(rest) = rest {
+                    self.declare(rest, true);
+                }
+            },
+            PatternKind::Array(patterns) | PatternKind::Variant { fields: patterns, .. } => {
+                for pattern in patterns {
+                    self.declare_pattern(pattern, in_case);
+                }
+            },
+            PatternKind::Rest(pattern) | PatternKind::Default { pattern, .. } => self.declare_pattern(pattern, in_case),
+            // Le alternative successive alla prima legano gli stessi nomi
+            PatternKind::Or(alternatives) => {
+                let Some((first, rest)) = alternatives.split_first_mut() else {
+                    return;
+                };
+                self.declare_pattern(first, in_case);
+                let scope = self.scopes.last().expect("scope del blocco");
+                for alternative in rest {
+                    bindings_mut(alternative, &mut |id, name| match scope.get(&name.name) {
+                        Some(renamed) => name.name = renamed.clone(),
+                        None => {
+                            self.variant_patterns.insert(id);
+                        },
+                    });
+                }
+            },
+            PatternKind::TypeTest { binding: None, .. } | PatternKind::Wildcard | PatternKind::Literal(_) => {},
+        }
+    }
+    
+    fn declare_params(&mut self, params: &mut [Param]) {
+        for param in params {
+            self.declare_pattern(&mut param.pattern, false);
+        }
+    }
+}
+
+impl VisitorMut for Frame<'_> {
+    fn visit_block(&mut self, block: &mut Block) {
+        let hoist = self.hoist;
+        self.scoped(hoist, |frame| {
+            for stmt in &mut block.stmts {
+                frame.declare_stmt(stmt);
+            }
+            walk_block(frame, block);
+        });
+    }
+    
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        let hoist = self.hoist && splits(stmt);
+        let enclosing = std::mem::replace(&mut self.hoist, hoist);
+        match &mut stmt.kind {
+            // Il pattern è già dichiarato all'inizio del blocco
+            StmtKind::Var(decl) => {
+                if let Some(init) = &mut decl.init {
+                    self.visit_expr(init);
+                }
+                self.visit_pattern(&mut decl.pattern);
+            },
+            _ => walk_stmt(self, stmt),
+        }
+        self.hoist = enclosing;
+    }
+    
+    /// I locali delle funzioni annidate restano locali
+    fn visit_function(&mut self, function: &mut FunctionDecl) {
+        self.scoped(false, |frame| {
+            frame.declare_params(&mut function.params);
+            walk_function(frame, function);
+        });
+    }
+    
+    fn visit_class_member(&mut self, member: &mut ClassMember) {
+        self.scoped(false, |frame| {
+            if let ClassMemberKind::Constructor { params, .. } = &mut member.kind {
+                frame.declare_params(params);
+            }
+            walk_class_member(frame, member);
+        });
+    }
+    
+    fn visit_lambda(&mut self, lambda: &mut Lambda) {
+        self.scoped(false, |frame| {
+            frame.declare_params(&mut lambda.params);
+            walk_lambda(frame, lambda);
+        });
+    }
+    
+    fn visit_catch_clause(&mut self, catch: &mut CatchClause) {
+        let hoist = self.hoist;
+        self.scoped(hoist, |frame| {
+            if let Some(binding) = &mut catch.binding {
+                frame.declare(binding, true);
+            }
+            walk_catch_clause(frame, catch);
+        });
+    }
+    
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        match &mut expr.kind {
+            ExprKind::Ident(name) => self.lookup(name),
+            ExprKind::Match { scrutinee, arms } => {
+                self.visit_expr(scrutinee);
+                let hoist = self.hoist && arms.iter().any(splits_arm);
+                for arm in arms {
+                    self.scoped(hoist, |frame| {
+                        frame.declare_pattern(&mut arm.pattern, true);
+                        walk_match_arm(frame, arm);
+                    });
+                }
+            },
+            _ => walk_expr(self, expr),
+        }
+    }
+    
+    /// `{ nome }` diventa `{ nome: nuovo }` se la variabile è stata rinominata
+    fn visit_property(&mut self, property: &mut Property) {
+        let PropertyKind::Shorthand(name) = &property.kind else {
+            return walk_property(self, property);
+        };
+        let mut value = name.clone();
+        self.lookup(&mut value);
+        if value.name != name.name {
+            let key = PropertyKey::Ident(name.clone());
+            let value = Expr::new(self.ids.fresh(), ExprKind::Ident(value), property.span);
+            property.kind = PropertyKind::KeyValue { key, value };
+        }
+    }
+    
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        if let PatternKind::Variant { enum_name: Some(name), .. } = &mut pattern.kind {
+            self.lookup(name);
+        }
+        walk_pattern(self, pattern);
+    }
+}
+
+/// Chiama `f` con i nomi legati da un pattern e l'id del pattern che li lega
+fn bindings_mut(pattern: &mut Pattern, f: &mut impl FnMut(NodeId, &mut Ident)) {
+    match &mut pattern.kind {
+        PatternKind::Binding(name) | PatternKind::TypeTest { binding: Some(name), .. } => f(pattern.id, name),
+        PatternKind::Object { fields, rest } => {
+            for field in fields {
+                bindings_mut(&mut field.pattern, f);
+            }
+            if let Some(rest) = rest {
+                f(pattern.id, rest);
+            }
+        },
+        PatternKind::Array(patterns) | PatternKind::Variant { fields: patterns, .. } | PatternKind::Or(patterns) => {
+            for pattern in patterns {
+                bindings_mut(pattern, f);
+            }
+        },
+        PatternKind::Rest(pattern) | PatternKind::Default { pattern, .. } => bindings_mut(pattern, f),
+        PatternKind::TypeTest { binding: None, .. } | PatternKind::Wildcard | PatternKind::Literal(_) => {},
+    }
+}
+
+/// Cerca ciò che obbliga a dividere un nodo tra più stati: un `yield`, oppure
+/// un `return` dentro un ciclo, perché da un ciclo rimasto intero non si può
+/// saltare allo stato di un `finally`
+#[derive(Default)]
+struct Splits {
+    found: bool,
+    loops: usize,
+}
+
+impl<'ast> Visitor<'ast> for Splits {
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        match &stmt.kind {
+            _ if self.found => {},
+            StmtKind::Return(_) if self.loops > 0 => self.found = true,
+            // Le funzioni annidate hanno i loro `return` e nessun `yield` del generatore
+            StmtKind::Function(_) | StmtKind::Class(_) => {},
+            StmtKind::While(_) | StmtKind::For(_) => {
+                self.loops += 1;
+                visit::walk_stmt(self, stmt);
+                self.loops -= 1;
+            },
+            _ => visit::walk_stmt(self, stmt),
+        }
+    }
+    
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match &expr.kind {
+            _ if self.found => {},
+            ExprKind::Yield { .. } => self.found = true,
+            ExprKind::Lambda(_) => {},
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+}
+
+fn splits(stmt: &Stmt) -> bool {
+    let mut splits = Splits::default();
+    splits.visit_stmt(stmt);
+    splits.found
+}
+
+fn splits_expr(expr: &Expr) -> bool {
+    let mut splits = Splits::default();
+    splits.visit_expr(expr);
+    splits.found
+}
+
+fn splits_arm(arm: &MatchArm) -> bool {
+    let mut splits = Splits::default();
+    splits.visit_match_arm(arm);
+    splits.found
+}
+
+// ---------------------------------------------------------------------------
+// Stati
+// ---------------------------------------------------------------------------
+
+/// Istruzioni eseguite da uno stato fino alla sospensione o al salto verso
+/// lo stato successivo
+struct State {
+    stmts: Vec<Stmt>,
+    /// Stato che riceve i valori lanciati dalle istruzioni di questo stato
+    handler: Option<u32>,
+}
+
+/// Ciclo diviso tra più stati
+#[derive(Clone, Copy)]
+struct Loop {
+    /// Stato che valuta la condizione, destinazione di `continue`
+    head: u32,
+    /// Stato dopo il ciclo, destinazione di `break`
+    exit: u32,
+    /// `finally` aperti all'inizio del ciclo
+    finallies: usize,
+}
+
+/// `finally` diviso tra più stati
+#[derive(Clone)]
+struct Finally {
+    entry: u32,
+    /// Variabile con lo stato da cui proseguire alla fine del `finally`
+    next: String,
+}
+
+struct Machine<'a> {
+    ids: &'a mut NodeIdGen,
+    span: Span,
+    variant_patterns: HashSet<NodeId>,
+    states: Vec<State>,
+    /// Stato che riceve le istruzioni; `None` dopo un salto, finché non si
+    /// entra in un altro stato
+    current: Option<u32>,
+    handlers: Vec<u32>,
+    loops: Vec<Loop>,
+    finallies: Vec<Finally>,
+    /// Stato che termina il generatore con il valore di `@result`, per i
+    /// `return` che attraversano un `finally`
+    returned: Option<u32>,
+    /// Variabili del frame create dall'abbassamento
+    temps: Vec<String>,
+    /// Tipi dichiarati delle variabili del frame
+    types: HashMap<String, TypeExpr>,
+    /// Funzioni e classi spostate nella funzione esterna
+    hoisted: Vec<Stmt>,
+}
+
+impl<'a> Machine<'a> {
+    fn new(ids: &'a mut NodeIdGen, span: Span, variant_patterns: HashSet<NodeId>) -> Self {
+        let mut machine = Self {
+            ids,
+            span,
+            variant_patterns,
+            states: Vec::new(),
+            current: None,
+            handlers: Vec::new(),
+            loops: Vec::new(),
+            finallies: Vec::new(),
+            returned: None,
+            temps: Vec::new(),
+            types: HashMap::new(),
+            hoisted: Vec::new(),
+        };
+        let start = machine.new_state();
+        machine.enter(start);
+        machine
+    }
+    
+    fn lower_stmts(&mut self, stmts: Vec<Stmt>) {
+        for stmt in stmts {
+            self.lower_stmt(stmt);
+        }
+    }
+    
+    fn lower_stmt(&mut self, stmt: Stmt) {
+        let declaration = matches!(
+            stmt.kind,
+            StmtKind::Var(_)
+                | StmtKind::Function(_)
+                | StmtKind::Class(_)
+                | StmtKind::Enum(_)
+                | StmtKind::Interface(_)
+                | StmtKind::TypeAlias(_)
+        );
+        if !declaration && !splits(&stmt) {
+            let stmts = self.kept(vec![stmt]);
+            return self.push_all(stmts);
+        }
+        
+        let Stmt { id, kind, span } = stmt;
+        match kind {
+            StmtKind::Var(decl) => self.var(decl),
+            StmtKind::Function(_)
+            | StmtKind::Class(_)
+            | StmtKind::Enum(_)
+            | StmtKind::Interface(_)
+            | StmtKind::TypeAlias(_) => self.hoisted.push(Stmt::new(id, kind, span)),
+            StmtKind::Expr(expr) => self.effect(expr),
+            StmtKind::Throw(value) => {
+                let value = self.value(value);
+                let throw = self.stmt(StmtKind::Throw(value));
+                self.push_all(vec![throw]);
+            },
+            StmtKind::Return(value) => {
+                let value = value.map(|value| self.value(value));
+                let stmts = self.return_stmts(value);
+                self.push_all(stmts);
+            },
+            StmtKind::If(if_stmt) => self.if_stmt(if_stmt),
+            StmtKind::While(while_stmt) => self.while_stmt(while_stmt),
+            StmtKind::Try(try_stmt) => self.try_stmt(try_stmt),
+            StmtKind::For(_) => unreachable!("i `for` sono abbassati prima dei generatori"),
+            // Direttive e decoratori non sono eseguiti
+            kind => self.push(Stmt::new(id, kind, span)),
+        }
+    }
+    
+    /// Una dichiarazione assegna la variabile del frame. Un pattern destruttura
+    /// in variabili locali allo stato, copiate poi nel frame.
+    fn var(&mut self, decl: VarDecl) {
+        let VarDecl { pattern, ty, init, .. } = decl;
+        let value = match init {
+            Some(init) => self.value(init),
+            None => self.literal(Literal::Null),
+        };
+        match pattern.kind {
+            PatternKind::Binding(name) => {
+                if let Some(ty) = ty {
+                    self.types.insert(name.name.clone(), ty);
+                }
+                let assign = self.assign(&name.name, value);
+                self.push(assign);
+            },
+            kind => {
+                let (pattern, copies) = self.locals(Pattern { kind, ..pattern });
+                let decl = VarDecl {
+                    kind: VarKind::Let,
+                    pattern,
+                    ty,
+                    init: Some(value),
+                };
+                let stmt = self.stmt(StmtKind::Var(decl));
+                self.push(stmt);
+                for copy in copies {
+                    self.push(copy);
+                }
+            },
+        }
+    }
+    
+    /// Espressione usata come istruzione
+    fn effect(&mut self, expr: Expr) {
+        let value = match expr.kind {
+            // Il valore ricevuto da `next()` non è usato
+            ExprKind::Yield {
+                argument,
+                delegate: false,
+            } => {
+                let argument = argument.map(|argument| self.value(*argument));
+                return self.suspend(argument);
+            },
+            kind => self.value(Expr { kind, ..expr }),
+        };
+        if !is_pure(&value) {
+            let stmt = self.stmt(StmtKind::Expr(value));
+            self.push(stmt);
+        }
+    }
+    
+    fn if_stmt(&mut self, if_stmt: IfStmt) {
+        let IfStmt {
+            condition,
+            then_branch,
+            else_branch,
+        } = if_stmt;
+        let condition = self.value(condition);
+        let then = self.new_state();
+        let join = self.new_state();
+        let otherwise = if else_branch.is_some() { self.new_state() } else { join };
+        self.branch(condition, then, otherwise);
+        
+        self.enter(then);
+        self.lower_stmts(then_branch.stmts);
+        self.goto(join);
+        if let Some(else_branch) = else_branch {
+            self.enter(otherwise);
+            match else_branch {
+                ElseBranch::If(stmt) => self.lower_stmt(*stmt),
+                ElseBranch::Block(block) => self.lower_stmts(block.stmts),
+            }
+            self.goto(join);
+        }
+        self.enter(join);
+    }
+    
+    fn while_stmt(&mut self, while_stmt: WhileStmt) {
+        let head = self.new_state();
+        self.goto(head);
+        self.enter(head);
+        let condition = self.value(while_stmt.condition);
+        let body = self.new_state();
+        let exit = self.new_state();
+        self.branch(condition, body, exit);
+        
+        self.loops.push(Loop {
+            head,
+            exit,
+            finallies: self.finallies.len(),
+        });
+        self.enter(body);
+        self.lower_stmts(while_stmt.body.stmts);
+        self.goto(head);
+        self.loops.pop();
+        self.enter(exit);
+    }
+    
+    /// Il corpo e il `catch` hanno come gestore lo stato del `catch` o quello
+    /// che esegue il `finally` e rilancia il valore
+    fn try_stmt(&mut self, try_stmt: TryStmt) {
+        let TryStmt { body, catches, finally } = try_stmt;
+        let catch = catches.into_iter().next();
+        let after = self.new_state();
+        let depth = self.finallies.len();
+        let finally = finally.map(|block| {
+            let entry = self.new_state();
+            let next = format!("@next{}", self.temps.len());
+            self.temps.push(next.clone());
+            let thrown = self.new_state();
+            (block, Finally { entry, next }, thrown)
+        });
+        let caught = catch.as_ref().map(|_| self.new_state());
+        let rethrow = finally.as_ref().map(|(_, _, thrown)| *thrown);
+        
+        if let Some((_, finally, _)) = &finally {
+            self.finallies.push(finally.clone());
+        }
+        self.handlers.extend(rethrow);
+        self.handlers.extend(caught);
+        let start = self.new_state();
+        self.goto(start);
+        self.enter(start);
+        self.lower_stmts(body.stmts);
+        let stmts = self.jump(after, depth);
+        self.push_all(stmts);
+        if caught.is_some() {
+            self.handlers.pop();
+        }
+        
+        if let (Some(catch), Some(caught)) = (catch, caught) {
+            self.enter(caught);
+            if let Some(binding) = &catch.binding {
+                let thrown = self.ident(THROWN);
+                let assign = self.assign(&binding.name, thrown);
+                self.push(assign);
+            }
+            self.lower_stmts(catch.body.stmts);
+            let stmts = self.jump(after, depth);
+            self.push_all(stmts);
+        }
+        
+        if let Some((block, finally, thrown)) = finally {
+            self.handlers.pop();
+            self.finallies.pop();
+            let error = self.temp();
+            let rethrow = self.new_state();
+            self.enter(rethrow);
+            let value = self.ident(&error);
+            let throw = self.stmt(StmtKind::Throw(value));
+            self.push_all(vec![throw]);
+            
+            self.enter(thrown);
+            let value = self.ident(THROWN);
+            let save = self.assign(&error, value);
+            self.push(save);
+            let target = self.number(rethrow);
+            let next = self.assign(&finally.next, target);
+            self.push(next);
+            self.goto(finally.entry);
+            
+            self.enter(finally.entry);
+            self.lower_stmts(block.stmts);
+            // Alla fine del `finally` prosegue dallo stato salvato
+            let next = self.ident(&finally.next);
+            let resume = self.assign(STATE, next);
+            let resume = vec![resume, self.stmt(StmtKind::Continue)];
+            self.push_all(resume);
+        }
+        self.enter(after);
+    }
+    
+    // -----------------------------------------------------------------------
+    // Espressioni
+    // -----------------------------------------------------------------------
+    
+    /// Abbassa un'espressione nello stato corrente e restituisce il suo valore,
+    /// che non contiene più `yield`
+    fn value(&mut self, expr: Expr) -> Expr {
+        if !splits_expr(&expr) {
+            return self.kept_expr(expr);
+        }
+        let Expr { id, kind, span } = expr;
+        match kind {
+            ExprKind::Yield { argument, delegate } => {
+                let argument = argument.map(|argument| self.value(*argument));
+                if delegate {
+                    return self.delegate(argument);
+                }
+                self.suspend(argument);
+                self.ident(SENT)
+            },
+            ExprKind::Binary {
+                op: op @ (BinaryOp::And | BinaryOp::Or | BinaryOp::Coalesce),
+                left,
+                right,
+            } if splits_expr(&right) => self.short_circuit(op, *left, *right),
+            ExprKind::Match { scrutinee, arms } if arms.iter().any(splits_arm) => self.match_value(*scrutinee, arms),
+            ExprKind::Assign { op, target, value } => self.assign_value(id, op, *target, *value, span),
+            kind => {
+                let mut expr = Expr::new(id, kind, span);
+                if let Some(depth) = optional_link(&expr) {
+                    return self.optional(expr, depth);
+                }
+                self.operands(&mut expr);
+                expr
+            },
+        }
+    }
+    
+    /// Valore che resta lo stesso dopo un `yield`: un temporaneo se necessario
+    fn stable(&mut self, value: Expr) -> Expr {
+        if self.is_stable(&value) {
+            return value;
+        }
+        let temp = self.temp();
+        let assign = self.assign(&temp, value);
+        self.push(assign);
+        self.ident(&temp)
+    }
+    
+    fn is_stable(&self, expr: &Expr) -> bool {
+        match &expr.kind {
+            ExprKind::Literal(_) | ExprKind::This | ExprKind::Super | ExprKind::Lambda(_) => true,
+            // I temporanei sono assegnati una volta prima di essere letti
+            ExprKind::Ident(name) => self.temps.contains(&name.name),
+            _ => false,
+        }
+    }
+    
+    /// Gli operandi che precedono l'ultimo `yield` sono valutati prima di lui
+    fn operands(&mut self, expr: &mut Expr) {
+        let operands = operands(&mut expr.kind);
+        let last = operands.iter().rposition(|operand| splits_expr(operand));
+        for (index, operand) in operands.into_iter().enumerate() {
+            let value = self.value(take(operand));
+            *operand = match last {
+                Some(last) if index < last => self.stable(value),
+                _ => value,
+            };
+        }
+        if let ExprKind::Match { arms, .. } = &mut expr.kind {
+            let kept = std::mem::take(arms);
+            *arms = kept.into_iter().map(|arm| Kept::new(self).fold_match_arm(arm)).collect();
+        }
+    }
+    
+    fn suspend(&mut self, value: Option<Expr>) {
+        let value = value.unwrap_or_else(|| self.literal(Literal::Null));
+        let resume = self.new_state();
+        let state = self.number(resume);
+        let save = self.assign(STATE, state);
+        let result = self.result(value, false);
+        let suspend = vec![save, self.stmt(StmtKind::Return(Some(result)))];
+        self.push_all(suspend);
+        self.enter(resume);
+    }
+    
+    /// `yield*`: produce i valori dell'iteratore delegato e vale il valore
+    /// con cui termina
+    fn delegate(&mut self, iterable: Option<Expr>) -> Expr {
+        let iterable = iterable.unwrap_or_else(|| self.literal(Literal::Null));
+        let iterator = self.temp();
+        let callee = self.ident(ITERATOR);
+        let call = self.call(callee, vec![iterable]);
+        let assign = self.assign(&iterator, call);
+        self.push(assign);
+        
+        let step = self.temp();
+        let head = self.new_state();
+        self.goto(head);
+        self.enter(head);
+        let object = self.ident(&iterator);
+        let next = self.member(object, "next");
+        let next = self.call(next, Vec::new());
+        let assign = self.assign(&step, next);
+        self.push(assign);
+        let forward = self.new_state();
+        let done = self.new_state();
+        let object = self.ident(&step);
+        let condition = self.member(object, "done");
+        self.branch(condition, done, forward);
+        
+        self.enter(forward);
+        let state = self.number(head);
+        let save = self.assign(STATE, state);
+        let object = self.ident(&step);
+        let value = self.member(object, "value");
+        let result = self.result(value, false);
+        let suspend = vec![save, self.stmt(StmtKind::Return(Some(result)))];
+        self.push_all(suspend);
+        
+        self.enter(done);
+        let object = self.ident(&step);
+        self.member(object, "value")
+    }
+    
+    /// `&&`, `||` e `??` valutano l'operando destro solo quando serve
+    fn short_circuit(&mut self, op: BinaryOp, left: Expr, right: Expr) -> Expr {
+        let left = self.value(left);
+        let result = self.temp();
+        let assign = self.assign(&result, left);
+        self.push(assign);
+        let current = self.ident(&result);
+        let condition = match op {
+            BinaryOp::And => current,
+            BinaryOp::Or => self.expr(ExprKind::Unary {
+                op: UnaryOp::Not,
+                operand: Box::new(current),
+            }),
+            _ => {
+                let null = self.literal(Literal::Null);
+                self.binary(BinaryOp::Eq, current, null)
+            },
+        };
+        let evaluate = self.new_state();
+        let join = self.new_state();
+        self.branch(condition, evaluate, join);
+        
+        self.enter(evaluate);
+        let right = self.value(right);
+        let assign = self.assign(&result, right);
+        self.push(assign);
+        self.goto(join);
+        self.enter(join);
+        self.ident(&result)
+    }
+    
+    /// Ogni braccio prova il suo pattern con un `match` che non contiene
+    /// `yield`; la guardia e il corpo sono stati. Senza bracci corrispondenti il
+    /// valore è `null`.
+    fn match_value(&mut self, scrutinee: Expr, arms: Vec<MatchArm>) -> Expr {
+        let scrutinee = self.value(scrutinee);
+        let subject = self.temp();
+        let assign = self.assign(&subject, scrutinee);
+        self.push(assign);
+        let result = self.temp();
+        let null = self.literal(Literal::Null);
+        let assign = self.assign(&result, null);
+        self.push(assign);
+        let join = self.new_state();
+        
+        for arm in arms {
+            let MatchArm {
+                id,
+                pattern,
+                guard,
+                body,
+                span,
+            } = arm;
+            let matched = self.new_state();
+            let next = self.new_state();
+            
+            // `match @subject  case pattern: copie; true  case _: false`
+            let (pattern, mut test) = self.locals(pattern);
+            let yes = self.literal(Literal::Bool(true));
+            test.push(self.stmt(StmtKind::Expr(yes)));
+            let test = MatchArm {
+                id,
+                pattern,
+                guard: None,
+                body: self.block(test),
+                span,
+            };
+            let no = self.literal(Literal::Bool(false));
+            let no = vec![self.stmt(StmtKind::Expr(no))];
+            let wildcard = self.pattern(PatternKind::Wildcard);
+            let otherwise = self.arm(wildcard, no);
+            let subject_value = self.ident(&subject);
+            let condition = self.expr(ExprKind::Match {
+                scrutinee: Box::new(subject_value),
+                arms: vec![test, otherwise],
+            });
+            self.branch(condition, matched, next);
+            
+            self.enter(matched);
+            if let Some(guard) = guard {
+                let guard = self.value(guard);
+                let accepted = self.new_state();
+                self.branch(guard, accepted, next);
+                self.enter(accepted);
+            }
+            let mut stmts = body.stmts;
+            let last = match stmts.last() {
+                Some(Stmt {
+                    kind: StmtKind::Expr(_),
+                    ..
+                }) => stmts.pop(),
+                _ => None,
+            };
+            self.lower_stmts(stmts);
+            if let Some(Stmt {
+                kind: StmtKind::Expr(value),
+                ..
+            }) = last
+            {
+                let value = self.value(value);
+                let assign = self.assign(&result, value);
+                self.push(assign);
+            }
+            self.goto(join);
+            self.enter(next);
+        }
+        self.goto(join);
+        self.enter(join);
+        self.ident(&result)
+    }
+    
+    /// Il bersaglio è valutato prima del valore, come senza `yield`
+    fn assign_value(&mut self, id: NodeId, op: AssignOp, target: Expr, value: Expr, span: Span) -> Expr {
+        let target = self.place(target);
+        let (op, value) = match op.binary_op() {
+            // Il valore corrente è letto prima del `yield`
+            Some(binary) => {
+                let current = Renumber { ids: self.ids }.expr(&target);
+                let current = self.stable(current);
+                let value = self.value(value);
+                (AssignOp::Assign, self.binary(binary, current, value))
+            },
+            None => (op, self.value(value)),
+        };
+        let kind = ExprKind::Assign {
+            op,
+            target: Box::new(target),
+            value: Box::new(value),
+        };
+        Expr::new(id, kind, span)
+    }
+    
+    /// Valuta l'oggetto e l'indice di un bersaglio di assegnazione
+    fn place(&mut self, target: Expr) -> Expr {
+        let Expr { id, kind, span } = target;
+        let kind = match kind {
+            ExprKind::Member {
+                object,
+                property,
+                optional,
+            } => {
+                let object = self.value(*object);
+                ExprKind::Member {
+                    object: Box::new(self.stable(object)),
+                    property,
+                    optional,
+                }
+            },
+            ExprKind::Index {
+                object,
+                index,
+                optional,
+            } => {
+                let object = self.value(*object);
+                let object = self.stable(object);
+                let index = self.value(*index);
+                ExprKind::Index {
+                    object: Box::new(object),
+                    index: Box::new(self.stable(index)),
+                    optional,
+                }
+            },
+            ExprKind::Array(elements) => ExprKind::Array(elements.into_iter().map(|element| self.place(element)).collect()),
+            kind => kind,
+        };
+        Expr::new(id, kind, span)
+    }
+    
+    /// L'anello opzionale salta il resto della catena quando il suo oggetto è
+    /// `null`, quindi il resto diventa uno stato
+    fn optional(&mut self, mut expr: Expr, depth: usize) -> Expr {
+        let link = chain_link(&mut expr, depth);
+        clear_optional(link);
+        let object = take(chain_object(link));
+        let object = self.value(object);
+        let object = self.stable(object);
+        *chain_object(chain_link(&mut expr, depth)) = Renumber { ids: self.ids }.expr(&object);
+        
+        let result = self.temp();
+        let null = self.literal(Literal::Null);
+        let condition = self.binary(BinaryOp::Eq, object, null);
+        let absent = self.new_state();
+        let present = self.new_state();
+        let join = self.new_state();
+        self.branch(condition, absent, present);
+        
+        self.enter(absent);
+        let null = self.literal(Literal::Null);
+        let assign = self.assign(&result, null);
+        self.push(assign);
+        self.goto(join);
+        
+        self.enter(present);
+        let value = self.value(expr);
+        let assign = self.assign(&result, value);
+        self.push(assign);
+        self.goto(join);
+        self.enter(join);
+        self.ident(&result)
+    }
+    
+    /// Rinomina i nomi di un pattern in variabili locali allo stato (`@nome`) e
+    /// restituisce le loro copie nelle variabili del frame
+    fn locals(&mut self, mut pattern: Pattern) -> (Pattern, Vec<Stmt>) {
+        let mut names = Vec::new();
+        let variant_patterns = &self.variant_patterns;
+        bindings_mut(&mut pattern, &mut |id, name| {
+            if variant_patterns.contains(&id) {
+                return;
+            }
+            if !names.contains(&name.name) {
+                names.push(name.name.clone());
+            }
+            name.name = format!("@{}", name.name);
+        });
+        // I valori predefiniti leggono i nomi già destrutturati
+        LocalNames(&names).visit_pattern(&mut pattern);
+        
+        let copies = names
+            .iter()
+            .map(|name| {
+                let local = self.ident(&format!("@{}", name));
+                self.assign(name, local)
+            })
+            .collect();
+        (pattern, copies)
+    }
+    
+    // -----------------------------------------------------------------------
+    // Salti
+    // -----------------------------------------------------------------------
+    
+    fn new_state(&mut self) -> u32 {
+        self.states.push(State {
+            stmts: Vec::new(),
+            handler: None,
+        });
+        (self.states.len() - 1) as u32
+    }
+    
+    fn enter(&mut self, state: u32) {
+        self.states[state as usize].handler = self.handlers.last().copied();
+        self.current = Some(state);
+    }
+    
+    fn push(&mut self, stmt: Stmt) {
+        // Dopo un salto le istruzioni non sono raggiungibili
+        if let Some(state) = self.current {
+            self.states[state as usize].stmts.push(stmt);
+        }
+    }
+    
+    fn push_all(&mut self, stmts: Vec<Stmt>) {
+        let ends = matches!(
+            stmts.last().map(|stmt| &stmt.kind),
+            Some(StmtKind::Return(_) | StmtKind::Throw(_) | StmtKind::Continue | StmtKind::Break)
+        );
+        for stmt in stmts {
+            self.push(stmt);
+        }
+        if ends {
+            self.current = None;
+        }
+    }
+    
+    fn goto(&mut self, target: u32) {
+        let stmts = self.jump(target, self.finallies.len());
+        self.push_all(stmts);
+    }
+    
+    fn branch(&mut self, condition: Expr, then: u32, otherwise: u32) {
+        if let ExprKind::Literal(Literal::Bool(value)) = condition.kind {
+            return self.goto(if value { then } else { otherwise });
+        }
+        let then = self.number(then);
+        let then = vec![self.assign(STATE, then)];
+        let otherwise = self.number(otherwise);
+        let otherwise = vec![self.assign(STATE, otherwise)];
+        let if_stmt = IfStmt {
+            condition,
+            then_branch: self.block(then),
+            else_branch: Some(ElseBranch::Block(self.block(otherwise))),
+        };
+        let branch = vec![self.stmt(StmtKind::If(if_stmt)), self.stmt(StmtKind::Continue)];
+        self.push_all(branch);
+    }
+    
+    /// `@state = stato` e `continue`, che torna al dispatch
+    fn set_state(&mut self, state: u32) -> Vec<Stmt> {
+        let state = self.number(state);
+        vec![self.assign(STATE, state), self.stmt(StmtKind::Continue)]
+    }
+    
+    /// Salta a `target` eseguendo prima i `finally` aperti oltre i primi
+    /// `depth`, dal più interno al più esterno
+    fn jump(&mut self, target: u32, depth: usize) -> Vec<Stmt> {
+        if depth >= self.finallies.len() {
+            return self.set_state(target);
+        }
+        let innermost = self.finallies.len() - 1;
+        let mut target = target;
+        for index in depth..innermost {
+            let trampoline = self.new_state();
+            let stmts = self.through_finally(index, target);
+            self.states[trampoline as usize].stmts = stmts;
+            target = trampoline;
+        }
+        self.through_finally(innermost, target)
+    }
+    
+    fn through_finally(&mut self, index: usize, target: u32) -> Vec<Stmt> {
+        let Finally { entry, next } = self.finallies[index].clone();
+        let target = self.number(target);
+        let mut stmts = vec![self.assign(&next, target)];
+        stmts.extend(self.set_state(entry));
+        stmts
+    }
+    
+    fn loop_jump(&mut self, is_break: bool) -> Vec<Stmt> {
+        match self.loops.last().copied() {
+            Some(Loop { head, exit, finallies }) => self.jump(if is_break { exit } else { head }, finallies),
+            None => vec![self.stmt(if is_break { StmtKind::Break } else { StmtKind::Continue })],
+        }
+    }
+    
+    /// `return` dal generatore: termina dopo i `finally` aperti
+    fn return_stmts(&mut self, value: Option<Expr>) -> Vec<Stmt> {
+        let mut stmts = Vec::new();
+        let mut value = value.unwrap_or_else(|| self.literal(Literal::Null));
+        if !self.finallies.is_empty() {
+            self.use_temp(RESULT);
+            stmts.push(self.assign(RESULT, value));
+            let returned = self.returned_state();
+            stmts.extend(self.jump(returned, 0));
+            return stmts;
+        }
+        // Il valore è calcolato prima che il generatore risulti terminato
+        if !self.is_stable(&value) {
+            self.use_temp(RESULT);
+            stmts.push(self.assign(RESULT, value));
+            value = self.ident(RESULT);
+        }
+        stmts.extend(self.finish_with(value));
+        stmts
+    }
+    
+    fn returned_state(&mut self) -> u32 {
+        if let Some(state) = self.returned {
+            return state;
+        }
+        let state = self.new_state();
+        let value = self.ident(RESULT);
+        self.states[state as usize].stmts = self.finish_with(value);
+        self.returned = Some(state);
+        state
+    }
+    
+    /// `@state = -1` e `return { value, done: true }`
+    fn finish_with(&mut self, value: Expr) -> Vec<Stmt> {
+        let finished = self.literal(Literal::Number(-1.0));
+        let result = self.result(value, true);
+        vec![self.assign(STATE, finished), self.stmt(StmtKind::Return(Some(result)))]
+    }
+    
+    fn temp(&mut self) -> String {
+        let name = format!("@t{}", self.temps.len());
+        self.temps.push(name.clone());
+        name
+    }
+    
+    fn use_temp(&mut self, name: &str) {
+        if !self.temps.iter().any(|temp| temp == name) {
+            self.temps.push(name.to_string());
+        }
+    }
+    
+    fn kept(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+        Kept::new(self).fold_stmts(stmts)
+    }
+    
+    fn kept_expr(&mut self, expr: Expr) -> Expr {
+        Kept::new(self).fold_expr(expr)
+    }
+    
+    // -----------------------------------------------------------------------
+    // Funzione esterna
+    // -----------------------------------------------------------------------
+    
+    /// Dichiarazioni del frame, funzioni spostate e `return @generator(passo)`
+    fn finish(mut self, slots: Vec<String>) -> Block {
+        // Stati dei gestori e stati che passano loro i valori lanciati
+        let mut handlers: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for (index, state) in self.states.iter().enumerate() {
+            if let (Some(handler), false) = (state.handler, state.stmts.is_empty()) {
+                handlers.entry(handler).or_default().push(index as u32);
+            }
+        }
+        if !handlers.is_empty() {
+            self.use_temp(THROWN);
+        }
+        
+        let start = self.number(0);
+        let mut body = vec![self.let_stmt(STATE, None, Some(start))];
+        for name in slots.into_iter().chain(std::mem::take(&mut self.temps)) {
+            let ty = self.types.remove(&name);
+            body.push(self.let_stmt(&name, ty, None));
+        }
+        body.append(&mut self.hoisted);
+        
+        let mut arms = Vec::new();
+        for (index, state) in std::mem::take(&mut self.states).into_iter().enumerate() {
+            if state.stmts.is_empty() {
+                continue;
+            }
+            let pattern = self.pattern(PatternKind::Literal(Literal::Number(index as f64)));
+            arms.push(self.arm(pattern, state.stmts));
+        }
+        let null = self.literal(Literal::Null);
+        let end = self.result(null, true);
+        let end = vec![self.stmt(StmtKind::Return(Some(end)))];
+        let wildcard = self.pattern(PatternKind::Wildcard);
+        arms.push(self.arm(wildcard, end));
+        let dispatch = self.dispatch(arms);
+        let mut step = vec![dispatch];
+        if !handlers.is_empty() {
+            step = vec![self.catch_thrown(step, handlers)];
+        }
+        
+        let forever = self.literal(Literal::Bool(true));
+        let step = WhileStmt {
+            condition: forever,
+            body: self.block(step),
+        };
+        let step = vec![self.stmt(StmtKind::While(step))];
+        let sent = self.pattern(PatternKind::Binding(Ident::new(SENT, self.span)));
+        let step = Lambda {
+            params: vec![Param {
+                pattern: sent,
+                ty: None,
+                default: None,
+                rest: false,
+                span: self.span,
+            }],
+            return_type: None,
+            body: LambdaBody::Block(self.block(step)),
+        };
+        let step = self.expr(ExprKind::Lambda(step));
+        let callee = self.ident(GENERATOR);
+        let generator = self.call(callee, vec![step]);
+        body.push(self.stmt(StmtKind::Return(Some(generator))));
+        self.block(body)
+    }
+    
+    /// `try` intorno al dispatch: il valore lanciato passa al gestore dello
+    /// stato che l'ha lanciato, oppure risale al chiamante di `next()`
+    fn catch_thrown(&mut self, step: Vec<Stmt>, handlers: BTreeMap<u32, Vec<u32>>) -> Stmt {
+        let error = "@error";
+        let value = self.ident(error);
+        let mut stmts = vec![self.assign(THROWN, value)];
+        let mut arms = Vec::new();
+        for (handler, states) in handlers {
+            let mut alternatives: Vec<Pattern> = states
+                .into_iter()
+                .map(|state| self.pattern(PatternKind::Literal(Literal::Number(state as f64))))
+                .collect();
+            let pattern = match alternatives.len() {
+                1 => alternatives.remove(0),
+                _ => self.pattern(PatternKind::Or(alternatives)),
+            };
+            let target = self.number(handler);
+            let body = vec![self.assign(STATE, target)];
+            arms.push(self.arm(pattern, body));
+        }
+        let value = self.ident(error);
+        let rethrow = vec![self.stmt(StmtKind::Throw(value))];
+        let wildcard = self.pattern(PatternKind::Wildcard);
+        arms.push(self.arm(wildcard, rethrow));
+        stmts.push(self.dispatch(arms));
+        
+        let catch = CatchClause {
+            id: self.ids.fresh(),
+            binding: Some(Ident::new(error, self.span)),
+            ty: None,
+            body: self.block(stmts),
+            span: self.span,
+        };
+        let try_stmt = TryStmt {
+            body: self.block(step),
+            catches: vec![catch],
+            finally: None,
+        };
+        self.stmt(StmtKind::Try(try_stmt))
+    }
+    
+    /// `match @state` con un braccio per stato
+    fn dispatch(&mut self, arms: Vec<MatchArm>) -> Stmt {
+        let state = self.ident(STATE);
+        let dispatch = self.expr(ExprKind::Match {
+            scrutinee: Box::new(state),
+            arms,
+        });
+        self.stmt(StmtKind::Expr(dispatch))
+    }
+    
+    // -----------------------------------------------------------------------
+    // Nodi
+    // -----------------------------------------------------------------------
+    
+    fn expr(&mut self, kind: ExprKind) -> Expr {
+        Expr::new(self.ids.fresh(), kind, self.span)
+    }
+    
+    fn stmt(&mut self, kind: StmtKind) -> Stmt {
+        Stmt::new(self.ids.fresh(), kind, self.span)
+    }
+    
+    fn pattern(&mut self, kind: PatternKind) -> Pattern {
+        Pattern::new(self.ids.fresh(), kind, self.span)
+    }
+    
+    fn block(&self, stmts: Vec<Stmt>) -> Block {
+        Block { stmts, span: self.span }
+    }
+    
+    fn arm(&mut self, pattern: Pattern, stmts: Vec<Stmt>) -> MatchArm {
+        MatchArm {
+            id: self.ids.fresh(),
+            pattern,
+            guard: None,
+            body: self.block(stmts),
+            span: self.span,
+        }
+    }
+    
+    fn ident(&mut self, name: &str) -> Expr {
+        let name = Ident::new(name, self.span);
+        self.expr(ExprKind::Ident(name))
+    }
+    
+    fn literal(&mut self, literal: Literal) -> Expr {
+        self.expr(ExprKind::Literal(literal))
+    }
+    
+    fn number(&mut self, state: u32) -> Expr {
+        self.literal(Literal::Number(state as f64))
+    }
+    
+    fn member(&mut self, object: Expr, property: &str) -> Expr {
+        self.expr(ExprKind::Member {
+            object: Box::new(object),
+            property: Ident::new(property, self.span),
+            optional: false,
+        })
+    }
+    
+    fn call(&mut self, callee: Expr, args: Vec<Expr>) -> Expr {
+        self.expr(ExprKind::Call {
+            callee: Box::new(callee),
+            type_args: Vec::new(),
+            args,
+            optional: false,
+        })
+    }
+    
+    fn binary(&mut self, op: BinaryOp, left: Expr, right: Expr) -> Expr {
+        self.expr(ExprKind::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    }
+    
+    /// `nome = valore`
+    fn assign(&mut self, name: &str, value: Expr) -> Stmt {
+        let target = self.ident(name);
+        let assign = self.expr(ExprKind::Assign {
+            op: AssignOp::Assign,
+            target: Box::new(target),
+            value: Box::new(value),
+        });
+        self.stmt(StmtKind::Expr(assign))
+    }
+    
+    fn let_stmt(&mut self, name: &str, ty: Option<TypeExpr>, init: Option<Expr>) -> Stmt {
+        let pattern = self.pattern(PatternKind::Binding(Ident::new(name, self.span)));
+        self.stmt(StmtKind::Var(VarDecl {
+            kind: VarKind::Let,
+            pattern,
+            ty,
+            init,
+        }))
+    }
+    
+    /// `{ value: valore, done: done }`
+    fn result(&mut self, value: Expr, done: bool) -> Expr {
+        let done = self.literal(Literal::Bool(done));
+        let properties = [("value", value), ("done", done)]
+            .into_iter()
+            .map(|(key, value)| Property {
+                kind: PropertyKind::KeyValue {
+                    key: PropertyKey::Ident(Ident::new(key, self.span)),
+                    value,
+                },
+                span: self.span,
+            })
+            .collect();
+        self.expr(ExprKind::Object(properties))
+    }
+}
+
+/// Adatta il codice senza `yield` che resta intero in uno stato: `return`
+/// termina il generatore, `break` e `continue` dei cicli divisi diventano salti
+struct Kept<'m, 'a> {
+    machine: &'m mut Machine<'a>,
+    /// Cicli interi che contengono il nodo visitato
+    loops: usize,
+}
+
+impl<'m, 'a> Kept<'m, 'a> {
+    fn new(machine: &'m mut Machine<'a>) -> Self {
+        Self { machine, loops: 0 }
+    }
+}
+
+impl Fold for Kept<'_, '_> {
+    fn fold_stmts(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+        let mut folded = Vec::with_capacity(stmts.len());
+        for stmt in stmts {
+            match stmt.kind {
+                StmtKind::Return(value) => {
+                    let value = value.map(|value| self.fold_expr(value));
+                    folded.extend(self.machine.return_stmts(value));
+                },
+                StmtKind::Break if self.loops == 0 => folded.extend(self.machine.loop_jump(true)),
+                StmtKind::Continue if self.loops == 0 => folded.extend(self.machine.loop_jump(false)),
+                kind => folded.push(self.fold_stmt(Stmt { kind, ..stmt })),
+            }
+        }
+        folded
+    }
+    
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+        match stmt.kind {
+            // Le funzioni annidate hanno i loro `return`
+            StmtKind::Function(_) | StmtKind::Class(_) => stmt,
+            StmtKind::While(_) => {
+                self.loops += 1;
+                let stmt = fold_stmt(self, stmt);
+                self.loops -= 1;
+                stmt
+            },
+            _ => fold_stmt(self, stmt),
+        }
+    }
+    
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        match expr.kind {
+            ExprKind::Lambda(_) => expr,
+            _ => fold_expr(self, expr),
+        }
+    }
+}
+
+/// Rinomina in `@nome` le letture dei nomi destrutturati da un pattern
+struct LocalNames<'a>(&'a [String]);
+
+impl VisitorMut for LocalNames<'_> {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        match &mut expr.kind {
+            ExprKind::Ident(name) if self.0.contains(&name.name) => name.name = format!("@{}", name.name),
+            _ => walk_expr(self, expr),
+        }
+    }
+}
+
+/// Espressione senza effetti, che come istruzione può essere omessa
+fn is_pure(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Literal(_) | ExprKind::Ident(_) | ExprKind::This => true,
+        ExprKind::Member {
+            object,
+            optional: false,
+            ..
+        } => is_pure(object),
+        _ => false,
+    }
+}
+
+fn take(expr: &mut Expr) -> Expr {
+    let span = expr.span;
+    std::mem::replace(expr, Expr::new(NodeId::DUMMY, ExprKind::Error, span))
+}
+
+/// Operandi di un'espressione nell'ordine in cui sono valutati. Il destinatario
+/// di una chiamata di metodo resta nella chiamata, che gli passa `this`.
+fn operands(kind: &mut ExprKind) -> Vec<&mut Expr> {
+    fn spread(expr: &mut Expr) -> &mut Expr {
+        if !matches!(expr.kind, ExprKind::Spread(_)) {
+            return expr;
+        }
+        let ExprKind::Spread(inner) = &mut expr.kind else {
+            unreachable!();
+        };
+        inner
+    }
+    
+    match kind {
+        ExprKind::Array(elements) => elements.iter_mut().map(spread).collect(),
+        ExprKind::Object(properties) => properties
+            .iter_mut()
+            .flat_map(|property| match &mut property.kind {
+                PropertyKind::KeyValue {
+                    key: PropertyKey::Computed(key),
+                    value,
+                } => vec![key.as_mut(), value],
+                PropertyKind::KeyValue { value, .. } | PropertyKind::Spread(value) => vec![value],
+                PropertyKind::Shorthand(_) => Vec::new(),
+            })
+            .collect(),
+        ExprKind::Spread(inner) | ExprKind::NullCheck(inner) | ExprKind::Unary { operand: inner, .. } => {
+            vec![inner.as_mut()]
+        },
+        ExprKind::Binary { left, right, .. } => vec![left.as_mut(), right.as_mut()],
+        ExprKind::Call { callee, args, .. } | ExprKind::New { callee, args, .. } => {
+            let mut operands = if matches!(callee.kind, ExprKind::Member { .. } | ExprKind::Index { .. }) {
+                match &mut callee.kind {
+                    ExprKind::Member { object, .. } => vec![object.as_mut()],
+                    ExprKind::Index { object, index, .. } => vec![object.as_mut(), index.as_mut()],
+                    _ => unreachable!(),
+                }
+            } else {
+                vec![callee.as_mut()]
+            };
+            operands.extend(args.iter_mut().map(spread));
+            operands
+        },
+        ExprKind::Member { object, .. } => vec![object.as_mut()],
+        ExprKind::Index { object, index, .. } => vec![object.as_mut(), index.as_mut()],
+        ExprKind::Is { expr, .. } | ExprKind::As { expr, .. } => vec![expr.as_mut()],
+        ExprKind::Match { scrutinee, .. } => vec![scrutinee.as_mut()],
+        ExprKind::Template(template) => template.values.iter_mut().collect(),
+        ExprKind::TaggedTemplate { tag, template } => {
+            std::iter::once(tag.as_mut()).chain(template.values.iter_mut()).collect()
+        },
+        ExprKind::Literal(_)
+        | ExprKind::Ident(_)
+        | ExprKind::This
+        | ExprKind::Super
+        | ExprKind::Assign { .. }
+        | ExprKind::Lambda(_)
+        | ExprKind::Yield { .. }
+        | ExprKind::Error => Vec::new(),
+    }
+}
+
+/// Profondità dell'anello opzionale più vicino alla base di una catena di
+/// accessi e chiamate seguito da un `yield`, a partire dall'anello esterno
+fn optional_link(expr: &Expr) -> Option<usize> {
+    let mut links = Vec::new();
+    let mut link = expr;
+    loop {
+        let (object, optional, splits) = match &link.kind {
+            ExprKind::Call {
+                callee, args, optional, ..
+            } => (callee, *optional, args.iter().any(splits_expr)),
+            ExprKind::Member { object, optional, .. } => (object, *optional, false),
+            ExprKind::Index {
+                object,
+                index,
+                optional,
+            } => (object, *optional, splits_expr(index)),
+            _ => break,
+        };
+        links.push((optional, splits));
+        link = object;
+    }
+    
+    // Dall'anello esterno verso la base, ricordando se un anello più esterno divide
+    let mut later = false;
+    let mut found = None;
+    for (depth, (optional, splits)) in links.into_iter().enumerate() {
+        later |= splits;
+        if optional && later {
+            found = Some(depth);
+        }
+    }
+    found
+}
+
+fn chain_link(expr: &mut Expr, depth: usize) -> &mut Expr {
+    let mut link = expr;
+    for _ in 0..depth {
+        link = chain_object(link);
+    }
+    link
+}
+
+fn chain_object(link: &mut Expr) -> &mut Expr {
+    match &mut link.kind {
+        ExprKind::Call { callee, .. } => callee.as_mut(),
+        ExprKind::Member { object, .. } | ExprKind::Index { object, .. } => object.as_mut(),
+        _ => unreachable!("anello di una catena"),
+    }
+}
+
+fn clear_optional(link: &mut Expr) {
+    if let ExprKind::Call { optional, .. } | ExprKind::Member { optional, .. } | ExprKind::Index { optional, .. } =
+        &mut link.kind
+    {
+        *optional = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::renumber::all_ids;
+    use crate::parser::parse_with_node_ids;
+    
+    /// Scrive le istruzioni abbassate, una per riga e indentate
+    fn render(stmts: &[Stmt], depth: usize, out: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+        for stmt in stmts {
+            match &stmt.kind {
+                StmtKind::Expr(Expr {
+                    kind: ExprKind::Match { scrutinee, arms },
+                    ..
+                }) => {
+                    out.push(format!("{}match {}", indent, expr(scrutinee)));
+                    for arm in arms {
+                        out.push(format!("{}  case {}:", indent, pattern(&arm.pattern)));
+                        render(&arm.body.stmts, depth + 2, out);
+                    }
+                },
+                StmtKind::Expr(value) => out.push(format!("{}{}", indent, expr(value))),
+                StmtKind::Var(decl) => {
+                    let init = decl.init.as_ref().map(|init| format!(" = {}", expr(init))).unwrap_or_default();
+                    out.push(format!("{}let {}{}", indent, pattern(&decl.pattern), init));
+                },
+                StmtKind::Return(Some(Expr {
+                    kind: ExprKind::Call { callee, args, .. },
+                    ..
+                })) if expr(callee) == GENERATOR => {
+                    let ExprKind::Lambda(Lambda {
+                        body: LambdaBody::Block(step),
+                        ..
+                    }) = &args[0].kind
+                    else {
+                        panic!("atteso il passo");
+                    };
+                    out.push(format!("{}return @generator((@sent) ->", indent));
+                    render(&step.stmts, depth + 1, out);
+                },
+                StmtKind::Return(value) => out.push(format!("{}return {}", indent, expr(value.as_ref().unwrap()))),
+                StmtKind::Throw(value) => out.push(format!("{}throw {}", indent, expr(value))),
+                StmtKind::Continue => out.push(format!("{}continue", indent)),
+                StmtKind::Break => out.push(format!("{}break", indent)),
+                StmtKind::If(if_stmt) => {
+                    out.push(format!("{}if {}", indent, expr(&if_stmt.condition)));
+                    render(&if_stmt.then_branch.stmts, depth + 1, out);
+                    if let Some(ElseBranch::Block(block)) = &if_stmt.else_branch {
+                        out.push(format!("{}else", indent));
+                        render(&block.stmts, depth + 1, out);
+                    }
+                },
+                StmtKind::While(while_stmt) => {
+                    out.push(format!("{}while {}", indent, expr(&while_stmt.condition)));
+                    render(&while_stmt.body.stmts, depth + 1, out);
+                },
+                StmtKind::Try(try_stmt) => {
+                    out.push(format!("{}try", indent));
+                    render(&try_stmt.body.stmts, depth + 1, out);
+                    for catch in &try_stmt.catches {
+                        out.push(format!("{}catch {}", indent, catch.binding.as_ref().unwrap()));
+                        render(&catch.body.stmts, depth + 1, out);
+                    }
+                },
+                StmtKind::Function(function) => out.push(format!("{}fn {}", indent, function.name)),
+                other => panic!("istruzione non prevista: {:?}", other),
+            }
+        }
+    }
+    
+    fn expr(expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Literal(Literal::Number(value)) => value.to_string(),
+            ExprKind::Literal(Literal::Bool(value)) => value.to_string(),
+            ExprKind::Literal(Literal::String(value)) => format!("{:?}", value),
+            ExprKind::Literal(Literal::Null) => "null".to_string(),
+            ExprKind::Ident(name) => name.name.clone(),
+            ExprKind::Member { object, property, .. } => format!("{}.{}", self::expr(object), property),
+            ExprKind::Call { callee, args, .. } => {
+                let args: Vec<String> = args.iter().map(self::expr).collect();
+                format!("{}({})", self::expr(callee), args.join(", "))
+            },
+            ExprKind::Unary { op, operand } => format!("{}{}", op, self::expr(operand)),
+            ExprKind::Binary { op, left, right } => format!("{} {} {}", self::expr(left), op, self::expr(right)),
+            ExprKind::Assign { op, target, value } => format!("{} {} {}", self::expr(target), op, self::expr(value)),
+            ExprKind::Object(properties) => {
+                let properties: Vec<String> = properties
+                    .iter()
+                    .map(|property| match &property.kind {
+                        PropertyKind::KeyValue {
+                            key: PropertyKey::Ident(key),
+                            value,
+                        } => format!("{}: {}", key, self::expr(value)),
+                        other => panic!("proprietà non prevista: {:?}", other),
+                    })
+                    .collect();
+                format!("{{ {} }}", properties.join(", "))
+            },
+            ExprKind::Match { scrutinee, arms } => {
+                let arms: Vec<String> = arms
+                    .iter()
+                    .map(|arm| {
+                        let mut body = Vec::new();
+                        render(&arm.body.stmts, 0, &mut body);
+                        format!("case {}: {}", pattern(&arm.pattern), body.join("; "))
+                    })
+                    .collect();
+                format!("match {} ({})", self::expr(scrutinee), arms.join(" "))
+            },
+            other => panic!("espressione non prevista: {:?}", other),
+        }
+    }
+    
+    fn pattern(pattern: &Pattern) -> String {
+        match &pattern.kind {
+            PatternKind::Wildcard => "_".to_string(),
+            PatternKind::Binding(name) => name.name.clone(),
+            PatternKind::Literal(Literal::Number(value)) => value.to_string(),
+            PatternKind::Array(patterns) => {
+                let patterns: Vec<String> = patterns.iter().map(self::pattern).collect();
+                format!("[{}]", patterns.join(", "))
+            },
+            PatternKind::Or(patterns) => {
+                let patterns: Vec<String> = patterns.iter().map(self::pattern).collect();
+                patterns.join(" | ")
+            },
+            other => panic!("pattern non previsto: {:?}", other),
+        }
+    }
+    
+    /// Cerca i `yield` e i `for` rimasti dopo l'abbassamento
+    struct Leftovers(Vec<String>);
+    
+    impl<'ast> Visitor<'ast> for Leftovers {
+        fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+            match &stmt.kind {
+                StmtKind::For(_) => self.0.push("for".to_string()),
+                StmtKind::Function(function) if function.is_generator => self.0.push(function.name.name.clone()),
+                _ => {},
+            }
+            visit::walk_stmt(self, stmt);
+        }
+        
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if let ExprKind::Yield { .. } = expr.kind {
+                self.0.push("yield".to_string());
+            }
+            visit::walk_expr(self, expr);
+        }
+    }
+    
+    fn lower(source: &str) -> Program {
+        let (mut program, mut ids) = parse_with_node_ids(source).unwrap();
+        lower_generators(&mut program, &mut ids);
+        
+        let mut leftovers = Leftovers(Vec::new());
+        leftovers.visit_program(&program);
+        assert!(leftovers.0.is_empty(), "non abbassati: {:?}", leftovers.0);
+        let all = all_ids(&program);
+        assert_eq!(all.iter().collect::<HashSet<_>>().len(), all.len(), "id ripetuti");
+        program
+    }
+    
+    fn lowered(source: &str) -> Vec<String> {
+        let program = lower(source);
+        let StmtKind::Function(function) = &program.body[0].kind else {
+            panic!("attesa una funzione");
+        };
+        let mut out = Vec::new();
+        render(&function.body.stmts, 0, &mut out);
+        out
+    }
+    
+    #[test]
+    fn test_loop_becomes_a_state_machine() {
+        let source = "\
+fn* count(n)
+  let i = 0
+  while i < n
+    yield i
+    i += 1
+";
+        assert_eq!(lowered(source), vec![
+            "let @state = 0",
+            "let i",
+            "return @generator((@sent) ->",
+            "  while true",
+            "    match @state",
+            "      case 0:",
+            "        i = 0",
+            "        @state = 1",
+            "        continue",
+            "      case 1:",
+            "        if i < n",
+            "          @state = 2",
+            "        else",
+            "          @state = 3",
+            "        continue",
+            "      case 2:",
+            "        @state = 4",
+            "        return { value: i, done: false }",
+            "      case 3:",
+            "        @state = -1",
+            "        return { value: null, done: true }",
+            "      case 4:",
+            "        i += 1",
+            "        @state = 1",
+            "        continue",
+            "      case _:",
+            "        return { value: null, done: true }",
+        ]);
+    }
+    
+    #[test]
+    fn test_yield_inside_expressions() {
+        let source = "\
+fn* pairs(a)
+  let x = f(a) + (yield 1)
+  return a && (yield x)
+";
+        assert_eq!(lowered(source), vec![
+            "let @state = 0",
+            "let x",
+            "let @t0",
+            "let @t1",
+            "return @generator((@sent) ->",
+            "  while true",
+            "    match @state",
+            "      case 0:",
+            "        @t0 = f(a)",
+            "        @state = 1",
+            "        return { value: 1, done: false }",
+            "      case 1:",
+            "        x = @t0 + @sent",
+            "        @t1 = a",
+            "        if @t1",
+            "          @state = 2",
+            "        else",
+            "          @state = 3",
+            "        continue",
+            "      case 2:",
+            "        @state = 4",
+            "        return { value: x, done: false }",
+            "      case 3:",
+            "        @state = -1",
+            "        return { value: @t1, done: true }",
+            "      case 4:",
+            "        @t1 = @sent",
+            "        @state = 3",
+            "        continue",
+            "      case _:",
+            "        return { value: null, done: true }",
+        ]);
+    }
+    
+    #[test]
+    fn test_variables_move_to_the_frame() {
+        let source = "\
+fn* shadow(x)
+  let y = x
+  if y
+    let y = 2
+    yield { y }
+  let [a, b] = yield y
+  return a
+";
+        let lowered = lowered(source);
+        // Le dichiarazioni di un blocco precedono quelle dei blocchi interni
+        assert_eq!(lowered[..5], ["let @state = 0", "let y", "let a", "let b", "let y@1"]);
+        assert!(lowered.contains(&"        y@1 = 2".to_string()));
+        assert!(lowered.contains(&"        return { value: { y: y@1 }, done: false }".to_string()));
+        // La destrutturazione usa variabili locali allo stato, copiate nel frame
+        let destructuring = lowered.iter().position(|line| line == "        let [@a, @b] = @sent").unwrap();
+        assert_eq!(lowered[destructuring + 1..destructuring + 3], ["        a = @a", "        b = @b"]);
+    }
+    
+    #[test]
+    fn test_finally_runs_before_leaving() {
+        let source = "\
+fn* lines(file)
+  try
+    yield file.read()
+    return 1
+  finally
+    file.close()
+";
+        let lowered = lowered(source);
+        let lowered: Vec<&str> = lowered.iter().map(|line| line.trim()).collect();
+        // Il `return` passa dal `finally`, che prosegue dallo stato salvato
+        let exit = lowered.iter().position(|line| *line == "@result = 1").unwrap();
+        assert_eq!(lowered[exit + 1..exit + 3], ["@next0 = 6", "@state = 2"]);
+        let entry = lowered.iter().position(|line| *line == "file.close()").unwrap();
+        assert_eq!(lowered[entry - 1], "case 2:");
+        assert_eq!(lowered[entry + 1..entry + 3], ["@state = @next0", "continue"]);
+        // Un'eccezione del corpo salva il valore, esegue il `finally` e lo rilancia
+        let thrown = lowered.iter().position(|line| *line == "@t2 = @thrown").unwrap();
+        assert_eq!(lowered[thrown + 1..thrown + 3], ["@next0 = 7", "@state = 2"]);
+        assert!(lowered.windows(2).any(|lines| lines == ["case 7:", "throw @t2"]));
+        let catch = lowered.iter().position(|line| *line == "catch @error").unwrap();
+        assert_eq!(lowered[catch + 1..], [
+            "@thrown = @error",
+            "match @state",
+            "case 4 | 5:",
+            "@state = 3",
+            "case _:",
+            "throw @error",
+        ]);
+    }
+    
+    #[test]
+    fn test_for_over_a_generator() {
+        let source = "\
+fn* evens(limit)
+  for n in range(limit)
+    if n % 2 == 0
+      yield n
+
+fn sum(limit)
+  let total = 0
+  for n in evens(limit)
+    total += n
+  return total
+";
+        let (mut program, mut ids) = parse_with_node_ids(source).unwrap();
+        crate::ir::lower_for_in(&mut program, &mut ids);
+        lower_generators(&mut program, &mut ids);
+        
+        let mut leftovers = Leftovers(Vec::new());
+        leftovers.visit_program(&program);
+        assert!(leftovers.0.is_empty(), "non abbassati: {:?}", leftovers.0);
+        
+        // Il ciclo sul generatore chiama `next()` sul valore di `@iterator`
+        let StmtKind::Function(sum) = &program.body[1].kind else {
+            panic!("attesa una funzione");
+        };
+        let mut out = Vec::new();
+        render(&sum.body.stmts, 0, &mut out);
+        assert!(out[1].starts_with("let @iter") && out[1].ends_with(" = @iterator(evens(limit))"));
+        assert!(out[3].trim().starts_with("let @step") && out[3].ends_with(".next()"));
+        
+        let StmtKind::Function(evens) = &program.body[0].kind else {
+            panic!("attesa una funzione");
+        };
+        let mut out = Vec::new();
+        render(&evens.body.stmts, 0, &mut out);
+        assert!(out.iter().any(|line| line.trim() == "return { value: n, done: false }"));
+        
+        let all = all_ids(&program);
+        assert_eq!(all.iter().collect::<HashSet<_>>().len(), all.len());
+    }
+}
@@ -0,0 +1,194 @@
+//! Abbassamento dei cicli `for ... in`.
+//!
+//! Ogni `for` diventa un `while` che usa il protocollo degli iteratori: la
+//! funzione di runtime `@iterator` restituisce l'iteratore di un valore
+//! iterabile (un generatore è già il proprio iteratore, array e stringhe ne
+//! ricevono uno dal runtime) e ogni chiamata a `next()` restituisce un oggetto
+//! `{ value, done }`:
+//!
+//! ```text
+//! for n in numbers              let @iter = @iterator(numbers)
+//!   println(n)          -->     while true
+//!                                 let @step = @iter.next()
+//!                                 if @step.done
+//!                                   break
+//!                                 let n = @step.value
+//!                                 println(n)
+//! ```
+//!
+//! `break` e `continue` nel corpo mantengono il loro significato, perché il
+//! `while` prende il posto del `for`. Il contratto di `@iterator` e `next()` è
+//! descritto in `docs/runtime.md`.
+
+use crate::ast::fold::{fold_stmt, Fold};
+use crate::ast::{
+    Block, Expr, ExprKind, ForStmt, Ident, IfStmt, Literal, NodeId, NodeIdGen, Pattern, PatternKind, Program, Stmt,
+    StmtKind, VarDecl, VarKind, WhileStmt,
+};
+use crate::lexer::Span;
+
+/// Funzione di runtime che restituisce l'iteratore di un valore iterabile
+pub(super) const ITERATOR: &str = "@iterator";
+
+/// Sostituisce ogni `for ... in` con il `while` equivalente. I nodi creati
+/// prendono gli id da `ids`, che deve proseguire quello del parser.
+pub fn lower_for_in(program: &mut Program, ids: &mut NodeIdGen) {
+    let body = std::mem::take(&mut program.body);
+    program.body = ForInLowering { ids }.fold_stmts(body);
+}
+
+/// Come [`lower_for_in`], per le istruzioni di un solo blocco
+pub(super) fn lower_for_in_block(block: &mut Block, ids: &mut NodeIdGen) {
+    let stmts = std::mem::take(&mut block.stmts);
+    block.stmts = ForInLowering { ids }.fold_stmts(stmts);
+}
+
+struct ForInLowering<'a> {
+    ids: &'a mut NodeIdGen,
+}
+
+impl Fold for ForInLowering<'_> {
+    fn fold_stmts(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+        let mut lowered = Vec::with_capacity(stmts.len());
+        for stmt in stmts {
+            let stmt = fold_stmt(self, stmt);
+            match stmt.kind {
+                StmtKind::For(for_stmt) => lowered.extend(self.lower_for(for_stmt, stmt.id, stmt.span)),
+                kind => lowered.push(Stmt { kind, ..stmt }),
+            }
+        }
+        lowered
+    }
+}
+
+impl ForInLowering<'_> {
+    fn lower_for(&mut self, for_stmt: ForStmt, id: NodeId, span: Span) -> [Stmt; 2] {
+        let iter = Ident::new(&format!("@iter{}", id.0), span);
+        let step = Ident::new(&format!("@step{}", id.0), span);
+        let iterable_span = for_stmt.iterable.span;
+        
+        let callee = self.ident(ITERATOR, iterable_span);
+        let iterator = self.call(callee, vec![for_stmt.iterable], iterable_span);
+        let pattern = self.binding(&iter);
+        let init = self.var(pattern, iterator, span);
+        
+        let next = self.member(&iter, "next", span);
+        let next = self.call(next, Vec::new(), span);
+        let pattern = self.binding(&step);
+        let mut body = vec![self.var(pattern, next, span)];
+        let done = IfStmt {
+            condition: self.member(&step, "done", span),
+            then_branch: Block {
+                stmts: vec![Stmt::new(self.ids.fresh(), StmtKind::Break, span)],
+                span,
+            },
+            else_branch: None,
+        };
+        body.push(Stmt::new(self.ids.fresh(), StmtKind::If(done), span));
+        let value = self.member(&step, "value", for_stmt.pattern.span);
+        let pattern_span = for_stmt.pattern.span;
+        body.push(self.var(for_stmt.pattern, value, pattern_span));
+        body.extend(for_stmt.body.stmts);
+        
+        let condition = Expr::new(self.ids.fresh(), ExprKind::Literal(Literal::Bool(true)), span);
+        let loop_stmt = WhileStmt {
+            condition,
+            body: Block {
+                stmts: body,
+                span: for_stmt.body.span,
+            },
+        };
+        [init, Stmt::new(id, StmtKind::While(loop_stmt), span)]
+    }
+    
+    /// `let pattern = valore`
+    fn var(&mut self, pattern: Pattern, init: Expr, span: Span) -> Stmt {
+        let decl = VarDecl {
+            kind: VarKind::Let,
+            pattern,
+            ty: None,
+            init: Some(init),
+        };
+        Stmt::new(self.ids.fresh(), StmtKind::Var(decl), span)
+    }
+    
+    fn binding(&mut self, name: &Ident) -> Pattern {
+        Pattern::new(self.ids.fresh(), PatternKind::Binding(name.clone()), name.span)
+    }
+    
+    fn ident(&mut self, name: &str, span: Span) -> Expr {
+        Expr::new(self.ids.fresh(), ExprKind::Ident(Ident::new(name, span)), span)
+    }
+    
+    fn member(&mut self, object: &Ident, property: &str, span: Span) -> Expr {
+        let object = self.ident(&object.name, span);
+        let kind = ExprKind::Member {
+            object: Box::new(object),
+            property: Ident::new(property, span),
+            optional: false,
+        };
+        Expr::new(self.ids.fresh(), kind, span)
+    }
+    
+    fn call(&mut self, callee: Expr, args: Vec<Expr>, span: Span) -> Expr {
+        let kind = ExprKind::Call {
+            callee: Box::new(callee),
+            type_args: Vec::new(),
+            args,
+            optional: false,
+        };
+        Expr::new(self.ids.fresh(), kind, span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::renumber::all_ids;
+    use crate::parser::parse_with_node_ids;
+    use std::collections::HashSet;
+    
+    #[test]
+    fn test_for_uses_the_iterator_protocol() {
+        let source = "\
+fn sum(rows)
+  let total = 0
+  for [a, b] in rows
+    for x in a
+      if x < 0
+        continue
+      total += x * b
+  return total
+";
+        let (mut program, mut ids) = parse_with_node_ids(source).unwrap();
+        lower_for_in(&mut program, &mut ids);
+        
+        let StmtKind::Function(function) = &program.body[0].kind else {
+            panic!("attesa una funzione");
+        };
+        let kinds: Vec<&str> = function.body.stmts.iter().map(|stmt| match &stmt.kind {
+            StmtKind::Var(_) => "let",
+            StmtKind::While(_) => "while",
+            StmtKind::Return(_) => "return",
+            other => panic!("istruzione non prevista: {:?}", other),
+        }).collect();
+        assert_eq!(kinds, vec!["let", "let", "while", "return"]);
+        
+        let StmtKind::While(outer) = &function.body.stmts[2].kind else {
+            unreachable!();
+        };
+        // `next()`, il controllo di `done`, l'elemento e le due istruzioni del ciclo interno
+        assert_eq!(outer.body.stmts.len(), 5);
+        let StmtKind::Var(element) = &outer.body.stmts[2].kind else {
+            panic!("attesa la destrutturazione dell'elemento");
+        };
+        assert!(matches!(&element.pattern.kind, PatternKind::Array(items) if items.len() == 2));
+        let StmtKind::While(inner) = &outer.body.stmts[4].kind else {
+            panic!("atteso il ciclo interno");
+        };
+        assert!(matches!(inner.body.stmts[3].kind, StmtKind::If(_)));
+        
+        let all = all_ids(&program);
+        assert_eq!(all.iter().collect::<HashSet<_>>().len(), all.len());
+    }
+}
@@ -0,0 +1,27 @@
+//! Rappresentazione intermedia (NovaIR) e trasformazioni dall'AST.
+//!
+//! La conversione delle chiusure è il primo passo dell'abbassamento: ogni
+//! lambda diventa una [`Closure`] che elenca le variabili catturate, così le
+//! fasi successive possono allocarle nell'ambiente della chiusura. I `for ... in`
+//! diventano cicli `while` sul protocollo degli iteratori e i generatori
+//! diventano funzioni che restituiscono la loro macchina a stati.
+//! I decoratori dell'utente diventano chiamate dopo la dichiarazione decorata.
+//! I template diventano concatenazioni di stringhe o chiamate della funzione tag.
+//! Le clausole `catch` tipate diventano controlli `is` in un'unica clausola.
+//! I valori `any` ricevuti da codice tipizzato diventano cast verificati a runtime.
+
+pub mod closure;
+pub mod decorators;
+pub mod exceptions;
+pub mod generator;
+pub mod iteration;
+pub mod runtime_checks;
+pub mod template;
+
+pub use closure::{lower_closures, Capture, Closure};
+pub use decorators::apply_decorators;
+pub use exceptions::lower_exceptions;
+pub use generator::lower_generators;
+pub use iteration::lower_for_in;
+pub use runtime_checks::insert_runtime_checks;
+pub use template::lower_templates;
@@ -0,0 +1,117 @@
+//! Controlli di tipo a runtime.
+//!
+//! Dove il controllo dei tipi ha accettato un valore `any` in codice tipizzato
+//! il valore diventa un cast `valore as Tipo`. Un cast da `any` verifica il
+//! tipo durante l'esecuzione e lancia un `TypeError` se non corrisponde, così
+//! il codice tipizzato può fidarsi delle sue annotazioni:
+//!
+//! ```text
+//! fn area(r: number)        area(input())  -->  area(input() as number)
+//! ```
+
+use std::collections::HashMap;
+
+use crate::ast::visit_mut::{walk_expr, VisitorMut};
+use crate::ast::{Expr, ExprKind, NodeId, NodeIdGen, Program};
+use crate::semantics::Type;
+
+/// Inserisce un cast verificato per ogni espressione registrata in
+/// [`crate::semantics::TypeCheck::runtime_checks`]. Il cast e il suo tipo
+/// prendono gli id da `ids`, che deve proseguire quello del parser.
+pub fn insert_runtime_checks(program: &mut Program, checks: &HashMap<NodeId, Type>, ids: &mut NodeIdGen) {
+    RuntimeChecks { checks, ids }.visit_program(program);
+}
+
+struct RuntimeChecks<'a> {
+    checks: &'a HashMap<NodeId, Type>,
+    ids: &'a mut NodeIdGen,
+}
+
+impl VisitorMut for RuntimeChecks<'_> {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr);
+        
+        if let Some(ty) = self.checks.get(&expr.id) {
+            let value = std::mem::replace(&mut expr.kind, ExprKind::Error);
+            let value = Expr::new(expr.id, value, expr.span);
+            expr.id = self.ids.fresh();
+            expr.kind = ExprKind::As {
+                expr: Box::new(value),
+                ty: ty.to_type_expr(expr.span, self.ids),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{StmtKind, TypeExprKind};
+    use crate::parser::parse_with_node_ids;
+    use crate::semantics::{check_types, resolve};
+    
+    /// Scrive le espressioni con i cast inseriti
+    fn render(expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Ident(name) => name.name.clone(),
+            ExprKind::Literal(_) => "1".to_string(),
+            ExprKind::Call { callee, args, .. } => {
+                let args: Vec<String> = args.iter().map(render).collect();
+                format!("{}({})", render(callee), args.join(", "))
+            },
+            ExprKind::As { expr, ty } => match &ty.kind {
+                TypeExprKind::Named { name, .. } => format!("({} as {})", render(expr), name),
+                TypeExprKind::Array(_) => format!("({} as array)", render(expr)),
+                other => panic!("tipo non previsto: {:?}", other),
+            },
+            other => panic!("espressione non prevista: {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_any_values_are_cast_where_typed_code_receives_them() {
+        let source = "\
+fn area(r: number, label)
+  return r * r
+fn load(x)
+  let n: number = x
+  let names: string[] = x
+  area(x, x)
+  area(n, 1)
+";
+        let (mut program, mut ids) = parse_with_node_ids(source).unwrap();
+        let parsed = ids.count();
+        let (resolution, errors) = resolve(&program);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let (check, errors) = check_types(&program, &resolution);
+        assert!(errors.is_empty(), "{:?}", errors);
+        insert_runtime_checks(&mut program, &check.runtime_checks, &mut ids);
+        
+        let StmtKind::Function(load) = &program.body[1].kind else {
+            panic!("attesa una funzione");
+        };
+        let rendered: Vec<String> = load
+            .body
+            .stmts
+            .iter()
+            .map(|stmt| match &stmt.kind {
+                StmtKind::Var(decl) => render(decl.init.as_ref().unwrap()),
+                StmtKind::Expr(expr) => render(expr),
+                other => panic!("istruzione non prevista: {:?}", other),
+            })
+            .collect();
+        assert_eq!(rendered, vec!["(x as number)", "(x as array)", "area((x as number), x)", "area(n, 1)"]);
+        
+        // Il cast è un nodo nuovo; l'espressione controllata conserva il suo id
+        let StmtKind::Var(decl) = &load.body.stmts[0].kind else {
+            panic!("attesa una dichiarazione");
+        };
+        let cast = decl.init.as_ref().unwrap();
+        let ExprKind::As { expr: value, ty } = &cast.kind else {
+            panic!("atteso un cast");
+        };
+        let created = parsed..ids.count();
+        assert!(created.contains(&cast.id.0) && created.contains(&ty.id.0) && cast.id != ty.id);
+        assert!(value.id.0 < parsed && check.runtime_checks.contains_key(&value.id));
+    }
+}
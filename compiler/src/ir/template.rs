@@ -0,0 +1,153 @@
+//! Abbassamento dei template.
+//!
+//! Un template senza tag diventa una concatenazione di stringhe che parte
+//! sempre dal primo tratto di testo, anche se vuoto, così `+` converte in
+//! stringa ogni valore interpolato: `` `a${x}b${y}` `` diventa
+//! `"a" + x + "b" + y`. Un template con tag diventa una chiamata della funzione
+//! tag con l'array dei tratti di testo seguito dai valori, ognuno come
+//! argomento separato: `` sql`select ${id}` `` diventa `sql(["select ", ""], id)`.
+
+use crate::ast::visit_mut::{walk_expr, VisitorMut};
+use crate::ast::{BinaryOp, Expr, ExprKind, Literal, NodeIdGen, Program, StringLit, Template};
+
+/// Sostituisce ogni template con la concatenazione o la chiamata equivalente.
+/// I nodi creati prendono gli id da `ids`, che deve proseguire quello del parser.
+pub fn lower_templates(program: &mut Program, ids: &mut NodeIdGen) {
+    TemplateLowering { ids }.visit_program(program);
+}
+
+struct TemplateLowering<'a> {
+    ids: &'a mut NodeIdGen,
+}
+
+impl VisitorMut for TemplateLowering<'_> {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr);
+        
+        let kind = std::mem::replace(&mut expr.kind, ExprKind::Error);
+        expr.kind = match kind {
+            ExprKind::Template(template) => self.concatenation(template),
+            ExprKind::TaggedTemplate { tag, template } => {
+                let strings = template.strings.into_iter().map(|text| self.string(text)).collect();
+                let mut args = vec![Expr::new(self.ids.fresh(), ExprKind::Array(strings), expr.span)];
+                args.extend(template.values);
+                ExprKind::Call {
+                    callee: tag,
+                    type_args: Vec::new(),
+                    args,
+                    optional: false,
+                }
+            },
+            kind => kind,
+        };
+    }
+}
+
+impl TemplateLowering<'_> {
+    /// `"testo" + valore + "testo" ...`, omettendo i tratti vuoti dopo il primo
+    fn concatenation(&mut self, template: Template) -> ExprKind {
+        let mut strings = template.strings.into_iter();
+        let mut result = self.string(strings.next().expect("un template ha almeno un tratto di testo"));
+        
+        for (value, text) in template.values.into_iter().zip(strings) {
+            result = self.add(result, value);
+            if !text.value.is_empty() {
+                let text = self.string(text);
+                result = self.add(result, text);
+            }
+        }
+        
+        result.kind
+    }
+    
+    fn add(&mut self, left: Expr, right: Expr) -> Expr {
+        let span = left.span.to(right.span);
+        let kind = ExprKind::Binary {
+            op: BinaryOp::Add,
+            left: Box::new(left),
+            right: Box::new(right),
+        };
+        Expr::new(self.ids.fresh(), kind, span)
+    }
+    
+    fn string(&mut self, text: StringLit) -> Expr {
+        Expr::new(self.ids.fresh(), ExprKind::Literal(Literal::String(text.value)), text.span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    
+    use crate::ast::visit::{walk_expr, Visitor};
+    use crate::ast::{NodeId, StmtKind};
+    use crate::parser::parse_with_node_ids;
+    
+    /// Raccoglie gli id di tutte le espressioni
+    struct ExprIds(Vec<NodeId>);
+    
+    impl<'ast> Visitor<'ast> for ExprIds {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            self.0.push(expr.id);
+            walk_expr(self, expr);
+        }
+    }
+    
+    /// Scrive l'espressione abbassata con le parentesi esplicite
+    fn render(expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Ident(name) => name.name.clone(),
+            ExprKind::Literal(Literal::String(value)) => format!("{:?}", value),
+            ExprKind::Binary { op, left, right } => format!("({} {} {})", render(left), op, render(right)),
+            ExprKind::Member { object, property, .. } => format!("{}.{}", render(object), property),
+            ExprKind::Array(elements) => {
+                let elements: Vec<String> = elements.iter().map(render).collect();
+                format!("[{}]", elements.join(", "))
+            },
+            ExprKind::Call { callee, args, .. } => {
+                let args: Vec<String> = args.iter().map(render).collect();
+                format!("{}({})", render(callee), args.join(", "))
+            },
+            other => panic!("espressione non prevista: {:?}", other),
+        }
+    }
+    
+    fn lowered(source: &str) -> Vec<String> {
+        let (mut program, mut ids) = parse_with_node_ids(source).unwrap();
+        lower_templates(&mut program, &mut ids);
+        
+        // Ogni espressione creata ha un id nuovo
+        let mut collected = ExprIds(Vec::new());
+        collected.visit_program(&program);
+        let count = collected.0.len();
+        let unique: HashSet<NodeId> = collected.0.into_iter().filter(|id| id.0 < ids.count()).collect();
+        assert_eq!(unique.len(), count);
+        
+        program
+            .body
+            .iter()
+            .map(|stmt| match &stmt.kind {
+                StmtKind::Expr(expr) => render(expr),
+                other => panic!("attesa un'espressione: {:?}", other),
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn test_templates_become_concatenations() {
+        assert_eq!(lowered("`ciao ${name}!`\n`${a}${b}`\n`solo testo`\n"), vec![
+            "((\"ciao \" + name) + \"!\")",
+            "((\"\" + a) + b)",
+            "\"solo testo\"",
+        ]);
+    }
+    
+    #[test]
+    fn test_tagged_templates_receive_strings_and_values() {
+        assert_eq!(lowered("db.sql`select * from t where id = ${id} and k = ${`x${k}`}`\ntag`vuoto`\n"), vec![
+            "db.sql([\"select * from t where id = \", \" and k = \", \"\"], id, (\"x\" + k))",
+            "tag([\"vuoto\"])",
+        ]);
+    }
+}
@@ -1,12 +1,11 @@
 mod token;
 mod scanner;
 
-pub use token::{Token, TokenType, Position};
+pub use token::{keyword, Token, TokenType, Position, Span, KEYWORDS};
 pub use scanner::Scanner;
 
 use crate::error::{Error, ErrorKind};
-use std::iter::Peekable;
-use std::str::Chars;
+use crate::utils::diagnostics::{Applicability, Suggestion};
 
 /// Lexer per NovaScript che converte una stringa di codice sorgente in un flusso di token
 pub struct Lexer<'a> {
@@ -14,7 +13,48 @@ pub struct Lexer<'a> {
     indent_stack: Vec<usize>,
     tokens_buffer: Vec<Token>,
     at_line_start: bool,
-    current_indent: usize,
+    /// Profondità di annidamento di parentesi tonde, quadre e graffe
+    bracket_depth: usize,
+    /// Parentesi aperte sulla riga corrente e non ancora chiuse
+    line_brackets: usize,
+    /// Corpi di lambda su più righe aperti dentro parentesi, dal più esterno
+    layout_regions: Vec<LayoutRegion>,
+    /// Profondità delle parentesi all'apertura di ogni `${` di un template
+    /// ancora aperto, dal più esterno
+    template_depths: Vec<usize>,
+}
+
+/// Parole chiave che iniziano un'istruzione: a inizio riga, al livello del
+/// blocco corrente, indicano che le parentesi delle righe precedenti non
+/// verranno più chiuse
+const STATEMENT_KEYWORDS: &[TokenType] = &[
+    TokenType::Let,
+    TokenType::Const,
+    TokenType::Fn,
+    TokenType::If,
+    TokenType::For,
+    TokenType::While,
+    TokenType::Break,
+    TokenType::Continue,
+    TokenType::Return,
+    TokenType::Throw,
+    TokenType::Try,
+    TokenType::Import,
+    TokenType::Export,
+    TokenType::Class,
+    TokenType::Interface,
+    TokenType::Enum,
+];
+
+/// Blocco indentato che segue un `->` a fine riga dentro parentesi. Al suo
+/// interno l'indentazione torna significativa finché una riga non risale al
+/// livello di quella che contiene la lambda, o finché non si chiude la
+/// parentesi che la racchiude.
+struct LayoutRegion {
+    /// Profondità delle parentesi da ripristinare alla chiusura
+    bracket_depth: usize,
+    /// Lunghezza di `indent_stack` all'apertura
+    indent_depth: usize,
 }
 
 impl<'a> Lexer<'a> {
@@ -25,7 +65,10 @@ impl<'a> Lexer<'a> {
             indent_stack: vec![0], // Lo stack inizia con l'indentazione 0
             tokens_buffer: Vec::new(),
             at_line_start: true,
-            current_indent: 0,
+            bracket_depth: 0,
+            line_brackets: 0,
+            layout_regions: Vec::new(),
+            template_depths: Vec::new(),
         }
     }
     
@@ -51,96 +94,158 @@ impl<'a> Lexer<'a> {
     
     /// Restituisce il prossimo token
     pub fn next_token(&mut self) -> Result<Token, Error> {
-        // Se ci sono token nel buffer, restituiscili prima
-        if !self.tokens_buffer.is_empty() {
-            return Ok(self.tokens_buffer.remove(0));
+        loop {
+            // Se ci sono token nel buffer, restituiscili prima
+            if !self.tokens_buffer.is_empty() {
+                return Ok(self.tokens_buffer.remove(0));
+            }
+            
+            // Se siamo all'inizio di una linea, gestisci l'indentazione.
+            // Dentro le parentesi l'indentazione non è significativa.
+            if self.at_line_start && self.bracket_depth == 0 {
+                self.at_line_start = false;
+                let line_start = self.scanner.position();
+                let indent = self.scanner.calculate_indent();
+                let indent_span = Span::new(line_start, self.scanner.position());
+                self.scanner.skip_whitespace_and_comments();
+                
+                // Le righe vuote o con soli commenti non cambiano l'indentazione
+                if self.scanner.peek() == '\n' {
+                    self.scanner.advance();
+                    self.line_brackets = 0;
+                    self.at_line_start = true;
+                    continue;
+                }
+                
+                if !self.scanner.is_at_end() {
+                    self.handle_indentation(indent, indent_span)?;
+                    continue;
+                }
+            }
+            
+            // Salta spazi bianchi e commenti
+            self.scanner.skip_whitespace_and_comments();
+            
+            if self.scanner.is_at_end() {
+                // Genera token DEDENT per indentazioni rimanenti prima di EOF
+                if self.indent_stack.len() > 1 {
+                    while self.indent_stack.len() > 1 {
+                        self.indent_stack.pop();
+                        self.tokens_buffer.push(Token::new(
+                            TokenType::Dedent,
+                            "",
+                            self.scanner.current_span(),
+                        ));
+                    }
+                    continue;
+                }
+                return Ok(Token::new(
+                    TokenType::Eof,
+                    "",
+                    self.scanner.current_span(),
+                ));
+            }
+            
+            // Se troviamo un carattere newline
+            if self.scanner.match_char('\n') {
+                self.line_brackets = 0;
+                if self.bracket_depth > 0 {
+                    if !self.brackets_left_open() {
+                        continue;
+                    }
+                    // Le parentesi aperte non verranno chiuse: la riga successiva
+                    // inizia una nuova istruzione, così l'errore resta confinato
+                    // all'istruzione che le contiene
+                    self.bracket_depth = 0;
+                }
+                self.at_line_start = true;
+                let mut token = self.scanner.make_token(TokenType::Newline);
+                if self.scanner.blank_line_ahead() {
+                    token.lexeme.push('\n');
+                }
+                return Ok(token);
+            }
+            
+            // Scansiona il prossimo token
+            return self.scan_token();
         }
-        
-        // Salta spazi bianchi e commenti
-        self.scanner.skip_whitespace_and_comments();
-        
-        // Se siamo all'inizio di una linea, gestisci l'indentazione
-        if self.at_line_start {
-            self.at_line_start = false;
-            return self.handle_indentation();
+    }
+    
+    /// Indica se la riga che segue l'a capo appena letto, dentro parentesi,
+    /// non può continuare l'espressione: è meno indentata del blocco corrente,
+    /// oppure è al suo livello e inizia con la parola chiave di un'istruzione.
+    fn brackets_left_open(&self) -> bool {
+        if !self.template_depths.is_empty() {
+            return false;
         }
         
-        // Rileva il fine riga
-        if self.scanner.is_at_end() {
-            // Genera token DEDENT per indentazioni rimanenti
-            if !self.indent_stack.is_empty() && self.indent_stack.last().unwrap() > &0 {
-                while self.indent_stack.len() > 1 {
-                    self.indent_stack.pop();
-                    self.tokens_buffer.push(Token::new(
-                        TokenType::Dedent,
-                        "",
-                        self.scanner.position(),
-                    ));
-                }
-            }
-            return Ok(Token::new(
-                TokenType::Eof,
-                "",
-                self.scanner.position(),
-            ));
+        let mut scanner = self.scanner.clone();
+        let indent = scanner.calculate_indent();
+        scanner.skip_whitespace_and_comments();
+        if scanner.is_at_end() || scanner.peek() == '\n' {
+            return false;
         }
         
-        // Se troviamo un carattere newline
-        if self.scanner.match_char('\n') {
-            self.at_line_start = true;
-            return Ok(Token::new(
-                TokenType::Newline,
-                "\n",
-                self.scanner.position(),
-            ));
+        let block_indent = *self.indent_stack.last().unwrap();
+        if indent != block_indent {
+            return indent < block_indent;
         }
         
-        // Scansiona il prossimo token
-        self.scan_token()
+        let mut word = String::new();
+        while scanner.peek().is_alphanumeric() || scanner.peek() == '_' {
+            word.push(scanner.advance());
+        }
+        scanner.skip_whitespace_and_comments();
+        // In `return: 1` la parola chiave è il nome di una proprietà
+        keyword(&word).is_some_and(|keyword| STATEMENT_KEYWORDS.contains(&keyword)) && scanner.peek() != ':'
     }
     
-    /// Gestisce l'indentazione all'inizio di una linea
-    fn handle_indentation(&mut self) -> Result<Token, Error> {
-        // Calcola l'indentazione corrente
-        let current_indent = self.scanner.calculate_indent();
+    /// Gestisce l'indentazione all'inizio di una linea, accodando i token
+    /// INDENT o DEDENT necessari
+    fn handle_indentation(&mut self, current_indent: usize, span: Span) -> Result<(), Error> {
         let previous_indent = *self.indent_stack.last().unwrap();
         
-        // Aggiorna l'indentazione corrente
-        self.current_indent = current_indent;
-        
         // Genera token INDENT o DEDENT in base alla differenza di indentazione
         if current_indent > previous_indent {
             // Indentazione aumentata
             self.indent_stack.push(current_indent);
-            return Ok(Token::new(
+            self.tokens_buffer.push(Token::new(
                 TokenType::Indent,
                 &" ".repeat(current_indent),
-                self.scanner.position(),
+                span,
             ));
-        } else if current_indent < previous_indent {
-            // Indentazione diminuita
-            while !self.indent_stack.is_empty() && self.indent_stack.last().unwrap() > &current_indent {
-                self.indent_stack.pop();
-                self.tokens_buffer.push(Token::new(
-                    TokenType::Dedent,
-                    "",
-                    self.scanner.position(),
-                ));
-            }
-            
-            if self.indent_stack.is_empty() || self.indent_stack.last().unwrap() != &current_indent {
-                return Err(Error::new(
-                    ErrorKind::SyntaxError,
-                    "Indentazione non valida".to_string(),
-                    self.scanner.position(),
-                ));
+            return Ok(());
+        }
+        
+        // Indentazione diminuita, senza uscire dal corpo di lambda corrente
+        let base = self.layout_regions.last().map_or(1, |region| region.indent_depth);
+        while self.indent_stack.len() > base && self.indent_stack.last().unwrap() > &current_indent {
+            self.indent_stack.pop();
+            self.tokens_buffer.push(Token::new(
+                TokenType::Dedent,
+                "",
+                Span::point(span.end),
+            ));
+        }
+        
+        // La riga risale al livello di quella che contiene la lambda: il corpo
+        // è finito e si torna dentro le parentesi
+        if self.indent_stack.len() == base {
+            if let Some(region) = self.layout_regions.pop() {
+                self.bracket_depth = region.bracket_depth;
+                return Ok(());
             }
-            
-            return Ok(self.tokens_buffer.remove(0));
         }
         
-        // Indentazione non cambiata, continua con il prossimo token
-        self.scan_token()
+        if self.indent_stack.last().unwrap() != &current_indent {
+            return Err(Error::new(
+                ErrorKind::SyntaxError,
+                "Indentazione non valida".to_string(),
+                span,
+            ));
+        }
+        
+        Ok(())
     }
     
     /// Scansiona e restituisce il prossimo token
@@ -151,22 +256,63 @@ impl<'a> Lexer<'a> {
         // Corrispondenza in base al carattere
         match c {
             // Operatori a carattere singolo
-            '+' => Ok(self.scanner.make_token(TokenType::Plus)),
-            '-' => Ok(self.scanner.make_token(TokenType::Minus)),
-            '*' => Ok(self.scanner.make_token(TokenType::Star)),
-            '/' => Ok(self.scanner.make_token(TokenType::Slash)),
             '%' => Ok(self.scanner.make_token(TokenType::Percent)),
-            '(' => Ok(self.scanner.make_token(TokenType::LeftParen)),
-            ')' => Ok(self.scanner.make_token(TokenType::RightParen)),
-            '{' => Ok(self.scanner.make_token(TokenType::LeftBrace)),
-            '}' => Ok(self.scanner.make_token(TokenType::RightBrace)),
-            '[' => Ok(self.scanner.make_token(TokenType::LeftBracket)),
-            ']' => Ok(self.scanner.make_token(TokenType::RightBracket)),
+            '^' => Ok(self.scanner.make_token(TokenType::Caret)),
+            '~' => Ok(self.scanner.make_token(TokenType::Tilde)),
+            '(' => Ok(self.open_bracket(TokenType::LeftParen)),
+            ')' => Ok(self.close_bracket(TokenType::RightParen)),
+            '{' => Ok(self.open_bracket(TokenType::LeftBrace)),
+            '}' if self.template_depths.last().map(|depth| depth + 1) == Some(self.bracket_depth) => {
+                // La graffa chiude un'interpolazione: il template prosegue
+                self.template_depths.pop();
+                self.bracket_depth -= 1;
+                self.template(TokenType::TemplateMiddle, TokenType::TemplateTail)
+            },
+            '}' => Ok(self.close_bracket(TokenType::RightBrace)),
+            '[' => Ok(self.open_bracket(TokenType::LeftBracket)),
+            ']' => Ok(self.close_bracket(TokenType::RightBracket)),
             ',' => Ok(self.scanner.make_token(TokenType::Comma)),
-            '.' => Ok(self.scanner.make_token(TokenType::Dot)),
+            '.' => {
+                if self.scanner.peek() == '.' && self.scanner.peek_next() == '.' {
+                    self.scanner.advance();
+                    self.scanner.advance();
+                    Ok(self.scanner.make_token(TokenType::DotDotDot))
+                } else {
+                    Ok(self.scanner.make_token(TokenType::Dot))
+                }
+            },
             ':' => Ok(self.scanner.make_token(TokenType::Colon)),
+            '@' => Ok(self.scanner.make_token(TokenType::At)),
+            '#' => Ok(self.scanner.make_token(TokenType::Hash)),
             
             // Operatori composti
+            '+' => Ok(self.either('=', TokenType::PlusEqual, TokenType::Plus)),
+            '*' => {
+                if self.scanner.match_char('*') {
+                    Ok(self.scanner.make_token(TokenType::StarStar))
+                } else {
+                    Ok(self.either('=', TokenType::StarEqual, TokenType::Star))
+                }
+            },
+            '/' => Ok(self.either('=', TokenType::SlashEqual, TokenType::Slash)),
+            '-' => {
+                if self.scanner.match_char('>') {
+                    let arrow = self.scanner.make_token(TokenType::Arrow);
+                    self.open_layout_after_arrow();
+                    Ok(arrow)
+                } else {
+                    Ok(self.either('=', TokenType::MinusEqual, TokenType::Minus))
+                }
+            },
+            '&' => Ok(self.either('&', TokenType::AmpersandAmpersand, TokenType::Ampersand)),
+            '|' => Ok(self.either('|', TokenType::PipePipe, TokenType::Pipe)),
+            '?' => {
+                if self.scanner.match_char('.') {
+                    Ok(self.scanner.make_token(TokenType::QuestionDot))
+                } else {
+                    Ok(self.either('?', TokenType::QuestionQuestion, TokenType::Question))
+                }
+            },
             '=' => {
                 if self.scanner.match_char('=') {
                     Ok(self.scanner.make_token(TokenType::EqualEqual))
@@ -185,41 +331,100 @@ impl<'a> Lexer<'a> {
                 if self.scanner.match_char('=') {
                     Ok(self.scanner.make_token(TokenType::LessEqual))
                 } else {
-                    Ok(self.scanner.make_token(TokenType::Less))
+                    Ok(self.either('<', TokenType::LessLess, TokenType::Less))
                 }
             },
             '>' => {
                 if self.scanner.match_char('=') {
                     Ok(self.scanner.make_token(TokenType::GreaterEqual))
                 } else {
-                    Ok(self.scanner.make_token(TokenType::Greater))
+                    Ok(self.either('>', TokenType::GreaterGreater, TokenType::Greater))
                 }
             },
             
             // Letterali stringa
             '"' | '\'' => self.string(),
+            '`' => self.template(TokenType::TemplateHead, TokenType::TemplateString),
             
             // Identificatori e parole chiave
             c if c.is_alphabetic() || c == '_' => self.identifier_or_keyword(),
             
             // Numeri
-            c if c.is_digit(10) => self.number(),
+            c if c.is_ascii_digit() => self.number(),
             
             // Caratteri non riconosciuti
             _ => Err(Error::new(
                 ErrorKind::SyntaxError,
                 format!("Carattere non riconosciuto: '{}'", c),
-                self.scanner.position(),
+                self.scanner.current_span(),
             )),
         }
     }
     
+    /// Crea un token `double` se il carattere successivo è `next`, altrimenti
+    /// un token `single`
+    fn either(&mut self, next: char, double: TokenType, single: TokenType) -> Token {
+        if self.scanner.match_char(next) {
+            self.scanner.make_token(double)
+        } else {
+            self.scanner.make_token(single)
+        }
+    }
+    
+    /// Crea il token di una parentesi aperta
+    fn open_bracket(&mut self, token_type: TokenType) -> Token {
+        self.bracket_depth += 1;
+        self.line_brackets += 1;
+        self.scanner.make_token(token_type)
+    }
+    
+    /// Crea il token di una parentesi chiusa
+    fn close_bracket(&mut self, token_type: TokenType) -> Token {
+        // La parentesi chiude anche il corpo di lambda aperto al suo interno
+        if self.bracket_depth == 0 {
+            if let Some(region) = self.layout_regions.pop() {
+                let span = Span::point(self.scanner.start_position());
+                while self.indent_stack.len() > region.indent_depth {
+                    self.indent_stack.pop();
+                    self.tokens_buffer.push(Token::new(TokenType::Dedent, "", span));
+                }
+                self.bracket_depth = region.bracket_depth;
+            }
+        }
+        self.bracket_depth = self.bracket_depth.saturating_sub(1);
+        self.line_brackets = self.line_brackets.saturating_sub(1);
+        
+        let token = self.scanner.make_token(token_type);
+        if self.tokens_buffer.is_empty() {
+            token
+        } else {
+            self.tokens_buffer.push(token);
+            self.tokens_buffer.remove(0)
+        }
+    }
+    
+    /// Dopo un `->` a fine riga dentro parentesi, rende di nuovo significativi
+    /// a capo e indentazione per il corpo su più righe della lambda
+    fn open_layout_after_arrow(&mut self) {
+        if self.bracket_depth == 0 {
+            return;
+        }
+        self.scanner.skip_whitespace_and_comments();
+        if self.scanner.peek() == '\n' {
+            self.layout_regions.push(LayoutRegion {
+                bracket_depth: self.bracket_depth,
+                indent_depth: self.indent_stack.len(),
+            });
+            self.bracket_depth = 0;
+        }
+    }
+    
     /// Scansiona un letterale stringa
     fn string(&mut self) -> Result<Token, Error> {
         let quote = self.scanner.previous();
-        let start_pos = self.scanner.position();
+        let start_pos = self.scanner.start_position();
         
-        while !self.scanner.is_at_end() && self.scanner.peek() != quote {
+        while !self.scanner.is_at_end() && self.scanner.peek() != quote && self.scanner.peek() != '\n' {
             // Gestisci sequenze di escape
             if self.scanner.peek() == '\\' {
                 self.scanner.advance(); // Consuma il backslash
@@ -232,12 +437,32 @@ impl<'a> Lexer<'a> {
             }
         }
         
-        if self.scanner.is_at_end() {
+        if self.scanner.peek() != quote {
+            // Proponi di chiudere la stringa a fine riga, prima di spazi finali e
+            // delle parentesi che chiudono quelle aperte sulla stessa riga prima
+            // della stringa
+            let content = &self.scanner.current_lexeme()[1..];
+            let trimmed = content.trim_end();
+            let closing = trimmed
+                .chars()
+                .rev()
+                .take(self.line_brackets)
+                .take_while(|c| matches!(c, ')' | ']' | '}'))
+                .count();
+            let moved_back = content.len() - trimmed.len() + closing;
+            let mut end = self.scanner.position();
+            end.offset -= moved_back;
+            end.column -= moved_back;
             return Err(Error::new(
                 ErrorKind::SyntaxError,
                 "Stringa non terminata".to_string(),
-                start_pos,
-            ));
+                Span::new(start_pos, end),
+            ).with_suggestion(Suggestion::new(
+                Span::point(end),
+                quote.to_string(),
+                format!("chiudi la stringa con {}", quote),
+                Applicability::MachineApplicable,
+            )));
         }
         
         // Consuma la chiusura della stringa
@@ -250,10 +475,66 @@ impl<'a> Lexer<'a> {
         Ok(Token::new(
             TokenType::StringLiteral,
             content,
-            start_pos,
+            self.scanner.current_span(),
         ))
     }
     
+    /// Scansiona un tratto di testo di un template fino alla prossima
+    /// interpolazione, restituendo un token `open`, o fino al backtick di
+    /// chiusura, restituendo un token `closed`. Il lessema contiene il testo
+    /// grezzo senza delimitatori; a differenza delle stringhe, un template può
+    /// andare a capo.
+    fn template(&mut self, open: TokenType, closed: TokenType) -> Result<Token, Error> {
+        let text_start = self.scanner.position().offset;
+        
+        loop {
+            if self.scanner.is_at_end() {
+                let end = self.scanner.position();
+                return Err(Error::new(
+                    ErrorKind::SyntaxError,
+                    "Template non terminato".to_string(),
+                    Span::new(self.scanner.start_position(), end),
+                ).with_suggestion(Suggestion::new(
+                    Span::point(end),
+                    "`".to_string(),
+                    "chiudi il template con `".to_string(),
+                    Applicability::MachineApplicable,
+                )));
+            }
+            match self.scanner.peek() {
+                '`' => {
+                    let text_end = self.scanner.position().offset;
+                    self.scanner.advance();
+                    return Ok(self.template_token(closed, text_start, text_end));
+                },
+                '$' if self.scanner.peek_next() == '{' => {
+                    let text_end = self.scanner.position().offset;
+                    self.scanner.advance();
+                    self.scanner.advance();
+                    // Dentro l'interpolazione a capo e indentazione non contano
+                    self.template_depths.push(self.bracket_depth);
+                    self.bracket_depth += 1;
+                    return Ok(self.template_token(open, text_start, text_end));
+                },
+                '\\' => {
+                    self.scanner.advance();
+                    if !self.scanner.is_at_end() {
+                        self.scanner.advance();
+                    }
+                },
+                _ => {
+                    self.scanner.advance();
+                },
+            }
+        }
+    }
+    
+    /// Crea il token di un tratto di template con il testo compreso tra gli
+    /// offset `start` e `end`
+    fn template_token(&self, token_type: TokenType, start: usize, end: usize) -> Token {
+        Token::new(token_type, &self.scanner.source()[start..end], self.scanner.current_span())
+    }
+    
     /// Scansiona un identificatore o una parola chiave
     fn identifier_or_keyword(&mut self) -> Result<Token, Error> {
         while !self.scanner.is_at_end() && 
@@ -262,78 +543,51 @@ impl<'a> Lexer<'a> {
         }
         
         let lexeme = self.scanner.current_lexeme();
-        let token_type = match lexeme {
-            // Parole chiave
-            "let" => TokenType::Let,
-            "const" => TokenType::Const,
-            "fn" => TokenType::Fn,
-            "if" => TokenType::If,
-            "else" => TokenType::Else,
-            "for" => TokenType::For,
-            "while" => TokenType::While,
-            "return" => TokenType::Return,
-            "true" => TokenType::True,
-            "false" => TokenType::False,
-            "null" => TokenType::Null,
-            "import" => TokenType::Import,
-            "export" => TokenType::Export,
-            "from" => TokenType::From,
-            "class" => TokenType::Class,
-            "interface" => TokenType::Interface,
-            "implements" => TokenType::Implements,
-            "try" => TokenType::Try,
-            "catch" => TokenType::Catch,
-            "finally" => TokenType::Finally,
-            "throw" => TokenType::Throw,
-            "break" => TokenType::Break,
-            "continue" => TokenType::Continue,
-            "in" => TokenType::In,
-            "is" => TokenType::Is,
-            "as" => TokenType::As,
-            "type" => TokenType::Type,
-            "enum" => TokenType::Enum,
-            "constructor" => TokenType::Constructor,
-            "this" => TokenType::This,
-            "super" => TokenType::Super,
-            "match" => TokenType::Match,
-            "case" => TokenType::Case,
-            "default" => TokenType::Default,
-            "new" => TokenType::New,
-            // Altrimenti è un identificatore
-            _ => TokenType::Identifier,
-        };
+        // Parola chiave oppure identificatore
+        let token_type = keyword(lexeme).unwrap_or(TokenType::Identifier);
         
-        Ok(Token::new(
-            token_type,
-            lexeme,
-            self.scanner.position(),
-        ))
+        Ok(self.scanner.make_token(token_type))
     }
     
     /// Scansiona un letterale numerico
     fn number(&mut self) -> Result<Token, Error> {
+        // Letterali esadecimali (`0xFF`) e binari (`0b1010`)
+        if self.scanner.previous() == '0' && matches!(self.scanner.peek(), 'x' | 'X' | 'b' | 'B') {
+            let radix = if matches!(self.scanner.peek(), 'x' | 'X') { 16 } else { 2 };
+            self.scanner.advance();
+            while !self.scanner.is_at_end() && self.scanner.peek().is_alphanumeric() {
+                self.scanner.advance();
+            }
+            
+            let digits = &self.scanner.current_lexeme()[2..];
+            if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+                return Err(Error::new(
+                    ErrorKind::SyntaxError,
+                    format!("Letterale numerico non valido: '{}'", self.scanner.current_lexeme()),
+                    self.scanner.current_span(),
+                ));
+            }
+            return Ok(self.scanner.make_token(TokenType::NumberLiteral));
+        }
+        
         // Consuma la parte intera
-        while !self.scanner.is_at_end() && self.scanner.peek().is_digit(10) {
+        while !self.scanner.is_at_end() && self.scanner.peek().is_ascii_digit() {
             self.scanner.advance();
         }
         
         // Controlla se c'è una parte decimale
         if !self.scanner.is_at_end() && self.scanner.peek() == '.' && 
-           !self.scanner.is_at_end_plus(1) && self.scanner.peek_next().is_digit(10) {
+           !self.scanner.is_at_end_plus(1) && self.scanner.peek_next().is_ascii_digit() {
             // Consuma il punto
             self.scanner.advance();
             
             // Consuma la parte decimale
-            while !self.scanner.is_at_end() && self.scanner.peek().is_digit(10) {
+            while !self.scanner.is_at_end() && self.scanner.peek().is_ascii_digit() {
                 self.scanner.advance();
             }
         }
         
-        Ok(Token::new(
-            TokenType::NumberLiteral,
-            self.scanner.current_lexeme(),
-            self.scanner.position(),
-        ))
+        Ok(self.scanner.make_token(TokenType::NumberLiteral))
     }
 }
 
@@ -379,4 +633,135 @@ mod tests {
         assert_eq!(tokens[0].token_type, TokenType::StringLiteral);
         assert_eq!(tokens[0].lexeme, "hello world");
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_template_literal() {
+        let source = "`a ${x + `b${y}`} c\\n${z}`";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize().unwrap();
+        
+        let token_types: Vec<TokenType> = tokens.iter().map(|t| t.token_type).collect();
+        assert_eq!(token_types, vec![
+            TokenType::TemplateHead,
+            TokenType::Identifier,
+            TokenType::Plus,
+            TokenType::TemplateHead,
+            TokenType::Identifier,
+            TokenType::TemplateTail,
+            TokenType::TemplateMiddle,
+            TokenType::Identifier,
+            TokenType::TemplateTail,
+            TokenType::Eof,
+        ]);
+        let lexemes: Vec<&str> = tokens.iter().map(|t| t.lexeme.as_str()).collect();
+        assert_eq!(lexemes[..4], ["a ", "x", "+", "b"]);
+        assert_eq!(tokens[6].lexeme, " c\\n");
+        assert_eq!(tokens[8].lexeme, "");
+        
+        let tokens = Lexer::new("f(`x\ny {`)\n").tokenize().unwrap();
+        assert_eq!(tokens[2].token_type, TokenType::TemplateString);
+        assert_eq!(tokens[2].lexeme, "x\ny {");
+        assert_eq!(tokens[4].token_type, TokenType::Newline);
+        
+        let error = Lexer::new("`abc ${x}").tokenize().unwrap_err();
+        assert!(error.to_string().contains("Template non terminato"));
+    }
+    
+    #[test]
+    fn test_blank_lines_and_comments() {
+        let source = "if x\n\n  // commento\n  y = 10\n\nz = 20\n";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize().unwrap();
+        
+        let token_types: Vec<TokenType> = tokens.iter().map(|t| t.token_type).collect();
+        assert_eq!(token_types, vec![
+            TokenType::If, TokenType::Identifier, TokenType::Newline,
+            TokenType::Indent, TokenType::Identifier, TokenType::Equal, TokenType::NumberLiteral, TokenType::Newline,
+            TokenType::Dedent, TokenType::Identifier, TokenType::Equal, TokenType::NumberLiteral, TokenType::Newline,
+            TokenType::Eof,
+        ]);
+    }
+    
+    #[test]
+    fn test_dedent_before_eof() {
+        let source = "fn f()\n  return 1";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize().unwrap();
+        
+        let last: Vec<TokenType> = tokens.iter().rev().take(2).map(|t| t.token_type).collect();
+        assert_eq!(last, vec![TokenType::Eof, TokenType::Dedent]);
+    }
+    
+    #[test]
+    fn test_lambda_body_inside_brackets() {
+        // Il corpo finisce con una riga che risale al livello esterno
+        let source = "f((x) ->\n  y = x\n  g(y, (z) ->\n    z)\n, 1)\n";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize().unwrap();
+        
+        let token_types: Vec<TokenType> = tokens.iter().map(|t| t.token_type).collect();
+        assert_eq!(token_types, vec![
+            TokenType::Identifier, TokenType::LeftParen, TokenType::LeftParen, TokenType::Identifier,
+            TokenType::RightParen, TokenType::Arrow, TokenType::Newline,
+            TokenType::Indent, TokenType::Identifier, TokenType::Equal, TokenType::Identifier, TokenType::Newline,
+            TokenType::Identifier, TokenType::LeftParen, TokenType::Identifier, TokenType::Comma,
+            TokenType::LeftParen, TokenType::Identifier, TokenType::RightParen, TokenType::Arrow, TokenType::Newline,
+            TokenType::Indent, TokenType::Identifier, TokenType::Dedent, TokenType::RightParen, TokenType::Newline,
+            TokenType::Dedent, TokenType::Comma, TokenType::NumberLiteral, TokenType::RightParen, TokenType::Newline,
+            TokenType::Eof,
+        ]);
+        
+        // Fuori dal corpo della lambda gli a capo tornano a essere ignorati
+        let tokens = Lexer::new("f((x) ->\n  x\n,\n  2)").tokenize().unwrap();
+        assert!(!tokens.iter().skip(10).any(|t| t.token_type == TokenType::Newline));
+    }
+    
+    #[test]
+    fn test_compound_operators() {
+        let source = "a += b -> c && d || e & f | g -= h ...i";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize().unwrap();
+        
+        let operators: Vec<TokenType> = tokens
+            .iter()
+            .filter(|t| !matches!(t.token_type, TokenType::Identifier | TokenType::Eof))
+            .map(|t| t.token_type)
+            .collect();
+        assert_eq!(operators, vec![
+            TokenType::PlusEqual, TokenType::Arrow, TokenType::AmpersandAmpersand,
+            TokenType::PipePipe, TokenType::Ampersand, TokenType::Pipe, TokenType::MinusEqual,
+            TokenType::DotDotDot,
+        ]);
+    }
+    
+    #[test]
+    fn test_operators_and_radix_literals() {
+        let source = "a ?? b?.c ** 0xFF ^ ~0b1010 << 2 >> x?";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize().unwrap();
+        
+        let token_types: Vec<TokenType> = tokens.iter().map(|t| t.token_type).collect();
+        assert_eq!(token_types, vec![
+            TokenType::Identifier, TokenType::QuestionQuestion, TokenType::Identifier,
+            TokenType::QuestionDot, TokenType::Identifier, TokenType::StarStar,
+            TokenType::NumberLiteral, TokenType::Caret, TokenType::Tilde, TokenType::NumberLiteral,
+            TokenType::LessLess, TokenType::NumberLiteral, TokenType::GreaterGreater,
+            TokenType::Identifier, TokenType::Question, TokenType::Eof,
+        ]);
+        assert_eq!(tokens[6].lexeme, "0xFF");
+        
+        assert!(Lexer::new("0b102").tokenize().is_err());
+    }
+    
+    #[test]
+    fn test_token_spans() {
+        let source = "let è = \"ciao\"";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize().unwrap();
+        
+        assert_eq!(&source[tokens[1].span.range()], "è");
+        assert_eq!(tokens[1].span.start.column, 5);
+        assert_eq!(&source[tokens[3].span.range()], "\"ciao\"");
+        assert_eq!(tokens[3].lexeme, "ciao");
+    }
+}
@@ -1,15 +1,16 @@
-use super::token::{Position, Token, TokenType};
-use std::iter::Peekable;
-use std::str::Chars;
+use super::token::{Position, Span, Token, TokenType};
 
 /// Scanner che analizza carattere per carattere il codice sorgente
+#[derive(Clone)]
 pub struct Scanner<'a> {
     source: &'a str,
-    chars: Peekable<Chars<'a>>,
+    /// Offset in byte dell'inizio del lessema corrente
     start: usize,
+    /// Offset in byte del carattere corrente
     current: usize,
     line: usize,
     column: usize,
+    start_position: Position,
 }
 
 impl<'a> Scanner<'a> {
@@ -17,43 +18,43 @@ impl<'a> Scanner<'a> {
     pub fn new(source: &'a str) -> Self {
         Self {
             source,
-            chars: source.chars().peekable(),
             start: 0,
             current: 0,
             line: 1,
             column: 1,
+            start_position: Position::new(1, 1, 0),
         }
     }
     
     /// Restituisce la posizione corrente nel codice
     pub fn position(&self) -> Position {
-        Position::new(self.line, self.column)
+        Position::new(self.line, self.column, self.current)
+    }
+    
+    /// Restituisce la posizione di inizio del lessema corrente
+    pub fn start_position(&self) -> Position {
+        self.start_position
+    }
+    
+    /// Restituisce il codice sorgente completo
+    pub fn source(&self) -> &'a str {
+        self.source
     }
     
     /// Verifica se lo scanner ha raggiunto la fine del codice
     pub fn is_at_end(&self) -> bool {
-        self.chars.peek().is_none()
+        self.current >= self.source.len()
     }
     
     /// Verifica se lo scanner è a N caratteri dalla fine
     pub fn is_at_end_plus(&self, n: usize) -> bool {
-        let mut count = 0;
-        let mut iter = self.chars.clone();
-        
-        while count <= n {
-            if iter.next().is_none() {
-                return true;
-            }
-            count += 1;
-        }
-        
-        false
+        self.source[self.current..].chars().nth(n).is_none()
     }
     
     /// Avanza di un carattere e lo restituisce
     pub fn advance(&mut self) -> char {
-        let c = self.chars.next().unwrap_or('\0');
-        self.current += 1;
+        let c = self.source[self.current..].chars().next().unwrap_or('\0');
+        self.current += c.len_utf8();
         
         if c == '\n' {
             self.line += 1;
@@ -66,24 +67,20 @@ impl<'a> Scanner<'a> {
     }
     
     /// Restituisce il carattere corrente senza avanzare
-    pub fn peek(&mut self) -> char {
-        *self.chars.peek().unwrap_or(&'\0')
+    pub fn peek(&self) -> char {
+        self.source[self.current..].chars().next().unwrap_or('\0')
     }
     
     /// Restituisce il carattere successivo senza avanzare
-    pub fn peek_next(&mut self) -> char {
-        let mut iter = self.chars.clone();
+    pub fn peek_next(&self) -> char {
+        let mut iter = self.source[self.current..].chars();
         iter.next(); // Salta il carattere corrente
         iter.next().unwrap_or('\0')
     }
     
     /// Restituisce il carattere precedente
     pub fn previous(&self) -> char {
-        if self.current > 0 && self.current <= self.source.len() {
-            self.source.chars().nth(self.current - 1).unwrap_or('\0')
-        } else {
-            '\0'
-        }
+        self.source[..self.current].chars().next_back().unwrap_or('\0')
     }
     
     /// Verifica se il carattere corrente corrisponde a quello atteso e avanza
@@ -96,6 +93,17 @@ impl<'a> Scanner<'a> {
         }
     }
     
+    /// Inizia un nuovo lessema dalla posizione corrente
+    pub fn begin_lexeme(&mut self) {
+        self.start = self.current;
+        self.start_position = self.position();
+    }
+    
+    /// Intervallo del lessema corrente
+    pub fn current_span(&self) -> Span {
+        Span::new(self.start_position, self.position())
+    }
+    
     /// Restituisce il lessema corrente
     pub fn current_lexeme(&self) -> &'a str {
         &self.source[self.start..self.current]
@@ -106,10 +114,29 @@ impl<'a> Scanner<'a> {
         Token::new(
             token_type,
             self.current_lexeme(),
-            Position::new(self.line, self.column - (self.current - self.start)),
+            self.current_span(),
         )
     }
     
+    /// Indica se tra la posizione corrente, a inizio riga, e la prossima riga
+    /// con del codice c'è una riga vuota. Le righe con soli commenti non
+    /// contano come vuote.
+    pub fn blank_line_ahead(&self) -> bool {
+        let mut scanner = self.clone();
+        while !scanner.is_at_end() {
+            let line_start = scanner.current;
+            scanner.skip_whitespace_and_comments();
+            if scanner.peek() != '\n' {
+                return false;
+            }
+            if scanner.source[line_start..scanner.current].trim().is_empty() {
+                return true;
+            }
+            scanner.advance();
+        }
+        false
+    }
+    
     /// Salta spazi bianchi e commenti
     pub fn skip_whitespace_and_comments(&mut self) {
         loop {
@@ -159,7 +186,7 @@ impl<'a> Scanner<'a> {
             }
         }
         
-        self.start = self.current;
+        self.begin_lexeme();
     }
     
     /// Calcola l'indentazione corrente (numero di spazi all'inizio della riga)
@@ -181,7 +208,7 @@ impl<'a> Scanner<'a> {
             }
         }
         
-        self.start = self.current;
+        self.begin_lexeme();
         indent
     }
 }
\ No newline at end of file
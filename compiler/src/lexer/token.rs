@@ -1,15 +1,25 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Posizione nel codice sorgente
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Position {
     pub line: usize,
     pub column: usize,
+    /// Offset in byte dall'inizio del sorgente
+    pub offset: usize,
 }
 
 impl Position {
-    pub fn new(line: usize, column: usize) -> Self {
-        Self { line, column }
+    pub fn new(line: usize, column: usize, offset: usize) -> Self {
+        Self { line, column, offset }
+    }
+}
+
+impl Default for Position {
+    /// Inizio del sorgente
+    fn default() -> Self {
+        Self::new(1, 1, 0)
     }
 }
 
@@ -19,8 +29,49 @@ impl fmt::Display for Position {
     }
 }
 
+/// Intervallo nel codice sorgente (fine esclusa)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+    
+    /// Crea un intervallo vuoto in una posizione
+    pub fn point(position: Position) -> Self {
+        Self { start: position, end: position }
+    }
+    
+    /// Restituisce l'intervallo che copre sia `self` che `other`
+    pub fn to(&self, other: Span) -> Span {
+        let start = if other.start.offset < self.start.offset { other.start } else { self.start };
+        let end = if other.end.offset > self.end.offset { other.end } else { self.end };
+        Span { start, end }
+    }
+    
+    /// Verifica se l'intervallo contiene interamente `other`
+    pub fn contains(&self, other: Span) -> bool {
+        self.start.offset <= other.start.offset && other.end.offset <= self.end.offset
+    }
+    
+    /// Intervallo in byte, utilizzabile per indicizzare il sorgente
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.start.offset..self.end.offset
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.start)
+    }
+}
+
 /// Tipo di token
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TokenType {
     // Parole chiave
     Let,
@@ -38,6 +89,7 @@ pub enum TokenType {
     From,
     Class,
     Interface,
+    Extends,
     Implements,
     Try,
     Catch,
@@ -49,9 +101,11 @@ pub enum TokenType {
     Type,
     Enum,
     Constructor,
+    Static,
     This,
     Super,
     Match,
+    Yield,
     Case,
     Default,
     New,
@@ -60,6 +114,14 @@ pub enum TokenType {
     Identifier,
     NumberLiteral,
     StringLiteral,
+    /// Template senza interpolazioni: `` `testo` ``
+    TemplateString,
+    /// Inizio di un template fino alla prima interpolazione: `` `testo${ ``
+    TemplateHead,
+    /// Testo tra due interpolazioni: `}testo${`
+    TemplateMiddle,
+    /// Fine di un template dopo l'ultima interpolazione: `` }testo` ``
+    TemplateTail,
     True,
     False,
     Null,
@@ -68,8 +130,13 @@ pub enum TokenType {
     Plus,
     Minus,
     Star,
+    StarStar,
     Slash,
     Percent,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
     Equal,
     EqualEqual,
     Bang,
@@ -83,6 +150,13 @@ pub enum TokenType {
     AmpersandAmpersand,
     Pipe,
     PipePipe,
+    Caret,
+    Tilde,
+    LessLess,
+    GreaterGreater,
+    Question,
+    QuestionDot,
+    QuestionQuestion,
     
     // Punteggiatura
     LeftParen,
@@ -93,7 +167,10 @@ pub enum TokenType {
     RightBracket,
     Comma,
     Dot,
+    DotDotDot,
     Colon,
+    At,
+    Hash,
     
     // Indentazione
     Indent,
@@ -122,6 +199,7 @@ impl fmt::Display for TokenType {
             TokenType::From => "From",
             TokenType::Class => "Class",
             TokenType::Interface => "Interface",
+            TokenType::Extends => "Extends",
             TokenType::Implements => "Implements",
             TokenType::Try => "Try",
             TokenType::Catch => "Catch",
@@ -133,23 +211,34 @@ impl fmt::Display for TokenType {
             TokenType::Type => "Type",
             TokenType::Enum => "Enum",
             TokenType::Constructor => "Constructor",
+            TokenType::Static => "Static",
             TokenType::This => "This",
             TokenType::Super => "Super",
             TokenType::Match => "Match",
+            TokenType::Yield => "Yield",
             TokenType::Case => "Case",
             TokenType::Default => "Default",
             TokenType::New => "New",
             TokenType::Identifier => "Identifier",
             TokenType::NumberLiteral => "NumberLiteral",
             TokenType::StringLiteral => "StringLiteral",
+            TokenType::TemplateString => "TemplateString",
+            TokenType::TemplateHead => "TemplateHead",
+            TokenType::TemplateMiddle => "TemplateMiddle",
+            TokenType::TemplateTail => "TemplateTail",
             TokenType::True => "True",
             TokenType::False => "False",
             TokenType::Null => "Null",
             TokenType::Plus => "Plus",
             TokenType::Minus => "Minus",
             TokenType::Star => "Star",
+            TokenType::StarStar => "StarStar",
             TokenType::Slash => "Slash",
             TokenType::Percent => "Percent",
+            TokenType::PlusEqual => "PlusEqual",
+            TokenType::MinusEqual => "MinusEqual",
+            TokenType::StarEqual => "StarEqual",
+            TokenType::SlashEqual => "SlashEqual",
             TokenType::Equal => "Equal",
             TokenType::EqualEqual => "EqualEqual",
             TokenType::Bang => "Bang",
@@ -163,6 +252,13 @@ impl fmt::Display for TokenType {
             TokenType::AmpersandAmpersand => "AmpersandAmpersand",
             TokenType::Pipe => "Pipe",
             TokenType::PipePipe => "PipePipe",
+            TokenType::Caret => "Caret",
+            TokenType::Tilde => "Tilde",
+            TokenType::LessLess => "LessLess",
+            TokenType::GreaterGreater => "GreaterGreater",
+            TokenType::Question => "Question",
+            TokenType::QuestionDot => "QuestionDot",
+            TokenType::QuestionQuestion => "QuestionQuestion",
             TokenType::LeftParen => "LeftParen",
             TokenType::RightParen => "RightParen",
             TokenType::LeftBrace => "LeftBrace",
@@ -171,7 +267,10 @@ impl fmt::Display for TokenType {
             TokenType::RightBracket => "RightBracket",
             TokenType::Comma => "Comma",
             TokenType::Dot => "Dot",
+            TokenType::DotDotDot => "DotDotDot",
             TokenType::Colon => "Colon",
+            TokenType::At => "At",
+            TokenType::Hash => "Hash",
             TokenType::Indent => "Indent",
             TokenType::Dedent => "Dedent",
             TokenType::Newline => "Newline",
@@ -181,22 +280,84 @@ impl fmt::Display for TokenType {
     }
 }
 
+/// Tabella delle parole chiave del linguaggio
+pub const KEYWORDS: &[(&str, TokenType)] = &[
+    ("let", TokenType::Let),
+    ("const", TokenType::Const),
+    ("fn", TokenType::Fn),
+    ("if", TokenType::If),
+    ("else", TokenType::Else),
+    ("for", TokenType::For),
+    ("while", TokenType::While),
+    ("return", TokenType::Return),
+    ("true", TokenType::True),
+    ("false", TokenType::False),
+    ("null", TokenType::Null),
+    ("import", TokenType::Import),
+    ("export", TokenType::Export),
+    ("from", TokenType::From),
+    ("class", TokenType::Class),
+    ("interface", TokenType::Interface),
+    ("extends", TokenType::Extends),
+    ("implements", TokenType::Implements),
+    ("try", TokenType::Try),
+    ("catch", TokenType::Catch),
+    ("finally", TokenType::Finally),
+    ("throw", TokenType::Throw),
+    ("break", TokenType::Break),
+    ("continue", TokenType::Continue),
+    ("in", TokenType::In),
+    ("is", TokenType::Is),
+    ("as", TokenType::As),
+    ("type", TokenType::Type),
+    ("enum", TokenType::Enum),
+    ("constructor", TokenType::Constructor),
+    ("static", TokenType::Static),
+    ("this", TokenType::This),
+    ("super", TokenType::Super),
+    ("match", TokenType::Match),
+    ("yield", TokenType::Yield),
+    ("case", TokenType::Case),
+    ("default", TokenType::Default),
+    ("new", TokenType::New),
+];
+
+/// Restituisce il tipo di token della parola chiave, se `lexeme` lo è
+pub fn keyword(lexeme: &str) -> Option<TokenType> {
+    KEYWORDS
+        .iter()
+        .find(|(word, _)| *word == lexeme)
+        .map(|(_, token_type)| *token_type)
+}
+
 /// Token del linguaggio NovaScript
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
-    pub position: Position,
+    pub span: Span,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: &str, position: Position) -> Self {
+    pub fn new(token_type: TokenType, lexeme: &str, span: Span) -> Self {
         Self {
             token_type,
             lexeme: lexeme.to_string(),
-            position,
+            span,
         }
     }
+    
+    /// Posizione di inizio del token
+    pub fn position(&self) -> Position {
+        self.span.start
+    }
+    
+    /// Indica se il token è un a capo seguito da almeno una riga vuota, senza
+    /// contare le righe con soli commenti. Il lexer lo registra nel lessema,
+    /// che in questo caso contiene due `\n`.
+    pub fn is_blank_line(&self) -> bool {
+        self.token_type == TokenType::Newline && self.lexeme.len() > 1
+    }
 }
 
 impl fmt::Display for Token {
@@ -206,7 +367,7 @@ impl fmt::Display for Token {
             "{} '{}' at {}",
             self.token_type,
             self.lexeme,
-            self.position
+            self.span.start
         )
     }
 }
\ No newline at end of file
@@ -2,8 +2,10 @@ pub mod ast;
 pub mod compiler;
 pub mod error;
 pub mod lexer;
+pub mod lint;
 pub mod parser;
 pub mod semantics;
+pub mod syntax;
 pub mod ir;
 pub mod codegen;
 pub mod utils;
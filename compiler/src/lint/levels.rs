@@ -0,0 +1,291 @@
+use super::{find_lint, Lint, LINTS, LINT_GROUPS};
+use crate::lexer::{Position, Span, Token, TokenType};
+use crate::utils::diagnostics::{Diagnostic, Level};
+use crate::utils::suggest;
+
+/// Livello configurabile di una regola del linter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    /// Regola disattivata
+    Allow,
+    /// La regola produce avvisi
+    Warn,
+    /// La regola produce errori
+    Deny,
+}
+
+impl LintLevel {
+    /// Interpreta un livello scritto in `nova.config.ns`
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "off" | "allow" | "0" => Some(LintLevel::Allow),
+            "warn" | "warning" | "1" => Some(LintLevel::Warn),
+            "error" | "deny" | "2" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+    
+    /// Interpreta il nome di un decoratore di livello (`@allow`, `@warn`, `@deny`)
+    pub fn from_decorator(name: &str) -> Option<Self> {
+        match name {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+}
+
+impl From<Level> for LintLevel {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Warning => LintLevel::Warn,
+            Level::Error => LintLevel::Deny,
+        }
+    }
+}
+
+/// Livello imposto da un decoratore su una porzione del sorgente
+#[derive(Debug, Clone)]
+struct ScopedLevel {
+    span: Span,
+    selector: String,
+    level: LintLevel,
+}
+
+/// Risolve il livello effettivo di ogni regola in ogni punto del sorgente.
+///
+/// In ordine di precedenza: opzioni da riga di comando, decoratore più interno
+/// che contiene la diagnostica, `lint.rules` di `nova.config.ns`, livello
+/// predefinito della regola. Un selettore può essere il nome di una regola, il
+/// nome di un gruppo oppure `all`.
+#[derive(Debug, Clone, Default)]
+pub struct LintLevels {
+    command_line: Vec<(String, LintLevel)>,
+    scopes: Vec<ScopedLevel>,
+    config: Vec<(String, LintLevel)>,
+}
+
+impl LintLevels {
+    /// Crea una risoluzione che usa solo i livelli predefiniti
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Imposta i livelli letti da `lint.rules` nella configurazione del progetto
+    pub fn set_config(&mut self, rules: Vec<(String, LintLevel)>) {
+        self.config = normalize_all(rules);
+    }
+    
+    /// Imposta i livelli passati da riga di comando; a parità di selettore
+    /// vince l'ultimo
+    pub fn set_command_line(&mut self, rules: Vec<(String, LintLevel)>) {
+        self.command_line = normalize_all(rules);
+    }
+    
+    /// Imposta un livello su una porzione del sorgente
+    pub fn add_scope(&mut self, span: Span, selector: &str, level: LintLevel) {
+        self.scopes.push(ScopedLevel {
+            span,
+            selector: normalize(selector),
+            level,
+        });
+    }
+    
+    /// Selettori di configurazione e riga di comando che non corrispondono a
+    /// nessuna regola o gruppo
+    pub fn unknown_selectors(&self) -> Vec<&str> {
+        self.command_line
+            .iter()
+            .chain(self.config.iter())
+            .map(|(selector, _)| selector.as_str())
+            .filter(|selector| !is_known_selector(selector))
+            .collect()
+    }
+    
+    /// Livello effettivo della regola `name` per una diagnostica in `span`
+    pub fn level_for(&self, name: &str, span: Option<Span>) -> LintLevel {
+        let lint = find_lint(name);
+        let selects = |selector: &str| {
+            selector == "all"
+                || selector == name
+                || lint.is_some_and(|lint| selector == lint.group)
+        };
+        
+        if let Some((_, level)) = self.command_line.iter().rev().find(|(s, _)| selects(s)) {
+            return *level;
+        }
+        
+        if let Some(span) = span {
+            // Vince lo scope più interno; a parità di scope l'ultimo decoratore
+            let innermost = self
+                .scopes
+                .iter()
+                .enumerate()
+                .filter(|(_, scope)| scope.span.contains(span) && selects(&scope.selector))
+                .min_by_key(|(index, scope)| (scope.span.range().len(), usize::MAX - index));
+            if let Some((_, scope)) = innermost {
+                return scope.level;
+            }
+        }
+        
+        if let Some((_, level)) = self.config.iter().rev().find(|(s, _)| selects(s)) {
+            return *level;
+        }
+        
+        lint.map_or(LintLevel::Warn, |lint| lint.level.into())
+    }
+    
+    /// Legge i decoratori `@allow`, `@warn` e `@deny` dal flusso di token.
+    ///
+    /// Un decoratore si applica alla dichiarazione che lo segue, incluso il suo
+    /// blocco, anche se tra i due ci sono righe di commento. Un decoratore
+    /// separato da una riga vuota da ciò che segue, o in fondo a un blocco, si
+    /// applica invece all'intero blocco che lo contiene: all'inizio del file,
+    /// quindi, vale per tutto il file. Restituisce gli avvisi per i selettori
+    /// sconosciuti.
+    pub fn add_source_scopes(&mut self, tokens: &[Token]) -> Vec<Diagnostic> {
+        let mut warnings = Vec::new();
+        let mut blocks = vec![Position::new(1, 1, 0)];
+        let mut index = 0;
+        
+        while index < tokens.len() {
+            match tokens[index].token_type {
+                TokenType::Indent => blocks.push(tokens[index].span.start),
+                TokenType::Dedent => {
+                    blocks.pop();
+                },
+                TokenType::At if is_statement_start(tokens, index) => {
+                    let start = tokens[index].span.start;
+                    let mut levels = Vec::new();
+                    let mut next = index;
+                    while next < tokens.len() && tokens[next].token_type == TokenType::At {
+                        next = read_decorator(tokens, next, &mut levels);
+                        if tokens[next - 1].is_blank_line() {
+                            break;
+                        }
+                    }
+                    
+                    // Come nel parser, solo una riga vuota separa i decoratori
+                    // da ciò che segue, anche da altri decoratori: le righe di
+                    // commento non contano
+                    let dangling = next >= tokens.len()
+                        || matches!(tokens[next].token_type, TokenType::Dedent | TokenType::Eof)
+                        || tokens[next - 1].is_blank_line();
+                    let span = if dangling {
+                        let block_start = *blocks.last().unwrap();
+                        Span::new(block_start, block_end(tokens, next))
+                    } else {
+                        Span::new(start, item_end(tokens, next))
+                    };
+                    
+                    for (selector, level) in levels {
+                        if !is_known_selector(&normalize(&selector.lexeme)) {
+                            warnings.push(unknown_selector_warning(&selector));
+                        }
+                        self.add_scope(span, &selector.lexeme, level);
+                    }
+                    index = next;
+                    continue;
+                },
+                _ => {},
+            }
+            index += 1;
+        }
+        
+        warnings
+    }
+}
+
+/// I selettori usano `-` come separatore, ma nei decoratori si può scrivere
+/// `no_tabs` al posto di `"no-tabs"`
+fn normalize(selector: &str) -> String {
+    selector.replace('_', "-")
+}
+
+fn normalize_all(rules: Vec<(String, LintLevel)>) -> Vec<(String, LintLevel)> {
+    rules
+        .into_iter()
+        .map(|(selector, level)| (normalize(&selector), level))
+        .collect()
+}
+
+fn is_known_selector(selector: &str) -> bool {
+    selector == "all" || find_lint(selector).is_some() || LINT_GROUPS.contains(&selector)
+}
+
+/// Avviso per un selettore sconosciuto, con le regole dal nome simile
+fn unknown_selector_warning(selector: &Token) -> Diagnostic {
+    let name = normalize(&selector.lexeme);
+    let names = LINTS.iter().map(|lint: &&Lint| lint.name).chain(LINT_GROUPS.iter().copied());
+    let mut message = format!("Regola del linter sconosciuta: `{}`", name);
+    if let Some((matches, _)) = suggest::closest(&name, names) {
+        message.push_str(&format!("; forse intendevi `{}`?", matches[0]));
+    }
+    Diagnostic::warning(message, selector.span)
+}
+
+fn is_statement_start(tokens: &[Token], index: usize) -> bool {
+    index == 0
+        || matches!(
+            tokens[index - 1].token_type,
+            TokenType::Newline | TokenType::Indent | TokenType::Dedent
+        )
+}
+
+/// Legge un decoratore a partire da `@` e restituisce l'indice del token
+/// successivo alla sua riga. Solo i decoratori di livello producono selettori.
+fn read_decorator(tokens: &[Token], at: usize, levels: &mut Vec<(Token, LintLevel)>) -> usize {
+    let mut index = at + 1;
+    let level = tokens
+        .get(index)
+        .and_then(|name| LintLevel::from_decorator(&name.lexeme));
+    
+    while index < tokens.len() {
+        let token = &tokens[index];
+        match token.token_type {
+            TokenType::Newline => return index + 1,
+            TokenType::Eof => return index,
+            TokenType::Identifier | TokenType::StringLiteral if index > at + 1 => {
+                if let Some(level) = level {
+                    levels.push((token.clone(), level));
+                }
+            },
+            _ => {},
+        }
+        index += 1;
+    }
+    
+    index
+}
+
+/// Fine della dichiarazione che inizia in `start`, incluso il suo blocco
+fn item_end(tokens: &[Token], start: usize) -> Position {
+    let mut index = start;
+    while index < tokens.len() && !matches!(tokens[index].token_type, TokenType::Newline | TokenType::Eof) {
+        index += 1;
+    }
+    if index >= tokens.len() {
+        return tokens.last().map_or(Position::new(1, 1, 0), |t| t.span.end);
+    }
+    
+    match tokens.get(index + 1) {
+        Some(next) if next.token_type == TokenType::Indent => block_end(tokens, index + 2),
+        _ => tokens[index].span.end,
+    }
+}
+
+/// Fine del blocco che contiene il token in `start`
+fn block_end(tokens: &[Token], start: usize) -> Position {
+    let mut depth = 0usize;
+    for token in tokens.iter().skip(start) {
+        match token.token_type {
+            TokenType::Indent => depth += 1,
+            TokenType::Dedent if depth == 0 => return token.span.start,
+            TokenType::Dedent => depth -= 1,
+            TokenType::Eof => return token.span.end,
+            _ => {},
+        }
+    }
+    tokens.last().map_or(Position::new(1, 1, 0), |t| t.span.end)
+}
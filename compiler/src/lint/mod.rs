@@ -0,0 +1,259 @@
+pub mod levels;
+mod rules;
+
+pub use levels::{LintLevel, LintLevels};
+
+use crate::lexer::Lexer;
+use crate::semantics::{check_decorators, resolve};
+use crate::utils::diagnostics::{Diagnostic, Diagnostics, Level};
+
+/// Descrizione di una regola del linter
+#[derive(Debug)]
+pub struct Lint {
+    /// Nome della regola, usato nei messaggi e nella configurazione
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Gruppo della regola, selezionabile al posto del nome
+    pub group: &'static str,
+    /// Livello predefinito
+    pub level: Level,
+}
+
+/// Gruppi di regole selezionabili in configurazione e nei decoratori
+pub static LINT_GROUPS: &[&str] = &["style", "correctness", "unused"];
+
+/// Indentazione con tabulazioni invece che con spazi
+pub static NO_TABS: Lint = Lint {
+    name: "no-tabs",
+    description: "l'indentazione deve usare spazi e non tabulazioni",
+    group: "style",
+    level: Level::Warning,
+};
+
+/// Identificatore che sembra una parola chiave scritta male
+pub static MISSPELLED_KEYWORD: Lint = Lint {
+    name: "misspelled-keyword",
+    description: "un identificatore a inizio istruzione sembra una parola chiave scritta male",
+    group: "correctness",
+    level: Level::Error,
+};
+
+/// Assegnazione `=` usata come condizione al posto del confronto `==`
+pub static ASSIGN_IN_CONDITION: Lint = Lint {
+    name: "assign-in-condition",
+    description: "una condizione contiene `=` invece di `==`",
+    group: "correctness",
+    level: Level::Error,
+};
+
+/// Uso di una funzione, classe o metodo marcati `@deprecated`
+pub static DEPRECATED: Lint = Lint {
+    name: "deprecated",
+    description: "uso di una dichiarazione marcata `@deprecated`",
+    group: "correctness",
+    level: Level::Warning,
+};
+
+/// Variabile locale dichiarata e mai usata
+pub static UNUSED_VARIABLE: Lint = Lint {
+    name: "no-unused-vars",
+    description: "una variabile locale non è mai usata",
+    group: "unused",
+    level: Level::Warning,
+};
+
+/// Nome importato e mai usato
+pub static UNUSED_IMPORT: Lint = Lint {
+    name: "no-unused-imports",
+    description: "un nome importato non è mai usato",
+    group: "unused",
+    level: Level::Warning,
+};
+
+/// Tutte le regole del linter
+pub static LINTS: &[&Lint] = &[
+    &NO_TABS,
+    &MISSPELLED_KEYWORD,
+    &ASSIGN_IN_CONDITION,
+    &DEPRECATED,
+    &UNUSED_VARIABLE,
+    &UNUSED_IMPORT,
+];
+
+impl Lint {
+    /// Crea una diagnostica per questa regola
+    pub fn diagnostic(&self, message: String, span: crate::lexer::Span) -> Diagnostic {
+        Diagnostic::new(self.level, message, Some(span)).with_code(self.name)
+    }
+}
+
+/// Cerca una regola per nome
+pub fn find_lint(name: &str) -> Option<&'static Lint> {
+    LINTS.iter().copied().find(|lint| lint.name == name)
+}
+
+/// Esegue tutte le regole del linter sul sorgente. I livelli di `levels` sono
+/// completati con i decoratori `@allow`, `@warn` e `@deny` del sorgente.
+pub fn lint_source(source: &str, levels: &LintLevels) -> Diagnostics {
+    let tokens = Lexer::new(source).tokenize();
+    let mut levels = levels.clone();
+    let warnings = match &tokens {
+        Ok(tokens) => levels.add_source_scopes(tokens),
+        Err(_) => Vec::new(),
+    };
+    
+    let mut diagnostics = Diagnostics::with_levels(levels);
+    for warning in warnings {
+        diagnostics.push(warning);
+    }
+    
+    rules::no_tabs(source, &mut diagnostics);
+    
+    match tokens {
+        Ok(tokens) => {
+            rules::misspelled_keyword(&tokens, &mut diagnostics);
+            rules::assign_in_condition(&tokens, &mut diagnostics);
+            
+            // Le regole sui nomi richiedono un programma valido; gli errori di
+            // risoluzione sono segnalati dalla compilazione
+            if let Ok(program) = crate::parser::parse(source) {
+                let (resolution, _) = resolve(&program);
+                rules::unused(&resolution, &mut diagnostics);
+                check_decorators(&program, &resolution, &mut diagnostics);
+            }
+        },
+        // Senza token le altre regole non possono essere eseguite
+        Err(error) => diagnostics.push(Diagnostic::from(&error)),
+    }
+    
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fix::apply_suggestions;
+    
+    fn lint(source: &str) -> Diagnostics {
+        lint_source(source, &LintLevels::new())
+    }
+    
+    fn fix(source: &str) -> String {
+        let diagnostics = lint(source);
+        apply_suggestions(source, diagnostics.suggestions()).source
+    }
+    
+    #[test]
+    fn test_unterminated_string() {
+        assert_eq!(fix("let s = \"hello\nlet t = 1"), "let s = \"hello\"\nlet t = 1");
+        assert_eq!(fix("println(\"ciao)  \r\n"), "println(\"ciao\")  \r\n");
+        
+        // La parentesi della riga successiva chiude una chiamata di un'altra riga
+        assert_eq!(fix("foo(\n  \"abc)\n)\n"), "foo(\n  \"abc)\"\n)\n");
+    }
+    
+    #[test]
+    fn test_tabs_to_spaces() {
+        assert_eq!(fix("if x\n\ty = 1\n"), "if x\n    y = 1\n");
+    }
+    
+    #[test]
+    fn test_misspelled_keyword() {
+        assert_eq!(fix("lte x = 1\nretrun x"), "let x = 1\nreturn x");
+        assert_eq!(fix("fucntion add(a, b)\n  return a + b"), "fn add(a, b)\n  return a + b");
+        
+        // Un refuso ambiguo viene segnalato ma non corretto automaticamente
+        let diagnostics = lint("fo x");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(fix("fo x"), "fo x");
+    }
+    
+    #[test]
+    fn test_assign_in_condition() {
+        assert_eq!(fix("if x = 1\n  y = 2"), "if x == 1\n  y = 2");
+        assert_eq!(fix("while f(a) = b\n  g()"), "while f(a) == b\n  g()");
+        assert!(lint("if x == 1\n  y = 2").is_empty());
+    }
+    
+    #[test]
+    fn test_allow_is_scoped_to_item() {
+        let source = "@allow(no_tabs)\nfn a()\n\treturn 1\n\nfn b()\n\treturn 2\n";
+        let diagnostics = lint(source);
+        
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = diagnostics.iter().next().unwrap();
+        assert_eq!(diagnostic.span.unwrap().start.line, 6);
+        
+        // Una riga di commento non separa il decoratore dalla sua funzione
+        let source = "@allow(no_tabs)\n// Indentata con i tab\nfn a()\n\treturn 1\n\nfn b()\n\treturn 2\n";
+        let diagnostics = lint(source);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics.iter().next().unwrap().span.unwrap().start.line, 7);
+    }
+    
+    #[test]
+    fn test_file_level_and_precedence() {
+        let source = "@deny(style)\n\nfn a()\n\treturn 1\n";
+        let diagnostics = lint(source);
+        assert_eq!(diagnostics.iter().next().unwrap().level, Level::Error);
+        
+        // La riga di comando ha la precedenza sui decoratori
+        let mut levels = LintLevels::new();
+        levels.set_command_line(vec![("no-tabs".to_string(), LintLevel::Allow)]);
+        assert!(lint_source(source, &levels).is_empty());
+        
+        // La configurazione del progetto non supera i decoratori
+        let mut levels = LintLevels::new();
+        levels.set_config(vec![("no-tabs".to_string(), LintLevel::Allow)]);
+        assert_eq!(lint_source(source, &levels).len(), 1);
+        assert!(lint_source("fn a()\n\treturn 1\n", &levels).is_empty());
+        
+        // Il livello del file vale anche se la dichiarazione seguente ha decoratori
+        assert!(lint("@allow(no_tabs)\n\n@inline\nfn a()\n  return 1\n\nfn b()\n\treturn 1\n").is_empty());
+    }
+    
+    #[test]
+    fn test_unknown_selector() {
+        let diagnostics = lint("@allow(no_tab)\nlet x = 1\n");
+        let warning = diagnostics.iter().next().unwrap();
+        assert_eq!(warning.message, "Regola del linter sconosciuta: `no-tab`; forse intendevi `no-tabs`?");
+    }
+    
+    #[test]
+    fn test_unused_names() {
+        let source = "\
+import { sqrt, PI, E } from \"Math\"
+export { E }
+
+fn area(r)
+  let unused = 1
+  let _ignored = 2
+  let used = r * r
+  return { used, pi: PI }
+";
+        let messages: Vec<String> = lint(source).into_sorted_vec().into_iter().map(|d| d.message).collect();
+        assert_eq!(messages, vec![
+            "`sqrt` è importato ma non è mai usato",
+            "La variabile `unused` non è mai usata",
+        ]);
+        
+        // Il gruppo `unused` disattiva entrambe le regole
+        let source = format!("@allow(unused)\n\n{}", source);
+        assert!(lint(&source).is_empty());
+    }
+    
+    #[test]
+    fn test_deprecated_uses() {
+        let source = "\
+@deprecated(\"Usa load()\")
+fn fetch()
+  return 1
+
+let data = fetch()
+export { data }
+";
+        let messages: Vec<String> = lint(source).into_sorted_vec().into_iter().map(|d| d.message).collect();
+        assert_eq!(messages, vec!["`fetch` è deprecato: Usa load()"]);
+        assert!(lint(&format!("@allow(deprecated)\n\n{}", source)).is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use super::{ASSIGN_IN_CONDITION, MISSPELLED_KEYWORD, NO_TABS, UNUSED_IMPORT, UNUSED_VARIABLE};
+use crate::lexer::{Position, Span, Token, TokenType, KEYWORDS};
+use crate::semantics::{Resolution, ScopeKind, SymbolKind};
+use crate::utils::diagnostics::{Applicability, Diagnostics, Suggestion};
+use crate::utils::suggest;
+
+/// Parole chiave di altri linguaggi con il loro equivalente NovaScript
+const FOREIGN_KEYWORDS: &[(&str, &str)] = &[
+    ("function", "fn"),
+    ("func", "fn"),
+    ("def", "fn"),
+    ("var", "let"),
+    ("elif", "else if"),
+];
+
+/// Segnala le tabulazioni nell'indentazione. Il lexer conta ogni tabulazione
+/// come 4 spazi, quindi la sostituzione non cambia la struttura dei blocchi.
+pub(super) fn no_tabs(source: &str, diagnostics: &mut Diagnostics) {
+    let mut offset = 0;
+    
+    for (index, line) in source.split('\n').enumerate() {
+        let indent_len = line.len() - line.trim_start_matches([' ', '\t']).len();
+        let indent = &line[..indent_len];
+        
+        if indent.contains('\t') {
+            let start = Position::new(index + 1, 1, offset);
+            let end = Position::new(index + 1, indent_len + 1, offset + indent_len);
+            let replacement = indent.replace('\t', "    ");
+            diagnostics.push(
+                NO_TABS
+                    .diagnostic("Tabulazioni nell'indentazione".to_string(), Span::new(start, end))
+                    .with_suggestion(Suggestion::new(
+                        Span::new(start, end),
+                        replacement,
+                        "sostituisci le tabulazioni con spazi".to_string(),
+                        Applicability::MachineApplicable,
+                    )),
+            );
+        }
+        
+        offset += line.len() + 1;
+    }
+}
+
+/// Segnala un identificatore a inizio istruzione seguito da un altro operando,
+/// come in `lte x = 1` o `retrun x`: nessuna istruzione valida inizia così,
+/// quindi il primo identificatore è quasi sempre una parola chiave scritta male.
+pub(super) fn misspelled_keyword(tokens: &[Token], diagnostics: &mut Diagnostics) {
+    for (index, window) in tokens.windows(2).enumerate() {
+        let (word, next) = (&window[0], &window[1]);
+        let at_statement_start = index == 0 || matches!(
+            tokens[index - 1].token_type,
+            TokenType::Newline | TokenType::Indent | TokenType::Dedent
+        );
+        let followed_by_operand = matches!(
+            next.token_type,
+            TokenType::Identifier
+                | TokenType::NumberLiteral
+                | TokenType::StringLiteral
+                | TokenType::True
+                | TokenType::False
+                | TokenType::Null
+                | TokenType::This
+                | TokenType::New
+        );
+        if !at_statement_start || word.token_type != TokenType::Identifier || !followed_by_operand {
+            continue;
+        }
+        
+        let candidates = KEYWORDS
+            .iter()
+            .map(|(keyword, _)| *keyword)
+            .chain(FOREIGN_KEYWORDS.iter().map(|(foreign, _)| *foreign));
+        let Some((matches, distance)) = suggest::closest(&word.lexeme, candidates) else {
+            continue;
+        };
+        
+        let mut replacements: Vec<&str> = Vec::new();
+        for candidate in matches {
+            let replacement = FOREIGN_KEYWORDS
+                .iter()
+                .find(|(foreign, _)| *foreign == candidate)
+                .map_or(candidate, |(_, native)| *native);
+            if !replacements.contains(&replacement) {
+                replacements.push(replacement);
+            }
+        }
+        
+        // Solo un refuso minimo e non ambiguo può essere corretto automaticamente
+        let applicability = if replacements.len() == 1 && distance <= 1 {
+            Applicability::MachineApplicable
+        } else {
+            Applicability::MaybeIncorrect
+        };
+        
+        let mut diagnostic = MISSPELLED_KEYWORD.diagnostic(
+            format!("`{}` non è una parola chiave", word.lexeme),
+            word.span,
+        );
+        for replacement in replacements {
+            diagnostic = diagnostic.with_suggestion(Suggestion::new(
+                word.span,
+                replacement.to_string(),
+                format!("forse intendevi `{}`", replacement),
+                applicability,
+            ));
+        }
+        diagnostics.push(diagnostic);
+    }
+}
+
+/// Segnala `=` usato nella condizione di un `if` o di un `while`. In NovaScript
+/// l'assegnazione è un'istruzione, quindi il confronto `==` è l'unica lettura valida.
+pub(super) fn assign_in_condition(tokens: &[Token], diagnostics: &mut Diagnostics) {
+    for (index, token) in tokens.iter().enumerate() {
+        if !matches!(token.token_type, TokenType::If | TokenType::While) {
+            continue;
+        }
+        
+        let mut depth = 0usize;
+        for condition_token in &tokens[index + 1..] {
+            match condition_token.token_type {
+                TokenType::Newline | TokenType::Indent | TokenType::Eof => break,
+                TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace => depth += 1,
+                TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => {
+                    depth = depth.saturating_sub(1)
+                },
+                TokenType::Equal if depth == 0 => {
+                    diagnostics.push(
+                        ASSIGN_IN_CONDITION
+                            .diagnostic(
+                                "Assegnazione usata come condizione".to_string(),
+                                condition_token.span,
+                            )
+                            .with_suggestion(Suggestion::new(
+                                condition_token.span,
+                                "==".to_string(),
+                                "usa `==` per il confronto".to_string(),
+                                Applicability::MachineApplicable,
+                            )),
+                    );
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+/// Segnala le variabili locali e gli import mai usati. I nomi che iniziano con
+/// `_` sono esclusi; le variabili del modulo possono essere esportate, quindi
+/// sono controllate solo quelle dentro funzioni e blocchi.
+pub(super) fn unused(resolution: &Resolution, diagnostics: &mut Diagnostics) {
+    let used = resolution.used_symbols();
+    
+    for (id, symbol) in resolution.symbols.symbols() {
+        if used.contains(&id) || symbol.name.starts_with('_') {
+            continue;
+        }
+        match symbol.kind {
+            SymbolKind::Import => diagnostics.push(UNUSED_IMPORT.diagnostic(
+                format!("`{}` è importato ma non è mai usato", symbol.name),
+                symbol.span,
+            )),
+            SymbolKind::Let | SymbolKind::Const
+                if resolution.symbols.scope(symbol.scope).kind != ScopeKind::Module =>
+            {
+                diagnostics.push(UNUSED_VARIABLE.diagnostic(
+                    format!("La variabile `{}` non è mai usata", symbol.name),
+                    symbol.span,
+                ));
+            },
+            _ => {},
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use novascript::ast::DumpFormat;
 use novascript::compiler::Compiler;
 use novascript::error::Error;
+use novascript::lint::LintLevel;
+use novascript::utils::ice::{self, IceOptions};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -8,6 +11,10 @@ use std::path::PathBuf;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    
+    /// In caso di errore interno scrive in DIR un report per la segnalazione del bug
+    #[arg(long, global = true, value_name = "DIR")]
+    ice_report_dir: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -54,6 +61,22 @@ enum Commands {
         /// Compila in modalità release
         #[arg(short, long)]
         release: bool,
+        
+        /// Stampa una rappresentazione intermedia invece di compilare
+        #[arg(long, value_enum, value_name = "FASE")]
+        emit: Option<Emit>,
+        
+        #[command(flatten)]
+        dump: DumpArgs,
+    },
+    
+    /// Stampa l'AST di un file, per il debug del parser
+    Parse {
+        /// Percorso del file da analizzare
+        file: PathBuf,
+        
+        #[command(flatten)]
+        dump: DumpArgs,
     },
     
     /// Esegue i test
@@ -87,6 +110,18 @@ enum Commands {
         /// Correggi automaticamente i problemi quando possibile
         #[arg(short, long)]
         fix: bool,
+        
+        /// Disattiva una regola o un gruppo di regole
+        #[arg(short = 'A', long, value_name = "REGOLA")]
+        allow: Vec<String>,
+        
+        /// Segnala una regola o un gruppo di regole come avviso
+        #[arg(short = 'W', long, value_name = "REGOLA")]
+        warn: Vec<String>,
+        
+        /// Segnala una regola o un gruppo di regole come errore
+        #[arg(short = 'D', long, value_name = "REGOLA")]
+        deny: Vec<String>,
     },
     
     /// Controlla i tipi nel codice
@@ -107,13 +142,66 @@ enum Commands {
     },
 }
 
+/// Opzioni della stampa dell'AST, per `parse` e `build --emit ast`
+#[derive(Args)]
+struct DumpArgs {
+    /// Formato dell'AST stampato
+    #[arg(long, value_enum, default_value_t = AstFormat::Sexpr)]
+    format: AstFormat,
+    
+    /// Omette le posizioni, così l'uscita non cambia quando cambiano solo gli spazi
+    #[arg(long)]
+    no_spans: bool,
+}
+
+impl DumpArgs {
+    fn format(&self) -> DumpFormat {
+        match self.format {
+            AstFormat::Sexpr => DumpFormat::SExpr,
+            AstFormat::Json => DumpFormat::Json,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AstFormat {
+    /// S-espressioni indentate
+    Sexpr,
+    /// JSON con le posizioni di ogni nodo
+    Json,
+}
+
+/// Rappresentazioni intermedie stampabili da `build --emit`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Emit {
+    /// Albero sintattico
+    Ast,
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Error> {
     env_logger::init();
     
     let cli = Cli::parse();
+    let ice_options = IceOptions {
+        report_dir: cli.ice_report_dir.clone(),
+    };
     
-    match &cli.command {
+    // Il server di sviluppo è l'unico comando asincrono
+    if let Commands::Dev { file, port } = &cli.command {
+        println!("Avvio server di sviluppo su porta {}...", port);
+        return ice::catch_async(&ice_options, async {
+            let _stage = ice::enter_stage("dev", file);
+            novascript::commands::dev::execute(file, *port).await
+        }).await;
+    }
+    
+    ice::catch(&ice_options, || run(&cli.command))
+}
+
+/// Esegue i comandi sincroni della CLI
+fn run(command: &Commands) -> Result<(), Error> {
+    match command {
         Commands::New { name, template } => {
             println!("Creazione nuovo progetto '{}' con template '{}'...", name, template);
             novascript::commands::new::execute(name, template)?;
@@ -121,20 +209,29 @@ async fn main() -> Result<(), Error> {
         
         Commands::Run { file, args } => {
             println!("Esecuzione '{}'...", file.display());
+            let _stage = ice::enter_stage("run", file);
             novascript::commands::run::execute(file, args)?;
         },
         
-        Commands::Dev { file, port } => {
-            println!("Avvio server di sviluppo su porta {}...", port);
-            novascript::commands::dev::execute(file, *port).await?;
+        Commands::Dev { .. } => unreachable!("gestito in main"),
+        
+        Commands::Build { file, emit: Some(Emit::Ast), dump, .. } => {
+            let _stage = ice::enter_stage("parse", file);
+            novascript::commands::parse::execute(file, dump.format(), !dump.no_spans)?;
         },
         
-        Commands::Build { file, out_dir, release } => {
+        Commands::Build { file, out_dir, release, .. } => {
             println!("Compilazione '{}'...", file.display());
+            let _stage = ice::enter_stage("build", file);
             let compiler = Compiler::new();
             compiler.compile(file, out_dir, *release)?;
         },
         
+        Commands::Parse { file, dump } => {
+            let _stage = ice::enter_stage("parse", file);
+            novascript::commands::parse::execute(file, dump.format(), !dump.no_spans)?;
+        },
+        
         Commands::Test { pattern, watch } => {
             println!("Esecuzione test{}{}...", 
                 pattern.as_ref().map_or("".to_string(), |p| format!(" con pattern '{}'", p)), 
@@ -148,10 +245,14 @@ async fn main() -> Result<(), Error> {
             novascript::commands::fmt::execute(path, *check)?;
         },
         
-        Commands::Lint { path, fix } => {
+        Commands::Lint { path, fix, allow, warn, deny } => {
             println!("Linting '{}'{}...", path,
                 if *fix { " con correzione automatica" } else { "" });
-            novascript::commands::lint::execute(path, *fix)?;
+            let overrides = allow.iter().map(|r| (r.clone(), LintLevel::Allow))
+                .chain(warn.iter().map(|r| (r.clone(), LintLevel::Warn)))
+                .chain(deny.iter().map(|r| (r.clone(), LintLevel::Deny)))
+                .collect();
+            novascript::commands::lint::execute(path, *fix, overrides)?;
         },
         
         Commands::Check { path } => {
@@ -0,0 +1,399 @@
+//! Dichiarazioni di tipi nominali: classi, interfacce ed enum. Il corpo è un
+//! blocco indentato con un membro per riga. Qui si analizzano anche i
+//! decoratori che precedono funzioni, classi, metodi e campi.
+
+use super::Parser;
+use crate::ast::{
+    ClassDecl, ClassMember, ClassMemberKind, Decorator, EnumDecl, EnumVariant, ExportDecl, InterfaceDecl,
+    InterfaceMember, InterfaceMemberKind, Stmt, StmtKind, TypeExpr,
+};
+use crate::error::Result;
+use crate::lexer::TokenType;
+
+impl Parser {
+    /// `class Nome<T> extends Base implements A, B`
+    pub(super) fn class_declaration(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        let name = self.identifier("il nome della classe")?;
+        let type_params = self.type_params()?;
+        let extends = if self.matches(TokenType::Extends) { Some(self.type_expr()?) } else { None };
+        let implements = if self.matches(TokenType::Implements) { self.type_list()? } else { Vec::new() };
+        let members = self.members(Self::class_member)?;
+        
+        let mut constructors = members.iter().filter(|m| matches!(m.kind, ClassMemberKind::Constructor { .. }));
+        if let Some(duplicate) = constructors.nth(1) {
+            return Err(self.error_at(duplicate.span, "Una classe può avere un solo costruttore".to_string()));
+        }
+        
+        let decl = ClassDecl {
+            decorators: Vec::new(),
+            name,
+            type_params,
+            extends,
+            implements,
+            members,
+        };
+        Ok(Stmt::new(self.next_id(), StmtKind::Class(decl), self.span_from(keyword.span)))
+    }
+    
+    /// Campo, costruttore o metodo, eventualmente preceduto da decoratori e da `static`
+    fn class_member(&mut self) -> Result<ClassMember> {
+        let start = self.peek().span;
+        let decorators = self.decorators()?;
+        let is_static = self.matches(TokenType::Static);
+        
+        let kind = match self.peek().token_type {
+            TokenType::Constructor => {
+                let token = self.advance();
+                if is_static {
+                    return Err(self.error_at(token.span, "Un costruttore non può essere `static`".to_string()));
+                }
+                if let Some(decorator) = decorators.first() {
+                    return Err(self.error_at(decorator.span, "Un costruttore non può avere decoratori".to_string()));
+                }
+                self.expect(TokenType::LeftParen, "'(' dopo `constructor`")?;
+                let params = self.parameters()?;
+                let body = self.function_body(false, |this| this.block("la firma del costruttore"))?;
+                ClassMemberKind::Constructor { params, body }
+            },
+            TokenType::Fn => {
+                self.advance();
+                ClassMemberKind::Method(self.function()?)
+            },
+            // Come `constructor` e `fn`, `static` inizia un altro membro e non è il nome di un campo
+            TokenType::Static => return Err(self.unexpected("il nome di un campo")),
+            _ => {
+                let name = self.property_name()?;
+                let ty = if self.matches(TokenType::Colon) { Some(self.type_expr()?) } else { None };
+                let init = if self.matches(TokenType::Equal) { Some(self.expression_or_error()) } else { None };
+                self.end_statement();
+                ClassMemberKind::Field { name, ty, init }
+            },
+        };
+        
+        Ok(ClassMember {
+            id: self.next_id(),
+            decorators,
+            is_static,
+            kind,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Decoratori seguiti dalla funzione o dalla classe a cui si applicano.
+    /// Se una riga vuota li separa da ciò che segue valgono invece per il
+    /// blocco che li contiene, come i decoratori `@allow` letti dal linter.
+    /// Una riga con soli commenti non li separa dalla dichiarazione.
+    pub(super) fn decorated_declaration(&mut self) -> Result<Stmt> {
+        let start = self.peek().span;
+        let decorators = self.decorators()?;
+        
+        let blank_line = self.tokens[self.current - 1].is_blank_line();
+        let next = self.peek();
+        if matches!(next.token_type, TokenType::Dedent | TokenType::Eof) || blank_line {
+            let span = self.span_from(start);
+            return Ok(Stmt::new(self.next_id(), StmtKind::Decorators(decorators), span));
+        }
+        
+        if !matches!(next.token_type, TokenType::Fn | TokenType::Class | TokenType::Export) {
+            return Err(self.error_at(next.span, "Un decoratore deve precedere una funzione o una classe".to_string()));
+        }
+        let mut stmt = self.statement()?;
+        let target = match &mut stmt.kind {
+            StmtKind::Export(ExportDecl::Declaration(inner) | ExportDecl::Default(inner)) => &mut inner.kind,
+            kind => kind,
+        };
+        match target {
+            StmtKind::Function(function) => function.decorators = decorators,
+            StmtKind::Class(class) => class.decorators = decorators,
+            _ => return Err(self.error_at(stmt.span, "Un decoratore deve precedere una funzione o una classe".to_string())),
+        }
+        stmt.span = start.to(stmt.span);
+        
+        Ok(stmt)
+    }
+    
+    /// `@nome` oppure `@nome(argomenti)`, anche più d'uno, ciascuno sulla sua
+    /// riga o sulla stessa riga della dichiarazione
+    fn decorators(&mut self) -> Result<Vec<Decorator>> {
+        let mut decorators = Vec::new();
+        while self.check(TokenType::At) {
+            let at = self.advance();
+            let name = self.identifier("il nome del decoratore")?;
+            let args = if self.matches(TokenType::LeftParen) { Some(self.arguments()?) } else { None };
+            decorators.push(Decorator {
+                name,
+                args,
+                span: self.span_from(at.span),
+            });
+            self.matches(TokenType::Newline);
+        }
+        Ok(decorators)
+    }
+    
+    /// `interface Nome<T> extends A, B`
+    pub(super) fn interface_declaration(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        let name = self.identifier("il nome dell'interfaccia")?;
+        let type_params = self.type_params()?;
+        let extends = if self.matches(TokenType::Extends) { self.type_list()? } else { Vec::new() };
+        let members = self.members(Self::interface_member)?;
+        
+        let decl = InterfaceDecl {
+            name,
+            type_params,
+            extends,
+            members,
+        };
+        Ok(Stmt::new(self.next_id(), StmtKind::Interface(decl), self.span_from(keyword.span)))
+    }
+    
+    /// Proprietà `nome?: tipo` oppure firma di metodo `nome<T>(params): tipo`
+    fn interface_member(&mut self) -> Result<InterfaceMember> {
+        let name = self.property_name()?;
+        let optional = self.matches(TokenType::Question);
+        
+        let kind = if self.check(TokenType::LeftParen) || self.check(TokenType::Less) {
+            let type_params = self.type_params()?;
+            self.expect(TokenType::LeftParen, "'(' dopo il nome del metodo")?;
+            let params = self.parameters()?;
+            let return_type = self.return_type()?;
+            InterfaceMemberKind::Method {
+                type_params,
+                params,
+                return_type,
+            }
+        } else {
+            self.expect(TokenType::Colon, "':' o '(' dopo il nome del membro")?;
+            InterfaceMemberKind::Property(self.type_expr()?)
+        };
+        let span = self.span_from(name.span);
+        self.end_statement();
+        
+        Ok(InterfaceMember {
+            name,
+            optional,
+            kind,
+            span,
+        })
+    }
+    
+    /// `enum Nome<T>`, seguito da una variante per riga
+    pub(super) fn enum_declaration(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        let name = self.identifier("il nome dell'enum")?;
+        let type_params = self.type_params()?;
+        let variants = self.members(Self::enum_variant)?;
+        
+        let decl = EnumDecl {
+            name,
+            type_params,
+            variants,
+        };
+        Ok(Stmt::new(self.next_id(), StmtKind::Enum(decl), self.span_from(keyword.span)))
+    }
+    
+    /// Variante `Nome` oppure `Nome(Tipo, ...)` con i tipi dei valori trasportati
+    fn enum_variant(&mut self) -> Result<EnumVariant> {
+        let name = self.identifier("il nome di una variante")?;
+        
+        let mut payload = Vec::new();
+        if self.matches(TokenType::LeftParen) {
+            while !self.check(TokenType::RightParen) {
+                payload.push(self.type_expr()?);
+                if !self.matches(TokenType::Comma) {
+                    break;
+                }
+            }
+            self.expect(TokenType::RightParen, "',' o ')' dopo i tipi della variante")?;
+        }
+        let span = self.span_from(name.span);
+        self.end_statement();
+        
+        Ok(EnumVariant {
+            id: self.next_id(),
+            name,
+            payload,
+            span,
+        })
+    }
+    
+    /// Corpo indentato di una dichiarazione, analizzato con `member`. Senza
+    /// corpo la dichiarazione non ha membri, come in `class Marker`.
+    fn members<T>(&mut self, member: fn(&mut Self) -> Result<T>) -> Result<Vec<T>> {
+        let mut members = Vec::new();
+        if !(self.check(TokenType::Newline) && self.peek_at(1).token_type == TokenType::Indent) {
+            self.end_statement();
+            return Ok(members);
+        }
+        self.advance();
+        self.advance();
+        
+        self.skip_newlines();
+        while !self.check(TokenType::Dedent) && !self.is_at_end() {
+            members.push(member(self)?);
+            self.skip_newlines();
+        }
+        self.expect(TokenType::Dedent, "fine del blocco")?;
+        
+        Ok(members)
+    }
+    
+    /// Tipi separati da virgole dopo `extends` o `implements`
+    fn type_list(&mut self) -> Result<Vec<TypeExpr>> {
+        let mut types = vec![self.type_expr()?];
+        while self.matches(TokenType::Comma) {
+            types.push(self.type_expr()?);
+        }
+        Ok(types)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::*;
+    use crate::parser::parse;
+    
+    fn declaration(source: &str) -> StmtKind {
+        parse(source).unwrap().body.remove(0).kind
+    }
+    
+    #[test]
+    fn test_class_declaration() {
+        let source = "\
+class Rectangle<T> extends Shape<T> implements Printable, Comparable<Rectangle<T>>
+  width: number
+  static count = 0
+  
+  constructor(width: number)
+    this.width = width
+  
+  fn area(): number
+    return this.width * this.width
+  
+  static fn unit()
+    return new Rectangle(1)
+";
+        let StmtKind::Class(class) = declaration(source) else {
+            panic!("attesa una classe");
+        };
+        assert_eq!(class.name.name, "Rectangle");
+        assert_eq!(class.type_params.len(), 1);
+        assert!(class.extends.is_some());
+        assert_eq!(class.implements.len(), 2);
+        assert_eq!(class.members.len(), 5);
+        
+        let ClassMemberKind::Field { name, ty, init } = &class.members[0].kind else {
+            panic!("atteso un campo");
+        };
+        assert_eq!(name.name, "width");
+        assert!(ty.is_some() && init.is_none());
+        assert!(class.members[1].is_static);
+        assert!(matches!(class.members[2].kind, ClassMemberKind::Constructor { .. }));
+        assert!(matches!(&class.members[3].kind, ClassMemberKind::Method(f) if f.name.name == "area"));
+        assert!(class.members[4].is_static);
+    }
+    
+    #[test]
+    fn test_interface_and_enum() {
+        let source = "\
+interface Shape extends Named
+  area(): number
+  label?: string
+  map<U>(f: (Shape) -> U) -> U
+";
+        let StmtKind::Interface(interface) = declaration(source) else {
+            panic!("attesa un'interfaccia");
+        };
+        assert_eq!(interface.extends.len(), 1);
+        assert_eq!(interface.members.len(), 3);
+        assert!(matches!(interface.members[0].kind, InterfaceMemberKind::Method { .. }));
+        assert!(interface.members[1].optional);
+        assert!(matches!(interface.members[1].kind, InterfaceMemberKind::Property(_)));
+        let InterfaceMemberKind::Method { type_params, .. } = &interface.members[2].kind else {
+            panic!("atteso un metodo");
+        };
+        assert_eq!(type_params.len(), 1);
+        
+        let StmtKind::Enum(decl) = declaration("enum Option<T>\n  Some(T)\n  None\n") else {
+            panic!("atteso un enum");
+        };
+        assert_eq!(decl.variants.len(), 2);
+        assert_eq!(decl.variants[0].payload.len(), 1);
+        assert!(decl.variants[1].payload.is_empty());
+        
+        let StmtKind::Class(class) = declaration("class Marker\nlet x = 1") else {
+            panic!("attesa una classe");
+        };
+        assert!(class.members.is_empty());
+    }
+    
+    #[test]
+    fn test_declaration_errors() {
+        let error = parse("class A\n  constructor()\n    pass()\n  constructor()\n    pass()\n").unwrap_err();
+        assert_eq!(error.message, "Una classe può avere un solo costruttore");
+        assert_eq!(error.span.unwrap().start.line, 4);
+        
+        let error = parse("class A\n  static constructor()\n    pass()\n").unwrap_err();
+        assert_eq!(error.message, "Un costruttore non può essere `static`");
+        
+        let error = parse("interface A\n  nome number\n").unwrap_err();
+        assert_eq!(error.message, "Atteso ':' o '(' dopo il nome del membro, trovato 'number'");
+    }
+    
+    #[test]
+    fn test_decorators() {
+        let source = "\
+@deprecated(\"Usa newMethod() invece\")
+fn oldMethod()
+  return 1
+
+@logged @sealed
+export class Service
+  @observable
+  static count = 0
+  
+  @inline
+  fn run()
+    return 1
+
+@deny(style)
+
+let x = 1
+";
+        let body = parse(source).unwrap().body;
+        let StmtKind::Function(function) = &body[0].kind else {
+            panic!("attesa una funzione");
+        };
+        assert_eq!(function.decorators[0].name.name, "deprecated");
+        assert_eq!(function.decorators[0].args.as_ref().unwrap().len(), 1);
+        assert_eq!(body[0].span.start.line, 1);
+        
+        let StmtKind::Export(ExportDecl::Declaration(inner)) = &body[1].kind else {
+            panic!("atteso un export");
+        };
+        let StmtKind::Class(class) = &inner.kind else {
+            panic!("attesa una classe");
+        };
+        assert_eq!(class.decorators.len(), 2);
+        assert!(class.decorators[1].args.is_none());
+        assert_eq!(class.members[0].decorators[0].name.name, "observable");
+        assert!(class.members[0].is_static);
+        assert_eq!(class.members[1].decorators[0].name.name, "inline");
+        
+        // Separato da una riga vuota, il decoratore vale per il blocco
+        assert!(matches!(&body[2].kind, StmtKind::Decorators(decorators) if decorators.len() == 1));
+        assert!(matches!(body[3].kind, StmtKind::Var(_)));
+        
+        // Una riga con soli commenti non li separa
+        let body = parse("@logged\n// Traccia le chiamate\n\n  // e i risultati\nfn f()\n  return 1\n").unwrap().body;
+        assert!(matches!(&body[0].kind, StmtKind::Decorators(_)));
+        let body = parse("@logged\n// Traccia le chiamate\nfn f()\n  return 1\n").unwrap().body;
+        assert!(matches!(&body[0].kind, StmtKind::Function(function) if function.decorators.len() == 1));
+        
+        let error = parse("@logged\nlet x = 1\n").unwrap_err();
+        assert_eq!(error.message, "Un decoratore deve precedere una funzione o una classe");
+        let error = parse("class A\n  @logged\n  constructor()\n    pass()\n").unwrap_err();
+        assert_eq!(error.message, "Un costruttore non può avere decoratori");
+    }
+}
@@ -0,0 +1,808 @@
+use super::precedence::{self, Associativity, InfixOperator, Precedence};
+use super::Parser;
+use crate::ast::{
+    AssignOp, Expr, ExprKind, Ident, Lambda, LambdaBody, Literal, Param, Property, PropertyKey, PropertyKind,
+    StringLit, Template, TypeExpr, UnaryOp,
+};
+use crate::error::Result;
+use crate::lexer::{keyword, Span, Token, TokenType};
+
+impl Parser {
+    /// Analizza un'espressione, comprese le assegnazioni
+    pub(super) fn expression(&mut self) -> Result<Expr> {
+        self.expression_with(Precedence::Lowest)
+    }
+    
+    /// Analizza un'espressione i cui operatori infissi hanno precedenza almeno
+    /// `min`; gli operatori più deboli restano al chiamante
+    pub(super) fn expression_with(&mut self, min: Precedence) -> Result<Expr> {
+        // Lambda e `yield` non sono operandi: il loro corpo o argomento si
+        // estende fino alla fine dell'espressione, quindi `a + (x) -> x` va
+        // scritto `a + ((x) -> x)`
+        if let Some(expr) = self.lambda_or_yield()? {
+            if min > Precedence::Assignment {
+                let message = match expr.kind {
+                    ExprKind::Yield { .. } => "`yield` usato come operando va racchiuso tra parentesi",
+                    _ => "Una lambda usata come operando va racchiusa tra parentesi",
+                };
+                return Err(self.error_at(expr.span, message.to_string()));
+            }
+            return Ok(expr);
+        }
+        
+        let mut left = self.prefix()?;
+        // I suffissi seguono solo l'operando iniziale: quelli degli operandi
+        // destri sono già stati letti, e dopo il tipo di `is` e `as` non sono
+        // ammessi, come in `x as T.campo`
+        let mut suffixes = true;
+        
+        loop {
+            if suffixes && self.at_postfix() {
+                left = self.postfix(left)?;
+                continue;
+            }
+            if suffixes && self.check(TokenType::Less) && matches!(left.kind, ExprKind::Ident(_) | ExprKind::Member { .. }) {
+                if let Some(type_args) = self.generic_arguments() {
+                    left = self.generic_call(left, type_args)?;
+                    continue;
+                }
+            }
+            
+            let Some((operator, precedence, associativity)) = precedence::infix(self.peek().token_type) else {
+                return Ok(left);
+            };
+            if precedence < min {
+                return Ok(left);
+            }
+            let token = self.advance();
+            let right_min = match associativity {
+                Associativity::Left => precedence.next(),
+                Associativity::Right => precedence,
+            };
+            
+            let kind = match operator {
+                InfixOperator::Binary(op) => {
+                    let right = self.expression_with(right_min)?;
+                    ExprKind::Binary {
+                        op,
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }
+                },
+                InfixOperator::Assign(op) => {
+                    if !is_assignable(&left, op) {
+                        return Err(self.error_at(
+                            left.span,
+                            format!("Destinazione non valida per `{}`", token.lexeme),
+                        ));
+                    }
+                    let value = self.expression_with(right_min)?;
+                    ExprKind::Assign {
+                        op,
+                        target: Box::new(left),
+                        value: Box::new(value),
+                    }
+                },
+                InfixOperator::Is => ExprKind::Is {
+                    expr: Box::new(left),
+                    ty: self.type_expr()?,
+                },
+                InfixOperator::As => ExprKind::As {
+                    expr: Box::new(left),
+                    ty: self.type_expr()?,
+                },
+            };
+            left = self.finish(kind)?;
+            suffixes = false;
+        }
+    }
+    
+    /// Crea il nodo `kind`, il cui intervallo va dal primo operando all'ultimo
+    /// token consumato
+    fn finish(&mut self, kind: ExprKind) -> Result<Expr> {
+        let start = match &kind {
+            ExprKind::Binary { left, .. } => left.span,
+            ExprKind::Assign { target, .. } => target.span,
+            ExprKind::Is { expr, .. } | ExprKind::As { expr, .. } => expr.span,
+            _ => unreachable!("solo gli operatori infissi usano `finish`"),
+        };
+        let span = self.span_from(start);
+        Ok(Expr::new(self.next_id(), kind, span))
+    }
+    
+    /// Operatori prefissi, seguiti da un operando di livello `Unary`
+    fn prefix(&mut self) -> Result<Expr> {
+        let op = match self.peek().token_type {
+            TokenType::Bang => UnaryOp::Not,
+            TokenType::Minus => UnaryOp::Neg,
+            TokenType::Plus => UnaryOp::Plus,
+            TokenType::Tilde => UnaryOp::BitNot,
+            _ => return self.primary(),
+        };
+        let operator = self.advance();
+        let operand = self.expression_with(Precedence::Unary)?;
+        let span = operator.span.to(operand.span);
+        
+        Ok(Expr::new(
+            self.next_id(),
+            ExprKind::Unary {
+                op,
+                operand: Box::new(operand),
+            },
+            span,
+        ))
+    }
+    
+    fn at_postfix(&self) -> bool {
+        matches!(
+            self.peek().token_type,
+            TokenType::LeftParen
+                | TokenType::Dot
+                | TokenType::LeftBracket
+                | TokenType::QuestionDot
+                | TokenType::Question
+                | TokenType::TemplateString
+                | TokenType::TemplateHead
+        )
+    }
+    
+    /// Chiamate, accessi ai membri, indicizzazioni, verifiche di non nullità e
+    /// template con tag
+    fn postfix(&mut self, expr: Expr) -> Result<Expr> {
+        let start = expr.span;
+        
+        let kind = match self.peek().token_type {
+            TokenType::LeftParen => {
+                self.advance();
+                ExprKind::Call {
+                    callee: Box::new(expr),
+                    type_args: Vec::new(),
+                    args: self.arguments()?,
+                    optional: false,
+                }
+            },
+            TokenType::Question => {
+                self.advance();
+                ExprKind::NullCheck(Box::new(expr))
+            },
+            TokenType::TemplateString | TokenType::TemplateHead => ExprKind::TaggedTemplate {
+                tag: Box::new(expr),
+                template: self.template()?,
+            },
+            TokenType::QuestionDot => {
+                self.advance();
+                // `a?.(x)`, `a?.[i]` oppure `a?.b`
+                if self.matches(TokenType::LeftParen) {
+                    ExprKind::Call {
+                        callee: Box::new(expr),
+                        type_args: Vec::new(),
+                        args: self.arguments()?,
+                        optional: true,
+                    }
+                } else if self.matches(TokenType::LeftBracket) {
+                    self.index(expr, true)?
+                } else {
+                    ExprKind::Member {
+                        object: Box::new(expr),
+                        property: self.property_name()?,
+                        optional: true,
+                    }
+                }
+            },
+            _ => return self.member(expr),
+        };
+        let span = self.span_from(start);
+        
+        Ok(Expr::new(self.next_id(), kind, span))
+    }
+    
+    /// Argomenti di tipo di una chiamata generica `f<T>(x)`. Poiché `<` può
+    /// anche iniziare un confronto, la lista viene solo tentata: è accettata se
+    /// si chiude con `>` ed è seguita da `(`, altrimenti il parser torna
+    /// indietro. Come in TypeScript, `a < b > (c)` è quindi una chiamata.
+    fn generic_arguments(&mut self) -> Option<Vec<TypeExpr>> {
+        let checkpoint = self.checkpoint();
+        match self.type_arguments() {
+            Ok(type_args) if self.check(TokenType::LeftParen) => Some(type_args),
+            _ => {
+                self.rewind(checkpoint);
+                None
+            },
+        }
+    }
+    
+    fn generic_call(&mut self, callee: Expr, type_args: Vec<TypeExpr>) -> Result<Expr> {
+        let start = callee.span;
+        self.expect(TokenType::LeftParen, "'('")?;
+        let kind = ExprKind::Call {
+            callee: Box::new(callee),
+            type_args,
+            args: self.arguments()?,
+            optional: false,
+        };
+        let span = self.span_from(start);
+        
+        Ok(Expr::new(self.next_id(), kind, span))
+    }
+    
+    /// Indice dopo `[`, fino a `]` inclusa
+    fn index(&mut self, object: Expr, optional: bool) -> Result<ExprKind> {
+        let index = self.expression()?;
+        self.expect(TokenType::RightBracket, "']' dopo l'indice")?;
+        Ok(ExprKind::Index {
+            object: Box::new(object),
+            index: Box::new(index),
+            optional,
+        })
+    }
+    
+    /// `.nome` oppure `[indice]` dopo `object`
+    fn member(&mut self, object: Expr) -> Result<Expr> {
+        let start = object.span;
+        let kind = if self.matches(TokenType::Dot) {
+            ExprKind::Member {
+                object: Box::new(object),
+                property: self.property_name()?,
+                optional: false,
+            }
+        } else {
+            self.expect(TokenType::LeftBracket, "'['")?;
+            self.index(object, false)?
+        };
+        let span = self.span_from(start);
+        
+        Ok(Expr::new(self.next_id(), kind, span))
+    }
+    
+    /// Nome di una proprietà: anche le parole chiave sono ammesse
+    pub(super) fn property_name(&mut self) -> Result<Ident> {
+        let token = self.peek();
+        if token.token_type == TokenType::Identifier || keyword(&token.lexeme).is_some() {
+            let token = self.advance();
+            Ok(Ident::new(&token.lexeme, token.span))
+        } else {
+            Err(self.unexpected("il nome di una proprietà"))
+        }
+    }
+    
+    /// Argomenti dopo `(`, fino a `)` inclusa
+    pub(super) fn arguments(&mut self) -> Result<Vec<Expr>> {
+        let mut args = Vec::new();
+        
+        while !self.check(TokenType::RightParen) {
+            args.push(self.element()?);
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenType::RightParen, "',' o ')' dopo gli argomenti")?;
+        
+        Ok(args)
+    }
+    
+    /// Elemento di un array o argomento di una chiamata, anche `...valore`
+    fn element(&mut self) -> Result<Expr> {
+        if !self.check(TokenType::DotDotDot) {
+            return self.expression();
+        }
+        let dots = self.advance();
+        let value = self.expression()?;
+        let span = self.span_from(dots.span);
+        
+        Ok(Expr::new(self.next_id(), ExprKind::Spread(Box::new(value)), span))
+    }
+    
+    fn primary(&mut self) -> Result<Expr> {
+        let token = self.peek().clone();
+        
+        let kind = match token.token_type {
+            TokenType::NumberLiteral => {
+                self.advance();
+                ExprKind::Literal(Literal::Number(self.number(&token.lexeme, token.span)?))
+            },
+            TokenType::StringLiteral => {
+                self.advance();
+                ExprKind::Literal(Literal::String(unescape(&token.lexeme)))
+            },
+            TokenType::TemplateString | TokenType::TemplateHead => ExprKind::Template(self.template()?),
+            TokenType::True | TokenType::False => {
+                self.advance();
+                ExprKind::Literal(Literal::Bool(token.token_type == TokenType::True))
+            },
+            TokenType::Null => {
+                self.advance();
+                ExprKind::Literal(Literal::Null)
+            },
+            TokenType::Identifier => {
+                self.advance();
+                ExprKind::Ident(Ident::new(&token.lexeme, token.span))
+            },
+            TokenType::This => {
+                self.advance();
+                ExprKind::This
+            },
+            TokenType::Super => {
+                self.advance();
+                ExprKind::Super
+            },
+            TokenType::LeftParen => {
+                self.advance();
+                let expr = self.expression()?;
+                self.expect(TokenType::RightParen, "')' dopo l'espressione")?;
+                // Le parentesi non producono un nodo, ma l'intervallo le include
+                return Ok(Expr {
+                    span: self.span_from(token.span),
+                    ..expr
+                });
+            },
+            TokenType::LeftBracket => {
+                self.advance();
+                ExprKind::Array(self.array_elements()?)
+            },
+            TokenType::LeftBrace => {
+                self.advance();
+                ExprKind::Object(self.object_properties()?)
+            },
+            TokenType::New => return self.new_expression(),
+            TokenType::Match => return self.match_expression(),
+            TokenType::DotDotDot => {
+                return Err(self.error_at(
+                    token.span,
+                    "`...` è ammesso solo in array, oggetti, argomenti e parametri".to_string(),
+                ));
+            },
+            _ => return Err(self.unexpected("un'espressione")),
+        };
+        
+        Ok(Expr::new(self.next_id(), kind, self.span_from(token.span)))
+    }
+    
+    /// Template dal token di apertura al backtick di chiusura: i tratti di
+    /// testo si alternano alle espressioni interpolate
+    fn template(&mut self) -> Result<Template> {
+        let head = self.advance();
+        let mut strings = vec![template_text(&head)];
+        let mut values = Vec::new();
+        
+        if head.token_type == TokenType::TemplateHead {
+            loop {
+                values.push(self.expression()?);
+                if !matches!(self.peek().token_type, TokenType::TemplateMiddle | TokenType::TemplateTail) {
+                    return Err(self.unexpected("'}' dopo l'interpolazione"));
+                }
+                let part = self.advance();
+                strings.push(template_text(&part));
+                if part.token_type == TokenType::TemplateTail {
+                    break;
+                }
+            }
+        }
+        
+        Ok(Template { strings, values })
+    }
+    
+    pub(super) fn number(&self, lexeme: &str, span: Span) -> Result<f64> {
+        parse_number(lexeme).ok_or_else(|| self.error_at(span, format!("Numero non valido: '{}'", lexeme)))
+    }
+    
+    /// Elementi dopo `[`, fino a `]` inclusa
+    fn array_elements(&mut self) -> Result<Vec<Expr>> {
+        let mut elements = Vec::new();
+        
+        while !self.check(TokenType::RightBracket) {
+            elements.push(self.element()?);
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenType::RightBracket, "',' o ']' nell'array")?;
+        
+        Ok(elements)
+    }
+    
+    /// Proprietà dopo `{`, fino a `}` inclusa
+    fn object_properties(&mut self) -> Result<Vec<Property>> {
+        let mut properties = Vec::new();
+        
+        while !self.check(TokenType::RightBrace) {
+            let start = self.peek().span;
+            let kind = if self.matches(TokenType::DotDotDot) {
+                PropertyKind::Spread(self.expression()?)
+            } else if self.check(TokenType::StringLiteral) {
+                let key = self.string_literal("una chiave")?;
+                self.expect(TokenType::Colon, "':' dopo la chiave")?;
+                PropertyKind::KeyValue {
+                    key: PropertyKey::String(key),
+                    value: self.expression()?,
+                }
+            } else {
+                let name = self.property_name()?;
+                if self.matches(TokenType::Colon) {
+                    PropertyKind::KeyValue {
+                        key: PropertyKey::Ident(name),
+                        value: self.expression()?,
+                    }
+                } else if keyword(&name.name).is_some() {
+                    // Una parola chiave può essere una chiave, ma non una
+                    // proprietà abbreviata
+                    return Err(self.unexpected("':' dopo la chiave"));
+                } else {
+                    PropertyKind::Shorthand(name)
+                }
+            };
+            properties.push(Property {
+                kind,
+                span: self.span_from(start),
+            });
+            
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenType::RightBrace, "',' o '}' nell'oggetto")?;
+        
+        Ok(properties)
+    }
+    
+    /// Lambda o `yield`, che nella specifica sono espressioni complete e non
+    /// operandi di un operatore
+    fn lambda_or_yield(&mut self) -> Result<Option<Expr>> {
+        match self.peek().token_type {
+            TokenType::LeftParen => self.lambda(),
+            TokenType::Yield => self.yield_expression().map(Some),
+            _ => Ok(None),
+        }
+    }
+    
+    /// Funzione anonima `(params): tipo -> corpo`, con il corpo su una riga o
+    /// in un blocco indentato. Fino a `->` la parentesi potrebbe racchiudere
+    /// un'espressione, quindi l'intestazione viene solo tentata: se non è
+    /// quella di una lambda il parser torna indietro e restituisce `None`.
+    fn lambda(&mut self) -> Result<Option<Expr>> {
+        let start = self.peek().span;
+        let checkpoint = self.checkpoint();
+        let Ok((params, return_type)) = self.lambda_header() else {
+            self.rewind(checkpoint);
+            return Ok(None);
+        };
+        
+        let body = self.function_body(false, |this| {
+            Ok(if this.check(TokenType::Newline) {
+                LambdaBody::Block(this.block("`->`")?)
+            } else {
+                LambdaBody::Expr(Box::new(this.expression()?))
+            })
+        })?;
+        let lambda = Lambda {
+            params,
+            return_type,
+            body,
+        };
+        
+        Ok(Some(Expr::new(self.next_id(), ExprKind::Lambda(lambda), self.span_from(start))))
+    }
+    
+    fn lambda_header(&mut self) -> Result<(Vec<Param>, Option<TypeExpr>)> {
+        self.expect(TokenType::LeftParen, "'('")?;
+        let params = self.parameters()?;
+        let return_type = if self.matches(TokenType::Colon) { Some(self.type_expr()?) } else { None };
+        self.expect(TokenType::Arrow, "'->'")?;
+        Ok((params, return_type))
+    }
+    
+    /// `yield valore`, `yield` senza valore oppure `yield* iterabile`
+    fn yield_expression(&mut self) -> Result<Expr> {
+        let keyword = self.advance();
+        if !self.in_generator {
+            return Err(self.error_at(keyword.span, "`yield` è ammesso solo nel corpo di un generatore (`fn*`)".to_string()));
+        }
+        
+        let delegate = self.matches(TokenType::Star);
+        let ends_expression = matches!(
+            self.peek().token_type,
+            TokenType::Newline
+                | TokenType::Dedent
+                | TokenType::Eof
+                | TokenType::RightParen
+                | TokenType::RightBracket
+                | TokenType::RightBrace
+                | TokenType::Comma
+                | TokenType::Colon
+        );
+        let argument = if delegate || !ends_expression { Some(Box::new(self.expression()?)) } else { None };
+        
+        Ok(Expr::new(self.next_id(), ExprKind::Yield { argument, delegate }, self.span_from(keyword.span)))
+    }
+    
+    /// `new Classe<T>(argomenti)`
+    fn new_expression(&mut self) -> Result<Expr> {
+        let keyword = self.advance();
+        
+        let mut callee = self.primary()?;
+        while self.check(TokenType::Dot) || self.check(TokenType::LeftBracket) {
+            callee = self.member(callee)?;
+        }
+        let type_args = if self.check(TokenType::Less) { self.type_arguments()? } else { Vec::new() };
+        let args = if !type_args.is_empty() {
+            self.expect(TokenType::LeftParen, "'(' dopo gli argomenti di tipo")?;
+            self.arguments()?
+        } else if self.matches(TokenType::LeftParen) {
+            self.arguments()?
+        } else {
+            // Senza argomenti `new Classe` non ammette altri suffissi
+            if self.at_postfix() {
+                return Err(self.unexpected("'(' dopo `new`"));
+            }
+            Vec::new()
+        };
+        
+        Ok(Expr::new(
+            self.next_id(),
+            ExprKind::New {
+                callee: Box::new(callee),
+                type_args,
+                args,
+            },
+            self.span_from(keyword.span),
+        ))
+    }
+}
+
+/// Valore di un letterale numerico decimale, esadecimale (`0x`) o binario (`0b`)
+pub(super) fn parse_number(lexeme: &str) -> Option<f64> {
+    let radix = match lexeme.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0b" | "0B") => 2,
+        _ => 10,
+    };
+    if radix == 10 {
+        lexeme.parse().ok()
+    } else {
+        u64::from_str_radix(&lexeme[2..], radix).ok().map(|n| n as f64)
+    }
+}
+
+/// Verifica se `target` può stare a sinistra dell'operatore di assegnazione.
+/// Array e oggetti sono ammessi solo con `=`, come destrutturazione.
+pub(super) fn is_assignable(target: &Expr, op: AssignOp) -> bool {
+    match &target.kind {
+        ExprKind::Ident(_) => true,
+        ExprKind::Member { optional, .. } | ExprKind::Index { optional, .. } => !optional,
+        ExprKind::Array(elements) => {
+            op == AssignOp::Assign
+                && elements.iter().all(|e| match &e.kind {
+                    ExprKind::Spread(inner) => is_assignable(inner, op),
+                    _ => is_assignable(e, op),
+                })
+        },
+        ExprKind::Object(properties) => {
+            op == AssignOp::Assign
+                && properties.iter().all(|p| match &p.kind {
+                    PropertyKind::KeyValue { value, .. } => is_assignable(value, op),
+                    PropertyKind::Shorthand(_) => true,
+                    PropertyKind::Spread(value) => matches!(value.kind, ExprKind::Ident(_)),
+                })
+        },
+        _ => false,
+    }
+}
+
+/// Interpreta le sequenze di escape nel contenuto di una stringa letterale
+pub(super) fn unescape(raw: &str) -> String {
+    let mut value = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('r') => value.push('\r'),
+            Some('0') => value.push('\0'),
+            Some(other) => value.push(other),
+            None => value.push('\\'),
+        }
+    }
+    
+    value
+}
+
+/// Testo di un tratto di template, con le sequenze di escape risolte
+fn template_text(token: &Token) -> StringLit {
+    StringLit {
+        value: unescape(&token.lexeme),
+        span: token.span,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::*;
+    use crate::parser::parse;
+    
+    fn expression(source: &str) -> Expr {
+        let mut body = parse(source).unwrap().body;
+        match body.remove(0).kind {
+            StmtKind::Expr(expr) => expr,
+            other => panic!("attesa un'espressione: {:?}", other),
+        }
+    }
+    
+    /// Scrive l'espressione con tutte le parentesi esplicite
+    fn grouped(expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Binary { op, left, right } => format!("({} {} {})", grouped(left), op, grouped(right)),
+            ExprKind::Assign { op, target, value } => format!("({} {} {})", grouped(target), op, grouped(value)),
+            ExprKind::Unary { op, operand } => format!("({}{})", op, grouped(operand)),
+            ExprKind::Literal(Literal::Number(n)) => n.to_string(),
+            ExprKind::Ident(name) => name.name.clone(),
+            ExprKind::Member { object, property, optional } => {
+                format!("{}{}{}", grouped(object), if *optional { "?." } else { "." }, property)
+            },
+            ExprKind::Call { callee, args, .. } => {
+                let args: Vec<String> = args.iter().map(grouped).collect();
+                format!("{}({})", grouped(callee), args.join(", "))
+            },
+            ExprKind::NullCheck(operand) => format!("{}?", grouped(operand)),
+            ExprKind::Array(elements) => {
+                let elements: Vec<String> = elements.iter().map(grouped).collect();
+                format!("[{}]", elements.join(", "))
+            },
+            ExprKind::Spread(value) => format!("...{}", grouped(value)),
+            ExprKind::As { expr, .. } => format!("({} as _)", grouped(expr)),
+            other => panic!("espressione non prevista: {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_tricky_precedence() {
+        let cases = [
+            ("-2 ** 2", "(-(2 ** 2))"),
+            ("2 ** -1", "(2 ** (-1))"),
+            ("2 ** 3 ** 2", "(2 ** (3 ** 2))"),
+            ("a ?? b || c", "(a ?? (b || c))"),
+            ("a || b ?? c", "((a || b) ?? c)"),
+            ("a - b - c", "((a - b) - c)"),
+            ("a & b == c", "(a & (b == c))"),
+            ("a | b ^ c & d", "(a | (b ^ (c & d)))"),
+            ("1 << 2 + 3", "(1 << (2 + 3))"),
+            ("!a && ~b", "((!a) && (~b))"),
+            ("x = y += 1 + 2", "(x = (y += (1 + 2)))"),
+            ("-a.b(c)", "(-a.b(c))"),
+            ("x as number + 1", "((x as _) + 1)"),
+            ("user?.profile?.name?.length || 0", "(user?.profile?.name?.length || 0)"),
+            ("name? && ok", "(name? && ok)"),
+            ("0xFF + 0b10", "(255 + 2)"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(grouped(&expression(source)), expected, "in `{}`", source);
+        }
+    }
+    
+    #[test]
+    fn test_binary_precedence() {
+        let expr = expression("1 + 2 * 3 == 7 && ok");
+        let ExprKind::Binary { op: BinaryOp::And, left, .. } = expr.kind else {
+            panic!("atteso &&");
+        };
+        let ExprKind::Binary { op: BinaryOp::Eq, left, .. } = left.kind else {
+            panic!("atteso ==");
+        };
+        let ExprKind::Binary { op: BinaryOp::Add, right, .. } = left.kind else {
+            panic!("atteso +");
+        };
+        assert!(matches!(right.kind, ExprKind::Binary { op: BinaryOp::Mul, .. }));
+    }
+    
+    #[test]
+    fn test_calls_members_and_literals() {
+        let expr = expression("person.greet(\"ciao\\n\", [1, 2], { name, age: 30 })[0]");
+        let ExprKind::Index { object, .. } = expr.kind else {
+            panic!("atteso un indice");
+        };
+        let ExprKind::Call { callee, args, .. } = object.kind else {
+            panic!("attesa una chiamata");
+        };
+        assert!(matches!(callee.kind, ExprKind::Member { .. }));
+        assert_eq!(args[0].kind, ExprKind::Literal(Literal::String("ciao\n".to_string())));
+        assert!(matches!(&args[2].kind, ExprKind::Object(properties) if properties.len() == 2));
+    }
+    
+    #[test]
+    fn test_assignment() {
+        let expr = expression("a = b += 1");
+        let ExprKind::Assign { op: AssignOp::Assign, value, .. } = expr.kind else {
+            panic!("attesa un'assegnazione");
+        };
+        assert!(matches!(value.kind, ExprKind::Assign { op: AssignOp::Add, .. }));
+        
+        let error = parse("f() = 1").unwrap_err();
+        assert_eq!(error.message, "Destinazione non valida per `=`");
+    }
+    
+    #[test]
+    fn test_new_and_spans() {
+        let source = "let p = new Person(\"Alice\", 30)";
+        let program = parse(source).unwrap();
+        let StmtKind::Var(decl) = &program.body[0].kind else {
+            panic!("attesa una dichiarazione");
+        };
+        let init = decl.init.as_ref().unwrap();
+        assert!(matches!(&init.kind, ExprKind::New { args, .. } if args.len() == 2));
+        assert_eq!(&source[init.span.range()], "new Person(\"Alice\", 30)");
+    }
+    
+    #[test]
+    fn test_templates() {
+        let ExprKind::Template(template) = expression("`ciao ${user.name}!\\n${n + 1}`").kind else {
+            panic!("atteso un template");
+        };
+        let strings: Vec<&str> = template.strings.iter().map(|s| s.value.as_str()).collect();
+        assert_eq!(strings, ["ciao ", "!\n", ""]);
+        assert_eq!(template.values.len(), 2);
+        assert_eq!(grouped(&template.values[1]), "(n + 1)");
+        
+        let source = "db.sql`select * from users where id = ${id}`.rows";
+        let expr = expression(source);
+        let ExprKind::Member { object, .. } = expr.kind else {
+            panic!("atteso un membro");
+        };
+        let ExprKind::TaggedTemplate { tag, template } = object.kind else {
+            panic!("atteso un template con tag");
+        };
+        assert!(matches!(tag.kind, ExprKind::Member { .. }));
+        assert_eq!(template.strings.len(), 2);
+        assert_eq!(&source[object.span.range()], "db.sql`select * from users where id = ${id}`");
+        
+        let error = parse("`a ${x y}`").unwrap_err();
+        assert_eq!(error.message, "Atteso '}' dopo l'interpolazione, trovato 'y'");
+    }
+    
+    #[test]
+    fn test_spread() {
+        assert_eq!(grouped(&expression("[...a, ...b, 1]")), "[...a, ...b, 1]");
+        assert_eq!(grouped(&expression("f(x, ...args)")), "f(x, ...args)");
+        
+        let ExprKind::Object(properties) = expression("({ ...obj, extra: v })").kind else {
+            panic!("atteso un oggetto");
+        };
+        assert!(matches!(properties[0].kind, PropertyKind::Spread(_)));
+        assert!(matches!(expression("[a, ...rest] = xs").kind, ExprKind::Assign { .. }));
+        
+        let error = parse("let x = ...a").unwrap_err();
+        assert_eq!(error.message, "`...` è ammesso solo in array, oggetti, argomenti e parametri");
+    }
+    
+    #[test]
+    fn test_lambdas() {
+        let program = parse("let multiply = (a: number, b: number) -> a * b").unwrap();
+        let StmtKind::Var(decl) = &program.body[0].kind else {
+            panic!("attesa una dichiarazione");
+        };
+        let Some(ExprKind::Lambda(lambda)) = decl.init.as_ref().map(|e| &e.kind) else {
+            panic!("attesa una lambda");
+        };
+        assert_eq!(lambda.params.len(), 2);
+        assert!(matches!(&lambda.body, LambdaBody::Expr(body) if grouped(body) == "(a * b)"));
+        
+        let ExprKind::Lambda(lambda) = expression("(x): number -> x").kind else {
+            panic!("attesa una lambda");
+        };
+        assert!(lambda.return_type.is_some());
+        assert_eq!(grouped(&expression("(a + b) * c")), "((a + b) * c)");
+        
+        // Corpo a blocco come argomento di una chiamata
+        let ExprKind::Call { args, .. } = expression("items.map((item) ->\n  let n = item * 2\n  return n\n)").kind else {
+            panic!("attesa una chiamata");
+        };
+        assert!(matches!(&args[0].kind, ExprKind::Lambda(Lambda { body: LambdaBody::Block(b), .. }) if b.stmts.len() == 2));
+        
+        // Come operando di un operatore la lambda va tra parentesi
+        let error = parse("f = a ?? (x) -> x").unwrap_err();
+        assert_eq!(error.message, "Una lambda usata come operando va racchiusa tra parentesi");
+        assert!(matches!(expression("a ?? ((x) -> x)").kind, ExprKind::Binary { .. }));
+    }
+}
@@ -0,0 +1,521 @@
+//! Parser a discesa ricorsiva di NovaScript.
+//!
+//! Consuma i token prodotti dal `Lexer` e costruisce l'AST. I blocchi sono
+//! delimitati dai token `Indent` e `Dedent`, le istruzioni terminano con
+//! `Newline` oppure alla fine del blocco che le contiene.
+//!
+//! Dopo un errore di sintassi il parser lo registra, salta i token fino a un
+//! punto di sincronizzazione (fine riga, fine blocco o parola chiave che
+//! inizia un'istruzione) e prosegue, lasciando nell'AST un nodo `Error` al
+//! posto della parte non valida. Gli errori che seguono sulla stessa riga sono
+//! quasi sempre conseguenze del primo e non vengono segnalati.
+
+mod decl;
+mod expr;
+mod pattern;
+pub mod precedence;
+pub mod spec;
+mod stmt;
+mod types;
+
+use crate::ast::{Expr, ExprKind, NodeId, NodeIdGen, Program, Stmt, StmtKind};
+use crate::error::{Error, ErrorKind, Result};
+use crate::lexer::{Lexer, Position, Span, Token, TokenType};
+
+/// Analizza un intero file sorgente e restituisce il primo errore, se presente
+pub fn parse(source: &str) -> Result<Program> {
+    let (program, mut errors) = parse_with_errors(source);
+    if errors.is_empty() {
+        Ok(program)
+    } else {
+        Err(errors.remove(0))
+    }
+}
+
+/// Come [`parse`], ma restituisce anche il generatore degli id, da cui le
+/// trasformazioni successive prendono gli id dei nodi che creano
+pub fn parse_with_node_ids(source: &str) -> Result<(Program, NodeIdGen)> {
+    let mut parser = Parser::new(Lexer::new(source).tokenize()?);
+    let program = parser.parse_program();
+    match parser.take_errors().into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok((program, parser.into_node_ids())),
+    }
+}
+
+/// Analizza un file sorgente proseguendo dopo gli errori di sintassi. Le parti
+/// non valide diventano nodi `Error` nel programma restituito.
+pub fn parse_with_errors(source: &str) -> (Program, Vec<Error>) {
+    match Lexer::new(source).tokenize() {
+        Ok(tokens) => {
+            let mut parser = Parser::new(tokens);
+            let program = parser.parse_program();
+            (program, parser.take_errors())
+        },
+        Err(error) => {
+            let program = Program {
+                body: Vec::new(),
+                span: Span::point(Position::new(1, 1, 0)),
+            };
+            (program, vec![error])
+        },
+    }
+}
+
+/// Parole chiave da cui può riprendere l'analisi dopo un errore
+const STATEMENT_KEYWORDS: &[TokenType] = &[
+    TokenType::Let,
+    TokenType::Const,
+    TokenType::Fn,
+    TokenType::If,
+    TokenType::For,
+    TokenType::While,
+    TokenType::Break,
+    TokenType::Continue,
+    TokenType::Return,
+    TokenType::Throw,
+    TokenType::Try,
+    TokenType::Import,
+    TokenType::Export,
+    TokenType::Class,
+    TokenType::Interface,
+    TokenType::Enum,
+    TokenType::Type,
+    TokenType::At,
+];
+
+/// Parser che trasforma un flusso di token in un `Program`
+pub struct Parser {
+    tokens: Vec<Token>,
+    current: usize,
+    ids: NodeIdGen,
+    errors: Vec<Error>,
+    /// Token divisi da `split_greater`, con la posizione e il token originale
+    splits: Vec<(usize, Token)>,
+    /// Vero nel corpo di un generatore, dove `yield` è ammesso
+    in_generator: bool,
+}
+
+/// Stato a cui il parser può tornare dopo un tentativo fallito
+struct Checkpoint {
+    current: usize,
+    splits: usize,
+}
+
+impl Parser {
+    /// Crea un parser dai token del lexer, che devono terminare con `Eof`
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens,
+            current: 0,
+            ids: NodeIdGen::new(),
+            errors: Vec::new(),
+            splits: Vec::new(),
+            in_generator: false,
+        }
+    }
+    
+    /// Analizza tutte le istruzioni fino alla fine del file. Gli errori
+    /// incontrati sono disponibili tramite [`Parser::errors`].
+    pub fn parse_program(&mut self) -> Program {
+        let start = self.peek().span;
+        let mut body = self.statements();
+        // Un `Dedent` senza blocco aperto resta solo dopo un errore: si salta
+        while self.matches(TokenType::Dedent) {
+            body.extend(self.statements());
+        }
+        
+        Program {
+            body,
+            span: start.to(self.peek().span),
+        }
+    }
+    
+    /// Errori di sintassi registrati finora
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+    
+    /// Restituisce gli errori registrati, svuotando l'elenco
+    pub fn take_errors(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
+    }
+    
+    /// Generatore degli id dei nodi, da proseguire per creare nuovi nodi
+    /// dopo il parsing
+    pub fn into_node_ids(self) -> NodeIdGen {
+        self.ids
+    }
+    
+    fn next_id(&mut self) -> NodeId {
+        self.ids.fresh()
+    }
+    
+    fn peek(&self) -> &Token {
+        self.peek_at(0)
+    }
+    
+    /// Token a `distance` posizioni dal corrente; oltre la fine resta su `Eof`
+    fn peek_at(&self, distance: usize) -> &Token {
+        let index = (self.current + distance).min(self.tokens.len() - 1);
+        &self.tokens[index]
+    }
+    
+    fn is_at_end(&self) -> bool {
+        self.check(TokenType::Eof)
+    }
+    
+    fn check(&self, token_type: TokenType) -> bool {
+        self.peek().token_type == token_type
+    }
+    
+    fn advance(&mut self) -> Token {
+        let token = self.peek().clone();
+        if !self.is_at_end() {
+            self.current += 1;
+        }
+        token
+    }
+    
+    /// Consuma il token corrente se è del tipo indicato
+    fn matches(&mut self, token_type: TokenType) -> bool {
+        if self.check(token_type) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+    
+    /// Consuma un token del tipo indicato o restituisce un errore che descrive
+    /// cosa ci si aspettava
+    fn expect(&mut self, token_type: TokenType, expected: &str) -> Result<Token> {
+        if self.check(token_type) {
+            Ok(self.advance())
+        } else {
+            Err(self.unexpected(expected))
+        }
+    }
+    
+    /// Errore per un token corrente diverso da quello atteso
+    fn unexpected(&self, expected: &str) -> Error {
+        let token = self.peek();
+        self.error_at(token.span, format!("Atteso {}, trovato {}", expected, describe(token)))
+    }
+    
+    fn error_at(&self, span: Span, message: String) -> Error {
+        Error::new(ErrorKind::SyntaxError, message, span)
+    }
+    
+    /// Intervallo dall'inizio di `start` alla fine dell'ultimo token consumato,
+    /// esclusi gli a capo e i token di indentazione
+    fn span_from(&self, start: Span) -> Span {
+        let last = self.tokens[..self.current]
+            .iter()
+            .rev()
+            .find(|t| !matches!(t.token_type, TokenType::Newline | TokenType::Indent | TokenType::Dedent));
+        match last {
+            Some(token) => start.to(token.span),
+            None => start,
+        }
+    }
+    
+    fn skip_newlines(&mut self) {
+        while self.matches(TokenType::Newline) {}
+    }
+    
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            current: self.current,
+            splits: self.splits.len(),
+        }
+    }
+    
+    /// Torna a un checkpoint, ricomponendo i token divisi nel frattempo
+    fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.current = checkpoint.current;
+        let undone = self.splits.split_off(checkpoint.splits);
+        for (index, original) in undone.into_iter().rev() {
+            self.tokens.remove(index + 1);
+            self.tokens[index] = original;
+        }
+    }
+    
+    /// Divide il token corrente dopo il primo carattere, che diventa `>`; il
+    /// resto diventa un token di tipo `rest`. Serve a chiudere liste generiche
+    /// annidate come `Map<K, Array<V>>`, dove il lexer produce `>>`.
+    fn split_greater(&mut self, rest: TokenType) {
+        let original = self.tokens[self.current].clone();
+        let start = original.span.start;
+        let middle = Position::new(start.line, start.column + 1, start.offset + 1);
+        
+        self.tokens[self.current] = Token::new(TokenType::Greater, ">", Span::new(start, middle));
+        self.tokens.insert(
+            self.current + 1,
+            Token::new(rest, &original.lexeme[1..], Span::new(middle, original.span.end)),
+        );
+        self.splits.push((self.current, original));
+    }
+    
+    /// Istruzioni fino alla fine del blocco corrente o del file
+    fn statements(&mut self) -> Vec<Stmt> {
+        let mut stmts = Vec::new();
+        
+        self.skip_newlines();
+        while !self.check(TokenType::Dedent) && !self.is_at_end() {
+            let before = self.current;
+            stmts.push(self.statement_or_error());
+            // Garantisce l'avanzamento anche se la sincronizzazione non consuma nulla
+            if self.current == before {
+                self.advance();
+            }
+            self.skip_newlines();
+        }
+        
+        stmts
+    }
+    
+    /// Analizza un'istruzione; in caso di errore lo registra, si sincronizza e
+    /// restituisce un segnaposto `Error`
+    fn statement_or_error(&mut self) -> Stmt {
+        let start = self.current;
+        match self.statement() {
+            Ok(stmt) => stmt,
+            Err(error) => {
+                self.report(error);
+                self.synchronize(start);
+                let span = self.span_since(start);
+                Stmt::new(self.next_id(), StmtKind::Error, span)
+            },
+        }
+    }
+    
+    /// Analizza un'espressione; in caso di errore lo registra, salta il resto
+    /// della riga e restituisce un segnaposto `Error`. L'istruzione che contiene
+    /// l'espressione, e il suo eventuale blocco, restano nell'AST.
+    fn expression_or_error(&mut self) -> Expr {
+        let start = self.current;
+        match self.expression() {
+            Ok(expr) => expr,
+            Err(error) => {
+                self.report(error);
+                self.skip_to_line_end();
+                let span = self.span_since(start);
+                Expr::new(self.next_id(), ExprKind::Error, span)
+            },
+        }
+    }
+    
+    /// Registra un errore, a meno che non sia una conseguenza del precedente:
+    /// un errore che inizia sulla stessa riga o prima dell'ultimo segnalato
+    fn report(&mut self, error: Error) {
+        let cascading = match (self.errors.last().and_then(Error::position), error.position()) {
+            (Some(last), Some(current)) => current.line <= last.line || current.offset <= last.offset,
+            _ => false,
+        };
+        if !cascading {
+            self.errors.push(error);
+        }
+    }
+    
+    /// Salta i token fino a un punto da cui l'analisi può riprendere: dopo la
+    /// fine della riga e dell'eventuale blocco che la segue, prima della fine
+    /// del blocco corrente oppure prima di una parola chiave che inizia
+    /// un'istruzione diversa da quella iniziata all'indice `start`
+    fn synchronize(&mut self, start: usize) {
+        let mut depth = 0usize;
+        
+        while !self.is_at_end() {
+            let token_type = self.peek().token_type;
+            match token_type {
+                TokenType::Indent => depth += 1,
+                TokenType::Dedent if depth == 0 => return,
+                TokenType::Dedent => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.advance();
+                        // `else`, `catch` e `finally` proseguono l'istruzione non valida
+                        if !matches!(
+                            self.peek().token_type,
+                            TokenType::Else | TokenType::Catch | TokenType::Finally
+                        ) {
+                            return;
+                        }
+                        continue;
+                    }
+                },
+                TokenType::Newline if depth == 0 => {
+                    self.advance();
+                    // Il blocco che segue appartiene all'istruzione non valida
+                    if !self.check(TokenType::Indent) {
+                        return;
+                    }
+                    continue;
+                },
+                _ if depth == 0 && self.current > start && STATEMENT_KEYWORDS.contains(&token_type) => return,
+                _ => {},
+            }
+            self.advance();
+        }
+    }
+    
+    /// Salta i token fino alla fine della riga, senza consumarla
+    fn skip_to_line_end(&mut self) {
+        while !matches!(
+            self.peek().token_type,
+            TokenType::Newline | TokenType::Dedent | TokenType::Eof
+        ) {
+            self.advance();
+        }
+    }
+    
+    /// Intervallo dei token consumati a partire dall'indice `start`; vuoto se
+    /// non ne è stato consumato nessuno
+    fn span_since(&self, start: usize) -> Span {
+        let first = self.tokens[start.min(self.tokens.len() - 1)].span;
+        if self.current > start {
+            self.span_from(first)
+        } else {
+            Span::point(first.start)
+        }
+    }
+}
+
+/// Descrizione leggibile di un token per i messaggi di errore
+fn describe(token: &Token) -> String {
+    match token.token_type {
+        TokenType::Newline => "fine riga".to_string(),
+        TokenType::Indent => "un'indentazione inattesa".to_string(),
+        TokenType::Dedent => "fine del blocco".to_string(),
+        TokenType::Eof => "fine del file".to_string(),
+        TokenType::StringLiteral => format!("\"{}\"", token.lexeme),
+        TokenType::TemplateString => format!("`{}`", token.lexeme),
+        TokenType::TemplateHead => format!("`{}${{", token.lexeme),
+        TokenType::TemplateMiddle => format!("}}{}${{", token.lexeme),
+        TokenType::TemplateTail => format!("}}{}`", token.lexeme),
+        _ => format!("'{}'", token.lexeme),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn messages(errors: &[Error]) -> Vec<(usize, &str)> {
+        errors
+            .iter()
+            .map(|e| (e.position().unwrap().line, e.message.as_str()))
+            .collect()
+    }
+    
+    #[test]
+    fn test_reports_errors_on_every_line() {
+        let source = "\
+let a = 1 +
+let b = 2
+print(b))
+const c
+let d = 4
+";
+        let (program, errors) = parse_with_errors(source);
+        assert_eq!(
+            messages(&errors),
+            vec![
+                (1, "Atteso un'espressione, trovato fine riga"),
+                (3, "Atteso fine riga, trovato ')'"),
+                (4, "Una costante deve essere inizializzata"),
+            ]
+        );
+        
+        assert_eq!(program.body.len(), 5);
+        let StmtKind::Var(decl) = &program.body[0].kind else {
+            panic!("attesa una dichiarazione: {:?}", program.body[0].kind);
+        };
+        assert!(matches!(decl.init.as_ref().unwrap().kind, ExprKind::Error));
+        assert!(matches!(program.body[1].kind, StmtKind::Var(_)));
+        assert!(matches!(program.body[3].kind, StmtKind::Error));
+        assert!(matches!(program.body[4].kind, StmtKind::Var(_)));
+    }
+    
+    #[test]
+    fn test_unclosed_bracket_does_not_hide_later_errors() {
+        let source = "\
+let x = foo(1, 2
+let y =
+fn g(
+let z = ]
+fn h()
+  let w = [1,
+  return w
+";
+        let (program, errors) = parse_with_errors(source);
+        assert_eq!(
+            messages(&errors),
+            vec![
+                (1, "Atteso ',' o ')' dopo gli argomenti, trovato fine riga"),
+                (2, "Atteso un'espressione, trovato fine riga"),
+                (3, "Atteso il nome del parametro, trovato fine riga"),
+                (4, "Atteso un'espressione, trovato ']'"),
+                (6, "Atteso un'espressione, trovato fine riga"),
+            ]
+        );
+        assert_eq!(program.body.len(), 5);
+        assert!(matches!(program.body[4].kind, StmtKind::Function(_)));
+    }
+    
+    #[test]
+    fn test_broken_condition_keeps_block() {
+        let source = "\
+if x ==
+  foo()
+  let y = ) 
+bar()
+";
+        let (program, errors) = parse_with_errors(source);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(program.body.len(), 2);
+        
+        let StmtKind::If(stmt) = &program.body[0].kind else {
+            panic!("atteso un if: {:?}", program.body[0].kind);
+        };
+        assert!(matches!(stmt.condition.kind, ExprKind::Error));
+        assert_eq!(stmt.then_branch.stmts.len(), 2);
+        assert!(matches!(program.body[1].kind, StmtKind::Expr(_)));
+    }
+    
+    #[test]
+    fn test_skips_block_of_broken_header() {
+        let source = "\
+fn (x)
+  return x
+else
+  nulla
+try
+  foo()
+fn ok()
+  return 1
+";
+        let (program, errors) = parse_with_errors(source);
+        assert_eq!(
+            messages(&errors),
+            vec![
+                (1, "Atteso il nome della funzione, trovato '('"),
+                (7, "Atteso `catch` o `finally` dopo il blocco `try`, trovato 'fn'"),
+            ]
+        );
+        
+        // Il blocco e il ramo `else` appartengono alla funzione non valida
+        assert_eq!(program.body.len(), 3);
+        assert!(matches!(program.body[0].kind, StmtKind::Error));
+        assert!(matches!(program.body[1].kind, StmtKind::Error));
+        assert!(matches!(program.body[2].kind, StmtKind::Function(_)));
+    }
+    
+    #[test]
+    fn test_suppresses_cascading_errors() {
+        let (program, errors) = parse_with_errors("let = = 1 ) ]\nlet ok = 2\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(program.body.len(), 2);
+        assert!(parse("let ok = 2").is_ok());
+    }
+}
@@ -0,0 +1,509 @@
+//! Pattern e l'espressione `match`. Gli stessi pattern servono ai `case` e
+//! alle destrutturazioni.
+
+use super::expr::unescape;
+use super::Parser;
+use crate::ast::{
+    Block, Expr, ExprKind, Ident, Literal, MatchArm, ObjectPatternField, Pattern, PatternKind, Stmt, StmtKind,
+};
+use crate::error::{Error, ErrorKind, Result};
+use crate::lexer::{keyword, TokenType};
+
+impl Parser {
+    /// `match valore` seguito da un blocco indentato di `case` e `default`
+    pub(super) fn match_expression(&mut self) -> Result<Expr> {
+        let keyword = self.advance();
+        let scrutinee = self.expression()?;
+        self.expect(TokenType::Newline, "fine riga dopo il valore di `match`")?;
+        self.expect(TokenType::Indent, "un blocco di `case` dopo `match`")?;
+        
+        let mut arms = Vec::new();
+        while !self.check(TokenType::Dedent) && !self.is_at_end() {
+            arms.push(self.match_arm()?);
+            self.skip_newlines();
+        }
+        self.expect(TokenType::Dedent, "fine del blocco")?;
+        
+        Ok(Expr::new(
+            self.next_id(),
+            ExprKind::Match {
+                scrutinee: Box::new(scrutinee),
+                arms,
+            },
+            self.span_from(keyword.span),
+        ))
+    }
+    
+    /// `case pattern if guardia:` oppure `default:`, seguito dal corpo su un
+    /// blocco indentato o sulla stessa riga
+    fn match_arm(&mut self) -> Result<MatchArm> {
+        let start = self.peek().span;
+        
+        let (pattern, guard) = if self.check(TokenType::Default) {
+            let token = self.advance();
+            (Pattern::new(self.next_id(), PatternKind::Wildcard, token.span), None)
+        } else {
+            self.expect(TokenType::Case, "`case` o `default`")?;
+            let pattern = self.pattern()?;
+            check_pattern(&pattern, false)?;
+            let guard = if self.matches(TokenType::If) { Some(self.expression()?) } else { None };
+            (pattern, guard)
+        };
+        self.expect(TokenType::Colon, "':' dopo il pattern")?;
+        
+        let body = if self.check(TokenType::Newline) {
+            self.block("il pattern")?
+        } else {
+            let expr = self.expression_or_error();
+            let span = expr.span;
+            self.end_statement();
+            Block {
+                stmts: vec![Stmt::new(self.next_id(), StmtKind::Expr(expr), span)],
+                span,
+            }
+        };
+        
+        Ok(MatchArm {
+            id: self.next_id(),
+            pattern,
+            guard,
+            body,
+            span: self.span_from(start),
+        })
+    }
+    
+    /// Pattern che introduce variabili in una dichiarazione, in un parametro o
+    /// in un `for`: un nome oppure una destrutturazione
+    pub(super) fn binding_pattern(&mut self, expected: &str) -> Result<Pattern> {
+        if !matches!(self.peek().token_type, TokenType::LeftBracket | TokenType::LeftBrace) {
+            let name = self.identifier(expected)?;
+            let span = name.span;
+            return Ok(Pattern::new(self.next_id(), PatternKind::Binding(name), span));
+        }
+        
+        let pattern = self.pattern()?;
+        check_pattern(&pattern, true)?;
+        Ok(pattern)
+    }
+    
+    /// Analizza un pattern, comprese le alternative `a | b`
+    pub(super) fn pattern(&mut self) -> Result<Pattern> {
+        let first = self.primary_pattern()?;
+        if !self.check(TokenType::Pipe) {
+            return Ok(first);
+        }
+        
+        let start = first.span;
+        let mut alternatives = vec![first];
+        while self.matches(TokenType::Pipe) {
+            alternatives.push(self.primary_pattern()?);
+        }
+        let span = self.span_from(start);
+        
+        Ok(Pattern::new(self.next_id(), PatternKind::Or(alternatives), span))
+    }
+    
+    fn primary_pattern(&mut self) -> Result<Pattern> {
+        let token = self.peek().clone();
+        
+        let kind = match token.token_type {
+            TokenType::Identifier if token.lexeme == "_" => {
+                self.advance();
+                PatternKind::Wildcard
+            },
+            TokenType::Identifier => {
+                self.advance();
+                self.named_pattern(Ident::new(&token.lexeme, token.span))?
+            },
+            TokenType::Is => {
+                self.advance();
+                PatternKind::TypeTest {
+                    binding: None,
+                    ty: self.type_expr()?,
+                }
+            },
+            TokenType::NumberLiteral
+            | TokenType::Minus
+            | TokenType::StringLiteral
+            | TokenType::True
+            | TokenType::False
+            | TokenType::Null => PatternKind::Literal(self.literal_pattern()?),
+            TokenType::LeftBracket => {
+                self.advance();
+                PatternKind::Array(self.array_pattern()?)
+            },
+            TokenType::LeftBrace => {
+                self.advance();
+                self.object_pattern()?
+            },
+            _ => return Err(self.unexpected("un pattern")),
+        };
+        
+        Ok(Pattern::new(self.next_id(), kind, self.span_from(token.span)))
+    }
+    
+    /// Pattern che inizia con un nome: una variabile, una variante di enum
+    /// (`Some(x)`, `Shape.Circle(r)`) o una verifica di tipo (`n is number`)
+    fn named_pattern(&mut self, name: Ident) -> Result<PatternKind> {
+        if self.matches(TokenType::Is) {
+            return Ok(PatternKind::TypeTest {
+                binding: Some(name),
+                ty: self.type_expr()?,
+            });
+        }
+        
+        let (enum_name, name) = if self.matches(TokenType::Dot) {
+            (Some(name), self.identifier("il nome della variante")?)
+        } else {
+            (None, name)
+        };
+        if !self.matches(TokenType::LeftParen) {
+            return Ok(match enum_name {
+                Some(_) => PatternKind::Variant {
+                    enum_name,
+                    name,
+                    fields: Vec::new(),
+                },
+                None => PatternKind::Binding(name),
+            });
+        }
+        
+        let mut fields = Vec::new();
+        while !self.check(TokenType::RightParen) {
+            fields.push(self.pattern()?);
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenType::RightParen, "',' o ')' dopo i valori della variante")?;
+        
+        Ok(PatternKind::Variant { enum_name, name, fields })
+    }
+    
+    /// Letterale, eventualmente un numero negativo
+    fn literal_pattern(&mut self) -> Result<Literal> {
+        let negative = self.matches(TokenType::Minus);
+        if negative && !self.check(TokenType::NumberLiteral) {
+            return Err(self.unexpected("un numero dopo '-'"));
+        }
+        let token = self.advance();
+        
+        let literal = match token.token_type {
+            TokenType::NumberLiteral => {
+                let value = self.number(&token.lexeme, token.span)?;
+                Literal::Number(if negative { -value } else { value })
+            },
+            TokenType::StringLiteral => Literal::String(unescape(&token.lexeme)),
+            TokenType::True => Literal::Bool(true),
+            TokenType::False => Literal::Bool(false),
+            _ => Literal::Null,
+        };
+        
+        Ok(literal)
+    }
+    
+    /// Elementi dopo `[`, fino a `]` inclusa. Solo l'ultimo può essere `...resto`.
+    fn array_pattern(&mut self) -> Result<Vec<Pattern>> {
+        let mut elements: Vec<Pattern> = Vec::new();
+        
+        while !self.check(TokenType::RightBracket) {
+            if let Some(rest) = elements.last().filter(|p| matches!(p.kind, PatternKind::Rest(_))) {
+                return Err(self.error_at(rest.span, "L'elemento `...` deve essere l'ultimo del pattern".to_string()));
+            }
+            
+            let element = if self.check(TokenType::DotDotDot) {
+                let dots = self.advance();
+                let pattern = self.pattern()?;
+                if self.check(TokenType::Equal) {
+                    return Err(self.error_at(
+                        self.peek().span,
+                        "Un elemento `...` non può avere un valore predefinito".to_string(),
+                    ));
+                }
+                let span = self.span_from(dots.span);
+                Pattern::new(self.next_id(), PatternKind::Rest(Box::new(pattern)), span)
+            } else {
+                let pattern = self.pattern()?;
+                self.with_default(pattern)?
+            };
+            elements.push(element);
+            
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenType::RightBracket, "',' o ']' nel pattern")?;
+        
+        Ok(elements)
+    }
+    
+    /// Aggiunge a `pattern` l'eventuale valore predefinito `= valore`
+    fn with_default(&mut self, pattern: Pattern) -> Result<Pattern> {
+        if !self.matches(TokenType::Equal) {
+            return Ok(pattern);
+        }
+        let start = pattern.span;
+        let default = self.expression()?;
+        let span = self.span_from(start);
+        
+        Ok(Pattern::new(
+            self.next_id(),
+            PatternKind::Default {
+                pattern: Box::new(pattern),
+                default: Box::new(default),
+            },
+            span,
+        ))
+    }
+    
+    /// Campi dopo `{`, fino a `}` inclusa, con un eventuale `...resto` finale
+    fn object_pattern(&mut self) -> Result<PatternKind> {
+        let mut fields = Vec::new();
+        let mut rest = None;
+        
+        while !self.check(TokenType::RightBrace) {
+            if self.check(TokenType::DotDotDot) {
+                let dots = self.advance();
+                let name = self.identifier("il nome dopo `...`")?;
+                if !self.check(TokenType::RightBrace) {
+                    let span = dots.span.to(name.span);
+                    return Err(self.error_at(span, "L'elemento `...` deve essere l'ultimo del pattern".to_string()));
+                }
+                rest = Some(name);
+                break;
+            }
+            
+            let key = self.property_name()?;
+            let pattern = if self.matches(TokenType::Colon) {
+                self.pattern()?
+            } else if keyword(&key.name).is_some() {
+                // Una parola chiave non può diventare il nome di una variabile
+                return Err(self.unexpected("':' dopo la chiave"));
+            } else {
+                // `{ nome = valore }` equivale a `{ nome: nome = valore }`
+                Pattern::new(self.next_id(), PatternKind::Binding(key.clone()), key.span)
+            };
+            let pattern = self.with_default(pattern)?;
+            fields.push(ObjectPatternField {
+                span: self.span_from(key.span),
+                key,
+                pattern,
+            });
+            
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenType::RightBrace, "',' o '}' nel pattern")?;
+        
+        Ok(PatternKind::Object { fields, rest })
+    }
+}
+
+/// Verifica che `pattern` sia ammesso dove compare. In una dichiarazione
+/// deve corrispondere a qualsiasi valore, quindi sono esclusi letterali,
+/// varianti, verifiche di tipo e alternative; in un `case` sono esclusi i
+/// valori predefiniti.
+pub(super) fn check_pattern(pattern: &Pattern, declaration: bool) -> Result<()> {
+    let message = match &pattern.kind {
+        PatternKind::Literal(_)
+        | PatternKind::Variant { .. }
+        | PatternKind::TypeTest { .. }
+        | PatternKind::Or(_)
+            if declaration =>
+        {
+            "Questo pattern può non corrispondere al valore: usalo in un `match`"
+        },
+        PatternKind::Default { .. } if !declaration => "Un pattern di `case` non può avere valori predefiniti",
+        PatternKind::Array(patterns) | PatternKind::Variant { fields: patterns, .. } | PatternKind::Or(patterns) => {
+            return patterns.iter().try_for_each(|p| check_pattern(p, declaration));
+        },
+        PatternKind::Object { fields, .. } => {
+            return fields.iter().try_for_each(|field| check_pattern(&field.pattern, declaration));
+        },
+        PatternKind::Rest(pattern) | PatternKind::Default { pattern, .. } => {
+            return check_pattern(pattern, declaration);
+        },
+        PatternKind::Wildcard | PatternKind::Binding(_) | PatternKind::Literal(_) | PatternKind::TypeTest { .. } => {
+            return Ok(());
+        },
+    };
+    Err(Error::new(ErrorKind::SyntaxError, message.to_string(), pattern.span))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::*;
+    use crate::parser::parse;
+    
+    fn match_expr(source: &str) -> (Expr, Vec<MatchArm>) {
+        let init = match parse(source).unwrap().body.remove(0).kind {
+            StmtKind::Var(decl) => decl.init.unwrap(),
+            other => panic!("attesa una dichiarazione: {:?}", other),
+        };
+        match init.kind {
+            ExprKind::Match { scrutinee, arms } => (*scrutinee, arms),
+            other => panic!("atteso un match: {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_match_with_guards() {
+        let source = "\
+let result = match value
+  case 0:
+    \"Zero\"
+  case n if n < 0:
+    \"Negativo\"
+  case 1 | 2 | 3: \"Piccolo\"
+  default:
+    \"Altro\"
+print(result)
+";
+        let (scrutinee, arms) = match_expr(source);
+        assert!(matches!(scrutinee.kind, ExprKind::Ident(_)));
+        assert_eq!(arms.len(), 4);
+        
+        assert!(matches!(arms[0].pattern.kind, PatternKind::Literal(Literal::Number(n)) if n == 0.0));
+        assert!(matches!(arms[1].pattern.kind, PatternKind::Binding(_)));
+        assert!(matches!(
+            arms[1].guard.as_ref().unwrap().kind,
+            ExprKind::Binary { op: BinaryOp::Less, .. }
+        ));
+        let PatternKind::Or(alternatives) = &arms[2].pattern.kind else {
+            panic!("attese delle alternative");
+        };
+        assert_eq!(alternatives.len(), 3);
+        assert_eq!(arms[2].body.stmts.len(), 1);
+        assert!(matches!(arms[3].pattern.kind, PatternKind::Wildcard));
+        
+        // L'istruzione successiva al `match` resta separata
+        assert_eq!(parse(source).unwrap().body.len(), 2);
+    }
+    
+    #[test]
+    fn test_destructuring_patterns() {
+        let source = "\
+let r = match shape
+  case Shape.Circle(radius): radius
+  case Some({ x, y: [first, ...others], ...rest }): x
+  case n is number: n
+  case is string | null: -1
+  case [_, -1, \"a\"]: 0
+";
+        let (_, arms) = match_expr(source);
+        
+        let PatternKind::Variant { enum_name, name, fields } = &arms[0].pattern.kind else {
+            panic!("attesa una variante");
+        };
+        assert_eq!(enum_name.as_ref().unwrap().name, "Shape");
+        assert_eq!(name.name, "Circle");
+        assert_eq!(fields.len(), 1);
+        
+        let PatternKind::Variant { enum_name: None, fields, .. } = &arms[1].pattern.kind else {
+            panic!("attesa una variante");
+        };
+        let PatternKind::Object { fields, rest } = &fields[0].kind else {
+            panic!("atteso un pattern oggetto");
+        };
+        assert!(matches!(&fields[0].pattern.kind, PatternKind::Binding(name) if name.name == "x"));
+        assert_eq!(rest.as_ref().unwrap().name, "rest");
+        let PatternKind::Array(elements) = &fields[1].pattern.kind else {
+            panic!("atteso un pattern array");
+        };
+        assert!(matches!(elements[1].kind, PatternKind::Rest(_)));
+        
+        assert!(matches!(&arms[2].pattern.kind, PatternKind::TypeTest { binding: Some(_), .. }));
+        let PatternKind::TypeTest { binding: None, ty } = &arms[3].pattern.kind else {
+            panic!("attesa una verifica di tipo");
+        };
+        assert!(matches!(ty.kind, TypeExprKind::Union(_)));
+        
+        let PatternKind::Array(elements) = &arms[4].pattern.kind else {
+            panic!("atteso un pattern array");
+        };
+        assert!(matches!(elements[0].kind, PatternKind::Wildcard));
+        assert!(matches!(elements[1].kind, PatternKind::Literal(Literal::Number(n)) if n == -1.0));
+    }
+    
+    #[test]
+    fn test_pattern_errors() {
+        let error = parse("let r = match x\n  case [...a, b]: 0\n").unwrap_err();
+        assert_eq!(error.message, "L'elemento `...` deve essere l'ultimo del pattern");
+        
+        let error = parse("let r = match x\n  case { ...a, b }: 0\n").unwrap_err();
+        assert_eq!(error.message, "L'elemento `...` deve essere l'ultimo del pattern");
+        
+        let error = parse("let r = match x\n  case 1 \"a\"\n").unwrap_err();
+        assert_eq!(error.message, "Atteso ':' dopo il pattern, trovato \"a\"");
+        
+        let error = parse("let r = match x\n  foo: 1\n").unwrap_err();
+        assert_eq!(error.message, "Atteso `case` o `default`, trovato 'foo'");
+    }
+    
+    #[test]
+    fn test_destructuring_declarations() {
+        let source = "\
+let [first, second = 2, ...rest] = [1, 2, 3, 4, 5]
+const { name, age: years = 18, address: { city }, ...others } = person
+fn sum(first, [a, b]: number[], { scale = 1 }, ...values: number[])
+  return first
+for [key, value] in entries
+  print(key)
+";
+        let body = parse(source).unwrap().body;
+        
+        let StmtKind::Var(decl) = &body[0].kind else {
+            panic!("attesa una dichiarazione");
+        };
+        let PatternKind::Array(elements) = &decl.pattern.kind else {
+            panic!("atteso un pattern array");
+        };
+        assert!(matches!(elements[1].kind, PatternKind::Default { .. }));
+        assert!(matches!(elements[2].kind, PatternKind::Rest(_)));
+        
+        let StmtKind::Var(decl) = &body[1].kind else {
+            panic!("attesa una dichiarazione");
+        };
+        let PatternKind::Object { fields, rest } = &decl.pattern.kind else {
+            panic!("atteso un pattern oggetto");
+        };
+        assert_eq!(fields.len(), 3);
+        assert!(matches!(fields[1].pattern.kind, PatternKind::Default { .. }));
+        assert!(matches!(fields[2].pattern.kind, PatternKind::Object { .. }));
+        assert_eq!(rest.as_ref().unwrap().name, "others");
+        
+        let StmtKind::Function(function) = &body[2].kind else {
+            panic!("attesa una funzione");
+        };
+        assert_eq!(function.params.len(), 4);
+        assert!(matches!(function.params[1].pattern.kind, PatternKind::Array(_)));
+        assert!(function.params[1].ty.is_some());
+        let PatternKind::Object { fields, .. } = &function.params[2].pattern.kind else {
+            panic!("atteso un pattern oggetto");
+        };
+        assert!(matches!(fields[0].pattern.kind, PatternKind::Default { .. }));
+        assert!(function.params[3].rest);
+        
+        let StmtKind::For(stmt) = &body[3].kind else {
+            panic!("atteso un for");
+        };
+        assert!(matches!(stmt.pattern.kind, PatternKind::Array(_)));
+    }
+    
+    #[test]
+    fn test_destructuring_errors() {
+        let cases = [
+            ("let [...a, b] = x", "L'elemento `...` deve essere l'ultimo del pattern"),
+            ("let [...a = 1] = x", "Un elemento `...` non può avere un valore predefinito"),
+            ("fn f(...a, b)\n  return a", "Il parametro `...` deve essere l'ultimo"),
+            ("fn f(...a = [])\n  return a", "Un parametro `...` non può avere un valore predefinito"),
+            ("let [1, a] = x", "Questo pattern può non corrispondere al valore: usalo in un `match`"),
+            ("let r = match x\n  case [a = 1]: a\n", "Un pattern di `case` non può avere valori predefiniti"),
+        ];
+        for (source, message) in cases {
+            let error = parse(source).unwrap_err();
+            assert_eq!(error.message, message, "sorgente: {}", source);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! Precedenza e associatività degli operatori.
+//!
+//! `INFIX_OPERATORS` è l'unica fonte di verità: il parser la usa per decidere
+//! come raggruppare gli operandi e il formatter, tramite [`needs_parens`], per
+//! decidere dove servono le parentesi.
+//!
+//! Dal livello più basso al più alto:
+//!
+//! | Livello          | Operatori                        | Associatività |
+//! |------------------|----------------------------------|---------------|
+//! | `Assignment`     | `=` `+=` `-=` `*=` `/=`          | destra        |
+//! | `Coalesce`       | `??`                             | sinistra      |
+//! | `Or`             | `\|\|`                           | sinistra      |
+//! | `And`            | `&&`                             | sinistra      |
+//! | `BitOr`          | `\|`                             | sinistra      |
+//! | `BitXor`         | `^`                              | sinistra      |
+//! | `BitAnd`         | `&`                              | sinistra      |
+//! | `Equality`       | `==` `!=`                        | sinistra      |
+//! | `Comparison`     | `<` `<=` `>` `>=` `is` `as`      | sinistra      |
+//! | `Shift`          | `<<` `>>`                        | sinistra      |
+//! | `Additive`       | `+` `-`                          | sinistra      |
+//! | `Multiplicative` | `*` `/` `%`                      | sinistra      |
+//! | `Unary`          | `-` `+` `!` `~` prefissi         |               |
+//! | `Exponent`       | `**`                             | destra        |
+//! | `Postfix`        | chiamate, `.`, `[]`, `?.`, `?`   | sinistra      |
+//!
+//! `??` è sotto `||`, quindi `a ?? b || c` vale `a ?? (b || c)`. `**` lega
+//! più dei prefissi alla sua sinistra, quindi `-2 ** 2` vale `-(2 ** 2)`,
+//! mentre l'esponente può essere a sua volta un prefisso: `2 ** -1`.
+
+use crate::ast::{AssignOp, BinaryOp, Expr, ExprKind};
+use crate::lexer::TokenType;
+
+/// Livello di precedenza; un valore maggiore lega più strettamente
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Precedence {
+    Lowest,
+    Assignment,
+    Coalesce,
+    Or,
+    And,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Equality,
+    Comparison,
+    Shift,
+    Additive,
+    Multiplicative,
+    Unary,
+    Exponent,
+    Postfix,
+    /// Letterali, nomi e altre espressioni che non hanno mai bisogno di parentesi
+    Primary,
+}
+
+impl Precedence {
+    /// Livello immediatamente superiore
+    pub fn next(self) -> Precedence {
+        match self {
+            Precedence::Lowest => Precedence::Assignment,
+            Precedence::Assignment => Precedence::Coalesce,
+            Precedence::Coalesce => Precedence::Or,
+            Precedence::Or => Precedence::And,
+            Precedence::And => Precedence::BitOr,
+            Precedence::BitOr => Precedence::BitXor,
+            Precedence::BitXor => Precedence::BitAnd,
+            Precedence::BitAnd => Precedence::Equality,
+            Precedence::Equality => Precedence::Comparison,
+            Precedence::Comparison => Precedence::Shift,
+            Precedence::Shift => Precedence::Additive,
+            Precedence::Additive => Precedence::Multiplicative,
+            Precedence::Multiplicative => Precedence::Unary,
+            Precedence::Unary => Precedence::Exponent,
+            Precedence::Exponent => Precedence::Postfix,
+            Precedence::Postfix | Precedence::Primary => Precedence::Primary,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+/// Operatore infisso riconosciuto dal parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfixOperator {
+    Binary(BinaryOp),
+    Assign(AssignOp),
+    /// `is`: l'operando destro è un tipo
+    Is,
+    /// `as`: l'operando destro è un tipo
+    As,
+}
+
+/// Tabella degli operatori infissi: token, operatore, precedenza e associatività
+pub const INFIX_OPERATORS: &[(TokenType, InfixOperator, Precedence, Associativity)] = &[
+    (TokenType::Equal, InfixOperator::Assign(AssignOp::Assign), Precedence::Assignment, Associativity::Right),
+    (TokenType::PlusEqual, InfixOperator::Assign(AssignOp::Add), Precedence::Assignment, Associativity::Right),
+    (TokenType::MinusEqual, InfixOperator::Assign(AssignOp::Sub), Precedence::Assignment, Associativity::Right),
+    (TokenType::StarEqual, InfixOperator::Assign(AssignOp::Mul), Precedence::Assignment, Associativity::Right),
+    (TokenType::SlashEqual, InfixOperator::Assign(AssignOp::Div), Precedence::Assignment, Associativity::Right),
+    (TokenType::QuestionQuestion, InfixOperator::Binary(BinaryOp::Coalesce), Precedence::Coalesce, Associativity::Left),
+    (TokenType::PipePipe, InfixOperator::Binary(BinaryOp::Or), Precedence::Or, Associativity::Left),
+    (TokenType::AmpersandAmpersand, InfixOperator::Binary(BinaryOp::And), Precedence::And, Associativity::Left),
+    (TokenType::Pipe, InfixOperator::Binary(BinaryOp::BitOr), Precedence::BitOr, Associativity::Left),
+    (TokenType::Caret, InfixOperator::Binary(BinaryOp::BitXor), Precedence::BitXor, Associativity::Left),
+    (TokenType::Ampersand, InfixOperator::Binary(BinaryOp::BitAnd), Precedence::BitAnd, Associativity::Left),
+    (TokenType::EqualEqual, InfixOperator::Binary(BinaryOp::Eq), Precedence::Equality, Associativity::Left),
+    (TokenType::BangEqual, InfixOperator::Binary(BinaryOp::NotEq), Precedence::Equality, Associativity::Left),
+    (TokenType::Less, InfixOperator::Binary(BinaryOp::Less), Precedence::Comparison, Associativity::Left),
+    (TokenType::LessEqual, InfixOperator::Binary(BinaryOp::LessEq), Precedence::Comparison, Associativity::Left),
+    (TokenType::Greater, InfixOperator::Binary(BinaryOp::Greater), Precedence::Comparison, Associativity::Left),
+    (TokenType::GreaterEqual, InfixOperator::Binary(BinaryOp::GreaterEq), Precedence::Comparison, Associativity::Left),
+    (TokenType::Is, InfixOperator::Is, Precedence::Comparison, Associativity::Left),
+    (TokenType::As, InfixOperator::As, Precedence::Comparison, Associativity::Left),
+    (TokenType::LessLess, InfixOperator::Binary(BinaryOp::Shl), Precedence::Shift, Associativity::Left),
+    (TokenType::GreaterGreater, InfixOperator::Binary(BinaryOp::Shr), Precedence::Shift, Associativity::Left),
+    (TokenType::Plus, InfixOperator::Binary(BinaryOp::Add), Precedence::Additive, Associativity::Left),
+    (TokenType::Minus, InfixOperator::Binary(BinaryOp::Sub), Precedence::Additive, Associativity::Left),
+    (TokenType::Star, InfixOperator::Binary(BinaryOp::Mul), Precedence::Multiplicative, Associativity::Left),
+    (TokenType::Slash, InfixOperator::Binary(BinaryOp::Div), Precedence::Multiplicative, Associativity::Left),
+    (TokenType::Percent, InfixOperator::Binary(BinaryOp::Rem), Precedence::Multiplicative, Associativity::Left),
+    (TokenType::StarStar, InfixOperator::Binary(BinaryOp::Pow), Precedence::Exponent, Associativity::Right),
+];
+
+/// Operatore infisso, precedenza e associatività del token, se è un operatore
+pub fn infix(token_type: TokenType) -> Option<(InfixOperator, Precedence, Associativity)> {
+    INFIX_OPERATORS
+        .iter()
+        .find(|(token, ..)| *token == token_type)
+        .map(|&(_, operator, precedence, associativity)| (operator, precedence, associativity))
+}
+
+/// Precedenza e associatività di un operatore binario
+pub fn binary(op: BinaryOp) -> (Precedence, Associativity) {
+    operator(InfixOperator::Binary(op))
+}
+
+fn operator(op: InfixOperator) -> (Precedence, Associativity) {
+    INFIX_OPERATORS
+        .iter()
+        .find(|(_, operator, ..)| *operator == op)
+        .map(|&(_, _, precedence, associativity)| (precedence, associativity))
+        .expect("ogni operatore infisso è nella tabella")
+}
+
+/// Precedenza con cui un'espressione si lega ai suoi vicini
+pub fn of_expr(expr: &Expr) -> Precedence {
+    match &expr.kind {
+        ExprKind::Binary { op, .. } => binary(*op).0,
+        ExprKind::Assign { op, .. } => operator(InfixOperator::Assign(*op)).0,
+        ExprKind::Is { .. } => operator(InfixOperator::Is).0,
+        ExprKind::As { .. } => operator(InfixOperator::As).0,
+        ExprKind::Unary { .. } => Precedence::Unary,
+        ExprKind::Call { .. }
+        | ExprKind::Member { .. }
+        | ExprKind::Index { .. }
+        | ExprKind::NullCheck(_)
+        | ExprKind::TaggedTemplate { .. } => Precedence::Postfix,
+        ExprKind::New { .. } => Precedence::Postfix,
+        ExprKind::Lambda(_) | ExprKind::Yield { .. } | ExprKind::Spread(_) => Precedence::Assignment,
+        ExprKind::Literal(_)
+        | ExprKind::Ident(_)
+        | ExprKind::This
+        | ExprKind::Super
+        | ExprKind::Array(_)
+        | ExprKind::Object(_)
+        | ExprKind::Template(_)
+        | ExprKind::Match { .. }
+        | ExprKind::Error => Precedence::Primary,
+    }
+}
+
+/// Posizione di un operando rispetto al suo operatore
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Left,
+    Right,
+}
+
+/// Verifica se `child`, operando in posizione `side` di `parent`, va scritto
+/// tra parentesi per essere riletto con la stessa struttura
+pub fn needs_parens(parent: &Expr, child: &Expr, side: Operand) -> bool {
+    let child_precedence = of_expr(child);
+    let (precedence, associativity) = match &parent.kind {
+        ExprKind::Binary { op, .. } => binary(*op),
+        ExprKind::Assign { op, .. } => operator(InfixOperator::Assign(*op)),
+        ExprKind::Is { .. } => operator(InfixOperator::Is),
+        ExprKind::As { .. } => operator(InfixOperator::As),
+        // L'operando di un prefisso è letto al livello `Unary`
+        ExprKind::Unary { .. } => return child_precedence < Precedence::Unary,
+        ExprKind::Call { .. }
+        | ExprKind::Member { .. }
+        | ExprKind::Index { .. }
+        | ExprKind::NullCheck(_)
+        | ExprKind::TaggedTemplate { .. }
+        | ExprKind::New { .. } => return side == Operand::Left && child_precedence < Precedence::Postfix,
+        _ => return false,
+    };
+    
+    // Un prefisso a destra si rilegge sempre allo stesso modo: `2 ** -1`
+    if side == Operand::Right && child_precedence == Precedence::Unary {
+        return false;
+    }
+    
+    match (side, associativity) {
+        (Operand::Left, Associativity::Left) | (Operand::Right, Associativity::Right) => {
+            child_precedence < precedence
+        },
+        _ => child_precedence <= precedence,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::StmtKind;
+    use crate::parser::parse;
+    
+    fn expression(source: &str) -> Expr {
+        match parse(source).unwrap().body.remove(0).kind {
+            StmtKind::Expr(expr) => expr,
+            other => panic!("attesa un'espressione: {:?}", other),
+        }
+    }
+    
+    /// Verifica quali operandi di `source` richiedono le parentesi
+    fn parens(source: &str) -> (bool, bool) {
+        let expr = expression(source);
+        match &expr.kind {
+            ExprKind::Binary { left, right, .. } => (
+                needs_parens(&expr, left, Operand::Left),
+                needs_parens(&expr, right, Operand::Right),
+            ),
+            ExprKind::Unary { operand, .. } => (false, needs_parens(&expr, operand, Operand::Right)),
+            other => panic!("atteso un operatore: {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_needs_parens() {
+        assert_eq!(parens("(a - b) - (c - d)"), (false, true));
+        assert_eq!(parens("(a ** b) ** (c ** d)"), (true, false));
+        assert_eq!(parens("(-2) ** 2"), (true, false));
+        assert_eq!(parens("-(2 ** 2)"), (false, false));
+        assert_eq!(parens("-(a * b)"), (false, true));
+        assert_eq!(parens("2 ** -1"), (false, false));
+        assert_eq!(parens("(a ?? b) || c"), (true, false));
+        assert_eq!(parens("a ?? (b || c)"), (false, false));
+    }
+    
+    #[test]
+    fn test_table_is_consistent() {
+        for &(token, operator, ..) in INFIX_OPERATORS {
+            assert_eq!(INFIX_OPERATORS.iter().filter(|(t, ..)| *t == token).count(), 1);
+            assert_eq!(INFIX_OPERATORS.iter().filter(|(_, o, ..)| *o == operator).count(), 1);
+        }
+        assert_eq!(binary(BinaryOp::Pow), (Precedence::Exponent, Associativity::Right));
+    }
+}
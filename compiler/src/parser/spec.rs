@@ -0,0 +1,997 @@
+//! Parser LALR(1) generato dalla grammatica in `grammar.lalrpop`.
+//!
+//! La grammatica riscrive in forma eseguibile l'EBNF della specifica del
+//! linguaggio e costruisce lo stesso AST del parser scritto a mano. Non
+//! sostituisce quest'ultimo, che recupera dagli errori e dà messaggi migliori:
+//! serve a verificare che implementazione e specifica descrivano lo stesso
+//! linguaggio. [`compare`] analizza un sorgente con entrambi i parser e
+//! restituisce il primo punto in cui gli alberi differiscono.
+//!
+//! Il parser scritto a mano risolve alcune ambiguità guardando avanti di
+//! più token, cosa che un parser LALR(1) non può fare. Prima del parsing i
+//! token del `Lexer` passano quindi da un adattatore che:
+//! - marca la `(` che apre i parametri di una lambda o di un tipo funzione,
+//!   cioè quella la cui parentesi di chiusura è seguita da `->` (eventualmente
+//!   dopo `: tipo`);
+//! - marca la `<` che apre gli argomenti di tipo di una chiamata generica
+//!   `f<T>(x)`, se la lista si chiude ed è seguita da `(`;
+//! - marca `|`, `&` e `<` nel tipo che segue `is` e `as`, dove il parser a
+//!   mano li legge come parte del tipo e non come operatori;
+//! - divide `>>` e `>=` in due token, così `>` può chiudere liste generiche
+//!   annidate come `Map<K, Array<V>>`;
+//! - rende uniforme la fine delle istruzioni: toglie l'a capo che precede un
+//!   `Indent`, ne aggiunge uno dopo un `Dedent` che chiude un'istruzione e
+//!   distingue l'a capo seguito da una riga vuota, che separa i decoratori
+//!   di un blocco dalla dichiarazione successiva.
+//!
+//! I token di indentazione prendono la posizione della fine dell'ultimo token
+//! significativo, quindi gli intervalli dei nodi coincidono con quelli del
+//! parser scritto a mano. I `NodeId` invece dipendono dall'ordine in cui i
+//! nodi vengono creati: il programma viene rinumerato dopo il parsing e il
+//! confronto rinumera allo stesso modo anche l'albero dell'altro parser.
+
+use lalrpop_util::{lalrpop_mod, ParseError};
+use serde_json::Value;
+
+use super::expr::{is_assignable, parse_number, unescape};
+use super::pattern;
+use crate::ast::visit::{self, Visitor};
+use crate::ast::visit_mut::VisitorMut;
+use crate::ast::{
+    AssignOp, BinaryOp, Block, ClassMember, ClassMemberKind, Decorator, ExportDecl, Expr, ExprKind, FunctionDecl, Ident,
+    ImportDecl, ImportName, ImportSpecifier, Lambda, LambdaBody, Literal, NodeId, NodeIdGen, Param, Pattern, PatternKind,
+    Program, Renumber, Stmt, StmtKind, StringLit, Template, TypeExpr, TypeExprKind,
+};
+use crate::error::{Error, ErrorKind, Result};
+use crate::lexer::{Lexer, Position, Span, Token, TokenType};
+
+lalrpop_mod!(
+    #[allow(clippy::all, clippy::pedantic, unused_imports, unused_parens)]
+    grammar,
+    "/parser/grammar.rs"
+);
+
+/// Token letto dalla grammatica
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tok {
+    /// Token del lexer con il suo testo
+    Lexed(TokenType, String),
+    /// `(` che apre i parametri di una lambda o di un tipo funzione
+    ArrowParen,
+    /// `<` che apre gli argomenti di tipo di una chiamata generica
+    GenericLess,
+    /// `|` nel tipo dopo `is` o `as`
+    TypePipe,
+    /// `&` nel tipo dopo `is` o `as`
+    TypeAmpersand,
+    /// `<` nel tipo dopo `is` o `as`
+    TypeLess,
+    /// Primo `>` di `>>`
+    ShiftGreater,
+    /// `>` di `>=`
+    GreaterBeforeEqual,
+    /// A capo seguito da almeno una riga vuota
+    BlankLine,
+}
+
+type Triple = (Position, Tok, Position);
+
+/// Analizza un file sorgente con il parser generato dalla grammatica
+pub fn parse(source: &str) -> Result<Program> {
+    let tokens = Lexer::new(source).tokenize()?;
+    let span = match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => first.span.to(last.span),
+        _ => Span::point(Position::new(1, 1, 0)),
+    };
+    
+    let body = grammar::ProgramParser::new()
+        .parse(adapt(tokens).into_iter().map(Ok::<_, Error>))
+        .map_err(syntax_error)?;
+    let mut program = Program { body, span };
+    check_yield(&program)?;
+    renumber(&mut program);
+    
+    Ok(program)
+}
+
+/// Punto in cui i due parser producono risultati diversi
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// Percorso del nodo nel dump JSON dell'AST, come `body[0].kind.Var.init`
+    pub path: String,
+    /// Risultato del parser scritto a mano
+    pub hand: String,
+    /// Risultato del parser generato dalla grammatica
+    pub spec: String,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "in `{}`: parser a mano {}, grammatica {}", self.path, self.hand, self.spec)
+    }
+}
+
+/// Analizza `source` con il parser scritto a mano e con quello generato dalla
+/// grammatica e restituisce la prima differenza tra i due AST. Un sorgente
+/// rifiutato da entrambi non è una differenza, anche se gli errori non
+/// coincidono.
+pub fn compare(source: &str) -> Option<Mismatch> {
+    let outcome = |result: Result<Program>| match result {
+        Ok(mut program) => {
+            renumber(&mut program);
+            Ok(serde_json::to_value(&program).expect("l'AST è serializzabile"))
+        },
+        Err(error) => Err(error),
+    };
+    
+    match (outcome(super::parse(source)), outcome(parse(source))) {
+        (Ok(hand), Ok(spec)) => first_difference(&hand, &spec, String::new()),
+        (Err(_), Err(_)) => None,
+        (hand, spec) => {
+            let describe = |result: std::result::Result<Value, Error>| match result {
+                Ok(_) => "accetta il sorgente".to_string(),
+                Err(error) => format!("lo rifiuta ({}: {})", error.position().map_or(String::new(), |p| p.to_string()), error.message),
+            };
+            Some(Mismatch {
+                path: String::new(),
+                hand: describe(hand),
+                spec: describe(spec),
+            })
+        },
+    }
+}
+
+fn first_difference(hand: &Value, spec: &Value, path: String) -> Option<Mismatch> {
+    match (hand, spec) {
+        (Value::Object(hand_fields), Value::Object(spec_fields)) if hand_fields.len() == spec_fields.len() => {
+            hand_fields.iter().find_map(|(key, hand)| {
+                let path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                match spec_fields.get(key) {
+                    Some(spec) => first_difference(hand, spec, path),
+                    None => Some(Mismatch {
+                        path,
+                        hand: hand.to_string(),
+                        spec: "nessun valore".to_string(),
+                    }),
+                }
+            })
+        },
+        (Value::Array(hand_items), Value::Array(spec_items)) if hand_items.len() == spec_items.len() => hand_items
+            .iter()
+            .zip(spec_items)
+            .enumerate()
+            .find_map(|(index, (hand, spec))| first_difference(hand, spec, format!("{}[{}]", path, index))),
+        _ if hand == spec => None,
+        _ => Some(Mismatch {
+            path,
+            hand: hand.to_string(),
+            spec: spec.to_string(),
+        }),
+    }
+}
+
+/// Assegna nuovi `NodeId` in ordine di visita, a partire da zero
+fn renumber(program: &mut Program) {
+    Renumber { ids: &mut NodeIdGen::new() }.visit_program(program);
+}
+
+fn syntax_error(error: ParseError<Position, Tok, Error>) -> Error {
+    let (span, message) = match error {
+        ParseError::User { error } => return error,
+        ParseError::InvalidToken { location } => (Span::point(location), "Token non valido".to_string()),
+        ParseError::UnrecognizedEof { location, .. } => (Span::point(location), "Fine del file inattesa".to_string()),
+        ParseError::UnrecognizedToken {
+            token: (start, token, end),
+            expected,
+        } => (Span::new(start, end), unexpected(&token, &expected)),
+        ParseError::ExtraToken {
+            token: (start, token, end),
+        } => (Span::new(start, end), format!("Token inatteso {}", describe(&token))),
+    };
+    Error::new(ErrorKind::SyntaxError, message, span)
+}
+
+/// Messaggio per un token non atteso. Se la grammatica ammette pochi token
+/// al suo posto li elenca, altrimenti l'elenco non aiuterebbe.
+fn unexpected(token: &Tok, expected: &[String]) -> String {
+    let mut names: Vec<String> = Vec::new();
+    for terminal in expected {
+        let name = match terminal.as_str() {
+            "Identifier" => "un nome".to_string(),
+            "Number" => "un numero".to_string(),
+            "Text" => "una stringa".to_string(),
+            "Newline" | "BlankLine" => "fine riga".to_string(),
+            "Indent" => "un blocco indentato".to_string(),
+            "Dedent" => "fine del blocco".to_string(),
+            "ArrowParen" => "'('".to_string(),
+            "GenericLess" | "TypeLess" => "'<'".to_string(),
+            "TypePipe" => "'|'".to_string(),
+            "TypeAmpersand" => "'&'".to_string(),
+            "ShiftGreater" | "GreaterBeforeEqual" => "'>'".to_string(),
+            quoted => format!("'{}'", quoted.trim_matches('"')),
+        };
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    match names.as_slice() {
+        [] => format!("Token inatteso {}", describe(token)),
+        [name] => format!("Atteso {}, trovato {}", name, describe(token)),
+        names if names.len() <= 5 => format!("Atteso uno tra {}, trovato {}", names.join(", "), describe(token)),
+        _ => format!("Trovato {} dove non è ammesso", describe(token)),
+    }
+}
+
+fn describe(token: &Tok) -> String {
+    match token {
+        Tok::Lexed(TokenType::Newline, _) | Tok::BlankLine => "fine riga".to_string(),
+        Tok::Lexed(TokenType::Indent, _) => "un'indentazione inattesa".to_string(),
+        Tok::Lexed(TokenType::Dedent, _) => "fine del blocco".to_string(),
+        Tok::Lexed(TokenType::StringLiteral, lexeme) => format!("\"{}\"", lexeme),
+        Tok::Lexed(TokenType::TemplateString, lexeme) => format!("`{}`", lexeme),
+        Tok::Lexed(TokenType::TemplateHead, lexeme) => format!("`{}${{", lexeme),
+        Tok::Lexed(TokenType::TemplateMiddle, lexeme) => format!("}}{}${{", lexeme),
+        Tok::Lexed(TokenType::TemplateTail, lexeme) => format!("}}{}`", lexeme),
+        Tok::Lexed(_, lexeme) => format!("'{}'", lexeme),
+        Tok::ArrowParen => "'('".to_string(),
+        Tok::GenericLess | Tok::TypeLess => "'<'".to_string(),
+        Tok::TypePipe => "'|'".to_string(),
+        Tok::TypeAmpersand => "'&'".to_string(),
+        Tok::ShiftGreater | Tok::GreaterBeforeEqual => "'>'".to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Adattatore dei token
+// ---------------------------------------------------------------------------
+
+/// Trasforma i token del lexer nel flusso letto dalla grammatica
+fn adapt(tokens: Vec<Token>) -> Vec<Triple> {
+    let mut tokens = split_greater(tokens);
+    mark_generic_calls(&mut tokens);
+    mark_arrow_parens(&mut tokens);
+    mark_type_operators(&mut tokens);
+    layout(tokens)
+}
+
+/// Token con il suo intervallo, durante l'adattamento
+struct Lexeme {
+    tok: Tok,
+    span: Span,
+}
+
+impl Lexeme {
+    fn is(&self, token_type: TokenType) -> bool {
+        matches!(&self.tok, Tok::Lexed(t, _) if *t == token_type)
+    }
+}
+
+/// Divide `>>` e `>=`, ricordando che il primo `>` era unito al carattere
+/// successivo: in un'espressione la coppia torna a essere un solo operatore
+fn split_greater(tokens: Vec<Token>) -> Vec<Lexeme> {
+    let mut lexemes = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let (first, rest) = match token.token_type {
+            TokenType::GreaterGreater => (Tok::ShiftGreater, TokenType::Greater),
+            TokenType::GreaterEqual => (Tok::GreaterBeforeEqual, TokenType::Equal),
+            token_type => {
+                lexemes.push(Lexeme {
+                    tok: Tok::Lexed(token_type, token.lexeme),
+                    span: token.span,
+                });
+                continue;
+            },
+        };
+        let start = token.span.start;
+        let middle = Position::new(start.line, start.column + 1, start.offset + 1);
+        lexemes.push(Lexeme {
+            tok: first,
+            span: Span::new(start, middle),
+        });
+        lexemes.push(Lexeme {
+            tok: Tok::Lexed(rest, token.lexeme[1..].to_string()),
+            span: Span::new(middle, token.span.end),
+        });
+    }
+    lexemes
+}
+
+/// `<` dopo un nome, seguita da argomenti di tipo, `>` e `(`
+fn mark_generic_calls(tokens: &mut [Lexeme]) {
+    for index in 1..tokens.len() {
+        if !tokens[index].is(TokenType::Less) || !is_name(tokens, index - 1) {
+            continue;
+        }
+        let scanner = TypeScanner { tokens };
+        if scanner.type_arguments(index).is_some_and(|end| scanner.is(end, TokenType::LeftParen)) {
+            tokens[index].tok = Tok::GenericLess;
+        }
+    }
+}
+
+/// `(` la cui parentesi di chiusura è seguita da `->`, anche dopo `: tipo`.
+/// Non si considerano le parentesi che aprono gli argomenti di una chiamata
+/// o i parametri di una funzione con nome.
+fn mark_arrow_parens(tokens: &mut [Lexeme]) {
+    for index in 0..tokens.len() {
+        if !tokens[index].is(TokenType::LeftParen) || (index > 0 && opens_arguments(tokens, index)) {
+            continue;
+        }
+        let scanner = TypeScanner { tokens };
+        let Some(close) = scanner.closing(index) else {
+            continue;
+        };
+        let arrow = if scanner.is(close + 1, TokenType::Colon) {
+            scanner.type_expr(close + 2).is_some_and(|end| scanner.is(end, TokenType::Arrow))
+        } else {
+            scanner.is(close + 1, TokenType::Arrow)
+        };
+        if arrow {
+            tokens[index].tok = Tok::ArrowParen;
+        }
+    }
+}
+
+/// Verifica se la `(` in `index` segue un nome o un'espressione, come negli
+/// argomenti di una chiamata
+fn opens_arguments(tokens: &[Lexeme], index: usize) -> bool {
+    is_name(tokens, index - 1)
+        || matches!(
+            tokens[index - 1].tok,
+            Tok::Lexed(
+                TokenType::RightParen
+                    | TokenType::RightBracket
+                    | TokenType::Greater
+                    | TokenType::Question
+                    | TokenType::QuestionDot
+                    | TokenType::Constructor,
+                _,
+            )
+        )
+}
+
+/// Verifica se il token in `index` è un nome, anche quello di un membro o di
+/// un metodo che coincide con una parola chiave: `a.default`, `fn new`
+fn is_name(tokens: &[Lexeme], index: usize) -> bool {
+    match &tokens[index].tok {
+        Tok::Lexed(TokenType::Identifier, _) => true,
+        Tok::Lexed(_, lexeme) if crate::lexer::keyword(lexeme).is_some() && index > 0 => {
+            let previous = &tokens[index - 1];
+            previous.is(TokenType::Dot) || previous.is(TokenType::QuestionDot) || previous.is(TokenType::Fn)
+        },
+        _ => false,
+    }
+}
+
+/// `|`, `&` e `<` nel tipo che segue `is` e `as`
+fn mark_type_operators(tokens: &mut [Lexeme]) {
+    for index in 0..tokens.len() {
+        if !tokens[index].is(TokenType::Is) && !tokens[index].is(TokenType::As) {
+            continue;
+        }
+        let Some(end) = (TypeScanner { tokens }).type_expr(index + 1) else {
+            continue;
+        };
+        for lexeme in &mut tokens[index + 1..end] {
+            lexeme.tok = match &lexeme.tok {
+                Tok::Lexed(TokenType::Pipe, _) => Tok::TypePipe,
+                Tok::Lexed(TokenType::Ampersand, _) => Tok::TypeAmpersand,
+                Tok::Lexed(TokenType::Less, _) | Tok::GenericLess => Tok::TypeLess,
+                _ => continue,
+            };
+        }
+    }
+}
+
+/// Riconosce un tipo a partire da un token, come farebbe il parser scritto a
+/// mano, senza costruire nodi. Ogni metodo restituisce l'indice del primo
+/// token dopo la forma riconosciuta.
+struct TypeScanner<'a> {
+    tokens: &'a [Lexeme],
+}
+
+impl TypeScanner<'_> {
+    fn is(&self, index: usize, token_type: TokenType) -> bool {
+        self.tokens.get(index).is_some_and(|lexeme| lexeme.is(token_type))
+    }
+    
+    fn is_less(&self, index: usize) -> bool {
+        self.is(index, TokenType::Less) || self.tokens.get(index).is_some_and(|lexeme| lexeme.tok == Tok::GenericLess)
+    }
+    
+    fn type_expr(&self, index: usize) -> Option<usize> {
+        let mut index = self.intersection(index)?;
+        while self.is(index, TokenType::Pipe) {
+            index = self.intersection(index + 1)?;
+        }
+        Some(index)
+    }
+    
+    fn intersection(&self, index: usize) -> Option<usize> {
+        let mut index = self.postfix(index)?;
+        while self.is(index, TokenType::Ampersand) {
+            index = self.postfix(index + 1)?;
+        }
+        Some(index)
+    }
+    
+    fn postfix(&self, index: usize) -> Option<usize> {
+        let mut index = self.primary(index)?;
+        loop {
+            if self.is(index, TokenType::LeftBracket) && self.is(index + 1, TokenType::RightBracket) {
+                index += 2;
+            } else if self.is(index, TokenType::Question) {
+                index += 1;
+            } else {
+                return Some(index);
+            }
+        }
+    }
+    
+    fn primary(&self, index: usize) -> Option<usize> {
+        let lexeme = self.tokens.get(index)?;
+        match &lexeme.tok {
+            Tok::Lexed(TokenType::Identifier | TokenType::Null, _) if self.is_less(index + 1) => {
+                self.type_arguments(index + 1).or(Some(index + 1))
+            },
+            Tok::Lexed(TokenType::Identifier | TokenType::Null, _) => Some(index + 1),
+            Tok::Lexed(TokenType::LeftParen, _) | Tok::ArrowParen => {
+                let close = self.closing(index)?;
+                if self.is(close + 1, TokenType::Arrow) {
+                    self.type_expr(close + 2)
+                } else {
+                    Some(close + 1)
+                }
+            },
+            Tok::Lexed(TokenType::LeftBracket | TokenType::LeftBrace, _) => Some(self.closing(index)? + 1),
+            _ => None,
+        }
+    }
+    
+    /// Argomenti di tipo a partire da `<`
+    fn type_arguments(&self, index: usize) -> Option<usize> {
+        let mut index = index + 1;
+        loop {
+            index = self.type_expr(index)?;
+            if !self.is(index, TokenType::Comma) {
+                break;
+            }
+            index += 1;
+        }
+        match self.tokens.get(index)?.tok {
+            Tok::Lexed(TokenType::Greater, _) | Tok::ShiftGreater | Tok::GreaterBeforeEqual => Some(index + 1),
+            _ => None,
+        }
+    }
+    
+    /// Indice della parentesi che chiude quella in `index`
+    fn closing(&self, index: usize) -> Option<usize> {
+        let mut depth = 0usize;
+        for (offset, lexeme) in self.tokens[index..].iter().enumerate() {
+            match lexeme.tok {
+                Tok::Lexed(TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace, _) | Tok::ArrowParen => {
+                    depth += 1;
+                },
+                Tok::Lexed(TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace, _) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(index + offset);
+                    }
+                },
+                _ => {},
+            }
+        }
+        None
+    }
+}
+
+/// Rende uniforme la fine delle istruzioni e dà ai token di indentazione la
+/// posizione della fine dell'ultimo token significativo
+fn layout(tokens: Vec<Lexeme>) -> Vec<Triple> {
+    let mut triples: Vec<Triple> = Vec::with_capacity(tokens.len());
+    let mut last_end = tokens.first().map_or(Position::new(1, 1, 0), |lexeme| lexeme.span.start);
+    let next_type = |index: usize| match tokens.get(index + 1).map(|lexeme| &lexeme.tok) {
+        Some(Tok::Lexed(token_type, _)) => Some(*token_type),
+        _ => None,
+    };
+    
+    for (index, lexeme) in tokens.iter().enumerate() {
+        match &lexeme.tok {
+            Tok::Lexed(TokenType::Eof, _) => break,
+            Tok::Lexed(TokenType::Newline, text) => {
+                // Un blocco inizia con `Indent`, senza l'a capo che lo precede
+                if next_type(index) == Some(TokenType::Indent) {
+                    continue;
+                }
+                // Il lexer raddoppia il lessema dell'a capo seguito da una riga vuota
+                push_newline(&mut triples, last_end, text.len() > 1);
+            },
+            Tok::Lexed(TokenType::Indent, _) => triples.push((last_end, lexeme.tok.clone(), last_end)),
+            Tok::Lexed(TokenType::Dedent, _) => {
+                triples.push((last_end, lexeme.tok.clone(), last_end));
+                // Dopo un blocco l'istruzione che lo contiene è finita, a
+                // meno che prosegua con `else`, `catch` o `finally` oppure il
+                // blocco sia il corpo di una lambda tra parentesi
+                let continues = matches!(
+                    next_type(index),
+                    Some(
+                        TokenType::Dedent
+                            | TokenType::Eof
+                            | TokenType::Newline
+                            | TokenType::Else
+                            | TokenType::Catch
+                            | TokenType::Finally
+                            | TokenType::RightParen
+                            | TokenType::RightBracket
+                            | TokenType::RightBrace
+                            | TokenType::Comma
+                    )
+                );
+                if !continues {
+                    push_newline(&mut triples, last_end, false);
+                }
+            },
+            tok => {
+                triples.push((lexeme.span.start, tok.clone(), lexeme.span.end));
+                last_end = lexeme.span.end;
+            },
+        }
+    }
+    
+    triples
+}
+
+/// Aggiunge un a capo, unendolo a quello precedente se ce n'è già uno
+fn push_newline(triples: &mut Vec<Triple>, at: Position, blank: bool) {
+    let tok = if blank { Tok::BlankLine } else { Tok::Lexed(TokenType::Newline, "\n".to_string()) };
+    match triples.last_mut() {
+        Some((_, previous @ Tok::Lexed(TokenType::Newline, _), _)) => {
+            if blank {
+                *previous = tok;
+            }
+        },
+        Some((_, Tok::BlankLine, _)) => {},
+        _ => triples.push((at, tok, at)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Funzioni usate dalle azioni della grammatica
+// ---------------------------------------------------------------------------
+
+type ActionResult<T> = std::result::Result<T, ParseError<Position, Tok, Error>>;
+
+fn user_error<T>(span: Span, message: &str) -> ActionResult<T> {
+    Err(ParseError::User {
+        error: Error::new(ErrorKind::SyntaxError, message.to_string(), span),
+    })
+}
+
+fn number(lexeme: &str, span: Span) -> ActionResult<f64> {
+    match parse_number(lexeme) {
+        Some(value) => Ok(value),
+        None => user_error(span, &format!("Numero non valido: '{}'", lexeme)),
+    }
+}
+
+fn string(lexeme: &str) -> String {
+    unescape(lexeme)
+}
+
+fn check_assignable(target: &Expr, op: AssignOp, lexeme: &str) -> ActionResult<()> {
+    if is_assignable(target, op) {
+        Ok(())
+    } else {
+        user_error(target.span, &format!("Destinazione non valida per `{}`", lexeme))
+    }
+}
+
+/// Applica i decoratori alla funzione o alla classe dichiarata da `stmt`,
+/// anche se esportata
+fn decorate(decorators: Vec<Decorator>, mut stmt: Stmt, span: Span) -> ActionResult<Stmt> {
+    let target = match &mut stmt.kind {
+        StmtKind::Export(ExportDecl::Declaration(inner) | ExportDecl::Default(inner)) => &mut inner.kind,
+        kind => kind,
+    };
+    match target {
+        StmtKind::Function(function) => function.decorators = decorators,
+        StmtKind::Class(class) => class.decorators = decorators,
+        _ => return user_error(stmt.span, "Un decoratore deve precedere una funzione o una classe"),
+    }
+    stmt.span = span;
+    Ok(stmt)
+}
+
+fn check_class_members(members: &[ClassMember]) -> ActionResult<()> {
+    let mut constructors = members.iter().filter(|m| matches!(m.kind, ClassMemberKind::Constructor { .. }));
+    match constructors.nth(1) {
+        Some(duplicate) => user_error(duplicate.span, "Una classe può avere un solo costruttore"),
+        None => Ok(()),
+    }
+}
+
+fn check_constructor(kind: &ClassMemberKind, decorators: &[Decorator], is_static: bool, span: Span) -> ActionResult<()> {
+    if !matches!(kind, ClassMemberKind::Constructor { .. }) {
+        return Ok(());
+    }
+    if is_static {
+        return user_error(span, "Un costruttore non può essere `static`");
+    }
+    match decorators.first() {
+        Some(decorator) => user_error(decorator.span, "Un costruttore non può avere decoratori"),
+        None => Ok(()),
+    }
+}
+
+/// Solo l'ultimo parametro può essere `...resto`, e senza valore predefinito
+fn check_params(params: Vec<Param>) -> ActionResult<Vec<Param>> {
+    if let Some(rest) = params.iter().rev().skip(1).find(|p| p.rest) {
+        return user_error(rest.span, "Il parametro `...` deve essere l'ultimo");
+    }
+    if let Some(rest) = params.iter().find(|p| p.rest && p.default.is_some()) {
+        return user_error(rest.span, "Un parametro `...` non può avere un valore predefinito");
+    }
+    Ok(params)
+}
+
+fn check_array_pattern(elements: &[Pattern]) -> ActionResult<()> {
+    let rest = elements.iter().rev().skip(1).find(|p| matches!(p.kind, PatternKind::Rest(_)));
+    match rest {
+        Some(rest) => user_error(rest.span, "L'elemento `...` deve essere l'ultimo del pattern"),
+        None => Ok(()),
+    }
+}
+
+fn check_pattern(pattern: &Pattern, declaration: bool) -> ActionResult<()> {
+    pattern::check_pattern(pattern, declaration).map_err(|error| ParseError::User { error })
+}
+
+fn check_pragma(directive: &Ident) -> ActionResult<()> {
+    if directive.name == "pragma" {
+        Ok(())
+    } else {
+        user_error(
+            directive.span,
+            &format!("Direttiva sconosciuta `#{}`: l'unica direttiva è `#pragma`", directive.name),
+        )
+    }
+}
+
+fn wildcard_or_binding(name: Ident, span: Span) -> Pattern {
+    let kind = if name.name == "_" { PatternKind::Wildcard } else { PatternKind::Binding(name) };
+    Pattern::new(NodeId::DUMMY, kind, span)
+}
+
+fn with_default(pattern: Pattern, default: Expr, start: Position, end: Position) -> Pattern {
+    let kind = PatternKind::Default {
+        pattern: Box::new(pattern),
+        default: Box::new(default),
+    };
+    Pattern::new(NodeId::DUMMY, kind, Span::new(start, end))
+}
+
+/// Blocco che inizia in `start`; l'intervallo va dalla prima all'ultima istruzione
+fn block(stmts: Vec<Stmt>, start: Position) -> Block {
+    let span = match (stmts.first(), stmts.last()) {
+        (Some(first), Some(last)) => first.span.to(last.span),
+        _ => Span::point(start),
+    };
+    Block { stmts, span }
+}
+
+/// Corpo di un `case` scritto sulla stessa riga
+fn inline_block(expr: Expr) -> Block {
+    let span = expr.span;
+    Block {
+        stmts: vec![Stmt::new(NodeId::DUMMY, StmtKind::Expr(expr), span)],
+        span,
+    }
+}
+
+fn import(
+    default: Option<ImportName>,
+    namespace: Option<ImportName>,
+    named: Vec<ImportSpecifier>,
+    source: StringLit,
+    start: Position,
+    end: Position,
+) -> Stmt {
+    let decl = ImportDecl {
+        default,
+        namespace,
+        named,
+        source,
+    };
+    Stmt::new(NodeId::DUMMY, StmtKind::Import(decl), Span::new(start, end))
+}
+
+fn literal(literal: Literal, start: Position, end: Position) -> Expr {
+    Expr::new(NodeId::DUMMY, ExprKind::Literal(literal), Span::new(start, end))
+}
+
+fn binary(op: BinaryOp, left: Expr, right: Expr, start: Position, end: Position) -> Expr {
+    let kind = ExprKind::Binary {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+    };
+    Expr::new(NodeId::DUMMY, kind, Span::new(start, end))
+}
+
+fn template(head: StringLit, first: Expr, rest: Vec<(StringLit, Expr)>, tail: StringLit) -> Template {
+    let mut strings = vec![head];
+    let mut values = vec![first];
+    for (text, value) in rest {
+        strings.push(text);
+        values.push(value);
+    }
+    strings.push(tail);
+    Template { strings, values }
+}
+
+fn call(callee: Expr, type_args: Vec<TypeExpr>, args: Vec<Expr>, optional: bool, start: Position, end: Position) -> Expr {
+    let kind = ExprKind::Call {
+        callee: Box::new(callee),
+        type_args,
+        args,
+        optional,
+    };
+    Expr::new(NodeId::DUMMY, kind, Span::new(start, end))
+}
+
+fn member(object: Expr, property: Ident, optional: bool, start: Position, end: Position) -> Expr {
+    let kind = ExprKind::Member {
+        object: Box::new(object),
+        property,
+        optional,
+    };
+    Expr::new(NodeId::DUMMY, kind, Span::new(start, end))
+}
+
+fn index(object: Expr, index: Expr, optional: bool, start: Position, end: Position) -> Expr {
+    let kind = ExprKind::Index {
+        object: Box::new(object),
+        index: Box::new(index),
+        optional,
+    };
+    Expr::new(NodeId::DUMMY, kind, Span::new(start, end))
+}
+
+/// Unione o intersezione di `types`; un solo tipo resta com'è
+fn sequence(mut types: Vec<TypeExpr>, kind: fn(Vec<TypeExpr>) -> TypeExprKind, start: Position, end: Position) -> TypeExpr {
+    if types.len() == 1 {
+        return types.remove(0);
+    }
+    TypeExpr::new(NodeId::DUMMY, kind(types), Span::new(start, end))
+}
+
+/// Verifica che `yield` compaia solo nel corpo di un generatore. Il parser
+/// scritto a mano lo controlla durante l'analisi; la grammatica non distingue
+/// i corpi dei generatori, quindi il controllo avviene sull'AST.
+fn check_yield(program: &Program) -> Result<()> {
+    struct YieldCheck {
+        in_generator: bool,
+        error: Option<Error>,
+    }
+    
+    impl YieldCheck {
+        fn body(&mut self, in_generator: bool, block: &Block) {
+            let enclosing = std::mem::replace(&mut self.in_generator, in_generator);
+            self.visit_block(block);
+            self.in_generator = enclosing;
+        }
+    }
+    
+    impl<'ast> Visitor<'ast> for YieldCheck {
+        fn visit_function(&mut self, function: &'ast FunctionDecl) {
+            for decorator in &function.decorators {
+                self.visit_decorator(decorator);
+            }
+            for param in &function.params {
+                self.visit_param(param);
+            }
+            self.body(function.is_generator, &function.body);
+        }
+        
+        fn visit_lambda(&mut self, lambda: &'ast Lambda) {
+            for param in &lambda.params {
+                self.visit_param(param);
+            }
+            let enclosing = std::mem::replace(&mut self.in_generator, false);
+            match &lambda.body {
+                LambdaBody::Expr(expr) => self.visit_expr(expr),
+                LambdaBody::Block(block) => self.visit_block(block),
+            }
+            self.in_generator = enclosing;
+        }
+        
+        fn visit_class_member(&mut self, member: &'ast ClassMember) {
+            match &member.kind {
+                ClassMemberKind::Constructor { params, body } => {
+                    for param in params {
+                        self.visit_param(param);
+                    }
+                    self.body(false, body);
+                },
+                _ => visit::walk_class_member(self, member),
+            }
+        }
+        
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if matches!(expr.kind, ExprKind::Yield { .. }) && !self.in_generator && self.error.is_none() {
+                let start = expr.span.start;
+                let end = Position::new(start.line, start.column + "yield".len(), start.offset + "yield".len());
+                self.error = Some(Error::new(
+                    ErrorKind::SyntaxError,
+                    "`yield` è ammesso solo nel corpo di un generatore (`fn*`)".to_string(),
+                    Span::new(start, end),
+                ));
+            }
+            visit::walk_expr(self, expr);
+        }
+    }
+    
+    let mut check = YieldCheck {
+        in_generator: false,
+        error: None,
+    };
+    check.visit_program(program);
+    match check.error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Blocchi ```novascript della documentazione
+    fn doc_examples() -> Vec<(&'static str, String)> {
+        let docs = [
+            ("getting_started.md", include_str!("../../../docs/getting_started.md")),
+            ("language_specification.md", include_str!("../../../docs/language_specification.md")),
+            ("runtime.md", include_str!("../../../docs/runtime.md")),
+            ("standard_library.md", include_str!("../../../docs/standard_library.md")),
+            ("tools.md", include_str!("../../../docs/tools.md")),
+        ];
+        let mut examples = Vec::new();
+        for (name, text) in docs {
+            let mut lines = text.lines();
+            while let Some(line) = lines.next() {
+                if line.trim() == "```novascript" {
+                    let block: Vec<&str> = lines.by_ref().take_while(|l| l.trim() != "```").collect();
+                    examples.push((name, block.join("\n") + "\n"));
+                }
+            }
+        }
+        examples
+    }
+    
+    const SNIPPETS: &[&str] = &[
+        "let x = 1 + 2 * 3 ** 2 ** -1\n",
+        "const y: number | string[] = -a ** 2\n",
+        "a ?? b || c && d | e ^ f & g == h != i < j <= k > l >= m << n >> o + p - q * r / s % t\n",
+        "x is number + 1\ny = x as Foo<T> * 2\nz = a == b is string\nw = -x is T\nv = x is A | B\n",
+        "x = a < b > (c)\nf<T>(x)\na.b<Map<K, Array<V>>>(y)\nnew Box<T>(1).value\n",
+        "let {a, b: [c, d = 1], ...rest} = obj\nlet [x, _, ...ys] = list\nfor {k = 0} in xs\n  k\n",
+        "fn* gen(a, b = 2, ...rest): Iterator<number>\n  yield a\n  yield* rest\n  let z = yield\n",
+        "let f = (x: number, y): number -> x + y\nlet g = () ->\n  print(1)\n  return 2\nh((a) -> a * 2, [1, 2])\n",
+        "obj?.a?.[0]?.(1)?\nx = a?\nthis.x += 1\nsuper.f()\n",
+        "if a\n  b()\nelse if c\n  d()\nelse\n  e()\nwhile x\n  break\nfor [k, v] in map\n  continue\n",
+        "try\n  f()\ncatch (e: Error)\n  g()\ncatch e\n  h()\ncatch\n  i()\nfinally\n  j()\n",
+        "import \"side\"\nimport a from \"a\"\nimport b, * as c from \"b\"\nimport d, { e, f as g } from \"d\"\nimport * as h from \"h\"\nimport { i, } from \"i\"\n",
+        "export { a, b as c }\nexport { d } from \"d\"\nexport * from \"e\"\nexport * as f from \"f\"\nexport default fn main()\n  return 1\nexport const k = 1\nexport default 42\n",
+        "@logged\n@memo(10)\nfn f()\n  return 1\n@sealed class A\n@allow(\"unused\")\n\nlet x = 1\n",
+        "class A<T> extends B<T> implements C, D<T>\n  x: number\n  static count = 0\n  @field y = 2\n  constructor(x)\n    super(x)\n  fn get(): T\n    return this.x\n  static fn make<U>(u: U) -> A<U>\n    return new A(u)\nclass Marker\n",
+        "interface Shape extends Named\n  area(): number\n  label?: string\n  map<U>(f: (Shape) -> U) -> U\n  default(): void\n",
+        "enum Option<T>\n  Some(T)\n  None\ntype Pair<A, B extends C> = [A, B]\ntype F = (x: number, string) -> number | null\ntype O = { a: number, b?: string[], c }\ntype U = A & B | (C | D)[]?\n",
+        "let r = match v\n  case 0 | 1: \"small\"\n  case -1: \"neg\"\n  case Some(x) if x > 0:\n    print(x)\n    x\n  case Shape.Circle(r): r\n  case Color.Red: 1\n  case n is number: n\n  case is string: 0\n  case [a, ...rest]: a\n  case {kind: \"x\", ...others}: 2\n  case _: 3\n  default: 4\n",
+        "#pragma inline(true)\nlet o = { a: 1, \"b\": 2, c, ...d, default: 3 }\nlet arr = [...xs, 1, 2,]\nx = 0x1F + 0b101 + 1.5\n",
+        "print(\"a\\n\\t\\\"b\\\"\")\nnew Foo\nnew Foo.Bar(1)\nnew a[0]()\nlet e = new Error(\"x\")\n",
+        "fn f()\n  let g = (x) ->\n    return x\n  return g\n\n\nfn h()\n  return\n",
+        "let x = [\n  1,\n  2,\n]\nf(\n  a,\n  b\n)\n",
+        "items.map((x) -> x * 2).filter((x) -> x > 2)\nlet t = (a, b) -> match a\n  case 1: b\n  default: a\n",
+        "a.default(1)\na.new<T>(2)\na.class\nx = {a, b} \n[a, b] = [b, a]\n",
+        "class A\n  @a\n  @b(1)\n\n  x = 1\n\n  @c fn f()\n    return match x\n      case 1:\n        if y\n          z\n      default: 0\n\n@allow\n",
+        "",
+        "// solo un commento\n",
+        "fn f()\n  if a\n    if b\n      c\n  d\n// fine\n",
+        "f((x) ->\n  x\n, 2)\nlet y = [(a) ->\n  a\n]\n",
+        "let s = x >> 1 >= y\nlet t: Map<string, Array<number>>= m\n",
+        "x = !a.b[c](d)?.e\ny = ~-+z\n",
+        "fn f(cb: (x: number) -> number = (x) -> x)\n  return cb\nlet k = x as number | null\nlet m = x is Array<number>[]\n",
+        "y = (x as T).campo\nz = x as T < w\nf = a ?? ((x) -> x)\ng = ((x) -> x)(1)\nn = (new Foo)?\n",
+        "fn* g()\n  let y = 1 + (yield x)\n  f(yield, yield 1)\n",
+        "let s = `ciao ${user.name}, hai ${n + 1} messaggi`\nlet q = db.sql`select * from t where id = ${id}`\nlog(`solo testo`, `a${`b${c}`}`)\nlet m = `riga\n${f({ a: 1 })}\n`\n",
+    ];
+    
+    const INVALID: &[&str] = &[
+        "const x\n",
+        "fn f(...a, b)\n  a\n",
+        "fn f(...a = [])\n  a\n",
+        "let [...a, b] = c\n",
+        "let {...a, b} = c\n",
+        "let o = { default }\n",
+        "1 = 2\n",
+        "a?.b = 1\n",
+        "[a, b] += c\n",
+        "let [1, a] = b\n",
+        "let {a: Some(x)} = b\n",
+        "match x\n  case a = 1: a\n",
+        "match x\n  case _ is T: 1\n",
+        "try\n  f()\n",
+        "#define x(1)\n",
+        "@a\nlet x = 1\n",
+        "class A\n  static constructor()\n    1\n",
+        "class A\n  @d constructor()\n    1\n",
+        "class A\n  constructor()\n    1\n  constructor()\n    2\n",
+        "yield 1\n",
+        "fn* g()\n  let f = () -> yield 1\n",
+        "class A\n  constructor()\n    yield 1\n",
+        "fn g(@x)\n  1\n",
+        "let x = 0x\n",
+        "let = 1\n",
+        "if x\nf()\n",
+        "y = x as T.campo\n",
+        "y = a + (x) -> x\n",
+        "y = !(x) -> x\n",
+        "fn* g()\n  y = a || yield\n",
+        "y = new Box<T>\n",
+        "y = new Foo?.a\n",
+        "class A\n  static static = 1\n",
+    ];
+    
+    /// Richiede che entrambi i parser accettino `source` e producano lo stesso
+    /// AST: `compare` da solo non basta, perché considera concordi due rifiuti.
+    fn assert_same_ast(source: &str, context: &str) {
+        if let Err(error) = crate::parser::parse(source) {
+            panic!("{}: il parser a mano rifiuta il sorgente ({})\n{}", context, error.message, source);
+        }
+        if let Err(error) = parse(source) {
+            panic!("{}: la grammatica rifiuta il sorgente ({})\n{}", context, error.message, source);
+        }
+        if let Some(mismatch) = compare(source) {
+            panic!("{}: {}\n{}", context, mismatch, source);
+        }
+    }
+    
+    #[test]
+    fn test_agrees_with_hand_parser() {
+        for (index, source) in SNIPPETS.iter().enumerate() {
+            assert_same_ast(source, &format!("frammento {}", index));
+        }
+    }
+    
+    #[test]
+    fn test_agrees_on_documentation_examples() {
+        let examples = doc_examples();
+        assert!(examples.len() > 30);
+        for (file, source) in examples {
+            assert_same_ast(&source, &format!("esempio in {}", file));
+        }
+    }
+    
+    #[test]
+    fn test_rejects_what_hand_parser_rejects() {
+        for source in INVALID {
+            assert!(crate::parser::parse(source).is_err(), "il parser a mano accetta {:?}", source);
+            assert!(parse(source).is_err(), "la grammatica accetta {:?}", source);
+        }
+        
+        let error = parse("fn f()\n  let x = 1\n  yield x\n").unwrap_err();
+        assert_eq!(error.message, "`yield` è ammesso solo nel corpo di un generatore (`fn*`)");
+        assert_eq!(error.position().map(|p| (p.line, p.column)), Some((3, 3)));
+        assert_eq!(parse("let = 1\n").unwrap_err().message, "Atteso uno tra '[', '{', un nome, trovato '='");
+    }
+    
+    #[test]
+    fn test_reports_first_difference() {
+        let hand = serde_json::json!({ "body": [{ "kind": "Break" }, { "kind": { "Return": 1 } }] });
+        let spec = serde_json::json!({ "body": [{ "kind": "Break" }, { "kind": { "Return": 2 } }] });
+        let mismatch = first_difference(&hand, &spec, String::new()).unwrap();
+        assert_eq!(mismatch.path, "body[1].kind.Return");
+        assert_eq!((mismatch.hand.as_str(), mismatch.spec.as_str()), ("1", "2"));
+        assert_eq!(first_difference(&hand, &hand, String::new()), None);
+    }
+}
@@ -0,0 +1,679 @@
+use super::Parser;
+use crate::ast::{
+    Block, CatchClause, ElseBranch, ExportDecl, ForStmt, FunctionDecl, Ident, IfStmt, ImportDecl, ImportName,
+    ImportSpecifier, Param, Pragma, Stmt, StmtKind, StringLit, TryStmt, TypeAlias, TypeExpr, VarDecl,
+    VarKind, WhileStmt,
+};
+use crate::error::Result;
+use crate::lexer::{Span, TokenType};
+
+impl Parser {
+    /// Analizza una singola istruzione
+    pub(super) fn statement(&mut self) -> Result<Stmt> {
+        match self.peek().token_type {
+            TokenType::Let | TokenType::Const => self.var_declaration(),
+            TokenType::Fn => self.function_declaration(),
+            TokenType::Type => self.type_alias(),
+            TokenType::Class => self.class_declaration(),
+            TokenType::Interface => self.interface_declaration(),
+            TokenType::Enum => self.enum_declaration(),
+            TokenType::If => self.if_statement(),
+            TokenType::For => self.for_statement(),
+            TokenType::While => self.while_statement(),
+            TokenType::Break => self.keyword_statement(StmtKind::Break),
+            TokenType::Continue => self.keyword_statement(StmtKind::Continue),
+            TokenType::Return => self.return_statement(),
+            TokenType::Throw => self.throw_statement(),
+            TokenType::Try => self.try_statement(),
+            TokenType::Import => self.import_declaration(),
+            TokenType::Export => self.export_declaration(),
+            TokenType::Hash => self.pragma(),
+            TokenType::At => self.decorated_declaration(),
+            TokenType::Indent | TokenType::Dedent => Err(self.unexpected("un'istruzione")),
+            _ => self.expression_statement(),
+        }
+    }
+    
+    /// Blocco indentato che segue l'intestazione descritta da `after`
+    pub(super) fn block(&mut self, after: &str) -> Result<Block> {
+        self.expect(TokenType::Newline, &format!("fine riga dopo {}", after))?;
+        let indent = self.expect(TokenType::Indent, &format!("un blocco indentato dopo {}", after))?;
+        let stmts = self.statements();
+        self.expect(TokenType::Dedent, "fine del blocco")?;
+        
+        let span = match (stmts.first(), stmts.last()) {
+            (Some(first), Some(last)) => first.span.to(last.span),
+            _ => Span::point(indent.span.end),
+        };
+        Ok(Block { stmts, span })
+    }
+    
+    /// Fine di un'istruzione semplice: un a capo oppure la fine del blocco.
+    /// I token in eccesso vengono segnalati e saltati, ma l'istruzione resta.
+    pub(super) fn end_statement(&mut self) {
+        if self.matches(TokenType::Newline) || self.check(TokenType::Dedent) || self.is_at_end() {
+            return;
+        }
+        // Un'espressione che termina con un blocco, come `match`, ha già
+        // consumato la fine della riga
+        if self.current > 0 && self.tokens[self.current - 1].token_type == TokenType::Dedent {
+            return;
+        }
+        let error = self.unexpected("fine riga");
+        self.report(error);
+        self.skip_to_line_end();
+        self.matches(TokenType::Newline);
+    }
+    
+    /// Verifica se l'istruzione corrente termina al token successivo
+    fn at_statement_end(&self) -> bool {
+        matches!(
+            self.peek().token_type,
+            TokenType::Newline | TokenType::Dedent | TokenType::Eof
+        )
+    }
+    
+    fn keyword_statement(&mut self, kind: StmtKind) -> Result<Stmt> {
+        let keyword = self.advance();
+        self.end_statement();
+        Ok(Stmt::new(self.next_id(), kind, keyword.span))
+    }
+    
+    fn var_declaration(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        let kind = if keyword.token_type == TokenType::Const { VarKind::Const } else { VarKind::Let };
+        
+        let pattern = self.binding_pattern("il nome della variabile")?;
+        let ty = if self.matches(TokenType::Colon) { Some(self.type_expr()?) } else { None };
+        let init = if self.matches(TokenType::Equal) { Some(self.expression_or_error()) } else { None };
+        
+        let span = self.span_from(keyword.span);
+        if kind == VarKind::Const && init.is_none() {
+            return Err(self.error_at(span, "Una costante deve essere inizializzata".to_string()));
+        }
+        self.end_statement();
+        
+        let decl = VarDecl { kind, pattern, ty, init };
+        Ok(Stmt::new(self.next_id(), StmtKind::Var(decl), span))
+    }
+    
+    pub(super) fn identifier(&mut self, expected: &str) -> Result<Ident> {
+        let token = self.expect(TokenType::Identifier, expected)?;
+        Ok(Ident::new(&token.lexeme, token.span))
+    }
+    
+    fn function_declaration(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        let function = self.function()?;
+        Ok(Stmt::new(self.next_id(), StmtKind::Function(function), self.span_from(keyword.span)))
+    }
+    
+    /// Firma e corpo di una funzione, dopo `fn`
+    pub(super) fn function(&mut self) -> Result<FunctionDecl> {
+        let is_generator = self.matches(TokenType::Star);
+        let name = self.identifier("il nome della funzione")?;
+        let type_params = self.type_params()?;
+        self.expect(TokenType::LeftParen, "'(' dopo il nome della funzione")?;
+        let params = self.parameters()?;
+        let return_type = self.return_type()?;
+        let body = self.function_body(is_generator, |this| this.block("la firma della funzione"))?;
+        
+        Ok(FunctionDecl {
+            decorators: Vec::new(),
+            name,
+            type_params,
+            params,
+            return_type,
+            body,
+            is_generator,
+        })
+    }
+    
+    /// Corpo di una funzione analizzato con `parse`: `yield` è ammesso solo
+    /// se la funzione è un generatore, non in quelle annidate
+    pub(super) fn function_body<T>(&mut self, is_generator: bool, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let enclosing = std::mem::replace(&mut self.in_generator, is_generator);
+        let body = parse(self);
+        self.in_generator = enclosing;
+        body
+    }
+    
+    /// Parametri dopo `(`, fino a `)` inclusa
+    pub(super) fn parameters(&mut self) -> Result<Vec<Param>> {
+        let mut params: Vec<Param> = Vec::new();
+        
+        while !self.check(TokenType::RightParen) {
+            if let Some(rest) = params.last().filter(|p| p.rest) {
+                return Err(self.error_at(rest.span, "Il parametro `...` deve essere l'ultimo".to_string()));
+            }
+            
+            let start = self.peek().span;
+            let rest = self.matches(TokenType::DotDotDot);
+            let pattern = self.binding_pattern("il nome del parametro")?;
+            let ty = if self.matches(TokenType::Colon) { Some(self.type_expr()?) } else { None };
+            if rest && self.check(TokenType::Equal) {
+                return Err(self.error_at(
+                    self.peek().span,
+                    "Un parametro `...` non può avere un valore predefinito".to_string(),
+                ));
+            }
+            let default = if self.matches(TokenType::Equal) { Some(self.expression()?) } else { None };
+            params.push(Param {
+                pattern,
+                ty,
+                default,
+                rest,
+                span: self.span_from(start),
+            });
+            
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenType::RightParen, "',' o ')' dopo i parametri")?;
+        
+        Ok(params)
+    }
+    
+    /// Tipo di ritorno opzionale, introdotto da `->` oppure da `:`
+    pub(super) fn return_type(&mut self) -> Result<Option<TypeExpr>> {
+        if self.matches(TokenType::Arrow) || self.matches(TokenType::Colon) {
+            Ok(Some(self.type_expr()?))
+        } else {
+            Ok(None)
+        }
+    }
+    
+    fn if_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        let condition = self.expression_or_error();
+        let then_branch = self.block("la condizione di `if`")?;
+        
+        let else_branch = if self.matches(TokenType::Else) {
+            if self.check(TokenType::If) {
+                Some(ElseBranch::If(Box::new(self.if_statement()?)))
+            } else {
+                Some(ElseBranch::Block(self.block("`else`")?))
+            }
+        } else {
+            None
+        };
+        
+        let stmt = IfStmt {
+            condition,
+            then_branch,
+            else_branch,
+        };
+        Ok(Stmt::new(self.next_id(), StmtKind::If(stmt), self.span_from(keyword.span)))
+    }
+    
+    fn for_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        let pattern = self.binding_pattern("il nome della variabile del ciclo")?;
+        self.expect(TokenType::In, "`in` dopo la variabile del ciclo")?;
+        let iterable = self.expression_or_error();
+        let body = self.block("l'intestazione del `for`")?;
+        
+        let stmt = ForStmt {
+            pattern,
+            iterable,
+            body,
+        };
+        Ok(Stmt::new(self.next_id(), StmtKind::For(stmt), self.span_from(keyword.span)))
+    }
+    
+    fn while_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        let condition = self.expression_or_error();
+        let body = self.block("la condizione di `while`")?;
+        
+        let stmt = WhileStmt { condition, body };
+        Ok(Stmt::new(self.next_id(), StmtKind::While(stmt), self.span_from(keyword.span)))
+    }
+    
+    fn return_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        let value = if self.at_statement_end() { None } else { Some(self.expression_or_error()) };
+        let span = self.span_from(keyword.span);
+        self.end_statement();
+        
+        Ok(Stmt::new(self.next_id(), StmtKind::Return(value), span))
+    }
+    
+    fn throw_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        let value = self.expression_or_error();
+        let span = self.span_from(keyword.span);
+        self.end_statement();
+        
+        Ok(Stmt::new(self.next_id(), StmtKind::Throw(value), span))
+    }
+    
+    fn try_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        let body = self.block("`try`")?;
+        
+        let mut catches = Vec::new();
+        while self.check(TokenType::Catch) {
+            catches.push(self.catch_clause()?);
+        }
+        let finally = if self.matches(TokenType::Finally) {
+            Some(self.block("`finally`")?)
+        } else {
+            None
+        };
+        
+        if catches.is_empty() && finally.is_none() {
+            return Err(self.unexpected("`catch` o `finally` dopo il blocco `try`"));
+        }
+        
+        let stmt = TryStmt {
+            body,
+            catches,
+            finally,
+        };
+        Ok(Stmt::new(self.next_id(), StmtKind::Try(stmt), self.span_from(keyword.span)))
+    }
+    
+    /// `catch`, `catch e`, `catch e: Tipo` oppure `catch (e: Tipo)`
+    fn catch_clause(&mut self) -> Result<CatchClause> {
+        let keyword = self.advance();
+        let parenthesized = self.matches(TokenType::LeftParen);
+        
+        let (binding, ty) = if self.check(TokenType::Identifier) {
+            let name = self.identifier("il nome dell'errore")?;
+            let ty = if self.matches(TokenType::Colon) { Some(self.type_expr()?) } else { None };
+            (Some(name), ty)
+        } else {
+            (None, None)
+        };
+        if parenthesized {
+            self.expect(TokenType::RightParen, "')' dopo l'errore catturato")?;
+        }
+        let body = self.block("`catch`")?;
+        
+        Ok(CatchClause {
+            id: self.next_id(),
+            binding,
+            ty,
+            body,
+            span: self.span_from(keyword.span),
+        })
+    }
+    
+    /// `import nome from "modulo"` oppure `import { a, b as c } from "modulo"`
+    fn import_declaration(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        
+        let (mut default, mut namespace, mut named) = (None, None, Vec::new());
+        // `import "modulo"` esegue il modulo senza importare nomi
+        if !self.check(TokenType::StringLiteral) {
+            if self.check(TokenType::Identifier) {
+                let name = self.identifier("un nome")?;
+                default = Some(ImportName { id: self.next_id(), name });
+            }
+            if default.is_none() || self.matches(TokenType::Comma) {
+                if self.matches(TokenType::Star) {
+                    self.expect(TokenType::As, "`as` dopo `*`")?;
+                    let name = self.identifier("un nome dopo `as`")?;
+                    namespace = Some(ImportName { id: self.next_id(), name });
+                } else {
+                    self.expect(TokenType::LeftBrace, "un nome, '*' o '{' dopo `import`")?;
+                    named = self.specifiers()?;
+                }
+            }
+            self.expect(TokenType::From, "`from` dopo i nomi importati")?;
+        }
+        let source = self.string_literal("il percorso del modulo")?;
+        
+        let span = self.span_from(keyword.span);
+        self.end_statement();
+        
+        let decl = ImportDecl {
+            default,
+            namespace,
+            named,
+            source,
+        };
+        Ok(Stmt::new(self.next_id(), StmtKind::Import(decl), span))
+    }
+    
+    /// Elenco `a, b as c` dopo `{`, fino a `}` inclusa
+    fn specifiers(&mut self) -> Result<Vec<ImportSpecifier>> {
+        let mut specifiers = Vec::new();
+        
+        while !self.check(TokenType::RightBrace) {
+            let name = self.identifier("un nome")?;
+            let alias = if self.matches(TokenType::As) {
+                Some(self.identifier("un nome dopo `as`")?)
+            } else {
+                None
+            };
+            specifiers.push(ImportSpecifier {
+                id: self.next_id(),
+                span: self.span_from(name.span),
+                name,
+                alias,
+            });
+            
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenType::RightBrace, "',' o '}'")?;
+        
+        Ok(specifiers)
+    }
+    
+    fn export_declaration(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        
+        let decl = if self.matches(TokenType::LeftBrace) {
+            let specifiers = self.specifiers()?;
+            let source = if self.matches(TokenType::From) { Some(self.string_literal("il percorso del modulo")?) } else { None };
+            self.end_statement();
+            ExportDecl::Named { specifiers, source }
+        } else if self.matches(TokenType::Star) {
+            let alias = if self.matches(TokenType::As) { Some(self.identifier("un nome dopo `as`")?) } else { None };
+            self.expect(TokenType::From, "`from` dopo `export *`")?;
+            let source = self.string_literal("il percorso del modulo")?;
+            self.end_statement();
+            ExportDecl::All { alias, source }
+        } else if self.matches(TokenType::Default) {
+            let stmt = match self.peek().token_type {
+                TokenType::Fn | TokenType::Class => self.statement()?,
+                _ => self.expression_statement()?,
+            };
+            ExportDecl::Default(Box::new(stmt))
+        } else if matches!(
+            self.peek().token_type,
+            TokenType::Let
+                | TokenType::Const
+                | TokenType::Fn
+                | TokenType::Type
+                | TokenType::Class
+                | TokenType::Interface
+                | TokenType::Enum
+        ) {
+            ExportDecl::Declaration(Box::new(self.statement()?))
+        } else {
+            return Err(self.unexpected("una dichiarazione, `default`, '*' o '{' dopo `export`"));
+        };
+        
+        Ok(Stmt::new(self.next_id(), StmtKind::Export(decl), self.span_from(keyword.span)))
+    }
+    
+    /// `type Nome<T> = Tipo`
+    fn type_alias(&mut self) -> Result<Stmt> {
+        let keyword = self.advance();
+        let name = self.identifier("il nome del tipo")?;
+        let type_params = self.type_params()?;
+        self.expect(TokenType::Equal, "'=' dopo il nome del tipo")?;
+        let ty = self.type_expr()?;
+        
+        let span = self.span_from(keyword.span);
+        self.end_statement();
+        
+        let alias = TypeAlias { name, type_params, ty };
+        Ok(Stmt::new(self.next_id(), StmtKind::TypeAlias(alias), span))
+    }
+    
+    /// `#pragma nome(argomenti)`
+    fn pragma(&mut self) -> Result<Stmt> {
+        let hash = self.advance();
+        let directive = self.identifier("`pragma` dopo '#'")?;
+        if directive.name != "pragma" {
+            return Err(self.error_at(
+                directive.span,
+                format!("Direttiva sconosciuta `#{}`: l'unica direttiva è `#pragma`", directive.name),
+            ));
+        }
+        
+        let name = self.identifier("il nome della direttiva")?;
+        self.expect(TokenType::LeftParen, "'(' dopo il nome della direttiva")?;
+        let args = self.arguments()?;
+        
+        let span = self.span_from(hash.span);
+        self.end_statement();
+        
+        Ok(Stmt::new(self.next_id(), StmtKind::Pragma(Pragma { name, args }), span))
+    }
+    
+    fn expression_statement(&mut self) -> Result<Stmt> {
+        let expr = self.expression_or_error();
+        let span = expr.span;
+        self.end_statement();
+        
+        Ok(Stmt::new(self.next_id(), StmtKind::Expr(expr), span))
+    }
+    
+    pub(super) fn string_literal(&mut self, expected: &str) -> Result<StringLit> {
+        let token = self.expect(TokenType::StringLiteral, expected)?;
+        Ok(StringLit {
+            value: super::expr::unescape(&token.lexeme),
+            span: token.span,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::*;
+    use crate::parser::parse;
+    
+    fn parse_ok(source: &str) -> Vec<Stmt> {
+        parse(source).unwrap().body
+    }
+    
+    #[test]
+    fn test_function_and_control_flow() {
+        let source = "\
+fn classify(x: number) -> string
+  if x > 10
+    return \"grande\"
+  else if x > 5
+    return \"medio\"
+  else
+    return \"piccolo\"
+
+for i in range(10)
+  while i < 10
+    i = i + 1
+    continue
+";
+        let body = parse_ok(source);
+        assert_eq!(body.len(), 2);
+        
+        let StmtKind::Function(function) = &body[0].kind else {
+            panic!("attesa una funzione: {:?}", body[0].kind);
+        };
+        assert_eq!(function.name.name, "classify");
+        assert_eq!(function.params.len(), 1);
+        assert!(function.return_type.is_some());
+        
+        let StmtKind::If(stmt) = &function.body.stmts[0].kind else {
+            panic!("atteso un if");
+        };
+        let Some(ElseBranch::If(else_if)) = &stmt.else_branch else {
+            panic!("atteso un else if");
+        };
+        let StmtKind::If(else_if) = &else_if.kind else {
+            panic!("atteso un if");
+        };
+        assert!(matches!(else_if.else_branch, Some(ElseBranch::Block(_))));
+        
+        let StmtKind::For(stmt) = &body[1].kind else {
+            panic!("atteso un for");
+        };
+        let StmtKind::While(inner) = &stmt.body.stmts[0].kind else {
+            panic!("atteso un while");
+        };
+        assert_eq!(inner.body.stmts.len(), 2);
+        assert!(matches!(inner.body.stmts[1].kind, StmtKind::Continue));
+    }
+    
+    #[test]
+    fn test_declarations_and_modules() {
+        let source = "\
+import { add, subtract as sub } from \"./math\"
+import Http from \"http\"
+let age: number = 1
+const PI = 3.14159
+export fn double(n)
+  return n * 2
+export { age }
+#pragma optimize(\"speed\")
+";
+        let body = parse_ok(source);
+        assert_eq!(body.len(), 7);
+        
+        let StmtKind::Import(import) = &body[0].kind else {
+            panic!("atteso un import");
+        };
+        assert_eq!(import.source.value, "./math");
+        assert_eq!(import.named[1].alias.as_ref().unwrap().name, "sub");
+        
+        let StmtKind::Var(decl) = &body[3].kind else {
+            panic!("attesa una costante");
+        };
+        assert_eq!(decl.kind, VarKind::Const);
+        
+        let StmtKind::Export(ExportDecl::Declaration(inner)) = &body[4].kind else {
+            panic!("atteso un export");
+        };
+        assert!(matches!(inner.kind, StmtKind::Function(_)));
+        assert!(matches!(body[6].kind, StmtKind::Pragma(_)));
+    }
+    
+    #[test]
+    fn test_module_syntax() {
+        let source = "\
+import React, { useState as state } from \"react\"
+import * as path from \"path\"
+import Http, * as http from \"http\"
+import \"./polyfill\"
+export { add as sum, subtract }
+export { parse } from \"./parser\"
+export * from \"./utils\"
+export * as math from \"./math\"
+export default fn main()
+  return 0
+export default 42
+";
+        let body = parse_ok(source);
+        assert_eq!(body.len(), 10);
+        
+        let imports: Vec<&ImportDecl> = body[..4]
+            .iter()
+            .map(|stmt| match &stmt.kind {
+                StmtKind::Import(import) => import,
+                other => panic!("atteso un import: {:?}", other),
+            })
+            .collect();
+        assert_eq!(imports[0].default.as_ref().unwrap().name.name, "React");
+        assert_eq!(imports[0].named[0].renamed().name, "state");
+        assert_eq!(imports[1].namespace.as_ref().unwrap().name.name, "path");
+        assert!(imports[2].default.is_some() && imports[2].namespace.is_some());
+        assert!(imports[3].is_side_effect());
+        assert_eq!(imports[3].source.value, "./polyfill");
+        
+        let StmtKind::Export(ExportDecl::Named { specifiers, source: None }) = &body[4].kind else {
+            panic!("atteso un export con nome");
+        };
+        assert_eq!(specifiers[0].renamed().name, "sum");
+        assert_eq!(specifiers[1].renamed().name, "subtract");
+        assert!(matches!(&body[5].kind, StmtKind::Export(ExportDecl::Named { source: Some(s), .. }) if s.value == "./parser"));
+        assert!(matches!(&body[6].kind, StmtKind::Export(ExportDecl::All { alias: None, .. })));
+        assert!(matches!(&body[7].kind, StmtKind::Export(ExportDecl::All { alias: Some(a), .. }) if a.name == "math"));
+        
+        let StmtKind::Export(ExportDecl::Default(inner)) = &body[8].kind else {
+            panic!("atteso un export default");
+        };
+        assert!(matches!(inner.kind, StmtKind::Function(_)));
+        assert!(matches!(&body[9].kind, StmtKind::Export(ExportDecl::Default(inner)) if matches!(inner.kind, StmtKind::Expr(_))));
+        
+        let error = parse("import * from \"x\"").unwrap_err();
+        assert_eq!(error.message, "Atteso `as` dopo `*`, trovato 'from'");
+        let error = parse("export * as m").unwrap_err();
+        assert_eq!(error.message, "Atteso `from` dopo `export *`, trovato fine del file");
+    }
+    
+    #[test]
+    fn test_try_catch_finally() {
+        let source = "\
+try
+  let result = operazioneRischiosa()
+catch e: Error
+  print(e.message)
+catch
+  throw \"sconosciuto\"
+finally
+  pulizia()
+";
+        let body = parse_ok(source);
+        let StmtKind::Try(stmt) = &body[0].kind else {
+            panic!("atteso un try");
+        };
+        assert_eq!(stmt.catches.len(), 2);
+        assert_eq!(stmt.catches[0].binding.as_ref().unwrap().name, "e");
+        assert!(stmt.catches[1].binding.is_none());
+        assert!(stmt.finally.is_some());
+    }
+    
+    #[test]
+    fn test_generators() {
+        let source = "\
+fn* fibonacci(): Generator<number>
+  let a = 0
+  let b = 1
+  while true
+    yield a
+    [a, b] = [b, a + b]
+
+fn* all(xs)
+  yield* xs
+  let next = (x) -> x + 1
+  yield
+";
+        let body = parse_ok(source);
+        let StmtKind::Function(function) = &body[0].kind else {
+            panic!("attesa una funzione");
+        };
+        assert!(function.is_generator);
+        assert_eq!(function.name.name, "fibonacci");
+        
+        let StmtKind::Function(function) = &body[1].kind else {
+            panic!("attesa una funzione");
+        };
+        let StmtKind::Expr(expr) = &function.body.stmts[0].kind else {
+            panic!("atteso yield*");
+        };
+        assert!(matches!(&expr.kind, ExprKind::Yield { argument: Some(_), delegate: true }));
+        let StmtKind::Expr(expr) = &function.body.stmts[2].kind else {
+            panic!("atteso yield");
+        };
+        assert!(matches!(expr.kind, ExprKind::Yield { argument: None, delegate: false }));
+        
+        let message = "`yield` è ammesso solo nel corpo di un generatore (`fn*`)";
+        assert_eq!(parse("fn f()\n  yield 1\n").unwrap_err().message, message);
+        assert_eq!(parse("fn* f()\n  let g = () -> yield 1\n").unwrap_err().message, message);
+        
+        let StmtKind::Class(class) = &parse_ok("class Bag\n  fn* items()\n    yield 1\n")[0].kind else {
+            panic!("attesa una classe");
+        };
+        assert!(matches!(&class.members[0].kind, ClassMemberKind::Method(method) if method.is_generator));
+    }
+    
+    #[test]
+    fn test_statement_errors() {
+        let error = parse("if x\nfoo()").unwrap_err();
+        assert_eq!(error.message, "Atteso un blocco indentato dopo la condizione di `if`, trovato 'foo'");
+        
+        let error = parse("const x").unwrap_err();
+        assert_eq!(error.message, "Una costante deve essere inizializzata");
+        
+        let error = parse("let x = 1 2").unwrap_err();
+        assert_eq!(error.message, "Atteso fine riga, trovato '2'");
+        assert_eq!(error.span.unwrap().start.column, 11);
+    }
+}
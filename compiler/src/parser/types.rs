@@ -0,0 +1,404 @@
+//! Annotazioni di tipo, dalla forma che lega meno a quella che lega di più:
+//!
+//! | Forma                   | Esempio                          |
+//! |-------------------------|----------------------------------|
+//! | Unione                  | `string \| number`               |
+//! | Intersezione            | `A & B`                          |
+//! | Suffissi array e `?`    | `number[]`, `string?`            |
+//! | Nome, anche generico    | `Map<K, V>`                      |
+//! | Funzione                | `(x: number) -> number`          |
+//! | Tupla                   | `[string, number]`               |
+//! | Oggetto                 | `{ name: string, age?: number }` |
+//! | Tra parentesi           | `(A \| B)[]`                     |
+
+use super::Parser;
+use crate::ast::{FunctionTypeParam, Ident, ObjectTypeMember, TypeExpr, TypeExprKind, TypeParam};
+use crate::error::Result;
+use crate::lexer::{Token, TokenType};
+
+impl Parser {
+    /// Analizza un'annotazione di tipo
+    pub(super) fn type_expr(&mut self) -> Result<TypeExpr> {
+        self.type_sequence(TokenType::Pipe, Self::intersection_type, TypeExprKind::Union)
+    }
+    
+    fn intersection_type(&mut self) -> Result<TypeExpr> {
+        self.type_sequence(TokenType::Ampersand, Self::postfix_type, TypeExprKind::Intersection)
+    }
+    
+    /// Uno o più tipi separati da `separator`; con un solo tipo non crea nodi
+    fn type_sequence(
+        &mut self,
+        separator: TokenType,
+        operand: fn(&mut Self) -> Result<TypeExpr>,
+        kind: fn(Vec<TypeExpr>) -> TypeExprKind,
+    ) -> Result<TypeExpr> {
+        let first = operand(self)?;
+        if !self.check(separator) {
+            return Ok(first);
+        }
+        
+        let start = first.span;
+        let mut types = vec![first];
+        while self.matches(separator) {
+            types.push(operand(self)?);
+        }
+        let span = self.span_from(start);
+        
+        Ok(TypeExpr::new(self.next_id(), kind(types), span))
+    }
+    
+    /// Tipo seguito da suffissi `[]` e `?`, applicati da sinistra a destra
+    fn postfix_type(&mut self) -> Result<TypeExpr> {
+        let mut ty = self.primary_type()?;
+        
+        loop {
+            let start = ty.span;
+            let kind = if self.check(TokenType::LeftBracket) && self.peek_at(1).token_type == TokenType::RightBracket {
+                self.advance();
+                self.advance();
+                TypeExprKind::Array(Box::new(ty))
+            } else if self.matches(TokenType::Question) {
+                TypeExprKind::Nullable(Box::new(ty))
+            } else {
+                return Ok(ty);
+            };
+            let span = self.span_from(start);
+            ty = TypeExpr::new(self.next_id(), kind, span);
+        }
+    }
+    
+    fn primary_type(&mut self) -> Result<TypeExpr> {
+        let token = self.peek().clone();
+        
+        let kind = match token.token_type {
+            TokenType::Identifier | TokenType::Null => {
+                self.advance();
+                let args = if self.check(TokenType::Less) { self.name_arguments() } else { Vec::new() };
+                TypeExprKind::Named {
+                    name: Ident::new(&token.lexeme, token.span),
+                    args,
+                }
+            },
+            TokenType::LeftParen if self.at_function_type() => {
+                self.advance();
+                self.function_type()?
+            },
+            TokenType::LeftParen => {
+                self.advance();
+                let mut ty = self.type_expr()?;
+                self.expect(TokenType::RightParen, "')' dopo il tipo")?;
+                ty.span = self.span_from(token.span);
+                return Ok(ty);
+            },
+            TokenType::LeftBracket => {
+                self.advance();
+                TypeExprKind::Tuple(self.tuple_elements()?)
+            },
+            TokenType::LeftBrace => {
+                self.advance();
+                TypeExprKind::Object(self.object_type_members()?)
+            },
+            _ => return Err(self.unexpected("un tipo")),
+        };
+        
+        Ok(TypeExpr::new(self.next_id(), kind, self.span_from(token.span)))
+    }
+    
+    /// Verifica se la parentesi corrente apre i parametri di un tipo funzione,
+    /// cioè se la parentesi che la chiude è seguita da `->`
+    fn at_function_type(&self) -> bool {
+        let mut depth = 0usize;
+        let mut distance = 0;
+        
+        loop {
+            match self.peek_at(distance).token_type {
+                TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace => depth += 1,
+                TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return self.peek_at(distance + 1).token_type == TokenType::Arrow;
+                    }
+                },
+                TokenType::Eof => return false,
+                _ => {},
+            }
+            distance += 1;
+        }
+    }
+    
+    /// Parametri dopo `(` e tipo di ritorno di un tipo funzione
+    fn function_type(&mut self) -> Result<TypeExprKind> {
+        let mut params = Vec::new();
+        
+        while !self.check(TokenType::RightParen) {
+            let start = self.peek().span;
+            // Il nome del parametro è facoltativo: `(number) -> number`
+            let name = if self.check(TokenType::Identifier) && self.peek_at(1).token_type == TokenType::Colon {
+                let name = self.identifier("il nome del parametro")?;
+                self.advance();
+                Some(name)
+            } else {
+                None
+            };
+            let ty = self.type_expr()?;
+            params.push(FunctionTypeParam {
+                name,
+                ty,
+                span: self.span_from(start),
+            });
+            
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenType::RightParen, "',' o ')' dopo i parametri")?;
+        self.expect(TokenType::Arrow, "'->' dopo i parametri")?;
+        let return_type = self.type_expr()?;
+        
+        Ok(TypeExprKind::Function {
+            params,
+            return_type: Box::new(return_type),
+        })
+    }
+    
+    /// Elementi di una tupla dopo `[`, fino a `]` inclusa
+    fn tuple_elements(&mut self) -> Result<Vec<TypeExpr>> {
+        let mut elements = Vec::new();
+        
+        while !self.check(TokenType::RightBracket) {
+            elements.push(self.type_expr()?);
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenType::RightBracket, "',' o ']' nella tupla")?;
+        
+        Ok(elements)
+    }
+    
+    /// Membri di un tipo oggetto dopo `{`, fino a `}` inclusa
+    fn object_type_members(&mut self) -> Result<Vec<ObjectTypeMember>> {
+        let mut members = Vec::new();
+        
+        while !self.check(TokenType::RightBrace) {
+            let name = self.property_name()?;
+            let optional = self.matches(TokenType::Question);
+            let ty = if self.matches(TokenType::Colon) { Some(self.type_expr()?) } else { None };
+            members.push(ObjectTypeMember {
+                span: self.span_from(name.span),
+                name,
+                ty,
+                optional,
+            });
+            
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenType::RightBrace, "',' o '}' nel tipo oggetto")?;
+        
+        Ok(members)
+    }
+    
+    /// Argomenti di tipo `<A, B>`, a partire da `<`
+    pub(super) fn type_arguments(&mut self) -> Result<Vec<TypeExpr>> {
+        self.expect(TokenType::Less, "'<'")?;
+        
+        let mut args = Vec::new();
+        loop {
+            args.push(self.type_expr()?);
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.close_angle("',' o '>' dopo gli argomenti di tipo")?;
+        
+        Ok(args)
+    }
+    
+    /// Argomenti di tipo dopo un nome. Se la lista non si chiude, il `<` non
+    /// le appartiene e il parser torna indietro: in `x as T < y` il tipo è `T`
+    /// e `<` è un confronto.
+    fn name_arguments(&mut self) -> Vec<TypeExpr> {
+        let checkpoint = self.checkpoint();
+        self.type_arguments().unwrap_or_else(|_| {
+            self.rewind(checkpoint);
+            Vec::new()
+        })
+    }
+    
+    /// Parametri generici facoltativi `<T, U extends Vincolo>`
+    pub(super) fn type_params(&mut self) -> Result<Vec<TypeParam>> {
+        let mut params = Vec::new();
+        if !self.matches(TokenType::Less) {
+            return Ok(params);
+        }
+        
+        loop {
+            let name = self.identifier("il nome di un parametro di tipo")?;
+            let bound = if self.matches(TokenType::Extends) { Some(self.type_expr()?) } else { None };
+            params.push(TypeParam {
+                id: self.next_id(),
+                span: self.span_from(name.span),
+                name,
+                bound,
+            });
+            
+            if !self.matches(TokenType::Comma) {
+                break;
+            }
+        }
+        self.close_angle("',' o '>' dopo i parametri di tipo")?;
+        
+        Ok(params)
+    }
+    
+    /// Consuma il `>` che chiude una lista generica. Se il lexer lo ha unito al
+    /// token successivo (`>>` o `>=`), il token viene diviso e il resto rimane
+    /// da analizzare.
+    fn close_angle(&mut self, expected: &str) -> Result<Token> {
+        let rest = match self.peek().token_type {
+            TokenType::GreaterGreater => TokenType::Greater,
+            TokenType::GreaterEqual => TokenType::Equal,
+            _ => return self.expect(TokenType::Greater, expected),
+        };
+        self.split_greater(rest);
+        Ok(self.advance())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::*;
+    use crate::parser::parse;
+    
+    /// Tipo dichiarato nell'alias `type T = ...` di una riga
+    fn type_of(source: &str) -> TypeExpr {
+        let mut body = parse(source).unwrap().body;
+        match body.remove(0).kind {
+            StmtKind::TypeAlias(alias) => alias.ty,
+            other => panic!("atteso un alias: {:?}", other),
+        }
+    }
+    
+    /// Rappresentazione compatta di un tipo, con le parentesi esplicite
+    fn show(ty: &TypeExpr) -> String {
+        let list = |types: &[TypeExpr], separator: &str| {
+            types.iter().map(show).collect::<Vec<_>>().join(separator)
+        };
+        match &ty.kind {
+            TypeExprKind::Named { name, args } if args.is_empty() => name.name.clone(),
+            TypeExprKind::Named { name, args } => format!("{}<{}>", name.name, list(args, ", ")),
+            TypeExprKind::Array(inner) => format!("{}[]", show(inner)),
+            TypeExprKind::Nullable(inner) => format!("{}?", show(inner)),
+            TypeExprKind::Tuple(types) => format!("[{}]", list(types, ", ")),
+            TypeExprKind::Union(types) => format!("({})", list(types, " | ")),
+            TypeExprKind::Intersection(types) => format!("({})", list(types, " & ")),
+            TypeExprKind::Function { params, return_type } => {
+                let params: Vec<_> = params
+                    .iter()
+                    .map(|p| match &p.name {
+                        Some(name) => format!("{}: {}", name.name, show(&p.ty)),
+                        None => show(&p.ty),
+                    })
+                    .collect();
+                format!("(({}) -> {})", params.join(", "), show(return_type))
+            },
+            TypeExprKind::Object(members) => {
+                let members: Vec<_> = members
+                    .iter()
+                    .map(|m| {
+                        let optional = if m.optional { "?" } else { "" };
+                        match &m.ty {
+                            Some(ty) => format!("{}{}: {}", m.name.name, optional, show(ty)),
+                            None => format!("{}{}", m.name.name, optional),
+                        }
+                    })
+                    .collect();
+                format!("{{ {} }}", members.join(", "))
+            },
+        }
+    }
+    
+    #[test]
+    fn test_type_grammar() {
+        let cases = [
+            ("number[]", "number[]"),
+            ("string[][]", "string[][]"),
+            ("string?", "string?"),
+            ("A | B & C", "(A | (B & C))"),
+            ("(A | B)[]", "(A | B)[]"),
+            ("[string, number]", "[string, number]"),
+            ("Map<K, V>", "Map<K, V>"),
+            ("Map<string, Array<number>>", "Map<string, Array<number>>"),
+            ("Array<Array<Array<T>>>", "Array<Array<Array<T>>>"),
+            ("(x: number, number) -> number", "((x: number, number) -> number)"),
+            ("() -> A | B", "(() -> (A | B))"),
+            ("(number) -> (string) -> boolean", "((number) -> ((string) -> boolean))"),
+            ("{ name: string, age?: number, type }", "{ name: string, age?: number, type }"),
+            ("{ callback: (T) -> null }?", "{ callback: ((T) -> null) }?"),
+        ];
+        for (source, expected) in cases {
+            let ty = type_of(&format!("type T = {}", source));
+            assert_eq!(show(&ty), expected, "sorgente: {}", source);
+        }
+    }
+    
+    #[test]
+    fn test_generic_parameters() {
+        let body = parse("type Pair<A, B extends Comparable<B>> = [A, B]\nfn first<T>(items: T[]) -> T?\n  return items[0]\n")
+            .unwrap()
+            .body;
+        
+        let StmtKind::TypeAlias(alias) = &body[0].kind else {
+            panic!("atteso un alias");
+        };
+        assert_eq!(alias.type_params.len(), 2);
+        assert!(alias.type_params[0].bound.is_none());
+        assert_eq!(show(alias.type_params[1].bound.as_ref().unwrap()), "Comparable<B>");
+        
+        let StmtKind::Function(function) = &body[1].kind else {
+            panic!("attesa una funzione");
+        };
+        assert_eq!(function.type_params[0].name.name, "T");
+        assert_eq!(show(function.return_type.as_ref().unwrap()), "T?");
+    }
+    
+    #[test]
+    fn test_generics_versus_comparisons() {
+        let expr = |source: &str| match parse(source).unwrap().body.remove(0).kind {
+            StmtKind::Expr(expr) => expr,
+            StmtKind::Var(decl) => decl.init.unwrap(),
+            other => panic!("attesa un'espressione: {:?}", other),
+        };
+        
+        let call = expr("identity<Map<string, number[]>>(x)");
+        let ExprKind::Call { type_args, args, .. } = &call.kind else {
+            panic!("attesa una chiamata: {:?}", call.kind);
+        };
+        assert_eq!(show(&type_args[0]), "Map<string, number[]>");
+        assert_eq!(args.len(), 1);
+        
+        let created = expr("let b = new Box<number>(42)");
+        let ExprKind::New { type_args, .. } = &created.kind else {
+            panic!("atteso un new");
+        };
+        assert_eq!(show(&type_args[0]), "number");
+        
+        // Senza `(` dopo `>` la lista generica viene scartata
+        let comparison = expr("a < b && c > d");
+        assert!(matches!(comparison.kind, ExprKind::Binary { op: BinaryOp::And, .. }));
+        let shift = expr("a < b >> c");
+        let ExprKind::Binary { op: BinaryOp::Less, right, .. } = &shift.kind else {
+            panic!("atteso un confronto: {:?}", shift.kind);
+        };
+        assert!(matches!(right.kind, ExprKind::Binary { op: BinaryOp::Shr, .. }));
+        
+        let ty = match parse("let m: Map<K, V>= x").unwrap().body.remove(0).kind {
+            StmtKind::Var(decl) => decl.ty.unwrap(),
+            other => panic!("attesa una dichiarazione: {:?}", other),
+        };
+        assert_eq!(show(&ty), "Map<K, V>");
+    }
+}
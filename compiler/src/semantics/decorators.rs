@@ -0,0 +1,358 @@
+//! Controllo dei decoratori.
+//!
+//! I decoratori predefiniti sono interpretati dal compilatore; tutti gli altri
+//! sono funzioni dell'utente applicate alla dichiarazione quando viene
+//! definita (vedi [`crate::ir::apply_decorators`]). L'uso di una dichiarazione
+//! marcata `@deprecated` produce un avviso nel punto in cui compare: per
+//! funzioni e classi vale solo dove il nome è risolto nella dichiarazione
+//! deprecata, non per altri simboli con lo stesso nome.
+
+use std::collections::HashMap;
+
+use crate::ast::visit::{walk_expr, Visitor};
+use crate::ast::{
+    ClassMemberKind, Decorator, ElseBranch, ExportDecl, Expr, ExprKind, Literal, NodeId, Program, Stmt, StmtKind,
+};
+use crate::lint::{LintLevel, DEPRECATED};
+use crate::semantics::{Resolution, SymbolId};
+use crate::utils::diagnostics::{Diagnostic, Diagnostics};
+
+/// Decoratore interpretato dal compilatore
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinDecorator {
+    /// `@deprecated` o `@deprecated("messaggio")`: l'uso produce un avviso
+    Deprecated,
+    /// `@inline`: la funzione viene espansa nei punti di chiamata
+    Inline,
+    /// `@export`: la funzione o la classe è esportata dal modulo WebAssembly
+    Export,
+    /// `@test`: la funzione è un test eseguito da `nova test`
+    Test,
+    /// `@allow`, `@warn` e `@deny`: livello delle regole del linter
+    LintLevel(LintLevel),
+}
+
+impl BuiltinDecorator {
+    /// Decoratore predefinito con questo nome, se esiste
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "deprecated" => Some(BuiltinDecorator::Deprecated),
+            "inline" => Some(BuiltinDecorator::Inline),
+            "export" => Some(BuiltinDecorator::Export),
+            "test" => Some(BuiltinDecorator::Test),
+            _ => LintLevel::from_decorator(name).map(BuiltinDecorator::LintLevel),
+        }
+    }
+}
+
+/// Dichiarazione a cui sono applicati dei decoratori
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Function { params: usize },
+    Class,
+    Method,
+    Field,
+    /// Decoratori separati da una riga vuota, che valgono per il blocco
+    Block,
+}
+
+/// Controlla i decoratori del programma e segnala gli usi di dichiarazioni
+/// deprecate, riconosciuti con i nomi risolti da `resolution`
+pub fn check_decorators(program: &Program, resolution: &Resolution, diagnostics: &mut Diagnostics) {
+    let mut checker = DecoratorChecker {
+        diagnostics,
+        resolution,
+        deprecated_decls: HashMap::new(),
+        deprecated: HashMap::new(),
+        deprecated_methods: HashMap::new(),
+    };
+    checker.declarations(&program.body, true);
+    
+    let deprecated_decls = std::mem::take(&mut checker.deprecated_decls);
+    checker.deprecated = resolution
+        .symbols
+        .symbols()
+        .filter_map(|(id, symbol)| deprecated_decls.get(&symbol.node).map(|message| (id, message.clone())))
+        .collect();
+    
+    // Un metodo è deprecato solo se lo è in tutte le classi che lo dichiarano
+    let mut methods = HashMap::new();
+    collect_methods(&program.body, &mut methods);
+    checker.deprecated_methods.retain(|name, _| methods.get(name).is_some_and(|&all| all));
+    
+    checker.visit_program(program);
+}
+
+struct DecoratorChecker<'a> {
+    diagnostics: &'a mut Diagnostics,
+    resolution: &'a Resolution,
+    /// Istruzioni che dichiarano funzioni e classi deprecate, con il
+    /// messaggio facoltativo
+    deprecated_decls: HashMap<NodeId, Option<String>>,
+    /// Simboli delle funzioni e delle classi deprecate
+    deprecated: HashMap<SymbolId, Option<String>>,
+    /// Metodi deprecati, per nome: il tipo dell'oggetto non è noto
+    deprecated_methods: HashMap<String, Option<String>>,
+}
+
+impl DecoratorChecker<'_> {
+    fn error(&mut self, message: String, decorator: &Decorator) {
+        self.diagnostics.push(Diagnostic::error(message, decorator.span));
+    }
+    
+    /// Controlla i decoratori delle dichiarazioni, anche annidate
+    fn declarations(&mut self, stmts: &[Stmt], top_level: bool) {
+        for stmt in stmts {
+            self.declaration(stmt, top_level);
+        }
+    }
+    
+    fn declaration(&mut self, stmt: &Stmt, top_level: bool) {
+        match &stmt.kind {
+            StmtKind::Function(function) => {
+                let target = Target::Function { params: function.params.len() };
+                if let Some(message) = self.check(&function.decorators, target, top_level) {
+                    self.deprecated_decls.insert(stmt.id, message);
+                }
+                self.declarations(&function.body.stmts, false);
+            },
+            StmtKind::Class(class) => {
+                if let Some(message) = self.check(&class.decorators, Target::Class, top_level) {
+                    self.deprecated_decls.insert(stmt.id, message);
+                }
+                for member in &class.members {
+                    let target = match &member.kind {
+                        ClassMemberKind::Method(_) => Target::Method,
+                        _ => Target::Field,
+                    };
+                    let deprecated = self.check(&member.decorators, target, false);
+                    match &member.kind {
+                        ClassMemberKind::Method(method) => {
+                            if let Some(message) = deprecated {
+                                self.deprecated_methods.insert(method.name.name.clone(), message);
+                            }
+                            self.declarations(&method.body.stmts, false);
+                        },
+                        ClassMemberKind::Constructor { body, .. } => self.declarations(&body.stmts, false),
+                        ClassMemberKind::Field { .. } => {},
+                    }
+                }
+            },
+            StmtKind::Decorators(decorators) => {
+                self.check(decorators, Target::Block, top_level);
+            },
+            StmtKind::Export(ExportDecl::Declaration(inner) | ExportDecl::Default(inner)) => self.declaration(inner, top_level),
+            StmtKind::If(stmt) => {
+                self.declarations(&stmt.then_branch.stmts, false);
+                match &stmt.else_branch {
+                    Some(ElseBranch::If(else_if)) => self.declaration(else_if, false),
+                    Some(ElseBranch::Block(block)) => self.declarations(&block.stmts, false),
+                    None => {},
+                }
+            },
+            StmtKind::For(stmt) => self.declarations(&stmt.body.stmts, false),
+            StmtKind::While(stmt) => self.declarations(&stmt.body.stmts, false),
+            StmtKind::Try(stmt) => {
+                self.declarations(&stmt.body.stmts, false);
+                for catch in &stmt.catches {
+                    self.declarations(&catch.body.stmts, false);
+                }
+                if let Some(finally) = &stmt.finally {
+                    self.declarations(&finally.stmts, false);
+                }
+            },
+            _ => {},
+        }
+    }
+    
+    /// Controlla i decoratori di una dichiarazione. Restituisce il messaggio
+    /// di `@deprecated` se la dichiarazione è deprecata.
+    fn check(&mut self, decorators: &[Decorator], target: Target, top_level: bool) -> Option<Option<String>> {
+        let mut deprecated = None;
+        
+        for decorator in decorators {
+            let name = &decorator.name.name;
+            let args = decorator.args.as_deref().unwrap_or_default();
+            let Some(builtin) = BuiltinDecorator::from_name(name) else {
+                if target == Target::Block {
+                    self.error(format!("Il decoratore `@{}` deve precedere una dichiarazione", name), decorator);
+                }
+                continue;
+            };
+            
+            if target == Target::Block && !matches!(builtin, BuiltinDecorator::LintLevel(_)) {
+                self.error(format!("Il decoratore `@{}` deve precedere una dichiarazione", name), decorator);
+                continue;
+            }
+            
+            match builtin {
+                BuiltinDecorator::Deprecated => match args {
+                    [] => deprecated = Some(None),
+                    [Expr { kind: ExprKind::Literal(Literal::String(message)), .. }] => {
+                        deprecated = Some(Some(message.clone()));
+                    },
+                    _ => self.error("`@deprecated` accetta al più un messaggio tra virgolette".to_string(), decorator),
+                },
+                BuiltinDecorator::LintLevel(_) => {
+                    let selectors = args.iter().all(|arg| {
+                        matches!(arg.kind, ExprKind::Ident(_) | ExprKind::Literal(Literal::String(_)))
+                    });
+                    if args.is_empty() || !selectors {
+                        let message = format!("`@{}` richiede un elenco di regole, come `@{}(no_tabs)`", name, name);
+                        self.error(message, decorator);
+                    }
+                },
+                BuiltinDecorator::Inline | BuiltinDecorator::Export | BuiltinDecorator::Test => {
+                    if decorator.args.is_some() {
+                        self.error(format!("`@{}` non accetta argomenti", name), decorator);
+                    }
+                    
+                    let applies = match builtin {
+                        BuiltinDecorator::Inline => matches!(target, Target::Function { .. } | Target::Method),
+                        BuiltinDecorator::Export => top_level && matches!(target, Target::Function { .. } | Target::Class),
+                        _ => top_level && matches!(target, Target::Function { .. }),
+                    };
+                    if !applies {
+                        let message = match builtin {
+                            BuiltinDecorator::Inline => "`@inline` si applica solo a funzioni e metodi",
+                            BuiltinDecorator::Export => "`@export` si applica solo a funzioni e classi del modulo",
+                            _ => "`@test` si applica solo a funzioni del modulo",
+                        };
+                        self.error(message.to_string(), decorator);
+                    } else if builtin == BuiltinDecorator::Test && target != (Target::Function { params: 0 }) {
+                        self.error("Una funzione `@test` non può avere parametri".to_string(), decorator);
+                    }
+                },
+            }
+        }
+        
+        deprecated
+    }
+    
+    fn deprecated_use(&mut self, expr: &Expr) {
+        let (name, span, message) = match &expr.kind {
+            ExprKind::Ident(ident) => match self.resolution.uses.get(&expr.id).and_then(|id| self.deprecated.get(id)) {
+                Some(message) => (&ident.name, ident.span, message),
+                None => return,
+            },
+            ExprKind::Member { property, .. } => match self.deprecated_methods.get(&property.name) {
+                Some(message) => (&property.name, property.span, message),
+                None => return,
+            },
+            _ => return,
+        };
+        
+        let text = match message {
+            Some(message) => format!("`{}` è deprecato: {}", name, message),
+            None => format!("`{}` è deprecato", name),
+        };
+        self.diagnostics.push(DEPRECATED.diagnostic(text, span));
+    }
+}
+
+/// Per ogni metodo, se è deprecato in tutte le classi che lo dichiarano
+fn collect_methods(stmts: &[Stmt], methods: &mut HashMap<String, bool>) {
+    for stmt in stmts {
+        let stmt = match &stmt.kind {
+            StmtKind::Export(ExportDecl::Declaration(inner) | ExportDecl::Default(inner)) => inner,
+            _ => stmt,
+        };
+        if let StmtKind::Class(class) = &stmt.kind {
+            for member in &class.members {
+                if let ClassMemberKind::Method(method) = &member.kind {
+                    let deprecated = member.decorators.iter().any(|d| d.name.name == "deprecated");
+                    let all = methods.entry(method.name.name.clone()).or_insert(true);
+                    *all &= deprecated;
+                }
+            }
+        }
+    }
+}
+
+// Visita ogni espressione del programma, comprese quelle nei corpi delle
+// funzioni, delle lambda e negli argomenti dei decoratori
+impl<'ast> Visitor<'ast> for DecoratorChecker<'_> {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        self.deprecated_use(expr);
+        walk_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::semantics::resolve;
+    use crate::utils::diagnostics::Level;
+    
+    fn check(source: &str) -> Vec<Diagnostic> {
+        let program = parse(source).unwrap();
+        let (resolution, _) = resolve(&program);
+        let mut diagnostics = Diagnostics::new();
+        check_decorators(&program, &resolution, &mut diagnostics);
+        diagnostics.into_sorted_vec()
+    }
+    
+    fn messages(source: &str) -> Vec<String> {
+        check(source).into_iter().map(|d| d.message).collect()
+    }
+    
+    #[test]
+    fn test_deprecated_use_warns_at_call_site() {
+        let source = "\
+@deprecated(\"Usa newMethod() invece\")
+fn oldMethod()
+  return 1
+
+class Service
+  @deprecated
+  fn stop()
+    return 0
+
+let a = oldMethod()
+new Service().stop()
+
+fn other(oldMethod)
+  return oldMethod()
+";
+        let diagnostics = check(source);
+        // Il parametro `oldMethod` non è la funzione deprecata
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].message, "`oldMethod` è deprecato: Usa newMethod() invece");
+        assert_eq!(diagnostics[0].level, Level::Warning);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("deprecated"));
+        assert_eq!(diagnostics[0].span.unwrap().start.line, 10);
+        assert_eq!(diagnostics[1].message, "`stop` è deprecato");
+    }
+    
+    #[test]
+    fn test_builtin_decorator_errors() {
+        let source = "\
+@inline
+fn fast()
+  return 1
+
+@test
+fn checks(x)
+  return x
+
+class Box
+  @inline
+  value = 1
+
+@logged
+@custom(1, 2)
+fn traced()
+  return 0
+";
+        assert_eq!(messages(source), vec![
+            "Una funzione `@test` non può avere parametri",
+            "`@inline` si applica solo a funzioni e metodi",
+        ]);
+        
+        assert_eq!(messages("@deny(style)\n\nfn a()\n  return 1\n"), Vec::<String>::new());
+        assert_eq!(messages("@logged\n\nfn a()\n  return 1\n"), vec!["Il decoratore `@logged` deve precedere una dichiarazione"]);
+        assert_eq!(messages("@allow\nfn a()\n  return 1\n"), vec!["`@allow` richiede un elenco di regole, come `@allow(no_tabs)`"]);
+        assert_eq!(messages("@deprecated(1)\nfn a()\n  return 1\n"), vec!["`@deprecated` accetta al più un messaggio tra virgolette"]);
+    }
+}
@@ -0,0 +1,189 @@
+//! Controllo delle clausole `catch`.
+//!
+//! Le clausole di un `try` sono provate nell'ordine in cui sono scritte e
+//! l'eccezione è catturata dalla prima il cui tipo corrisponde. Una clausola
+//! dopo un `catch` senza tipo (o di tipo `any`) non può mai essere raggiunta,
+//! come una il cui tipo è lo stesso di una clausola precedente o un suo
+//! sottotipo: dopo `catch e: Error` una clausola per `TypeError`, che estende
+//! `Error`, non riceve mai un'eccezione.
+
+use crate::ast::visit::{walk_stmt, Visitor};
+use crate::ast::{CatchClause, Program, Stmt, StmtKind, TypeExprKind};
+use crate::utils::diagnostics::{Diagnostic, Diagnostics};
+
+use super::type_checker::TypeCheck;
+use super::types::Type;
+
+/// Segnala le clausole `catch` irraggiungibili. I tipi catturati sono quelli
+/// risolti dal controllo dei tipi.
+pub fn check_exceptions(program: &Program, types: &TypeCheck, diagnostics: &mut Diagnostics) {
+    ExceptionChecker { types, diagnostics }.visit_program(program);
+}
+
+/// `catch`, `catch e` oppure `catch e: any`: cattura qualsiasi valore
+pub fn is_catch_all(catch: &CatchClause) -> bool {
+    match &catch.ty {
+        None => true,
+        Some(ty) => matches!(&ty.kind, TypeExprKind::Named { name, args } if args.is_empty() && name.name == "any"),
+    }
+}
+
+struct ExceptionChecker<'a> {
+    types: &'a TypeCheck,
+    diagnostics: &'a mut Diagnostics,
+}
+
+impl ExceptionChecker<'_> {
+    fn check(&mut self, catches: &[CatchClause]) {
+        let mut caught: Vec<&Type> = Vec::new();
+        
+        for (index, catch) in catches.iter().enumerate() {
+            if index > 0 && is_catch_all(&catches[index - 1]) {
+                self.diagnostics.push(Diagnostic::error(
+                    "Clausola `catch` irraggiungibile: la precedente cattura qualsiasi valore".to_string(),
+                    catch.span,
+                ));
+                // Le clausole successive sono irraggiungibili per lo stesso motivo
+                return;
+            }
+            
+            // I tipi non risolti valgono `any` e sono già stati segnalati
+            let Some(ty) = self.types.catch_types.get(&catch.id).filter(|ty| **ty != Type::Any) else {
+                continue;
+            };
+            let context = &self.types.context;
+            match caught.iter().find(|earlier| context.is_subtype(ty, earlier)) {
+                Some(earlier) if *earlier == ty => self.diagnostics.push(Diagnostic::warning(
+                    format!("Clausola `catch` irraggiungibile: `{}` è già catturato da una clausola precedente", ty),
+                    catch.span,
+                )),
+                Some(earlier) => self.diagnostics.push(Diagnostic::warning(
+                    format!(
+                        "Clausola `catch` irraggiungibile: `{}` è un sottotipo di `{}`, già catturato da una clausola precedente",
+                        ty, earlier
+                    ),
+                    catch.span,
+                )),
+                None => caught.push(ty),
+            }
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for ExceptionChecker<'_> {
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        if let StmtKind::Try(try_stmt) = &stmt.kind {
+            self.check(&try_stmt.catches);
+        }
+        walk_stmt(self, stmt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::semantics::{check_types, resolve};
+    use crate::utils::diagnostics::Level;
+    
+    /// Classi delle eccezioni usate negli esempi
+    const CLASSES: &str = "\
+class NotFound extends Error
+  path: string
+class Timeout extends Error
+  seconds: number
+class IoError extends Error
+  code: number
+";
+    
+    fn check(source: &str) -> Vec<(Level, usize, String)> {
+        let program = parse(&format!("{}{}", CLASSES, source)).unwrap();
+        // Le funzioni chiamate negli esempi non sono dichiarate: contano solo i tipi
+        let (resolution, _) = resolve(&program);
+        let (types, _) = check_types(&program, &resolution);
+        let mut diagnostics = Diagnostics::new();
+        check_exceptions(&program, &types, &mut diagnostics);
+        let offset = CLASSES.lines().count();
+        diagnostics
+            .iter()
+            .map(|d| (d.level, d.span.unwrap().start.line - offset, d.message.clone()))
+            .collect()
+    }
+    
+    fn lines(diagnostics: Vec<(Level, usize, String)>) -> Vec<(Level, usize)> {
+        diagnostics.into_iter().map(|(level, line, _)| (level, line)).collect()
+    }
+    
+    #[test]
+    fn test_reports_unreachable_catch_clauses() {
+        let source = "\
+try
+  f()
+catch e: NotFound
+  a()
+catch e: Timeout
+  b()
+catch e: NotFound
+  c()
+catch e
+  d()
+catch e: IoError
+  e()
+catch
+  g()
+";
+        assert_eq!(lines(check(source)), vec![(Level::Warning, 7), (Level::Error, 11)]);
+    }
+    
+    #[test]
+    fn test_reports_clauses_shadowed_by_a_supertype() {
+        let source = "\
+class TypeError extends Error
+  name: string
+try
+  f()
+catch (e: Error)
+  a()
+catch (e: TypeError)
+  b()
+catch (e: NotFound | Timeout)
+  c()
+";
+        let diagnostics = check(source);
+        assert_eq!(lines(diagnostics.clone()), vec![(Level::Warning, 7), (Level::Warning, 9)]);
+        assert_eq!(
+            diagnostics[0].2,
+            "Clausola `catch` irraggiungibile: `TypeError` è un sottotipo di `Error`, già catturato da una clausola precedente"
+        );
+        
+        // Nell'ordine inverso entrambe le clausole possono essere raggiunte
+        let source = "\
+try
+  f()
+catch (e: NotFound)
+  a()
+catch (e: Error)
+  b()
+";
+        assert!(check(source).is_empty());
+    }
+    
+    #[test]
+    fn test_accepts_ordered_clauses() {
+        let source = "\
+fn load()
+  let f = () ->
+    try
+      read()
+    catch (e: IoError)
+      retry()
+    catch e: any
+      log(e)
+    finally
+      close()
+  return f
+";
+        assert!(check(source).is_empty());
+        assert_eq!(lines(check("try\n  f()\ncatch e: any\n  a()\ncatch\n  b()\n")), vec![(Level::Error, 5)]);
+    }
+}
@@ -0,0 +1,18 @@
+//! Analisi semantiche dell'AST.
+//!
+//! Le analisi leggono l'albero prodotto dal parser senza modificarlo e
+//! riportano i problemi come diagnostiche.
+
+pub mod decorators;
+pub mod exceptions;
+pub mod resolver;
+pub mod symbol_table;
+pub mod type_checker;
+pub mod types;
+
+pub use decorators::{check_decorators, BuiltinDecorator};
+pub use exceptions::check_exceptions;
+pub use resolver::{resolve, Resolution};
+pub use symbol_table::{ScopeId, ScopeKind, Symbol, SymbolId, SymbolKind, SymbolTable};
+pub use type_checker::{check_types, TypeCheck, TypeChecker};
+pub use types::{ClassType, FunctionType, Member, Type, TypeContext};
@@ -0,0 +1,646 @@
+//! Risoluzione dei nomi.
+//!
+//! Ogni uso di un nome viene collegato alla sua dichiarazione. Funzioni,
+//! classi, interfacce, alias, enum e import sono visibili in tutto lo scope
+//! che li contiene, anche prima della dichiarazione; `let` e `const` solo
+//! dalla fine della loro dichiarazione. Dentro funzioni, lambda e classi un
+//! nome dello scope esterno può essere usato prima della sua dichiarazione,
+//! perché quel codice viene eseguito più tardi.
+//!
+//! I nomi di tipo che non corrispondono a nessuna dichiarazione restano non
+//! risolti: possono essere tipi predefiniti come `number`, e vengono
+//! controllati insieme ai tipi.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::visit::{walk_expr, walk_property, walk_type, Visitor};
+use crate::ast::{
+    ClassDecl, ClassMember, ClassMemberKind, Decorator, ExportDecl, Expr, ExprKind, FunctionDecl, Ident,
+    InterfaceMemberKind, Lambda, LambdaBody, MatchArm, NodeId, Param, Pattern, PatternKind, Program, Property,
+    PropertyKind, Stmt, StmtKind, TypeExpr, TypeExprKind, TypeParam, VarKind,
+};
+use crate::error::{Error, ErrorKind};
+use crate::lexer::Position;
+use crate::semantics::BuiltinDecorator;
+use crate::utils::suggest::{import_position, NameSuggester};
+
+use super::symbol_table::{ScopeId, ScopeKind, Symbol, SymbolId, SymbolKind, SymbolTable};
+
+/// Risultato della risoluzione dei nomi di un programma
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolution {
+    pub symbols: SymbolTable,
+    /// Simbolo usato da ogni espressione `Ident`, da ogni tipo con nome
+    /// risolto e da ogni pattern che nomina una variante di enum. Le
+    /// proprietà abbreviate `{ nome }` non hanno un `NodeId`: finiscono in
+    /// `unnamed_uses`.
+    pub uses: HashMap<NodeId, SymbolId>,
+    /// Simboli usati da nodi senza `NodeId`: proprietà abbreviate `{ nome }` e
+    /// nomi di `export { nome }`
+    pub unnamed_uses: HashSet<SymbolId>,
+}
+
+impl Resolution {
+    /// Dichiarazione a cui si riferisce il nodo
+    pub fn resolved(&self, id: NodeId) -> Option<&Symbol> {
+        self.uses.get(&id).map(|&symbol| self.symbols.symbol(symbol))
+    }
+    
+    /// Simboli usati almeno una volta
+    pub fn used_symbols(&self) -> HashSet<SymbolId> {
+        self.uses.values().chain(&self.unnamed_uses).copied().collect()
+    }
+}
+
+/// Risolve i nomi del programma, restituendo anche gli errori per i nomi non
+/// definiti, usati prima della dichiarazione o dichiarati due volte
+pub fn resolve(program: &Program) -> (Resolution, Vec<Error>) {
+    let mut table = SymbolTable::new();
+    let scope = table.add_scope(ScopeKind::Module, None);
+    let mut resolver = Resolver {
+        table,
+        uses: HashMap::new(),
+        unnamed_uses: HashSet::new(),
+        errors: Vec::new(),
+        scope,
+        import_at: import_position(program),
+    };
+    resolver.statements(&program.body);
+    
+    let resolution = Resolution {
+        symbols: resolver.table,
+        uses: resolver.uses,
+        unnamed_uses: resolver.unnamed_uses,
+    };
+    (resolution, resolver.errors)
+}
+
+struct Resolver {
+    table: SymbolTable,
+    uses: HashMap<NodeId, SymbolId>,
+    unnamed_uses: HashSet<SymbolId>,
+    errors: Vec<Error>,
+    scope: ScopeId,
+    import_at: Position,
+}
+
+impl Resolver {
+    /// Esegue `f` in un nuovo scope dentro quello corrente
+    fn scoped(&mut self, kind: ScopeKind, f: impl FnOnce(&mut Self)) {
+        let outer = self.scope;
+        self.scope = self.table.add_scope(kind, Some(outer));
+        f(self);
+        self.scope = outer;
+    }
+    
+    /// Dichiara le istruzioni nello scope corrente e poi le risolve
+    fn statements(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.hoist(stmt);
+        }
+        for stmt in stmts {
+            self.visit_stmt(stmt);
+        }
+    }
+    
+    /// Dichiara i nomi introdotti da un'istruzione prima di risolvere il
+    /// resto dello scope
+    fn hoist(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Export(ExportDecl::Declaration(inner) | ExportDecl::Default(inner)) => self.hoist(inner),
+            StmtKind::Function(function) => self.declare(&function.name, SymbolKind::Function, stmt.id, None),
+            StmtKind::Class(class) => self.declare(&class.name, SymbolKind::Class, stmt.id, None),
+            StmtKind::Interface(interface) => self.declare(&interface.name, SymbolKind::Interface, stmt.id, None),
+            StmtKind::TypeAlias(alias) => self.declare(&alias.name, SymbolKind::TypeAlias, stmt.id, None),
+            StmtKind::Enum(decl) => {
+                self.declare(&decl.name, SymbolKind::Enum, stmt.id, None);
+                for variant in &decl.variants {
+                    self.declare(&variant.name, SymbolKind::Variant, variant.id, None);
+                }
+            },
+            StmtKind::Import(import) => {
+                for (node, name) in import.bindings() {
+                    self.declare(name, SymbolKind::Import, node, None);
+                }
+            },
+            StmtKind::Var(decl) => {
+                let kind = match decl.kind {
+                    VarKind::Let => SymbolKind::Let,
+                    VarKind::Const => SymbolKind::Const,
+                };
+                self.declare_pattern(&decl.pattern, kind, Some(stmt.span.end.offset), false);
+            },
+            _ => {},
+        }
+    }
+    
+    fn declare(&mut self, name: &Ident, kind: SymbolKind, node: NodeId, defined_at: Option<usize>) {
+        let (_, previous) = self.table.declare(Symbol {
+            name: name.name.clone(),
+            kind,
+            span: name.span,
+            scope: self.scope,
+            node,
+            defined_at,
+        });
+        if let Some(previous) = previous {
+            let line = self.table.symbol(previous).span.start.line;
+            self.errors.push(Error::new(
+                ErrorKind::SyntaxError,
+                format!("`{}` è già dichiarato alla riga {}", name, line),
+                name.span,
+            ));
+        }
+    }
+    
+    /// Dichiara le variabili introdotte da un pattern. Con `variants`, come nei
+    /// `case`, un nome che indica una variante di enum la confronta invece di
+    /// introdurre una variabile.
+    fn declare_pattern(&mut self, pattern: &Pattern, kind: SymbolKind, defined_at: Option<usize>, variants: bool) {
+        match &pattern.kind {
+            PatternKind::Binding(name) => {
+                let variant = self
+                    .table
+                    .lookup_value(self.scope, &name.name)
+                    .filter(|&id| variants && self.table.symbol(id).kind == SymbolKind::Variant);
+                match variant {
+                    Some(id) => {
+                        self.uses.insert(pattern.id, id);
+                    },
+                    None => self.declare(name, kind, pattern.id, defined_at),
+                }
+            },
+            PatternKind::Array(patterns) => {
+                for pattern in patterns {
+                    self.declare_pattern(pattern, kind, defined_at, variants);
+                }
+            },
+            PatternKind::Object { fields, rest } => {
+                for field in fields {
+                    self.declare_pattern(&field.pattern, kind, defined_at, variants);
+                }
+                if let Some(rest) = rest {
+                    self.declare(rest, kind, pattern.id, defined_at);
+                }
+            },
+            PatternKind::Rest(pattern) | PatternKind::Default { pattern, .. } => {
+                self.declare_pattern(pattern, kind, defined_at, variants);
+            },
+            PatternKind::Variant { enum_name, name, fields } => {
+                self.use_value(enum_name.as_ref().unwrap_or(name), pattern.id);
+                for field in fields {
+                    self.declare_pattern(field, kind, defined_at, variants);
+                }
+            },
+            PatternKind::TypeTest { binding, .. } => {
+                if let Some(binding) = binding {
+                    self.declare(binding, kind, pattern.id, defined_at);
+                }
+            },
+            // Ogni alternativa introduce gli stessi nomi: quelli della prima sono
+            // visibili nel resto del `case`, le altre sono dichiarate a parte
+            // solo per collegarne le varianti e segnalarne i nomi ripetuti
+            PatternKind::Or(alternatives) => {
+                if let Some((first, rest)) = alternatives.split_first() {
+                    self.declare_pattern(first, kind, defined_at, variants);
+                    for alternative in rest {
+                        self.scoped(ScopeKind::MatchArm, |this| {
+                            this.declare_pattern(alternative, kind, defined_at, variants);
+                        });
+                    }
+                }
+            },
+            PatternKind::Wildcard | PatternKind::Literal(_) => {},
+        }
+    }
+    
+    /// Collega l'uso di un valore alla sua dichiarazione
+    fn use_value(&mut self, name: &Ident, node: NodeId) {
+        let Some(id) = self.table.lookup_value(self.scope, &name.name) else {
+            self.undefined(name);
+            return;
+        };
+        
+        let symbol = self.table.symbol(id);
+        let early = symbol.defined_at.is_some_and(|defined_at| name.span.start.offset < defined_at);
+        if early && !self.crosses_deferred_scope(symbol.scope) {
+            self.errors.push(Error::new(
+                ErrorKind::UndefinedSymbol,
+                format!("`{}` è usato prima della sua dichiarazione", name),
+                name.span,
+            ));
+        }
+        self.uses.insert(node, id);
+    }
+    
+    /// Verifica se tra lo scope corrente e `target` c'è il corpo di una
+    /// funzione o di una classe
+    fn crosses_deferred_scope(&self, target: ScopeId) -> bool {
+        let mut scope = self.scope;
+        while scope != target {
+            let current = self.table.scope(scope);
+            if current.kind.is_deferred() {
+                return true;
+            }
+            match current.parent {
+                Some(parent) => scope = parent,
+                None => return false,
+            }
+        }
+        false
+    }
+    
+    fn undefined(&mut self, name: &Ident) {
+        let mut suggester = NameSuggester::with_defaults();
+        suggester.add_scope_names(self.table.visible_values(self.scope));
+        self.errors.push(suggester.undefined_symbol(&name.name, name.span, self.import_at));
+    }
+    
+    fn type_params(&mut self, params: &[TypeParam]) {
+        for param in params {
+            if let Some(bound) = &param.bound {
+                self.visit_type(bound);
+            }
+            self.declare(&param.name, SymbolKind::TypeParam, param.id, None);
+        }
+    }
+    
+    /// Parametri di una funzione: ogni valore predefinito vede i parametri precedenti
+    fn params(&mut self, params: &[Param]) {
+        for param in params {
+            if let Some(ty) = &param.ty {
+                self.visit_type(ty);
+            }
+            if let Some(default) = &param.default {
+                self.visit_expr(default);
+            }
+            self.declare_pattern(&param.pattern, SymbolKind::Param, None, false);
+            self.visit_pattern(&param.pattern);
+        }
+    }
+    
+    fn decorators(&mut self, decorators: &[Decorator]) {
+        for decorator in decorators {
+            self.visit_decorator(decorator);
+        }
+    }
+    
+    fn class(&mut self, class: &ClassDecl) {
+        self.decorators(&class.decorators);
+        self.scoped(ScopeKind::Class, |this| {
+            this.type_params(&class.type_params);
+            for ty in class.extends.iter().chain(&class.implements) {
+                this.visit_type(ty);
+            }
+            for member in &class.members {
+                this.visit_class_member(member);
+            }
+        });
+    }
+    
+    /// Nomi esportati con `export { a, b as c }` da questo modulo
+    fn exported_names(&mut self, specifiers: &[crate::ast::ImportSpecifier]) {
+        for specifier in specifiers {
+            let name = &specifier.name;
+            let value = self.table.lookup_value(self.scope, &name.name);
+            let ty = self.table.lookup_type(self.scope, &name.name);
+            if value.is_none() && ty.is_none() {
+                self.undefined(name);
+            }
+            self.unnamed_uses.extend(value.into_iter().chain(ty));
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for Resolver {
+    fn visit_block(&mut self, block: &'ast crate::ast::Block) {
+        self.scoped(ScopeKind::Block, |this| this.statements(&block.stmts));
+    }
+    
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        match &stmt.kind {
+            StmtKind::Expr(expr) | StmtKind::Throw(expr) | StmtKind::Return(Some(expr)) => self.visit_expr(expr),
+            StmtKind::Var(decl) => {
+                if let Some(ty) = &decl.ty {
+                    self.visit_type(ty);
+                }
+                if let Some(init) = &decl.init {
+                    self.visit_expr(init);
+                }
+                self.visit_pattern(&decl.pattern);
+            },
+            StmtKind::Function(function) => self.visit_function(function),
+            StmtKind::Class(class) => self.class(class),
+            StmtKind::Interface(interface) => self.scoped(ScopeKind::Generic, |this| {
+                this.type_params(&interface.type_params);
+                for ty in &interface.extends {
+                    this.visit_type(ty);
+                }
+                for member in &interface.members {
+                    match &member.kind {
+                        InterfaceMemberKind::Property(ty) => this.visit_type(ty),
+                        InterfaceMemberKind::Method { type_params, params, return_type } => {
+                            this.scoped(ScopeKind::Generic, |this| {
+                                this.type_params(type_params);
+                                for ty in params.iter().filter_map(|p| p.ty.as_ref()).chain(return_type) {
+                                    this.visit_type(ty);
+                                }
+                            });
+                        },
+                    }
+                }
+            }),
+            StmtKind::TypeAlias(alias) => self.scoped(ScopeKind::Generic, |this| {
+                this.type_params(&alias.type_params);
+                this.visit_type(&alias.ty);
+            }),
+            StmtKind::Enum(decl) => self.scoped(ScopeKind::Generic, |this| {
+                this.type_params(&decl.type_params);
+                for ty in decl.variants.iter().flat_map(|variant| &variant.payload) {
+                    this.visit_type(ty);
+                }
+            }),
+            StmtKind::If(stmt) => {
+                self.visit_expr(&stmt.condition);
+                self.visit_block(&stmt.then_branch);
+                match &stmt.else_branch {
+                    Some(crate::ast::ElseBranch::If(else_if)) => self.visit_stmt(else_if),
+                    Some(crate::ast::ElseBranch::Block(block)) => self.visit_block(block),
+                    None => {},
+                }
+            },
+            StmtKind::For(stmt) => {
+                self.visit_expr(&stmt.iterable);
+                self.scoped(ScopeKind::Block, |this| {
+                    this.declare_pattern(&stmt.pattern, SymbolKind::Binding, None, false);
+                    this.visit_pattern(&stmt.pattern);
+                    this.statements(&stmt.body.stmts);
+                });
+            },
+            StmtKind::While(stmt) => {
+                self.visit_expr(&stmt.condition);
+                self.visit_block(&stmt.body);
+            },
+            StmtKind::Try(stmt) => {
+                self.visit_block(&stmt.body);
+                for catch in &stmt.catches {
+                    self.scoped(ScopeKind::Block, |this| {
+                        if let Some(ty) = &catch.ty {
+                            this.visit_type(ty);
+                        }
+                        if let Some(binding) = &catch.binding {
+                            this.declare(binding, SymbolKind::Binding, catch.id, None);
+                        }
+                        this.statements(&catch.body.stmts);
+                    });
+                }
+                if let Some(finally) = &stmt.finally {
+                    self.visit_block(finally);
+                }
+            },
+            StmtKind::Export(ExportDecl::Declaration(inner) | ExportDecl::Default(inner)) => self.visit_stmt(inner),
+            StmtKind::Export(ExportDecl::Named { specifiers, source: None }) => self.exported_names(specifiers),
+            StmtKind::Decorators(decorators) => self.decorators(decorators),
+            // Gli argomenti delle direttive non sono espressioni del programma
+            StmtKind::Pragma(_)
+            | StmtKind::Import(_)
+            | StmtKind::Export(_)
+            | StmtKind::Return(None)
+            | StmtKind::Break
+            | StmtKind::Continue
+            | StmtKind::Error => {},
+        }
+    }
+    
+    fn visit_function(&mut self, function: &'ast FunctionDecl) {
+        self.decorators(&function.decorators);
+        self.scoped(ScopeKind::Function, |this| {
+            this.type_params(&function.type_params);
+            this.params(&function.params);
+            if let Some(ty) = &function.return_type {
+                this.visit_type(ty);
+            }
+            this.statements(&function.body.stmts);
+        });
+    }
+    
+    fn visit_decorator(&mut self, decorator: &'ast Decorator) {
+        if BuiltinDecorator::from_name(&decorator.name.name).is_none()
+            && self.table.lookup_value(self.scope, &decorator.name.name).is_none()
+        {
+            self.undefined(&decorator.name);
+        }
+        // Gli argomenti dei decoratori predefiniti sono nomi di regole o messaggi
+        if BuiltinDecorator::from_name(&decorator.name.name).is_none() {
+            for arg in decorator.args.iter().flatten() {
+                self.visit_expr(arg);
+            }
+        }
+    }
+    
+    fn visit_class_member(&mut self, member: &'ast ClassMember) {
+        self.decorators(&member.decorators);
+        match &member.kind {
+            ClassMemberKind::Field { ty, init, .. } => {
+                if let Some(ty) = ty {
+                    self.visit_type(ty);
+                }
+                if let Some(init) = init {
+                    self.visit_expr(init);
+                }
+            },
+            ClassMemberKind::Constructor { params, body } => self.scoped(ScopeKind::Function, |this| {
+                this.params(params);
+                this.statements(&body.stmts);
+            }),
+            ClassMemberKind::Method(method) => self.visit_function(method),
+        }
+    }
+    
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match &expr.kind {
+            ExprKind::Ident(name) => self.use_value(name, expr.id),
+            _ => walk_expr(self, expr),
+        }
+    }
+    
+    fn visit_property(&mut self, property: &'ast Property) {
+        match &property.kind {
+            PropertyKind::Shorthand(name) => match self.table.lookup_value(self.scope, &name.name) {
+                Some(id) => {
+                    self.unnamed_uses.insert(id);
+                },
+                None => self.undefined(name),
+            },
+            _ => walk_property(self, property),
+        }
+    }
+    
+    fn visit_lambda(&mut self, lambda: &'ast Lambda) {
+        self.scoped(ScopeKind::Function, |this| {
+            this.params(&lambda.params);
+            if let Some(ty) = &lambda.return_type {
+                this.visit_type(ty);
+            }
+            match &lambda.body {
+                LambdaBody::Expr(body) => this.visit_expr(body),
+                LambdaBody::Block(body) => this.statements(&body.stmts),
+            }
+        });
+    }
+    
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        self.scoped(ScopeKind::MatchArm, |this| {
+            this.declare_pattern(&arm.pattern, SymbolKind::Binding, None, true);
+            this.visit_pattern(&arm.pattern);
+            if let Some(guard) = &arm.guard {
+                this.visit_expr(guard);
+            }
+            this.statements(&arm.body.stmts);
+        });
+    }
+    
+    fn visit_type(&mut self, ty: &'ast TypeExpr) {
+        if let TypeExprKind::Named { name, .. } = &ty.kind {
+            if let Some(id) = self.table.lookup_type(self.scope, &name.name) {
+                self.uses.insert(ty.id, id);
+            }
+        }
+        walk_type(self, ty);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::visit::walk_program;
+    use crate::parser::parse;
+    
+    /// Riga della dichiarazione a cui porta ogni uso di `name`, nell'ordine del sorgente
+    fn uses_of(source: &str, name: &str) -> Vec<Option<usize>> {
+        struct Uses<'a> {
+            name: &'a str,
+            ids: Vec<NodeId>,
+        }
+        
+        impl<'ast> Visitor<'ast> for Uses<'_> {
+            fn visit_expr(&mut self, expr: &'ast Expr) {
+                if matches!(&expr.kind, ExprKind::Ident(ident) if ident.name == self.name) {
+                    self.ids.push(expr.id);
+                }
+                walk_expr(self, expr);
+            }
+        }
+        
+        let program = parse(source).unwrap();
+        let (resolution, errors) = resolve(&program);
+        assert!(errors.is_empty(), "{:?}", errors);
+        
+        let mut uses = Uses { name, ids: Vec::new() };
+        walk_program(&mut uses, &program);
+        uses.ids.iter().map(|&id| resolution.resolved(id).map(|symbol| symbol.span.start.line)).collect()
+    }
+    
+    fn errors(source: &str) -> Vec<String> {
+        let (_, errors) = resolve(&parse(source).unwrap());
+        errors.iter().map(|error| format!("{} {}", error.span.unwrap().start.line, error.message)).collect()
+    }
+    
+    #[test]
+    fn test_uses_resolve_to_the_innermost_declaration() {
+        let source = "\
+let x = 1
+fn f(x)
+  if x > 0
+    let x = 2
+    return x
+  return x
+f(x)
+";
+        assert_eq!(uses_of(source, "x"), vec![Some(2), Some(4), Some(2), Some(1)]);
+        assert_eq!(uses_of(source, "f"), vec![Some(2)]);
+    }
+    
+    #[test]
+    fn test_declarations_are_hoisted_but_variables_are_not() {
+        assert!(errors("main()\nfn main()\n  return helper()\nfn helper()\n  return limit\nlet limit = 3\n").is_empty());
+        assert_eq!(errors("let a = b\nlet b = 1\nlet c = c\n"), vec![
+            "1 `b` è usato prima della sua dichiarazione",
+            "3 `c` è usato prima della sua dichiarazione",
+        ]);
+        assert_eq!(errors("fn f()\n  return 1\nlet f = 2\n"), vec!["3 `f` è già dichiarato alla riga 1"]);
+    }
+    
+    #[test]
+    fn test_reports_undefined_names() {
+        let (_, errors) = resolve(&parse("let count = 1\nprint(cout)\n").unwrap());
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].message.starts_with("`print` non trovato; è esportato da"));
+        assert!(errors[1].message.starts_with("`cout` non trovato"));
+        let span = errors[1].span.unwrap();
+        assert_eq!((span.start.line, span.start.column, span.end.column), (2, 7, 11));
+    }
+    
+    #[test]
+    fn test_match_patterns_distinguish_variants_from_bindings() {
+        let source = "\
+enum Shape
+  Circle(number)
+  Empty
+fn area(shape)
+  return match shape
+    case Shape.Circle(r): r * r
+    case Empty: 0
+    case other: other
+";
+        let program = parse(source).unwrap();
+        let (resolution, errors) = resolve(&program);
+        assert!(errors.is_empty(), "{:?}", errors);
+        
+        let kinds: Vec<SymbolKind> = resolution.symbols.symbols().map(|(_, symbol)| symbol.kind).collect();
+        assert_eq!(kinds, vec![
+            SymbolKind::Enum,
+            SymbolKind::Variant,
+            SymbolKind::Variant,
+            SymbolKind::Function,
+            SymbolKind::Param,
+            SymbolKind::Binding,
+            SymbolKind::Binding,
+        ]);
+        assert_eq!(uses_of(source, "r"), vec![Some(6), Some(6)]);
+        assert_eq!(uses_of(source, "other"), vec![Some(8)]);
+    }
+    
+    #[test]
+    fn test_type_params_are_declared_by_their_node() {
+        let program = parse("fn first<T>(items: T[]): T\n  return items[0]\n").unwrap();
+        let (resolution, errors) = resolve(&program);
+        assert!(errors.is_empty(), "{:?}", errors);
+        
+        let StmtKind::Function(function) = &program.body[0].kind else {
+            panic!("attesa una funzione");
+        };
+        let (id, symbol) = resolution.symbols.symbols().find(|(_, s)| s.kind == SymbolKind::TypeParam).unwrap();
+        assert_eq!(symbol.node, function.type_params[0].id);
+        assert_eq!(resolution.uses.values().filter(|&&used| used == id).count(), 2);
+    }    
+    #[test]
+    fn test_import_names_are_declared_by_their_node() {
+        let program = parse("import Http, { get, post as send } from \"http\"\nget(Http)\nsend(1)\n").unwrap();
+        let (resolution, errors) = resolve(&program);
+        assert!(errors.is_empty(), "{:?}", errors);
+        
+        let StmtKind::Import(decl) = &program.body[0].kind else {
+            panic!("atteso un import");
+        };
+        let nodes: Vec<NodeId> = decl.bindings().map(|(id, _)| id).collect();
+        let declared: Vec<NodeId> = resolution
+            .symbols
+            .symbols()
+            .filter(|(_, symbol)| symbol.kind == SymbolKind::Import)
+            .map(|(_, symbol)| symbol.node)
+            .collect();
+        assert_eq!(declared, nodes);
+        assert!(!nodes.contains(&program.body[0].id));
+    }
+}
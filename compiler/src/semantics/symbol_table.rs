@@ -0,0 +1,194 @@
+//! Tabelle dei simboli.
+//!
+//! Ogni scope ha due spazi di nomi: i valori (variabili, funzioni, varianti
+//! di enum) e i tipi (interfacce, alias, parametri di tipo). Classi ed enum
+//! stanno in entrambi. Uno scope interno può nascondere un nome di uno scope
+//! esterno, mentre lo stesso nome non può essere dichiarato due volte nello
+//! stesso scope.
+
+use std::collections::HashMap;
+
+use crate::ast::NodeId;
+use crate::lexer::Span;
+
+/// Indice di un simbolo nella tabella
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SymbolId(pub u32);
+
+/// Indice di uno scope nella tabella
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScopeId(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// Dichiarazioni di primo livello del file
+    Module,
+    /// Parametri e corpo di una funzione, di un metodo, di un costruttore o
+    /// di una lambda
+    Function,
+    /// Corpo di `if`, `while`, `for`, `try`, `catch` e `finally`
+    Block,
+    /// Parametri di tipo e membri di una classe
+    Class,
+    /// Parametri di tipo di un'interfaccia, di un alias o di un enum
+    Generic,
+    /// Variabili del pattern, guardia e corpo di un `case`
+    MatchArm,
+}
+
+impl ScopeKind {
+    /// Il codice di questo scope non viene eseguito nel punto in cui è
+    /// scritto, ma quando la funzione viene chiamata o la classe istanziata
+    pub fn is_deferred(self) -> bool {
+        matches!(self, ScopeKind::Function | ScopeKind::Class)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Let,
+    Const,
+    Param,
+    /// Variabile introdotta da un pattern di `for`, di `case` o da `catch`
+    Binding,
+    Function,
+    Class,
+    Interface,
+    TypeAlias,
+    Enum,
+    /// Variante di un enum, visibile anche senza il nome dell'enum
+    Variant,
+    TypeParam,
+    Import,
+}
+
+impl SymbolKind {
+    pub fn is_value(self) -> bool {
+        !matches!(self, SymbolKind::Interface | SymbolKind::TypeAlias | SymbolKind::TypeParam)
+    }
+    
+    pub fn is_type(self) -> bool {
+        matches!(
+            self,
+            SymbolKind::Class
+                | SymbolKind::Interface
+                | SymbolKind::TypeAlias
+                | SymbolKind::Enum
+                | SymbolKind::TypeParam
+                | SymbolKind::Import
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Intervallo del nome nella dichiarazione
+    pub span: Span,
+    pub scope: ScopeId,
+    /// Nodo che dichiara il simbolo: l'istruzione, il pattern, il nome
+    /// importato, la variante, la clausola `catch` o il parametro di tipo.
+    /// Ogni simbolo ha un nodo diverso.
+    pub node: NodeId,
+    /// Offset da cui il simbolo può essere usato nel suo scope: la fine della
+    /// dichiarazione per `let` e `const`, `None` per i simboli disponibili in
+    /// tutto lo scope come le funzioni
+    pub defined_at: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<ScopeId>,
+    values: HashMap<String, SymbolId>,
+    types: HashMap<String, SymbolId>,
+}
+
+/// Tutti gli scope e i simboli di un programma
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SymbolTable {
+    scopes: Vec<Scope>,
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Crea uno scope vuoto dentro `parent`
+    pub fn add_scope(&mut self, kind: ScopeKind, parent: Option<ScopeId>) -> ScopeId {
+        let id = ScopeId(self.scopes.len() as u32);
+        self.scopes.push(Scope {
+            kind,
+            parent,
+            values: HashMap::new(),
+            types: HashMap::new(),
+        });
+        id
+    }
+    
+    /// Aggiunge il simbolo al suo scope. Se lo scope contiene già un simbolo
+    /// con lo stesso nome lo restituisce come secondo valore e il nome
+    /// continua a riferirsi al precedente. Le varianti di enum cedono invece
+    /// il nome a qualsiasi altra dichiarazione senza conflitti.
+    pub fn declare(&mut self, symbol: Symbol) -> (SymbolId, Option<SymbolId>) {
+        let id = SymbolId(self.symbols.len() as u32);
+        let kind = symbol.kind;
+        let scope = &mut self.scopes[symbol.scope.0 as usize];
+        let mut previous = None;
+        
+        let mut namespaces = Vec::with_capacity(2);
+        if kind.is_value() {
+            namespaces.push(&mut scope.values);
+        }
+        if kind.is_type() {
+            namespaces.push(&mut scope.types);
+        }
+        for names in namespaces {
+            match names.get(&symbol.name).map(|&existing| (existing, self.symbols[existing.0 as usize].kind)) {
+                None | Some((_, SymbolKind::Variant)) => {
+                    names.insert(symbol.name.clone(), id);
+                },
+                Some(_) if kind == SymbolKind::Variant => {},
+                Some((existing, _)) => previous = previous.or(Some(existing)),
+            }
+        }
+        
+        self.symbols.push(symbol);
+        (id, previous)
+    }
+    
+    /// Valore visibile con questo nome da `scope`
+    pub fn lookup_value(&self, scope: ScopeId, name: &str) -> Option<SymbolId> {
+        self.ancestors(scope).find_map(|s| s.values.get(name).copied())
+    }
+    
+    /// Tipo visibile con questo nome da `scope`
+    pub fn lookup_type(&self, scope: ScopeId, name: &str) -> Option<SymbolId> {
+        self.ancestors(scope).find_map(|s| s.types.get(name).copied())
+    }
+    
+    /// Nomi dei valori visibili da `scope`, compresi quelli nascosti
+    pub fn visible_values(&self, scope: ScopeId) -> impl Iterator<Item = &str> {
+        self.ancestors(scope).flat_map(|s| s.values.keys().map(String::as_str))
+    }
+    
+    /// `scope` e gli scope che lo contengono, dal più interno
+    pub fn ancestors(&self, scope: ScopeId) -> impl Iterator<Item = &Scope> {
+        std::iter::successors(Some(self.scope(scope)), |s| s.parent.map(|parent| self.scope(parent)))
+    }
+    
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id.0 as usize]
+    }
+    
+    pub fn symbol(&self, id: SymbolId) -> &Symbol {
+        &self.symbols[id.0 as usize]
+    }
+    
+    pub fn symbols(&self) -> impl Iterator<Item = (SymbolId, &Symbol)> {
+        self.symbols.iter().enumerate().map(|(index, symbol)| (SymbolId(index as u32), symbol))
+    }
+}
@@ -0,0 +1,1440 @@
+//! Controllo graduale dei tipi.
+//!
+//! Le annotazioni sono facoltative: un parametro senza tipo è `any`, una
+//! variabile senza tipo prende quello del suo valore iniziale e una funzione
+//! senza tipo di ritorno quello dei suoi `return`. Il tipo di ogni espressione
+//! viene registrato per `NodeId`.
+//!
+//! Dove un valore `any` arriva in codice tipizzato, ad esempio come argomento
+//! di un parametro `number`, il tipo non può essere verificato durante la
+//! compilazione: l'espressione viene registrata tra i controlli da eseguire a
+//! runtime, che [`crate::ir::insert_runtime_checks`] trasforma in cast
+//! verificati.
+//!
+//! Il controllo usa la risoluzione dei nomi per collegare ogni uso alla sua
+//! dichiarazione. I parametri di tipo di funzioni e classi generiche valgono
+//! `any` per chi le usa; gli alias e le interfacce ricorsive sono espansi una
+//! sola volta e il riferimento ricorsivo vale `any`.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{
+    BinaryOp, ClassDecl, ClassMemberKind, ElseBranch, ExportDecl, Expr, ExprKind, FunctionDecl, Ident, InterfaceDecl,
+    InterfaceMemberKind, Lambda, LambdaBody, Literal, MatchArm, NodeId, Param, Pattern, PatternKind, Program, Property,
+    PropertyKey, PropertyKind, Stmt, StmtKind, TypeAlias, TypeExpr, TypeExprKind, TypeParam, UnaryOp, VarDecl,
+};
+use crate::error::{Error, ErrorKind};
+use crate::lexer::{Position, Span};
+use crate::utils::suggest::{import_position, NameSuggester};
+
+use super::resolver::Resolution;
+use super::symbol_table::{SymbolId, SymbolKind};
+use super::types::{ClassType, FunctionType, Member, Type, TypeContext};
+
+const PRIMITIVE_TYPES: &[&str] = &["any", "number", "string", "boolean", "null", "void", "never", "i32", "i64", "f32", "f64", "Array"];
+
+/// Tipi generici della libreria standard, disponibili senza import
+const BUILTIN_TYPES: &[&str] = &["Map", "Set", "Promise", "Generator", "Iterable", "Error", "Date", "RegExp"];
+
+/// Risultato del controllo dei tipi di un programma
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeCheck {
+    /// Tipo di ogni espressione
+    pub types: HashMap<NodeId, Type>,
+    /// Espressioni di tipo `any` usate dove è atteso un tipo più preciso, con
+    /// il tipo da verificare durante l'esecuzione
+    pub runtime_checks: HashMap<NodeId, Type>,
+    /// Tipo catturato da ogni clausola `catch` con un'annotazione, per id
+    /// della clausola
+    pub catch_types: HashMap<NodeId, Type>,
+    pub context: TypeContext,
+}
+
+impl TypeCheck {
+    pub fn type_of(&self, id: NodeId) -> Option<&Type> {
+        self.types.get(&id)
+    }
+}
+
+/// Controlla i tipi del programma, dopo la risoluzione dei nomi
+pub fn check_types(program: &Program, resolution: &Resolution) -> (TypeCheck, Vec<Error>) {
+    let mut checker = TypeChecker::new(resolution, import_position(program));
+    checker.check_stmts(&program.body);
+    checker.finish()
+}
+
+/// Alias o interfaccia, espansi ogni volta che vengono usati
+#[derive(Clone, Copy)]
+enum TypeDecl<'a> {
+    Alias(&'a TypeAlias),
+    Interface(&'a InterfaceDecl),
+}
+
+impl<'a> TypeDecl<'a> {
+    fn type_params(self) -> &'a [TypeParam] {
+        match self {
+            TypeDecl::Alias(alias) => &alias.type_params,
+            TypeDecl::Interface(interface) => &interface.type_params,
+        }
+    }
+}
+
+/// Funzione di cui si stanno controllando i `return` e gli `yield`
+struct FunctionScope {
+    /// Tipo di ritorno dichiarato
+    ret: Option<Type>,
+    /// Tipi restituiti, se il tipo di ritorno va inferito
+    returns: Vec<Type>,
+    yields: Yields,
+}
+
+/// Valori prodotti dagli `yield` di una funzione
+enum Yields {
+    /// La funzione non è un generatore
+    None,
+    /// Tipo degli elementi dichiarato con `Generator<T>`
+    Declared(Type),
+    /// Tipi prodotti, se il tipo degli elementi va inferito
+    Inferred(Vec<Type>),
+}
+
+pub struct TypeChecker<'a> {
+    resolution: &'a Resolution,
+    /// Simbolo dichiarato da ogni nodo di dichiarazione
+    declarations: HashMap<NodeId, SymbolId>,
+    /// Tipo delle variabili, dei parametri e delle funzioni
+    values: HashMap<SymbolId, Type>,
+    type_decls: HashMap<SymbolId, TypeDecl<'a>>,
+    /// Tipi assegnati ai parametri di tipo dove valgono `any` o durante
+    /// l'espansione di un alias generico
+    substitutions: HashMap<SymbolId, Type>,
+    /// Alias e interfacce in espansione
+    expanding: Vec<SymbolId>,
+    context: TypeContext,
+    functions: Vec<FunctionScope>,
+    /// Tipo di `this` nei membri delle classi
+    this: Vec<Type>,
+    types: HashMap<NodeId, Type>,
+    runtime_checks: HashMap<NodeId, Type>,
+    catch_types: HashMap<NodeId, Type>,
+    errors: Vec<Error>,
+    /// Annotazioni convertite più volte di cui è già stato segnalato l'errore
+    reported: HashSet<NodeId>,
+    import_at: Position,
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(resolution: &'a Resolution, import_at: Position) -> Self {
+        let declarations = resolution
+            .symbols
+            .symbols()
+            .map(|(id, symbol)| (symbol.node, id))
+            .collect();
+        Self {
+            resolution,
+            declarations,
+            values: HashMap::new(),
+            type_decls: HashMap::new(),
+            substitutions: HashMap::new(),
+            expanding: Vec::new(),
+            context: TypeContext::new(),
+            functions: Vec::new(),
+            this: Vec::new(),
+            types: HashMap::new(),
+            runtime_checks: HashMap::new(),
+            catch_types: HashMap::new(),
+            errors: Vec::new(),
+            reported: HashSet::new(),
+            import_at,
+        }
+    }
+    
+    pub fn finish(self) -> (TypeCheck, Vec<Error>) {
+        let check = TypeCheck {
+            types: self.types,
+            runtime_checks: self.runtime_checks,
+            catch_types: self.catch_types,
+            context: self.context,
+        };
+        (check, self.errors)
+    }
+    
+    /// Controlla le istruzioni di uno scope, dopo aver raccolto i tipi delle
+    /// dichiarazioni che possono essere usate prima di essere definite
+    pub fn check_stmts(&mut self, stmts: &'a [Stmt]) {
+        let declarations: Vec<&'a Stmt> = stmts.iter().map(declaration).collect();
+        for stmt in &declarations {
+            let decl = match &stmt.kind {
+                StmtKind::TypeAlias(alias) => TypeDecl::Alias(alias),
+                StmtKind::Interface(interface) => TypeDecl::Interface(interface),
+                _ => continue,
+            };
+            if let Some(id) = self.declared(stmt.id) {
+                self.type_decls.insert(id, decl);
+            }
+        }
+        for stmt in &declarations {
+            match &stmt.kind {
+                StmtKind::Function(function) => {
+                    let mut signature = self.signature(&function.type_params, &function.params, function.return_type.as_ref());
+                    if function.is_generator && function.return_type.is_none() {
+                        signature.ret = Box::new(generator(Type::Any));
+                    }
+                    if let Some(id) = self.declared(stmt.id) {
+                        self.values.insert(id, Type::Function(signature));
+                    }
+                },
+                StmtKind::Class(class) => self.declare_class(class),
+                _ => {},
+            }
+        }
+        
+        for stmt in stmts {
+            self.check_stmt(stmt);
+        }
+    }
+    
+    pub fn check_stmt(&mut self, stmt: &'a Stmt) {
+        match &stmt.kind {
+            StmtKind::Expr(expr) | StmtKind::Throw(expr) => {
+                self.infer_type(expr);
+            },
+            StmtKind::Var(decl) => self.var(decl),
+            StmtKind::Function(function) => self.function(stmt.id, function),
+            StmtKind::Class(class) => self.class(class),
+            StmtKind::Interface(interface) => {
+                self.interface_type(interface);
+            },
+            StmtKind::TypeAlias(alias) => {
+                self.resolve_type(&alias.ty);
+            },
+            StmtKind::Enum(decl) => {
+                for ty in decl.variants.iter().flat_map(|variant| &variant.payload) {
+                    self.resolve_type(ty);
+                }
+            },
+            StmtKind::If(stmt) => {
+                self.infer_type(&stmt.condition);
+                self.check_stmts(&stmt.then_branch.stmts);
+                match &stmt.else_branch {
+                    Some(ElseBranch::If(else_if)) => self.check_stmt(else_if),
+                    Some(ElseBranch::Block(block)) => self.check_stmts(&block.stmts),
+                    None => {},
+                }
+            },
+            StmtKind::For(stmt) => {
+                let iterable = self.infer_type(&stmt.iterable);
+                let element = self.iterated(&iterable, stmt.iterable.span);
+                self.bind_pattern(&stmt.pattern, &element);
+                self.check_stmts(&stmt.body.stmts);
+            },
+            StmtKind::While(stmt) => {
+                self.infer_type(&stmt.condition);
+                self.check_stmts(&stmt.body.stmts);
+            },
+            StmtKind::Return(value) => self.return_stmt(value.as_ref(), stmt.span),
+            StmtKind::Try(stmt) => {
+                self.check_stmts(&stmt.body.stmts);
+                for catch in &stmt.catches {
+                    let ty = catch.ty.as_ref().map_or(Type::Any, |ty| self.resolve_type(ty));
+                    if catch.ty.is_some() {
+                        self.catch_types.insert(catch.id, ty.clone());
+                    }
+                    if let Some(id) = self.declared(catch.id) {
+                        self.values.insert(id, ty);
+                    }
+                    self.check_stmts(&catch.body.stmts);
+                }
+                if let Some(finally) = &stmt.finally {
+                    self.check_stmts(&finally.stmts);
+                }
+            },
+            StmtKind::Export(ExportDecl::Declaration(inner) | ExportDecl::Default(inner)) => self.check_stmt(inner),
+            StmtKind::Import(_)
+            | StmtKind::Export(_)
+            | StmtKind::Break
+            | StmtKind::Continue
+            | StmtKind::Pragma(_)
+            | StmtKind::Decorators(_)
+            | StmtKind::Error => {},
+        }
+    }
+    
+    /// Controlla che l'espressione abbia un tipo assegnabile a `expected`.
+    /// Un valore `any` viene accettato e registrato tra i controlli a runtime.
+    pub fn check_expr(&mut self, expr: &'a Expr, expected: &Type) -> Type {
+        let found = match (&expr.kind, expected) {
+            (ExprKind::Lambda(lambda), Type::Function(context)) => self.lambda(lambda, Some(context)),
+            (ExprKind::Array(elements), Type::Array(element)) => {
+                for expr in elements {
+                    match &expr.kind {
+                        ExprKind::Spread(inner) => {
+                            self.check_expr(inner, expected);
+                        },
+                        _ => {
+                            self.check_expr(expr, element);
+                        },
+                    }
+                }
+                expected.clone()
+            },
+            (ExprKind::Array(elements), Type::Tuple(types))
+                if elements.len() == types.len() && !elements.iter().any(|e| matches!(e.kind, ExprKind::Spread(_))) =>
+            {
+                for (expr, ty) in elements.iter().zip(types) {
+                    self.check_expr(expr, ty);
+                }
+                expected.clone()
+            },
+            _ => self.infer_type(expr),
+        };
+        
+        let ty = if found == Type::Any && !matches!(expected, Type::Any | Type::Void) && !matches!(expr.kind, ExprKind::Error) {
+            self.runtime_checks.insert(expr.id, expected.clone());
+            expected.clone()
+        } else {
+            if !self.context.is_assignable(&found, expected) {
+                self.mismatch(expected, &found, expr.span);
+            }
+            found
+        };
+        self.types.insert(expr.id, ty.clone());
+        ty
+    }
+    
+    /// Tipo dell'espressione, senza un tipo atteso
+    pub fn infer_type(&mut self, expr: &'a Expr) -> Type {
+        let ty = match &expr.kind {
+            ExprKind::Literal(literal) => match literal {
+                Literal::Number(_) => Type::Number,
+                Literal::String(_) => Type::String,
+                Literal::Bool(_) => Type::Boolean,
+                Literal::Null => Type::Null,
+            },
+            ExprKind::Ident(_) => self
+                .resolution
+                .uses
+                .get(&expr.id)
+                .and_then(|id| self.values.get(id))
+                .cloned()
+                .unwrap_or(Type::Any),
+            ExprKind::This => self.this.last().cloned().unwrap_or(Type::Any),
+            ExprKind::Super | ExprKind::Error => Type::Any,
+            ExprKind::Array(elements) => {
+                let mut types = Vec::with_capacity(elements.len());
+                for element in elements {
+                    let ty = self.infer_type(element);
+                    types.push(match element.kind {
+                        ExprKind::Spread(_) => self.iterated(&ty, element.span),
+                        _ => ty,
+                    });
+                }
+                match types.is_empty() {
+                    true => Type::Array(Box::new(Type::Any)),
+                    false => Type::Array(Box::new(Type::union(types))),
+                }
+            },
+            ExprKind::Object(properties) => self.object(properties),
+            ExprKind::Spread(inner) => self.infer_type(inner),
+            ExprKind::Unary { op, operand } => {
+                let ty = self.infer_type(operand);
+                match op {
+                    UnaryOp::Not => Type::Boolean,
+                    _ => {
+                        if !self.context.is_assignable(&ty, &Type::Number) {
+                            self.type_error(format!("L'operatore `{}` non si applica a `{}`", op, ty), expr.span);
+                        }
+                        Type::Number
+                    },
+                }
+            },
+            ExprKind::Binary { op, left, right } => {
+                let left = self.infer_type(left);
+                let right = self.infer_type(right);
+                self.binary(*op, &left, &right, expr.span)
+            },
+            ExprKind::Assign { op, target, value } => {
+                let target = self.infer_type(target);
+                match op.binary_op() {
+                    None => {
+                        self.check_expr(value, &target);
+                    },
+                    Some(op) => {
+                        let ty = self.infer_type(value);
+                        let result = self.binary(op, &target, &ty, expr.span);
+                        if !self.context.is_assignable(&result, &target) {
+                            self.mismatch(&target, &result, value.span);
+                        }
+                    },
+                }
+                target
+            },
+            ExprKind::Call { callee, args, optional, .. } => {
+                let callee = self.infer_type(callee);
+                let callee = if *optional { callee.non_null() } else { callee };
+                self.call(&callee, args, expr.span)
+            },
+            ExprKind::Member { object, property, optional } => {
+                let object = self.infer_type(object);
+                let base = object.non_null();
+                match self.context.member(&base, &property.name) {
+                    Some(ty) if *optional && base != object => ty.nullable(),
+                    Some(ty) => ty,
+                    None => {
+                        self.type_error(format!("La proprietà `{}` non esiste nel tipo `{}`", property, base), property.span);
+                        Type::Any
+                    },
+                }
+            },
+            ExprKind::Index { object, index, .. } => {
+                let object = self.infer_type(object).non_null();
+                match &object {
+                    Type::Array(element) => {
+                        self.check_expr(index, &Type::Number);
+                        (**element).clone()
+                    },
+                    Type::String => {
+                        self.check_expr(index, &Type::Number);
+                        Type::String
+                    },
+                    Type::Tuple(types) => {
+                        self.check_expr(index, &Type::Number);
+                        match &index.kind {
+                            ExprKind::Literal(Literal::Number(n)) if n.fract() == 0.0 && *n >= 0.0 => {
+                                match types.get(*n as usize) {
+                                    Some(ty) => ty.clone(),
+                                    None => {
+                                        self.type_error(format!("Indice {} fuori dalla tupla `{}`", n, object), index.span);
+                                        Type::Any
+                                    },
+                                }
+                            },
+                            _ => Type::union(types.iter().cloned()),
+                        }
+                    },
+                    _ => {
+                        self.infer_type(index);
+                        Type::Any
+                    },
+                }
+            },
+            ExprKind::New { callee, type_args, args } => {
+                self.infer_type(callee);
+                let class = self
+                    .resolution
+                    .resolved(callee.id)
+                    .filter(|symbol| symbol.kind == SymbolKind::Class)
+                    .and_then(|symbol| Some((symbol.name.clone(), self.context.constructor(&symbol.name)?)));
+                match class {
+                    Some((name, constructor)) => {
+                        self.check_args(&constructor, args, expr.span);
+                        let args = type_args.iter().map(|ty| self.resolve_type(ty)).collect();
+                        Type::Named { name, args }
+                    },
+                    None => {
+                        for arg in args {
+                            self.infer_type(arg);
+                        }
+                        Type::Any
+                    },
+                }
+            },
+            ExprKind::Is { expr, ty } => {
+                self.infer_type(expr);
+                self.resolve_type(ty);
+                Type::Boolean
+            },
+            ExprKind::As { expr, ty } => {
+                self.infer_type(expr);
+                self.resolve_type(ty)
+            },
+            ExprKind::NullCheck(inner) => {
+                self.infer_type(inner);
+                Type::Boolean
+            },
+            ExprKind::Template(template) => {
+                for value in &template.values {
+                    self.infer_type(value);
+                }
+                Type::String
+            },
+            ExprKind::TaggedTemplate { tag, template } => {
+                let tag = self.infer_type(tag);
+                for value in &template.values {
+                    self.infer_type(value);
+                }
+                match tag {
+                    Type::Function(function) => *function.ret,
+                    _ => Type::Any,
+                }
+            },
+            ExprKind::Lambda(lambda) => self.lambda(lambda, None),
+            ExprKind::Match { scrutinee, arms } => self.match_expr(scrutinee, arms),
+            ExprKind::Yield { argument, delegate } => {
+                self.yield_expr(argument.as_deref(), *delegate, expr.span);
+                // Il valore di `yield` è quello passato a `next()`
+                Type::Any
+            },
+        };
+        self.types.insert(expr.id, ty.clone());
+        ty
+    }
+    
+    /// Converte un'annotazione nel tipo corrispondente
+    pub fn resolve_type(&mut self, ty: &TypeExpr) -> Type {
+        match &ty.kind {
+            TypeExprKind::Named { name, args } => self.named_type(ty.id, name, args),
+            TypeExprKind::Array(element) => Type::Array(Box::new(self.resolve_type(element))),
+            TypeExprKind::Tuple(types) => Type::Tuple(types.iter().map(|ty| self.resolve_type(ty)).collect()),
+            TypeExprKind::Function { params, return_type } => {
+                let params: Vec<Type> = params.iter().map(|param| self.resolve_type(&param.ty)).collect();
+                Type::Function(FunctionType {
+                    required: params.len(),
+                    params,
+                    rest: None,
+                    ret: Box::new(self.resolve_type(return_type)),
+                })
+            },
+            TypeExprKind::Object(members) => {
+                let members: Vec<Member> = members
+                    .iter()
+                    .map(|member| Member {
+                        name: member.name.name.clone(),
+                        ty: member.ty.as_ref().map_or(Type::Any, |ty| self.resolve_type(ty)),
+                        optional: member.optional,
+                    })
+                    .collect();
+                Type::object(members)
+            },
+            TypeExprKind::Union(types) => Type::union(types.iter().map(|ty| self.resolve_type(ty))),
+            TypeExprKind::Intersection(types) => {
+                let types: Vec<Type> = types.iter().map(|ty| self.resolve_type(ty)).collect();
+                // L'intersezione di oggetti è l'oggetto con i membri di tutti
+                if types.iter().all(|ty| matches!(ty, Type::Object(_))) {
+                    Type::object(types.into_iter().flat_map(|ty| match ty {
+                        Type::Object(members) => members,
+                        _ => Vec::new(),
+                    }))
+                } else {
+                    Type::Intersection(types)
+                }
+            },
+            TypeExprKind::Nullable(inner) => self.resolve_type(inner).nullable(),
+        }
+    }
+    
+    fn named_type(&mut self, id: NodeId, name: &Ident, args: &[TypeExpr]) -> Type {
+        let args: Vec<Type> = args.iter().map(|ty| self.resolve_type(ty)).collect();
+        
+        if let Some(&symbol) = self.resolution.uses.get(&id) {
+            return match self.resolution.symbols.symbol(symbol).kind {
+                SymbolKind::TypeParam => self.substitutions.get(&symbol).cloned().unwrap_or(Type::Named {
+                    name: name.name.clone(),
+                    args,
+                }),
+                SymbolKind::TypeAlias | SymbolKind::Interface => self.expand(symbol, args),
+                SymbolKind::Class | SymbolKind::Enum => Type::Named {
+                    name: name.name.clone(),
+                    args,
+                },
+                // I tipi degli altri moduli non sono noti
+                _ => Type::Any,
+            };
+        }
+        
+        match name.name.as_str() {
+            "any" => Type::Any,
+            "number" | "i32" | "i64" | "f32" | "f64" => Type::Number,
+            "string" => Type::String,
+            "boolean" => Type::Boolean,
+            "null" => Type::Null,
+            "void" => Type::Void,
+            "never" => Type::Never,
+            "Array" if args.len() == 1 => Type::Array(Box::new(args.into_iter().next().unwrap_or(Type::Any))),
+            builtin if BUILTIN_TYPES.contains(&builtin) => Type::Named {
+                name: name.name.clone(),
+                args,
+            },
+            _ => {
+                if self.reported.insert(id) {
+                    let mut suggester = NameSuggester::with_defaults();
+                    suggester.add_scope_names(PRIMITIVE_TYPES.iter().chain(BUILTIN_TYPES).copied());
+                    self.errors.push(suggester.undefined_symbol(&name.name, name.span, self.import_at));
+                }
+                Type::Any
+            },
+        }
+    }
+    
+    /// Espande un alias o un'interfaccia con gli argomenti di tipo dati
+    fn expand(&mut self, symbol: SymbolId, args: Vec<Type>) -> Type {
+        let Some(&decl) = self.type_decls.get(&symbol) else {
+            return Type::Any;
+        };
+        if self.expanding.contains(&symbol) {
+            return Type::Any;
+        }
+        
+        let mut args = args.into_iter();
+        let substitutions = decl
+            .type_params()
+            .iter()
+            .filter_map(|param| self.declared(param.id))
+            .map(|param| (param, args.next().unwrap_or(Type::Any)))
+            .collect();
+        
+        self.expanding.push(symbol);
+        let ty = self.substituted(substitutions, |this| match decl {
+            TypeDecl::Alias(alias) => this.resolve_type(&alias.ty),
+            TypeDecl::Interface(interface) => this.interface_type(interface),
+        });
+        self.expanding.pop();
+        ty
+    }
+    
+    /// Tipo strutturale di un'interfaccia, con i membri delle interfacce estese
+    fn interface_type(&mut self, interface: &InterfaceDecl) -> Type {
+        let mut members = Vec::new();
+        for ty in &interface.extends {
+            if let Type::Object(inherited) = self.resolve_type(ty) {
+                members.extend(inherited);
+            }
+        }
+        for member in &interface.members {
+            let ty = match &member.kind {
+                InterfaceMemberKind::Property(ty) => self.resolve_type(ty),
+                InterfaceMemberKind::Method { type_params, params, return_type } => {
+                    Type::Function(self.signature(type_params, params, return_type.as_ref()))
+                },
+            };
+            members.push(Member {
+                name: member.name.name.clone(),
+                ty,
+                optional: member.optional,
+            });
+        }
+        Type::object(members)
+    }
+    
+    /// Tipo di una funzione visto da chi la chiama, in cui i parametri di
+    /// tipo valgono `any`
+    fn signature(&mut self, type_params: &[TypeParam], params: &[Param], return_type: Option<&TypeExpr>) -> FunctionType {
+        let erased = self.erased(type_params);
+        self.substituted(erased, |this| {
+            let mut function = FunctionType {
+                params: Vec::new(),
+                required: 0,
+                rest: None,
+                ret: Box::new(return_type.map_or(Type::Any, |ty| this.resolve_type(ty))),
+            };
+            for param in params {
+                let ty = param.ty.as_ref().map_or(Type::Any, |ty| this.resolve_type(ty));
+                add_param(&mut function, param, ty);
+            }
+            function
+        })
+    }
+    
+    fn erased(&self, type_params: &[TypeParam]) -> Vec<(SymbolId, Type)> {
+        type_params
+            .iter()
+            .filter_map(|param| self.declared(param.id))
+            .map(|param| (param, Type::Any))
+            .collect()
+    }
+    
+    /// Esegue `f` con i parametri di tipo sostituiti, ripristinando poi le
+    /// sostituzioni precedenti
+    fn substituted<T>(&mut self, substitutions: Vec<(SymbolId, Type)>, f: impl FnOnce(&mut Self) -> T) -> T {
+        let saved: Vec<(SymbolId, Option<Type>)> = substitutions
+            .into_iter()
+            .map(|(param, ty)| (param, self.substitutions.insert(param, ty)))
+            .collect();
+        let result = f(self);
+        for (param, previous) in saved {
+            match previous {
+                Some(ty) => self.substitutions.insert(param, ty),
+                None => self.substitutions.remove(&param),
+            };
+        }
+        result
+    }
+    
+    fn declare_class(&mut self, class: &ClassDecl) {
+        let erased = self.erased(&class.type_params);
+        let class_type = self.substituted(erased, |this| {
+            let mut members = Vec::new();
+            let mut constructor = None;
+            for member in class.members.iter().filter(|member| !member.is_static) {
+                match &member.kind {
+                    ClassMemberKind::Field { name, ty, .. } => members.push(Member {
+                        name: name.name.clone(),
+                        ty: ty.as_ref().map_or(Type::Any, |ty| this.resolve_type(ty)),
+                        optional: false,
+                    }),
+                    ClassMemberKind::Method(method) => {
+                        let mut signature = this.signature(&method.type_params, &method.params, method.return_type.as_ref());
+                        if method.is_generator && method.return_type.is_none() {
+                            signature.ret = Box::new(generator(Type::Any));
+                        }
+                        members.push(Member {
+                            name: method.name.name.clone(),
+                            ty: Type::Function(signature),
+                            optional: false,
+                        });
+                    },
+                    ClassMemberKind::Constructor { params, .. } => {
+                        let mut signature = this.signature(&[], params, None);
+                        signature.ret = Box::new(Type::Void);
+                        constructor = Some(signature);
+                    },
+                }
+            }
+            ClassType {
+                parent: class.extends.as_ref().map(|ty| this.resolve_type(ty)),
+                members,
+                constructor,
+            }
+        });
+        self.context.add_class(&class.name.name, class_type);
+    }
+    
+    fn var(&mut self, decl: &'a VarDecl) {
+        let ty = match (&decl.ty, &decl.init) {
+            (Some(ty), init) => {
+                let ty = self.resolve_type(ty);
+                if let Some(init) = init {
+                    self.check_expr(init, &ty);
+                }
+                ty
+            },
+            // `let x = null` non dice quali valori conterrà la variabile
+            (None, Some(init)) => match self.infer_type(init) {
+                Type::Null | Type::Never => Type::Any,
+                ty => ty,
+            },
+            (None, None) => Type::Any,
+        };
+        self.bind_pattern(&decl.pattern, &ty);
+    }
+    
+    fn function(&mut self, node: NodeId, function: &'a FunctionDecl) {
+        let ty = self.function_type(&function.params, function.return_type.as_ref(), &function.body.stmts, function.is_generator);
+        
+        // Il tipo di ritorno inferito vale per gli usi successivi al corpo
+        let inferred = function.return_type.is_none() && function.type_params.is_empty();
+        if let Some(Type::Function(signature)) = self.declared(node).and_then(|id| self.values.get_mut(&id)) {
+            if inferred {
+                signature.ret = ty.ret;
+            }
+        }
+    }
+    
+    fn class(&mut self, class: &'a ClassDecl) {
+        let instance = Type::Named {
+            name: class.name.name.clone(),
+            args: class
+                .type_params
+                .iter()
+                .map(|param| Type::Named {
+                    name: param.name.name.clone(),
+                    args: Vec::new(),
+                })
+                .collect(),
+        };
+        
+        for member in &class.members {
+            self.this.push(if member.is_static { Type::Any } else { instance.clone() });
+            match &member.kind {
+                ClassMemberKind::Field { ty, init, .. } => {
+                    let ty = ty.as_ref().map_or(Type::Any, |ty| self.resolve_type(ty));
+                    if let Some(init) = init {
+                        self.check_expr(init, &ty);
+                    }
+                },
+                ClassMemberKind::Constructor { params, body } => {
+                    self.function_type(params, None, &body.stmts, false);
+                },
+                ClassMemberKind::Method(method) => {
+                    self.function_type(&method.params, method.return_type.as_ref(), &method.body.stmts, method.is_generator);
+                },
+            }
+            self.this.pop();
+        }
+    }
+    
+    /// Controlla parametri e corpo di una funzione e ne restituisce il tipo,
+    /// con il tipo di ritorno dichiarato o inferito
+    fn function_type(
+        &mut self,
+        params: &'a [Param],
+        return_type: Option<&TypeExpr>,
+        body: &'a [Stmt],
+        is_generator: bool,
+    ) -> FunctionType {
+        let mut function = self.bind_params(params, None);
+        let declared = return_type.map(|ty| self.resolve_type(ty));
+        let ret = if is_generator {
+            // I `return` di un generatore non danno il tipo dei valori prodotti
+            let yields = match return_type.zip(declared.as_ref()) {
+                Some((annotation, ty)) => Yields::Declared(self.generator_element(ty, annotation.span)),
+                None => Yields::Inferred(Vec::new()),
+            };
+            let element = self.function_body(body, None, yields);
+            declared.unwrap_or_else(|| generator(element))
+        } else {
+            let inferred = self.function_body(body, declared.clone(), Yields::None);
+            declared.unwrap_or(inferred)
+        };
+        function.ret = Box::new(ret);
+        function
+    }
+    
+    /// Tipo degli elementi prodotti da un generatore con tipo di ritorno `ty`
+    fn generator_element(&mut self, ty: &Type, span: Span) -> Type {
+        match ty {
+            Type::Named { name, args } if matches!(name.as_str(), "Generator" | "Iterable") && args.len() == 1 => args[0].clone(),
+            Type::Any => Type::Any,
+            _ => {
+                self.type_error(format!("Un generatore deve restituire `Generator<T>`, trovato `{}`", ty), span);
+                Type::Any
+            },
+        }
+    }
+    
+    /// Dichiara i parametri con il tipo annotato o, per le lambda, con quello
+    /// atteso dal contesto
+    fn bind_params(&mut self, params: &'a [Param], context: Option<&FunctionType>) -> FunctionType {
+        let mut function = FunctionType {
+            params: Vec::new(),
+            required: 0,
+            rest: None,
+            ret: Box::new(Type::Any),
+        };
+        for (index, param) in params.iter().enumerate() {
+            let contextual = context.and_then(|context| match param.rest {
+                true => context.rest.as_deref().cloned(),
+                false => context.params.get(index).cloned(),
+            });
+            let ty = match &param.ty {
+                Some(ty) => self.resolve_type(ty),
+                None => contextual.unwrap_or(Type::Any),
+            };
+            if let Some(default) = &param.default {
+                self.check_expr(default, &ty);
+            }
+            self.bind_pattern(&param.pattern, &ty);
+            add_param(&mut function, param, ty);
+        }
+        function
+    }
+    
+    /// Controlla il corpo di una funzione e ne restituisce il tipo di ritorno
+    /// oppure, per un generatore, il tipo degli elementi prodotti
+    fn function_body(&mut self, body: &'a [Stmt], ret: Option<Type>, yields: Yields) -> Type {
+        self.functions.push(FunctionScope {
+            ret: ret.clone(),
+            returns: Vec::new(),
+            yields,
+        });
+        self.check_stmts(body);
+        let scope = self.functions.pop().expect("scope della funzione");
+        
+        match scope.yields {
+            Yields::Declared(element) => element,
+            // Un generatore senza `yield` non produce valori
+            Yields::Inferred(yields) => Type::union(yields),
+            Yields::None => ret.unwrap_or_else(|| match scope.returns.is_empty() {
+                true => Type::Void,
+                false => Type::union(scope.returns),
+            }),
+        }
+    }
+    
+    /// Controlla il valore prodotto da `yield`, o gli elementi prodotti da
+    /// `yield*`, rispetto al tipo degli elementi del generatore
+    fn yield_expr(&mut self, argument: Option<&'a Expr>, delegate: bool, span: Span) {
+        let declared = match self.functions.last().map(|scope| &scope.yields) {
+            Some(Yields::Declared(element)) => Some(element.clone()),
+            Some(Yields::Inferred(_)) => None,
+            // `yield` fuori da un generatore è già segnalato dal parser
+            Some(Yields::None) | None => {
+                if let Some(argument) = argument {
+                    self.infer_type(argument);
+                }
+                return;
+            },
+        };
+        
+        let produced = match (argument, delegate) {
+            (Some(argument), true) => {
+                let iterable = self.infer_type(argument);
+                let element = self.iterated(&iterable, argument.span);
+                if let Some(expected) = &declared {
+                    if !self.context.is_assignable(&element, expected) {
+                        self.mismatch(expected, &element, argument.span);
+                    }
+                }
+                element
+            },
+            (Some(argument), false) => match &declared {
+                Some(expected) => self.check_expr(argument, expected),
+                None => self.infer_type(argument),
+            },
+            // `yield` senza valore produce `null`
+            (None, _) => {
+                if let Some(expected) = &declared {
+                    if !self.context.is_assignable(&Type::Null, expected) {
+                        self.mismatch(expected, &Type::Null, span);
+                    }
+                }
+                Type::Null
+            },
+        };
+        if let Some(FunctionScope { yields: Yields::Inferred(yields), .. }) = self.functions.last_mut() {
+            yields.push(produced);
+        }
+    }
+    
+    fn return_stmt(&mut self, value: Option<&'a Expr>, span: Span) {
+        let Some(scope) = self.functions.last() else {
+            if let Some(value) = value {
+                self.infer_type(value);
+            }
+            return;
+        };
+        
+        match (scope.ret.clone(), value) {
+            (Some(expected), Some(value)) => {
+                self.check_expr(value, &expected);
+            },
+            (Some(expected), None) => {
+                if !self.context.is_assignable(&Type::Void, &expected) {
+                    self.mismatch(&expected, &Type::Void, span);
+                }
+            },
+            (None, value) => {
+                let ty = value.map_or(Type::Void, |value| self.infer_type(value));
+                if let Some(scope) = self.functions.last_mut() {
+                    scope.returns.push(ty);
+                }
+            },
+        }
+    }
+    
+    fn lambda(&mut self, lambda: &'a Lambda, context: Option<&FunctionType>) -> Type {
+        let mut function = self.bind_params(&lambda.params, context);
+        let declared = lambda.return_type.as_ref().map(|ty| self.resolve_type(ty));
+        let ret = match (&lambda.body, declared) {
+            (LambdaBody::Expr(body), Some(ty)) => {
+                self.check_expr(body, &ty);
+                ty
+            },
+            (LambdaBody::Expr(body), None) => self.infer_type(body),
+            (LambdaBody::Block(body), declared) => self.function_body(&body.stmts, declared, Yields::None),
+        };
+        function.ret = Box::new(ret);
+        Type::Function(function)
+    }
+    
+    /// Tipo di un `match`: l'unione dei valori dei `case` che terminano con
+    /// un'espressione, `any` se un `case` termina in altro modo
+    fn match_expr(&mut self, scrutinee: &'a Expr, arms: &'a [MatchArm]) -> Type {
+        let ty = self.infer_type(scrutinee);
+        let mut results = Some(Vec::new());
+        for arm in arms {
+            self.bind_pattern(&arm.pattern, &ty);
+            if let Some(guard) = &arm.guard {
+                self.infer_type(guard);
+            }
+            self.check_stmts(&arm.body.stmts);
+            
+            let result = match arm.body.stmts.last().map(|stmt| &stmt.kind) {
+                Some(StmtKind::Expr(expr)) => self.types.get(&expr.id).cloned(),
+                Some(StmtKind::Throw(_)) => Some(Type::Never),
+                _ => None,
+            };
+            results = results.zip(result).map(|(mut results, result)| {
+                results.push(result);
+                results
+            });
+        }
+        results.filter(|results| !results.is_empty()).map_or(Type::Any, Type::union)
+    }
+    
+    fn object(&mut self, properties: &'a [Property]) -> Type {
+        let mut members = Vec::new();
+        for property in properties {
+            match &property.kind {
+                PropertyKind::KeyValue { key, value } => {
+                    let ty = self.infer_type(value);
+                    let name = match key {
+                        PropertyKey::Ident(name) => name.name.clone(),
+                        PropertyKey::String(name) => name.value.clone(),
+                        PropertyKey::Computed(key) => {
+                            self.infer_type(key);
+                            continue;
+                        },
+                    };
+                    members.push(Member {
+                        name,
+                        ty,
+                        optional: false,
+                    });
+                },
+                PropertyKind::Shorthand(name) => members.push(Member {
+                    name: name.name.clone(),
+                    ty: Type::Any,
+                    optional: false,
+                }),
+                PropertyKind::Spread(expr) => {
+                    if let Type::Object(spread) = self.infer_type(expr) {
+                        members.extend(spread);
+                    }
+                },
+            }
+        }
+        Type::object(members)
+    }
+    
+    fn call(&mut self, callee: &Type, args: &'a [Expr], span: Span) -> Type {
+        match callee {
+            Type::Function(function) => {
+                self.check_args(function, args, span);
+                (*function.ret).clone()
+            },
+            _ => {
+                for arg in args {
+                    self.infer_type(arg);
+                }
+                match callee {
+                    Type::Never => Type::Never,
+                    Type::Any | Type::Named { .. } | Type::Union(_) | Type::Intersection(_) => Type::Any,
+                    _ => {
+                        self.type_error(format!("Il tipo `{}` non è una funzione", callee), span);
+                        Type::Any
+                    },
+                }
+            },
+        }
+    }
+    
+    fn check_args(&mut self, function: &FunctionType, args: &'a [Expr], span: Span) {
+        // Con uno spread il numero di argomenti è noto solo a runtime
+        if args.iter().any(|arg| matches!(arg.kind, ExprKind::Spread(_))) {
+            for arg in args {
+                self.infer_type(arg);
+            }
+            return;
+        }
+        
+        let max = function.params.len();
+        if args.len() < function.required || (args.len() > max && function.rest.is_none()) {
+            let expected = match (&function.rest, function.required == max) {
+                (Some(_), _) => format!("almeno {}", function.required),
+                (None, true) => max.to_string(),
+                (None, false) => format!("da {} a {}", function.required, max),
+            };
+            self.type_error(format!("Numero di argomenti errato: attesi {}, trovati {}", expected, args.len()), span);
+        }
+        
+        for (index, arg) in args.iter().enumerate() {
+            let expected = match (function.params.get(index), &function.rest) {
+                (Some(ty), _) => ty.clone(),
+                (None, Some(rest)) => match &**rest {
+                    Type::Array(element) => (**element).clone(),
+                    _ => Type::Any,
+                },
+                (None, None) => {
+                    self.infer_type(arg);
+                    continue;
+                },
+            };
+            self.check_expr(arg, &expected);
+        }
+    }
+    
+    fn binary(&mut self, op: BinaryOp, left: &Type, right: &Type, span: Span) -> Type {
+        let number = |ty: &Type| self.context.is_assignable(ty, &Type::Number);
+        let string = |ty: &Type| self.context.is_assignable(ty, &Type::String);
+        
+        let (ty, valid) = match op {
+            BinaryOp::Add => {
+                if *left == Type::String || *right == Type::String {
+                    (Type::String, true)
+                } else if *left == Type::Any || *right == Type::Any {
+                    (Type::Any, true)
+                } else {
+                    (Type::Number, number(left) && number(right))
+                }
+            },
+            BinaryOp::Sub
+            | BinaryOp::Mul
+            | BinaryOp::Div
+            | BinaryOp::Rem
+            | BinaryOp::Pow
+            | BinaryOp::BitAnd
+            | BinaryOp::BitOr
+            | BinaryOp::BitXor
+            | BinaryOp::Shl
+            | BinaryOp::Shr => (Type::Number, number(left) && number(right)),
+            BinaryOp::Less | BinaryOp::LessEq | BinaryOp::Greater | BinaryOp::GreaterEq => {
+                (Type::Boolean, (number(left) && number(right)) || (string(left) && string(right)))
+            },
+            BinaryOp::Eq | BinaryOp::NotEq => (Type::Boolean, true),
+            BinaryOp::And | BinaryOp::Or => (Type::union([left.clone(), right.clone()]), true),
+            BinaryOp::Coalesce => (Type::union([left.non_null(), right.clone()]), true),
+        };
+        
+        if !valid {
+            self.type_error(format!("L'operatore `{}` non si applica a `{}` e `{}`", op, left, right), span);
+        }
+        ty
+    }
+    
+    /// Tipo degli elementi prodotti iterando su un valore di tipo `ty`
+    fn iterated(&mut self, ty: &Type, span: Span) -> Type {
+        match iterated_type(ty) {
+            Some(ty) => ty,
+            None => {
+                self.type_error(format!("Il tipo `{}` non è iterabile", ty), span);
+                Type::Any
+            },
+        }
+    }
+    
+    /// Assegna il tipo `ty` alle variabili introdotte dal pattern
+    fn bind_pattern(&mut self, pattern: &'a Pattern, ty: &Type) {
+        match &pattern.kind {
+            PatternKind::Binding(_) => self.bind(pattern.id, ty.clone()),
+            PatternKind::Array(patterns) => {
+                for (index, pattern) in patterns.iter().enumerate() {
+                    let element = match (&pattern.kind, ty) {
+                        (PatternKind::Rest(_), Type::Tuple(types)) => Type::Tuple(types.iter().skip(index).cloned().collect()),
+                        (PatternKind::Rest(_), Type::Array(_)) => ty.clone(),
+                        (PatternKind::Rest(_), _) => Type::Array(Box::new(Type::Any)),
+                        (_, Type::Tuple(types)) => types.get(index).cloned().unwrap_or(Type::Any),
+                        (_, Type::Array(element)) => (**element).clone(),
+                        (_, Type::String) => Type::String,
+                        _ => Type::Any,
+                    };
+                    self.bind_pattern(pattern, &element);
+                }
+            },
+            PatternKind::Object { fields, rest } => {
+                let object = ty.non_null();
+                for field in fields {
+                    let member = match self.context.member(&object, &field.key.name) {
+                        Some(member) => member,
+                        None => {
+                            let message = format!("La proprietà `{}` non esiste nel tipo `{}`", field.key, object);
+                            self.type_error(message, field.key.span);
+                            Type::Any
+                        },
+                    };
+                    self.bind_pattern(&field.pattern, &member);
+                }
+                if rest.is_some() {
+                    self.bind(pattern.id, Type::Any);
+                }
+            },
+            PatternKind::Rest(pattern) => self.bind_pattern(pattern, ty),
+            PatternKind::Default { pattern, default } => {
+                let default = self.check_expr(default, &ty.non_null());
+                let ty = if *ty == Type::Any { default } else { ty.non_null() };
+                self.bind_pattern(pattern, &ty);
+            },
+            PatternKind::Variant { fields, .. } => {
+                for field in fields {
+                    self.bind_pattern(field, &Type::Any);
+                }
+            },
+            PatternKind::TypeTest { binding, ty } => {
+                let ty = self.resolve_type(ty);
+                if binding.is_some() {
+                    self.bind(pattern.id, ty);
+                }
+            },
+            PatternKind::Or(alternatives) => {
+                for alternative in alternatives {
+                    self.bind_pattern(alternative, ty);
+                }
+            },
+            PatternKind::Wildcard | PatternKind::Literal(_) => {},
+        }
+    }
+    
+    fn bind(&mut self, node: NodeId, ty: Type) {
+        // Un nome che indica una variante di enum non dichiara niente
+        if let Some(id) = self.declared(node) {
+            self.values.insert(id, ty);
+        }
+    }
+    
+    /// Simbolo dichiarato dal nodo
+    fn declared(&self, node: NodeId) -> Option<SymbolId> {
+        self.declarations.get(&node).copied()
+    }
+    
+    fn mismatch(&mut self, expected: &Type, found: &Type, span: Span) {
+        self.type_error(format!("Tipo non compatibile: atteso `{}`, trovato `{}`", expected, found), span);
+    }
+    
+    fn type_error(&mut self, message: String, span: Span) {
+        self.errors.push(Error::new(ErrorKind::TypeError, message, span));
+    }
+}
+
+/// Dichiarazione contenuta in un `export`
+fn declaration(stmt: &Stmt) -> &Stmt {
+    match &stmt.kind {
+        StmtKind::Export(ExportDecl::Declaration(inner) | ExportDecl::Default(inner)) => declaration(inner),
+        _ => stmt,
+    }
+}
+
+fn add_param(function: &mut FunctionType, param: &Param, ty: Type) {
+    if param.rest {
+        function.rest = Some(Box::new(ty));
+        return;
+    }
+    if param.default.is_none() && function.required == function.params.len() {
+        function.required += 1;
+    }
+    function.params.push(ty);
+}
+
+/// Tipo `Generator<T>` di un generatore che produce elementi di tipo `element`
+fn generator(element: Type) -> Type {
+    Type::Named {
+        name: "Generator".to_string(),
+        args: vec![element],
+    }
+}
+
+/// Tipo degli elementi di un iterabile; `None` se il tipo non è iterabile
+fn iterated_type(ty: &Type) -> Option<Type> {
+    match ty {
+        Type::Any | Type::Never => Some(ty.clone()),
+        Type::Array(element) => Some((**element).clone()),
+        Type::Tuple(types) => Some(Type::union(types.iter().cloned())),
+        Type::String => Some(Type::String),
+        Type::Union(alternatives) => alternatives.iter().map(iterated_type).collect::<Option<Vec<_>>>().map(Type::union),
+        Type::Named { name, args } => match (name.as_str(), args.as_slice()) {
+            ("Generator" | "Iterable" | "Set", [element]) => Some(element.clone()),
+            ("Map", [key, value]) => Some(Type::Tuple(vec![key.clone(), value.clone()])),
+            _ => Some(Type::Any),
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::semantics::resolve;
+    
+    fn check(source: &str) -> (Program, TypeCheck, Vec<String>) {
+        let program = parse(source).unwrap();
+        let (resolution, errors) = resolve(&program);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let (check, errors) = check_types(&program, &resolution);
+        let errors = errors
+            .iter()
+            .map(|error| format!("{}: {}", error.span.unwrap().start.line, error.message))
+            .collect();
+        (program, check, errors)
+    }
+    
+    fn errors(source: &str) -> Vec<String> {
+        check(source).2
+    }
+    
+    /// Tipo del valore iniziale di ogni `let` di primo livello
+    fn inferred(source: &str) -> Vec<String> {
+        let (program, check, errors) = check(source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        program
+            .body
+            .iter()
+            .filter_map(|stmt| match &stmt.kind {
+                StmtKind::Var(decl) => decl.init.as_ref(),
+                _ => None,
+            })
+            .map(|init| check.type_of(init.id).unwrap().to_string())
+            .collect()
+    }
+    
+    #[test]
+    fn test_infers_types_without_annotations() {
+        let source = "\
+fn add(a: number, b: number)
+  return a + b
+fn name(user)
+  if user
+    return user.name
+  return null
+let x = add(1, 2)
+let y = [1, \"due\", null]
+let z = { label: \"a\" + x, pair: [x, true] as [number, boolean] }
+let f = (n: number) -> n > 0
+let g = name
+let h = z.pair[1]
+";
+        assert_eq!(inferred(source), vec![
+            "number",
+            "(number | string | null)[]",
+            "{ label: string, pair: [number, boolean] }",
+            "(number) -> boolean",
+            "(any) -> any",
+            "boolean",
+        ]);
+    }
+    
+    #[test]
+    fn test_reports_expected_and_found_types() {
+        let source = "\
+fn scale(v: number, factor: number = 2): number
+  return \"grande\"
+let a: string = 1
+let b: number[] = [1, \"due\"]
+scale(1, 2, 3)
+scale(true)
+let p: { x: number, y: number } = { x: 1 }
+p.z
+let c = \"a\" - 1
+";
+        assert_eq!(errors(source), vec![
+            "2: Tipo non compatibile: atteso `number`, trovato `string`",
+            "3: Tipo non compatibile: atteso `string`, trovato `number`",
+            "4: Tipo non compatibile: atteso `number`, trovato `string`",
+            "5: Numero di argomenti errato: attesi da 1 a 2, trovati 3",
+            "6: Tipo non compatibile: atteso `number`, trovato `boolean`",
+            "7: Tipo non compatibile: atteso `{ x: number, y: number }`, trovato `{ x: number }`",
+            "8: La proprietà `z` non esiste nel tipo `{ x: number, y: number }`",
+            "9: L'operatore `-` non si applica a `string` e `number`",
+        ]);
+    }
+    
+    #[test]
+    fn test_interfaces_are_structural_and_classes_nominal() {
+        let source = "\
+interface Named
+  name: string
+type Box<T> = { value: T }
+class Animal
+  name: string
+  constructor(name: string)
+    this.name = name
+class Dog extends Animal
+  fn bark(): string
+    return this.name + \"!\"
+fn greet(who: Named): string
+  return who.name
+let dog = new Dog(\"Rex\")
+greet(dog)
+let animal: Animal = dog
+let puppy: Dog = animal
+let box: Box<number> = { value: \"x\" }
+dog.fly()
+";
+        assert_eq!(errors(source), vec![
+            "16: Tipo non compatibile: atteso `Dog`, trovato `Animal`",
+            "17: Tipo non compatibile: atteso `{ value: number }`, trovato `{ value: string }`",
+            "18: La proprietà `fly` non esiste nel tipo `Dog`",
+        ]);
+    }
+    
+    #[test]
+    fn test_any_flows_into_typed_code_with_runtime_checks() {
+        let source = "\
+fn twice(n: number): number
+  return n * 2
+fn run(input, items: number[])
+  let doubled = items.map((x) -> twice(x))
+  twice(input)
+  let total: number = input.total
+  return twice(doubled)
+";
+        let (program, check, errors) = check(source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        
+        let mut checked: Vec<(usize, String)> = check
+            .runtime_checks
+            .iter()
+            .map(|(id, ty)| (find(&program, *id).span.start.line, ty.to_string()))
+            .collect();
+        checked.sort();
+        assert_eq!(checked, vec![
+            (4, "number".to_string()),
+            (5, "number".to_string()),
+            (6, "number".to_string()),
+            (7, "number".to_string()),
+        ]);
+    }
+    
+    #[test]
+    fn test_generators_produce_their_yield_types() {
+        let source = "\
+fn* fibonacci()
+  let a = 0
+  let b = 1
+  while true
+    yield a
+    [a, b] = [b, a + b]
+fn* names(): Generator<string>
+  yield \"a\"
+  yield 1
+  yield* [\"b\", \"c\"]
+  yield* fibonacci()
+fn* bad(): number
+  yield 1
+let x: number = fibonacci()
+let ok: Generator<number> = fibonacci()
+for n in fibonacci()
+  let s: string = n
+";
+        assert_eq!(errors(source), vec![
+            "9: Tipo non compatibile: atteso `string`, trovato `number`",
+            "11: Tipo non compatibile: atteso `string`, trovato `number`",
+            "12: Un generatore deve restituire `Generator<T>`, trovato `number`",
+            "14: Tipo non compatibile: atteso `number`, trovato `Generator<number>`",
+            "17: Tipo non compatibile: atteso `string`, trovato `number`",
+        ]);
+        assert_eq!(inferred("fn* empty()\n  return\nlet g = empty()\nfn* pairs()\n  yield 1\n  yield \"x\"\nlet p = pairs()\n"), vec![
+            "Generator<never>",
+            "Generator<number | string>",
+        ]);
+    }
+    
+    #[test]
+    fn test_unknown_type_names_are_reported() {
+        assert_eq!(errors("let a: strng = \"x\"\nlet b: Option<number> = null\nlet c: Map<string, i32>? = null\n"), vec![
+            "1: `strng` non trovato; forse intendevi `string`?",
+            "2: `Option` non trovato; è esportato da Core",
+        ]);
+    }
+    
+    fn find(program: &Program, id: NodeId) -> &Expr {
+        use crate::ast::visit::{walk_expr, Visitor};
+        
+        struct Find<'a> {
+            id: NodeId,
+            found: Option<&'a Expr>,
+        }
+        
+        impl<'a> Visitor<'a> for Find<'a> {
+            fn visit_expr(&mut self, expr: &'a Expr) {
+                if expr.id == self.id {
+                    self.found = Some(expr);
+                }
+                walk_expr(self, expr);
+            }
+        }
+        
+        let mut finder = Find { id, found: None };
+        finder.visit_program(program);
+        finder.found.expect("espressione con il NodeId dato")
+    }
+}
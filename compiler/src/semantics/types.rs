@@ -0,0 +1,466 @@
+//! Tipi del controllo graduale.
+//!
+//! `any` è il tipo dinamico: un valore `any` può essere usato dove è atteso
+//! qualsiasi tipo e viceversa. Per tutti gli altri tipi l'assegnabilità segue
+//! la relazione di sottotipo: gli oggetti e le funzioni sono confrontati per
+//! struttura, le classi per nome risalendo la catena degli `extends`.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::ast::{FunctionTypeParam, Ident, NodeIdGen, ObjectTypeMember, TypeExpr, TypeExprKind};
+use crate::lexer::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    /// Tipo dinamico, compatibile con ogni altro tipo in entrambe le direzioni
+    Any,
+    Number,
+    String,
+    Boolean,
+    Null,
+    /// Risultato di una funzione che non restituisce valori
+    Void,
+    /// Tipo senza valori, sottotipo di ogni altro tipo
+    Never,
+    Array(Box<Type>),
+    Tuple(Vec<Type>),
+    /// Membri ordinati per nome
+    Object(Vec<Member>),
+    Function(FunctionType),
+    /// Alternative senza duplicati né unioni annidate, costruite con [`Type::union`]
+    Union(Vec<Type>),
+    Intersection(Vec<Type>),
+    /// Tipo nominale: una classe, un enum, un parametro di tipo o un tipo
+    /// generico della libreria come `Map<K, V>`
+    Named {
+        name: String,
+        args: Vec<Type>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub name: String,
+    pub ty: Type,
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionType {
+    pub params: Vec<Type>,
+    /// Numero di argomenti obbligatori: i parametri successivi hanno un
+    /// valore predefinito
+    pub required: usize,
+    /// Tipo array del parametro rest (`...args: number[]`)
+    pub rest: Option<Box<Type>>,
+    pub ret: Box<Type>,
+}
+
+impl Type {
+    /// Unione dei tipi: le unioni annidate sono appiattite, i duplicati e
+    /// `never` eliminati, e un'unione con `any` è `any`
+    pub fn union(types: impl IntoIterator<Item = Type>) -> Type {
+        let mut alternatives: Vec<Type> = Vec::new();
+        for ty in types {
+            let flattened = match ty {
+                Type::Union(inner) => inner,
+                Type::Any => return Type::Any,
+                Type::Never => continue,
+                ty => vec![ty],
+            };
+            for ty in flattened {
+                if !alternatives.contains(&ty) {
+                    alternatives.push(ty);
+                }
+            }
+        }
+        
+        match alternatives.len() {
+            0 => Type::Never,
+            1 => alternatives.remove(0),
+            _ => Type::Union(alternatives),
+        }
+    }
+    
+    /// `T?`
+    pub fn nullable(self) -> Type {
+        Type::union([self, Type::Null])
+    }
+    
+    /// Il tipo senza `null`
+    pub fn non_null(&self) -> Type {
+        match self {
+            Type::Union(alternatives) => Type::union(alternatives.iter().filter(|ty| **ty != Type::Null).cloned()),
+            ty => ty.clone(),
+        }
+    }
+    
+    /// Oggetto con i membri ordinati per nome; a parità di nome vale l'ultimo
+    pub fn object(members: impl IntoIterator<Item = Member>) -> Type {
+        let mut sorted: Vec<Member> = Vec::new();
+        for member in members {
+            match sorted.binary_search_by(|m| m.name.cmp(&member.name)) {
+                Ok(index) => sorted[index] = member,
+                Err(index) => sorted.insert(index, member),
+            }
+        }
+        Type::Object(sorted)
+    }
+    
+    /// Annotazione equivalente, usata per i controlli inseriti nel programma.
+    /// I nodi creati prendono gli id da `ids`.
+    pub fn to_type_expr(&self, span: Span, ids: &mut NodeIdGen) -> TypeExpr {
+        let named = |name: &str, args: Vec<TypeExpr>| TypeExprKind::Named {
+            name: Ident::new(name, span),
+            args,
+        };
+        let kind = match self {
+            Type::Any => named("any", Vec::new()),
+            Type::Number => named("number", Vec::new()),
+            Type::String => named("string", Vec::new()),
+            Type::Boolean => named("boolean", Vec::new()),
+            Type::Null => named("null", Vec::new()),
+            Type::Void => named("void", Vec::new()),
+            Type::Never => named("never", Vec::new()),
+            Type::Array(element) => TypeExprKind::Array(Box::new(element.to_type_expr(span, ids))),
+            Type::Tuple(elements) => TypeExprKind::Tuple(elements.iter().map(|ty| ty.to_type_expr(span, ids)).collect()),
+            Type::Object(members) => TypeExprKind::Object(
+                members
+                    .iter()
+                    .map(|member| ObjectTypeMember {
+                        name: Ident::new(&member.name, span),
+                        ty: Some(member.ty.to_type_expr(span, ids)),
+                        optional: member.optional,
+                        span,
+                    })
+                    .collect(),
+            ),
+            Type::Function(function) => TypeExprKind::Function {
+                params: function
+                    .params
+                    .iter()
+                    .map(|ty| FunctionTypeParam {
+                        name: None,
+                        ty: ty.to_type_expr(span, ids),
+                        span,
+                    })
+                    .collect(),
+                return_type: Box::new(function.ret.to_type_expr(span, ids)),
+            },
+            Type::Union(alternatives) => {
+                TypeExprKind::Union(alternatives.iter().map(|ty| ty.to_type_expr(span, ids)).collect())
+            },
+            Type::Intersection(types) => TypeExprKind::Intersection(types.iter().map(|ty| ty.to_type_expr(span, ids)).collect()),
+            Type::Named { name, args } => named(name, args.iter().map(|ty| ty.to_type_expr(span, ids)).collect()),
+        };
+        TypeExpr::new(ids.fresh(), kind, span)
+    }
+    
+    /// Tipo da racchiudere tra parentesi dentro un array, un nullable o un'unione
+    fn is_compound(&self) -> bool {
+        matches!(self, Type::Union(_) | Type::Intersection(_) | Type::Function(_))
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let grouped = |ty: &Type| {
+            if ty.is_compound() {
+                format!("({})", ty)
+            } else {
+                ty.to_string()
+            }
+        };
+        let list = |types: &[Type], separator: &str, group: bool| {
+            let types: Vec<String> = types.iter().map(|ty| if group { grouped(ty) } else { ty.to_string() }).collect();
+            types.join(separator)
+        };
+        
+        match self {
+            Type::Any => write!(f, "any"),
+            Type::Number => write!(f, "number"),
+            Type::String => write!(f, "string"),
+            Type::Boolean => write!(f, "boolean"),
+            Type::Null => write!(f, "null"),
+            Type::Void => write!(f, "void"),
+            Type::Never => write!(f, "never"),
+            Type::Array(element) => write!(f, "{}[]", grouped(element)),
+            Type::Tuple(elements) => write!(f, "[{}]", list(elements, ", ", false)),
+            Type::Object(members) if members.is_empty() => write!(f, "{{}}"),
+            Type::Object(members) => {
+                let members: Vec<String> = members
+                    .iter()
+                    .map(|m| format!("{}{}: {}", m.name, if m.optional { "?" } else { "" }, m.ty))
+                    .collect();
+                write!(f, "{{ {} }}", members.join(", "))
+            },
+            Type::Function(function) => {
+                let mut params: Vec<String> = function.params.iter().map(Type::to_string).collect();
+                if let Some(rest) = &function.rest {
+                    params.push(format!("...{}", rest));
+                }
+                write!(f, "({}) -> {}", params.join(", "), function.ret)
+            },
+            Type::Union(alternatives) => match alternatives.as_slice() {
+                [ty, Type::Null] | [Type::Null, ty] => write!(f, "{}?", grouped(ty)),
+                _ => write!(f, "{}", list(alternatives, " | ", true)),
+            },
+            Type::Intersection(types) => write!(f, "{}", list(types, " & ", true)),
+            Type::Named { name, args } if args.is_empty() => write!(f, "{}", name),
+            Type::Named { name, args } => write!(f, "{}<{}>", name, list(args, ", ", false)),
+        }
+    }
+}
+
+/// Membri e costruttore di una classe
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassType {
+    /// Classe estesa, se nota
+    pub parent: Option<Type>,
+    /// Campi e metodi delle istanze
+    pub members: Vec<Member>,
+    /// `None` se la classe eredita il costruttore della classe estesa
+    pub constructor: Option<FunctionType>,
+}
+
+/// Informazioni sulle classi necessarie per confrontare i tipi nominali
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TypeContext {
+    classes: HashMap<String, ClassType>,
+}
+
+impl TypeContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn add_class(&mut self, name: &str, class: ClassType) {
+        self.classes.insert(name.to_string(), class);
+    }
+    
+    pub fn class(&self, name: &str) -> Option<&ClassType> {
+        self.classes.get(name)
+    }
+    
+    /// Costruttore della classe, eventualmente ereditato. `None` se la classe
+    /// o quella da cui eredita il costruttore non è nota.
+    pub fn constructor(&self, name: &str) -> Option<FunctionType> {
+        let class = self.classes.get(name)?;
+        match (&class.constructor, &class.parent) {
+            (Some(constructor), _) => Some(constructor.clone()),
+            (None, Some(Type::Named { name, .. })) => self.constructor(name),
+            (None, Some(_)) => None,
+            (None, None) => Some(FunctionType {
+                params: Vec::new(),
+                required: 0,
+                rest: None,
+                ret: Box::new(Type::Void),
+            }),
+        }
+    }
+    
+    /// Tipo della proprietà `name` di un valore di tipo `ty`. `None` se il
+    /// tipo non ha sicuramente la proprietà; i tipi di cui non sono noti i
+    /// membri, come i metodi degli array, danno `any`.
+    pub fn member(&self, ty: &Type, name: &str) -> Option<Type> {
+        match ty {
+            Type::Array(_) | Type::Tuple(_) | Type::String if name == "length" => Some(Type::Number),
+            Type::Object(members) => members.iter().find(|m| m.name == name).map(|m| match m.optional {
+                true => m.ty.clone().nullable(),
+                false => m.ty.clone(),
+            }),
+            Type::Named { name: class, .. } => match self.classes.get(class) {
+                Some(class) => match class.members.iter().find(|m| m.name == name) {
+                    Some(member) => Some(member.ty.clone()),
+                    None => match &class.parent {
+                        Some(parent) => self.member(parent, name),
+                        None => None,
+                    },
+                },
+                None => Some(Type::Any),
+            },
+            Type::Union(alternatives) => {
+                let types: Option<Vec<Type>> = alternatives.iter().map(|ty| self.member(ty, name)).collect();
+                types.map(Type::union)
+            },
+            Type::Intersection(types) => types.iter().find_map(|ty| self.member(ty, name)),
+            Type::Null | Type::Void => None,
+            Type::Never => Some(Type::Never),
+            _ => Some(Type::Any),
+        }
+    }
+    
+    /// Un valore di tipo `from` può essere usato dove è atteso `to`. Rispetto
+    /// a [`TypeContext::is_subtype`] accetta `any` in entrambe le direzioni.
+    pub fn is_assignable(&self, from: &Type, to: &Type) -> bool {
+        self.relate(from, to, true)
+    }
+    
+    /// `sub` è un sottotipo di `sup`. `any` è il supertipo di ogni tipo ma non
+    /// è sottotipo di altri tipi.
+    pub fn is_subtype(&self, sub: &Type, sup: &Type) -> bool {
+        self.relate(sub, sup, false)
+    }
+    
+    fn relate(&self, from: &Type, to: &Type, gradual: bool) -> bool {
+        if from == to {
+            return true;
+        }
+        
+        match (from, to) {
+            (_, Type::Any) | (Type::Never, _) => true,
+            (Type::Any, _) => gradual,
+            (Type::Union(alternatives), _) => alternatives.iter().all(|ty| self.relate(ty, to, gradual)),
+            (_, Type::Union(alternatives)) => alternatives.iter().any(|ty| self.relate(from, ty, gradual)),
+            (_, Type::Intersection(types)) => types.iter().all(|ty| self.relate(from, ty, gradual)),
+            (Type::Intersection(types), _) => types.iter().any(|ty| self.relate(ty, to, gradual)),
+            (Type::Array(from), Type::Array(to)) => self.relate(from, to, gradual),
+            (Type::Tuple(from), Type::Tuple(to)) => {
+                from.len() == to.len() && from.iter().zip(to).all(|(from, to)| self.relate(from, to, gradual))
+            },
+            (Type::Tuple(elements), Type::Array(to)) => elements.iter().all(|ty| self.relate(ty, to, gradual)),
+            (_, Type::Object(members)) => members.iter().all(|member| match self.member(from, &member.name) {
+                Some(ty) if member.optional => self.relate(&ty, &member.ty.clone().nullable(), gradual),
+                Some(ty) => self.relate(&ty, &member.ty, gradual),
+                None => member.optional,
+            }) && matches!(from, Type::Object(_) | Type::Named { .. }),
+            (Type::Function(from), Type::Function(to)) => self.relate_functions(from, to, gradual),
+            (Type::Named { name, args }, Type::Named { name: other, args: other_args }) if name == other => {
+                // I parametri di tipo sono invarianti; senza argomenti valgono `any`
+                args.is_empty()
+                    || other_args.is_empty()
+                    || args.len() == other_args.len()
+                        && args
+                        .iter()
+                        .zip(other_args)
+                        .all(|(a, b)| self.relate(a, b, gradual) && self.relate(b, a, gradual))
+            },
+            (Type::Named { name, .. }, Type::Named { .. }) => {
+                match self.classes.get(name).and_then(|class| class.parent.as_ref()) {
+                    Some(parent) => self.relate(parent, to, gradual),
+                    None => false,
+                }
+            },
+            _ => false,
+        }
+    }
+    
+    /// Una funzione `from` può sostituire `to` se accetta tutti gli argomenti
+    /// con cui `to` può essere chiamata e restituisce un valore accettabile
+    fn relate_functions(&self, from: &FunctionType, to: &FunctionType, gradual: bool) -> bool {
+        if from.required > to.required || (from.params.len() < to.params.len() && from.rest.is_none() && to.rest.is_some()) {
+            return false;
+        }
+        let params = from.params.iter().zip(&to.params).all(|(from, to)| self.relate(to, from, gradual));
+        let ret = *to.ret == Type::Void || self.relate(&from.ret, &to.ret, gradual);
+        params && ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn function(params: Vec<Type>, ret: Type) -> Type {
+        Type::Function(FunctionType {
+            required: params.len(),
+            params,
+            rest: None,
+            ret: Box::new(ret),
+        })
+    }
+    
+    fn member(name: &str, ty: Type) -> Member {
+        Member {
+            name: name.to_string(),
+            ty,
+            optional: false,
+        }
+    }
+    
+    fn named(name: &str) -> Type {
+        Type::Named {
+            name: name.to_string(),
+            args: Vec::new(),
+        }
+    }
+    
+    #[test]
+    fn test_display() {
+        let point = Type::object([member("y", Type::Number), member("x", Type::Number)]);
+        assert_eq!(point.to_string(), "{ x: number, y: number }");
+        assert_eq!(Type::union([Type::String, Type::Number]).to_string(), "string | number");
+        assert_eq!(Type::Array(Box::new(Type::String.nullable())).to_string(), "(string?)[]");
+        assert_eq!(Type::Array(Box::new(Type::union([Type::String, Type::Number]))).to_string(), "(string | number)[]");
+        assert_eq!(function(vec![Type::Number, Type::Tuple(vec![Type::String, Type::Boolean])], Type::Void).to_string(), "(number, [string, boolean]) -> void");
+        assert_eq!(Type::Intersection(vec![named("A"), point]).to_string(), "A & { x: number, y: number }");
+    }
+    
+    #[test]
+    fn test_unions_are_normalized() {
+        assert_eq!(Type::union([Type::Number, Type::union([Type::String, Type::Number]), Type::Never]), Type::Union(vec![Type::Number, Type::String]));
+        assert_eq!(Type::union([Type::Number, Type::Any]), Type::Any);
+        assert_eq!(Type::union([Type::Number]), Type::Number);
+        assert_eq!(Type::String.nullable().non_null(), Type::String);
+    }
+    
+    #[test]
+    fn test_assignability() {
+        let context = TypeContext::new();
+        let point = Type::object([member("x", Type::Number), member("y", Type::Number)]);
+        let point3 = Type::object([member("x", Type::Number), member("y", Type::Number), member("z", Type::Number)]);
+        let nullable = Type::Number.nullable();
+        
+        assert!(context.is_assignable(&Type::Number, &nullable));
+        assert!(!context.is_assignable(&nullable, &Type::Number));
+        assert!(context.is_assignable(&point3, &point));
+        assert!(!context.is_assignable(&point, &point3));
+        assert!(context.is_assignable(&Type::Tuple(vec![Type::Number, Type::Number]), &Type::Array(Box::new(Type::Number))));
+        assert!(!context.is_assignable(&Type::Array(Box::new(Type::String)), &Type::Array(Box::new(Type::Number))));
+        assert!(context.is_assignable(&Type::Number, &Type::Intersection(vec![Type::Number, nullable.clone()])));
+        
+        // Parametri controvarianti, risultato covariante
+        let takes_point = function(vec![point.clone()], Type::Number);
+        let takes_point3 = function(vec![point3.clone()], Type::Number);
+        assert!(context.is_assignable(&takes_point, &takes_point3));
+        assert!(!context.is_assignable(&takes_point3, &takes_point));
+        assert!(context.is_assignable(&function(vec![], Type::Number), &function(vec![Type::String], Type::Void)));
+    }
+    
+    #[test]
+    fn test_any_is_compatible_but_not_a_subtype() {
+        let context = TypeContext::new();
+        assert!(context.is_assignable(&Type::Any, &Type::Number));
+        assert!(context.is_assignable(&Type::Number, &Type::Any));
+        assert!(!context.is_subtype(&Type::Any, &Type::Number));
+        assert!(context.is_subtype(&Type::Number, &Type::Any));
+        assert!(context.is_assignable(&Type::Array(Box::new(Type::Any)), &Type::Array(Box::new(Type::String))));
+        assert!(!context.is_subtype(&Type::Array(Box::new(Type::Any)), &Type::Array(Box::new(Type::String))));
+    }
+    
+    #[test]
+    fn test_classes_are_nominal_and_follow_extends() {
+        let mut context = TypeContext::new();
+        let Type::Function(constructor) = function(vec![Type::String], Type::Void) else {
+            unreachable!()
+        };
+        context.add_class("Animal", ClassType {
+            parent: None,
+            members: vec![member("name", Type::String)],
+            constructor: Some(constructor.clone()),
+        });
+        context.add_class("Dog", ClassType {
+            parent: Some(named("Animal")),
+            members: vec![member("bark", function(vec![], Type::Void))],
+            constructor: None,
+        });
+        
+        assert!(context.is_assignable(&named("Dog"), &named("Animal")));
+        assert!(!context.is_assignable(&named("Animal"), &named("Dog")));
+        assert_eq!(context.member(&named("Dog"), "name"), Some(Type::String));
+        assert_eq!(context.member(&named("Dog"), "age"), None);
+        assert!(context.is_assignable(&named("Dog"), &Type::object([member("name", Type::String)])));
+        assert_eq!(context.constructor("Dog"), Some(constructor));
+    }
+}
@@ -0,0 +1,390 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::ast::visit::{
+    walk_block, walk_catch_clause, walk_class_member, walk_decorator, walk_enum_variant, walk_expr,
+    walk_interface_member, walk_match_arm, walk_param, walk_pattern, walk_property, walk_stmt, walk_type,
+    walk_type_param, Visitor,
+};
+use crate::ast::{
+    Block, CatchClause, ClassMember, Decorator, EnumVariant, Expr, InterfaceMember, MatchArm, Param, Pattern, Property,
+    Stmt, TypeExpr, TypeParam,
+};
+use crate::error::Error;
+use crate::lexer::{Lexer, Span, Token};
+use crate::parser::Parser;
+
+use super::typed::{AstNode, SourceFile};
+use super::{GreenCache, GreenElement, GreenNode, SyntaxKind, SyntaxNode};
+
+/// CST di un file sorgente, con gli errori trovati costruendolo
+#[derive(Debug, Clone)]
+pub struct SyntaxTree {
+    root: SyntaxNode,
+    errors: Vec<Error>,
+}
+
+impl SyntaxTree {
+    /// Costruisce il CST di `source`. Anche un sorgente con errori produce un
+    /// albero che contiene tutto il testo.
+    pub fn parse(source: &str) -> Self {
+        Self::parse_with_cache(source, &mut GreenCache::new())
+    }
+    
+    /// Come [`SyntaxTree::parse`], ma i nodi green sono presi da `cache`:
+    /// dopo una modifica i sottoalberi che non cambiano sono gli stessi nodi
+    /// dell'albero precedente costruito con la stessa tabella
+    pub fn parse_with_cache(source: &str, cache: &mut GreenCache) -> Self {
+        let (tokens, structure, errors) = match Lexer::new(source).tokenize() {
+            Ok(tokens) => {
+                let mut parser = Parser::new(tokens.clone());
+                let program = parser.parse_program();
+                let mut structure = Structure::default();
+                structure.visit_program(&program);
+                (tokens, structure.finish(), parser.take_errors())
+            },
+            Err(error) => (Vec::new(), Frame::root(), vec![error]),
+        };
+        
+        let mut builder = Builder {
+            leaves: leaves(source, &tokens),
+            next: 0,
+            source,
+            cache,
+        };
+        let root = builder.node(structure);
+        Self {
+            root: SyntaxNode::new_root(root),
+            errors,
+        }
+    }
+    
+    pub fn root(&self) -> &SyntaxNode {
+        &self.root
+    }
+    
+    pub fn source_file(&self) -> SourceFile {
+        SourceFile::cast(self.root.clone()).expect("la radice è sempre un file sorgente")
+    }
+    
+    pub fn green(&self) -> &Arc<GreenNode> {
+        self.root.green()
+    }
+    
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+}
+
+/// Foglie del CST: i token del lexer con i loro intervalli e, negli spazi tra
+/// un token e l'altro, la trivia. Se il lexer fallisce tutto il testo è una
+/// foglia `Error`.
+fn leaves(source: &str, tokens: &[Token]) -> Vec<(SyntaxKind, Range<usize>)> {
+    if tokens.is_empty() {
+        return vec![(SyntaxKind::Error, 0..source.len())];
+    }
+    
+    let mut leaves = Vec::with_capacity(tokens.len() * 2);
+    let mut offset = 0;
+    for token in tokens {
+        // I token di layout possono precedere la fine del token precedente
+        let start = token.span.start.offset.max(offset);
+        let end = token.span.end.offset.max(start);
+        trivia(source, offset..start, &mut leaves);
+        leaves.push((SyntaxKind::Token(token.token_type), start..end));
+        offset = end;
+    }
+    trivia(source, offset..source.len(), &mut leaves);
+    leaves
+}
+
+/// Divide il testo tra due token in spazi e commenti
+fn trivia(source: &str, range: Range<usize>, leaves: &mut Vec<(SyntaxKind, Range<usize>)>) {
+    let mut start = range.start;
+    while start < range.end {
+        let rest = &source[start..range.end];
+        let (kind, len) = if rest.starts_with("//") {
+            (SyntaxKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else if rest.starts_with("/*") {
+            (SyntaxKind::Comment, rest.find("*/").map_or(rest.len(), |end| end + 2))
+        } else {
+            match rest.find(|c: char| !c.is_whitespace()).unwrap_or(rest.len()) {
+                0 => (SyntaxKind::Error, rest.chars().next().map_or(1, char::len_utf8)),
+                len => (SyntaxKind::Whitespace, len),
+            }
+        };
+        leaves.push((kind, start..start + len));
+        start += len;
+    }
+}
+
+/// Nodo del CST ancora senza foglie: tipo, intervallo e figli
+#[derive(Debug)]
+struct Frame {
+    kind: SyntaxKind,
+    range: Range<usize>,
+    children: Vec<Frame>,
+}
+
+impl Frame {
+    fn root() -> Self {
+        Frame {
+            kind: SyntaxKind::SourceFile,
+            range: 0..usize::MAX,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Ricava dall'AST la struttura dei nodi del CST
+struct Structure {
+    stack: Vec<Frame>,
+}
+
+impl Default for Structure {
+    fn default() -> Self {
+        Self {
+            stack: vec![Frame::root()],
+        }
+    }
+}
+
+impl Structure {
+    fn enter(&mut self, kind: SyntaxKind, span: Span) {
+        self.stack.push(Frame {
+            kind,
+            range: span.start.offset..span.end.offset,
+            children: Vec::new(),
+        });
+    }
+    
+    fn leave(&mut self) {
+        let mut frame = self.stack.pop().expect("`leave` senza `enter`");
+        // Un nodo comprende anche i figli che l'AST colloca fuori dal suo
+        // span, come i decoratori prima di una dichiarazione
+        frame.children.sort_by_key(|child| child.range.start);
+        for child in &frame.children {
+            frame.range.start = frame.range.start.min(child.range.start);
+            frame.range.end = frame.range.end.max(child.range.end);
+        }
+        self.stack.last_mut().expect("la radice non viene mai chiusa").children.push(frame);
+    }
+    
+    fn finish(mut self) -> Frame {
+        let mut root = self.stack.pop().expect("la radice non viene mai chiusa");
+        root.children.sort_by_key(|child| child.range.start);
+        root
+    }
+}
+
+impl<'ast> Visitor<'ast> for Structure {
+    fn visit_block(&mut self, block: &'ast Block) {
+        self.enter(SyntaxKind::Block, block.span);
+        walk_block(self, block);
+        self.leave();
+    }
+    
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        self.enter(SyntaxKind::from_stmt(&stmt.kind), stmt.span);
+        walk_stmt(self, stmt);
+        self.leave();
+    }
+    
+    fn visit_param(&mut self, param: &'ast Param) {
+        self.enter(SyntaxKind::Param, param.span);
+        walk_param(self, param);
+        self.leave();
+    }
+    
+    fn visit_type_param(&mut self, param: &'ast TypeParam) {
+        self.enter(SyntaxKind::TypeParam, param.span);
+        walk_type_param(self, param);
+        self.leave();
+    }
+    
+    fn visit_decorator(&mut self, decorator: &'ast Decorator) {
+        self.enter(SyntaxKind::Decorator, decorator.span);
+        walk_decorator(self, decorator);
+        self.leave();
+    }
+    
+    fn visit_class_member(&mut self, member: &'ast ClassMember) {
+        self.enter(SyntaxKind::ClassMember, member.span);
+        walk_class_member(self, member);
+        self.leave();
+    }
+    
+    fn visit_interface_member(&mut self, member: &'ast InterfaceMember) {
+        self.enter(SyntaxKind::InterfaceMember, member.span);
+        walk_interface_member(self, member);
+        self.leave();
+    }
+    
+    fn visit_enum_variant(&mut self, variant: &'ast EnumVariant) {
+        self.enter(SyntaxKind::EnumVariant, variant.span);
+        walk_enum_variant(self, variant);
+        self.leave();
+    }
+    
+    fn visit_catch_clause(&mut self, catch: &'ast CatchClause) {
+        self.enter(SyntaxKind::CatchClause, catch.span);
+        walk_catch_clause(self, catch);
+        self.leave();
+    }
+    
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        self.enter(SyntaxKind::from_expr(&expr.kind), expr.span);
+        walk_expr(self, expr);
+        self.leave();
+    }
+    
+    fn visit_property(&mut self, property: &'ast Property) {
+        self.enter(SyntaxKind::Property, property.span);
+        walk_property(self, property);
+        self.leave();
+    }
+    
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        self.enter(SyntaxKind::MatchArm, arm.span);
+        walk_match_arm(self, arm);
+        self.leave();
+    }
+    
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+        self.enter(SyntaxKind::Pattern, pattern.span);
+        walk_pattern(self, pattern);
+        self.leave();
+    }
+    
+    fn visit_type(&mut self, ty: &'ast TypeExpr) {
+        self.enter(SyntaxKind::Type, ty.span);
+        walk_type(self, ty);
+        self.leave();
+    }
+}
+
+/// Distribuisce le foglie nei nodi della struttura
+struct Builder<'a> {
+    leaves: Vec<(SyntaxKind, Range<usize>)>,
+    next: usize,
+    source: &'a str,
+    cache: &'a mut GreenCache,
+}
+
+impl Builder<'_> {
+    fn node(&mut self, frame: Frame) -> Arc<GreenNode> {
+        let mut children = Vec::new();
+        for child in frame.children {
+            self.leaves_before(child.range.start, &mut children);
+            children.push(GreenElement::Node(self.node(child)));
+        }
+        self.leaves_before(frame.range.end, &mut children);
+        self.cache.node(frame.kind, children)
+    }
+    
+    /// Aggiunge a `children` le foglie che iniziano prima di `offset`. La
+    /// trivia prima del primo token di un nodo e i token di layout vuoti al
+    /// suo inizio appartengono al genitore.
+    fn leaves_before(&mut self, offset: usize, children: &mut Vec<GreenElement>) {
+        while let Some((kind, range)) = self.leaves.get(self.next) {
+            if range.start > offset || (range.start == offset && !range.is_empty()) {
+                break;
+            }
+            let token = self.cache.token(*kind, &self.source[range.clone()]);
+            children.push(GreenElement::Token(token));
+            self.next += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::TokenType;
+    
+    const SOURCE: &str = "\
+// Somma due numeri
+fn add(a, b) /* inline */
+  let total = a + b  // risultato
+  
+  return total
+
+@logged
+class Counter
+  value = 0
+";
+    
+    #[test]
+    fn test_tree_is_lossless() {
+        let tree = SyntaxTree::parse(SOURCE);
+        assert!(tree.errors().is_empty());
+        assert_eq!(tree.root().text(), SOURCE);
+        assert_eq!(tree.root().text_range(), 0..SOURCE.len());
+        
+        let comments: Vec<String> = tree
+            .root()
+            .descendant_tokens()
+            .into_iter()
+            .filter(|token| token.kind() == SyntaxKind::Comment)
+            .map(|token| token.text().to_string())
+            .collect();
+        assert_eq!(comments, vec!["// Somma due numeri", "/* inline */", "// risultato"]);
+        
+        // Anche un sorgente che il lexer rifiuta viene conservato
+        let invalid = "let s = \"non chiusa\n";
+        assert_eq!(SyntaxTree::parse(invalid).root().text(), invalid);
+    }
+    
+    #[test]
+    fn test_offset_to_node() {
+        let tree = SyntaxTree::parse(SOURCE);
+        let offset = SOURCE.find("a + b").unwrap();
+        
+        let token = tree.root().token_at_offset(offset).unwrap();
+        assert_eq!(token.kind(), SyntaxKind::Token(TokenType::Identifier));
+        assert_eq!(token.text(), "a");
+        let kinds: Vec<SyntaxKind> = token.parent().ancestors().map(|node| node.kind()).collect();
+        assert_eq!(kinds, vec![
+            SyntaxKind::NameRef,
+            SyntaxKind::BinaryExpr,
+            SyntaxKind::VarDecl,
+            SyntaxKind::Block,
+            SyntaxKind::FnDecl,
+            SyntaxKind::SourceFile,
+        ]);
+        
+        // Il decoratore fa parte della dichiarazione della classe
+        let decorator = tree.root().token_at_offset(SOURCE.find("logged").unwrap()).unwrap();
+        assert!(decorator.parent().ancestors().any(|node| node.kind() == SyntaxKind::ClassDecl));
+        
+        let range = offset..offset + "a + b".len();
+        assert_eq!(tree.root().covering_node(range).kind(), SyntaxKind::BinaryExpr);
+    }
+    
+    #[test]
+    fn test_green_nodes_are_shared_between_edits() {
+        let mut cache = GreenCache::new();
+        let before = SyntaxTree::parse_with_cache(SOURCE, &mut cache);
+        let edited = SOURCE.replace("value = 0", "value = 1");
+        let after = SyntaxTree::parse_with_cache(&edited, &mut cache);
+        
+        let declarations = |tree: &SyntaxTree| tree.root().children().map(|node| node.green().clone()).collect::<Vec<_>>();
+        let (before, after) = (declarations(&before), declarations(&after));
+        assert!(Arc::ptr_eq(&before[0], &after[0]));
+        assert!(!Arc::ptr_eq(&before[1], &after[1]));
+    }
+    
+    #[test]
+    fn test_replace_keeps_untouched_subtrees() {
+        let tree = SyntaxTree::parse(SOURCE);
+        let class = tree.root().children().find(|node| node.kind() == SyntaxKind::ClassDecl).unwrap();
+        let replacement = SyntaxTree::parse("class Counter\n  value = 1\n");
+        let new_class = replacement.root().children().next().unwrap();
+        
+        let root = class.replace_with(new_class.green().clone());
+        assert_eq!(root.text(), SOURCE.replace("@logged\nclass Counter\n  value = 0", "class Counter\n  value = 1"));
+        let function = tree.root().children().next().unwrap();
+        let shared = |child: &GreenElement| matches!(child, GreenElement::Node(node) if Arc::ptr_eq(node, function.green()));
+        assert!(root.children().iter().any(shared));
+    }
+}
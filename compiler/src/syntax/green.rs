@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use super::SyntaxKind;
+
+/// Foglia immutabile: un token o un pezzo di trivia con il suo testo
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct GreenToken {
+    kind: SyntaxKind,
+    text: String,
+}
+
+impl GreenToken {
+    pub fn new(kind: SyntaxKind, text: &str) -> Self {
+        Self {
+            kind,
+            text: text.to_string(),
+        }
+    }
+    
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+    
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Nodo immutabile: conosce solo il proprio tipo, i figli e la lunghezza
+/// del testo che copre
+#[derive(Debug, PartialEq, Eq)]
+pub struct GreenNode {
+    kind: SyntaxKind,
+    text_len: usize,
+    children: Vec<GreenElement>,
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        let text_len = children.iter().map(GreenElement::text_len).sum();
+        Self { kind, text_len, children }
+    }
+    
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+    
+    /// Lunghezza in byte del testo coperto
+    pub fn text_len(&self) -> usize {
+        self.text_len
+    }
+    
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+    
+    /// Testo del sorgente coperto dal nodo, trivia compresa
+    pub fn text(&self) -> String {
+        let mut text = String::with_capacity(self.text_len);
+        self.write_text(&mut text);
+        text
+    }
+    
+    fn write_text(&self, text: &mut String) {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => node.write_text(text),
+                GreenElement::Token(token) => text.push_str(&token.text),
+            }
+        }
+    }
+    
+    /// Copia del nodo con il figlio `index` sostituito; gli altri figli sono
+    /// condivisi con l'originale
+    pub fn replace_child(&self, index: usize, child: GreenElement) -> GreenNode {
+        let mut children = self.children.clone();
+        children[index] = child;
+        GreenNode::new(self.kind, children)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GreenElement {
+    Node(Arc<GreenNode>),
+    Token(Arc<GreenToken>),
+}
+
+impl GreenElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            GreenElement::Node(node) => node.kind,
+            GreenElement::Token(token) => token.kind,
+        }
+    }
+    
+    pub fn text_len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.text_len,
+            GreenElement::Token(token) => token.text.len(),
+        }
+    }
+}
+
+/// Tabella che rende condivisi i sottoalberi uguali. Costruendo più versioni
+/// dello stesso file con la stessa tabella, le parti non modificate sono gli
+/// stessi nodi della versione precedente.
+#[derive(Debug, Default)]
+pub struct GreenCache {
+    tokens: HashMap<(SyntaxKind, String), Arc<GreenToken>>,
+    nodes: HashMap<(SyntaxKind, Vec<Interned>), Arc<GreenNode>>,
+}
+
+impl GreenCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn token(&mut self, kind: SyntaxKind, text: &str) -> Arc<GreenToken> {
+        self.tokens
+            .entry((kind, text.to_string()))
+            .or_insert_with(|| Arc::new(GreenToken::new(kind, text)))
+            .clone()
+    }
+    
+    /// Nodo con i figli dati. I figli vengono confrontati per identità, quindi
+    /// la condivisione funziona se sono stati creati anch'essi da questa tabella.
+    pub fn node(&mut self, kind: SyntaxKind, children: Vec<GreenElement>) -> Arc<GreenNode> {
+        let key = (kind, children.iter().cloned().map(Interned).collect());
+        self.nodes
+            .entry(key)
+            .or_insert_with(|| Arc::new(GreenNode::new(kind, children)))
+            .clone()
+    }
+}
+
+/// Figlio confrontato per identità. La chiave tiene vivo il figlio, così il
+/// suo indirizzo non può essere riusato da un altro nodo.
+#[derive(Debug)]
+struct Interned(GreenElement);
+
+impl Interned {
+    fn address(&self) -> *const () {
+        match &self.0 {
+            GreenElement::Node(node) => Arc::as_ptr(node).cast(),
+            GreenElement::Token(token) => Arc::as_ptr(token).cast(),
+        }
+    }
+}
+
+impl PartialEq for Interned {
+    fn eq(&self, other: &Self) -> bool {
+        self.address() == other.address()
+    }
+}
+
+impl Eq for Interned {}
+
+impl Hash for Interned {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.address().hash(state);
+    }
+}
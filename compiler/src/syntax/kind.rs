@@ -0,0 +1,145 @@
+use crate::ast::{ExprKind, StmtKind};
+use crate::lexer::TokenType;
+
+/// Tipo di un nodo o di una foglia del CST
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    // Foglie
+    /// Token prodotto dal lexer, compresi quelli di layout senza testo
+    /// (`Dedent`, `Eof`)
+    Token(TokenType),
+    /// Spazi e fine riga che non formano token
+    Whitespace,
+    Comment,
+    /// Testo che il lexer non è riuscito a leggere
+    Error,
+    
+    // Nodi
+    SourceFile,
+    Block,
+    
+    ExprStmt,
+    VarDecl,
+    FnDecl,
+    ClassDecl,
+    InterfaceDecl,
+    TypeAlias,
+    EnumDecl,
+    IfStmt,
+    ForStmt,
+    WhileStmt,
+    BreakStmt,
+    ContinueStmt,
+    ReturnStmt,
+    ThrowStmt,
+    TryStmt,
+    ImportDecl,
+    ExportDecl,
+    Pragma,
+    /// Decoratori che valgono per il blocco che li contiene
+    Decorators,
+    ErrorStmt,
+    
+    Param,
+    TypeParam,
+    Decorator,
+    ClassMember,
+    InterfaceMember,
+    EnumVariant,
+    CatchClause,
+    
+    Literal,
+    /// Uso di un nome in un'espressione
+    NameRef,
+    ThisExpr,
+    SuperExpr,
+    ArrayExpr,
+    ObjectExpr,
+    Property,
+    SpreadExpr,
+    UnaryExpr,
+    BinaryExpr,
+    AssignExpr,
+    CallExpr,
+    MemberExpr,
+    IndexExpr,
+    NewExpr,
+    IsExpr,
+    AsExpr,
+    NullCheckExpr,
+    TemplateExpr,
+    TaggedTemplateExpr,
+    LambdaExpr,
+    MatchExpr,
+    MatchArm,
+    YieldExpr,
+    ErrorExpr,
+    
+    Pattern,
+    Type,
+}
+
+impl SyntaxKind {
+    /// Spazi e commenti, che non hanno significato per il parser
+    pub fn is_trivia(self) -> bool {
+        matches!(self, SyntaxKind::Whitespace | SyntaxKind::Comment)
+    }
+    
+    /// Foglie: token, trivia e testo non valido
+    pub fn is_token(self) -> bool {
+        matches!(self, SyntaxKind::Token(_) | SyntaxKind::Whitespace | SyntaxKind::Comment | SyntaxKind::Error)
+    }
+    
+    pub(super) fn from_stmt(kind: &StmtKind) -> Self {
+        match kind {
+            StmtKind::Expr(_) => SyntaxKind::ExprStmt,
+            StmtKind::Var(_) => SyntaxKind::VarDecl,
+            StmtKind::Function(_) => SyntaxKind::FnDecl,
+            StmtKind::Class(_) => SyntaxKind::ClassDecl,
+            StmtKind::Interface(_) => SyntaxKind::InterfaceDecl,
+            StmtKind::TypeAlias(_) => SyntaxKind::TypeAlias,
+            StmtKind::Enum(_) => SyntaxKind::EnumDecl,
+            StmtKind::If(_) => SyntaxKind::IfStmt,
+            StmtKind::For(_) => SyntaxKind::ForStmt,
+            StmtKind::While(_) => SyntaxKind::WhileStmt,
+            StmtKind::Break => SyntaxKind::BreakStmt,
+            StmtKind::Continue => SyntaxKind::ContinueStmt,
+            StmtKind::Return(_) => SyntaxKind::ReturnStmt,
+            StmtKind::Throw(_) => SyntaxKind::ThrowStmt,
+            StmtKind::Try(_) => SyntaxKind::TryStmt,
+            StmtKind::Import(_) => SyntaxKind::ImportDecl,
+            StmtKind::Export(_) => SyntaxKind::ExportDecl,
+            StmtKind::Pragma(_) => SyntaxKind::Pragma,
+            StmtKind::Decorators(_) => SyntaxKind::Decorators,
+            StmtKind::Error => SyntaxKind::ErrorStmt,
+        }
+    }
+    
+    pub(super) fn from_expr(kind: &ExprKind) -> Self {
+        match kind {
+            ExprKind::Literal(_) => SyntaxKind::Literal,
+            ExprKind::Ident(_) => SyntaxKind::NameRef,
+            ExprKind::This => SyntaxKind::ThisExpr,
+            ExprKind::Super => SyntaxKind::SuperExpr,
+            ExprKind::Array(_) => SyntaxKind::ArrayExpr,
+            ExprKind::Object(_) => SyntaxKind::ObjectExpr,
+            ExprKind::Spread(_) => SyntaxKind::SpreadExpr,
+            ExprKind::Unary { .. } => SyntaxKind::UnaryExpr,
+            ExprKind::Binary { .. } => SyntaxKind::BinaryExpr,
+            ExprKind::Assign { .. } => SyntaxKind::AssignExpr,
+            ExprKind::Call { .. } => SyntaxKind::CallExpr,
+            ExprKind::Member { .. } => SyntaxKind::MemberExpr,
+            ExprKind::Index { .. } => SyntaxKind::IndexExpr,
+            ExprKind::New { .. } => SyntaxKind::NewExpr,
+            ExprKind::Is { .. } => SyntaxKind::IsExpr,
+            ExprKind::As { .. } => SyntaxKind::AsExpr,
+            ExprKind::NullCheck(_) => SyntaxKind::NullCheckExpr,
+            ExprKind::Template(_) => SyntaxKind::TemplateExpr,
+            ExprKind::TaggedTemplate { .. } => SyntaxKind::TaggedTemplateExpr,
+            ExprKind::Lambda(_) => SyntaxKind::LambdaExpr,
+            ExprKind::Match { .. } => SyntaxKind::MatchExpr,
+            ExprKind::Yield { .. } => SyntaxKind::YieldExpr,
+            ExprKind::Error => SyntaxKind::ErrorExpr,
+        }
+    }
+}
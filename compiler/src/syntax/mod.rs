@@ -0,0 +1,30 @@
+//! Albero sintattico concreto (CST) senza perdita di informazioni.
+//!
+//! Diversamente dall'AST, il CST conserva ogni carattere del sorgente: token,
+//! spazi, commenti e righe vuote. Concatenando il testo delle foglie si
+//! ottiene esattamente il file di partenza. È pensato per gli strumenti
+//! dell'editor, che devono passare da una posizione del cursore al nodo che
+//! la contiene e modificare il codice senza perdere i commenti.
+//!
+//! L'albero ha due livelli:
+//! - i nodi *green* ([`GreenNode`]) sono immutabili e non conoscono né la
+//!   propria posizione né il genitore, quindi possono essere condivisi: un
+//!   [`GreenCache`] restituisce lo stesso nodo per sottoalberi uguali, anche
+//!   tra due versioni dello stesso file;
+//! - i nodi *red* ([`SyntaxNode`]) sono creati al bisogno sopra i green e
+//!   aggiungono offset e genitore.
+//!
+//! Le viste di [`typed`] danno accesso per nome alle parti di un nodo. La
+//! struttura dei nodi viene dall'AST prodotto dal parser, i token e la trivia
+//! dal flusso di token del [`Lexer`](crate::lexer::Lexer).
+
+mod build;
+mod green;
+mod kind;
+mod node;
+pub mod typed;
+
+pub use build::SyntaxTree;
+pub use green::{GreenCache, GreenElement, GreenNode, GreenToken};
+pub use kind::SyntaxKind;
+pub use node::{SyntaxElement, SyntaxNode, SyntaxToken};
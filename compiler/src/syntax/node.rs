@@ -0,0 +1,248 @@
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::{GreenElement, GreenNode, GreenToken, SyntaxKind};
+
+/// Nodo del CST con posizione e genitore, creato al bisogno sopra un
+/// [`GreenNode`]. Clonarlo costa un incremento di contatore.
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: Arc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    /// Posizione tra i figli del genitore
+    index: usize,
+    /// Offset in byte dall'inizio del sorgente
+    offset: usize,
+}
+
+/// Foglia del CST con posizione e genitore
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Arc<GreenToken>,
+    parent: SyntaxNode,
+    index: usize,
+    offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Arc<GreenNode>) -> Self {
+        SyntaxNode(Rc::new(NodeData {
+            green,
+            parent: None,
+            index: 0,
+            offset: 0,
+        }))
+    }
+    
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind()
+    }
+    
+    pub fn green(&self) -> &Arc<GreenNode> {
+        &self.0.green
+    }
+    
+    /// Intervallo in byte del sorgente coperto dal nodo
+    pub fn text_range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.text_len()
+    }
+    
+    pub fn text(&self) -> String {
+        self.0.green.text()
+    }
+    
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+    
+    /// Il nodo stesso e i suoi antenati, fino alla radice
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> {
+        std::iter::successors(Some(self.clone()), SyntaxNode::parent)
+    }
+    
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> + '_ {
+        let mut offset = self.0.offset;
+        self.0.green.children().iter().enumerate().map(move |(index, child)| {
+            let element = match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                    green: green.clone(),
+                    parent: Some(self.clone()),
+                    index,
+                    offset,
+                }))),
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    green: green.clone(),
+                    parent: self.clone(),
+                    index,
+                    offset,
+                }),
+            };
+            offset += child.text_len();
+            element
+        })
+    }
+    
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> + '_ {
+        self.children_with_tokens().filter_map(|element| match element {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+    
+    /// Foglie figlie dirette del nodo, trivia compresa
+    pub fn child_tokens(&self) -> impl Iterator<Item = SyntaxToken> + '_ {
+        self.children_with_tokens().filter_map(|element| match element {
+            SyntaxElement::Token(token) => Some(token),
+            SyntaxElement::Node(_) => None,
+        })
+    }
+    
+    /// Il nodo e tutti i suoi discendenti, in ordine di sorgente
+    pub fn descendants(&self) -> Vec<SyntaxNode> {
+        let mut nodes = vec![self.clone()];
+        for child in self.children() {
+            nodes.extend(child.descendants());
+        }
+        nodes
+    }
+    
+    /// Tutte le foglie sotto il nodo, in ordine di sorgente
+    pub fn descendant_tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.descendant_tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+    
+    /// Foglia che contiene `offset`. Tra due foglie vince quella che inizia
+    /// in `offset`; alla fine del testo l'ultima foglia non vuota.
+    pub fn token_at_offset(&self, offset: usize) -> Option<SyntaxToken> {
+        let range = self.text_range();
+        if offset < range.start || offset > range.end || range.is_empty() {
+            return None;
+        }
+        let offset = offset.min(range.end - 1);
+        
+        let mut node = self.clone();
+        loop {
+            let child = node.children_with_tokens().find(|child| child.text_range().contains(&offset))?;
+            match child {
+                SyntaxElement::Node(child) => node = child,
+                SyntaxElement::Token(token) => return Some(token),
+            }
+        }
+    }
+    
+    /// Nodo più piccolo che contiene interamente `range`
+    pub fn covering_node(&self, range: Range<usize>) -> SyntaxNode {
+        let mut node = self.clone();
+        loop {
+            let child = node.children().find(|child| {
+                let child_range = child.text_range();
+                child_range.start <= range.start && range.end <= child_range.end && !child_range.is_empty()
+            });
+            match child {
+                Some(child) => node = child,
+                None => return node,
+            }
+        }
+    }
+    
+    /// Radice di un nuovo albero in cui questo nodo è sostituito da `green`.
+    /// Il resto dell'albero è condiviso con quello originale: vengono ricreati
+    /// solo gli antenati del nodo.
+    pub fn replace_with(&self, green: Arc<GreenNode>) -> Arc<GreenNode> {
+        match &self.0.parent {
+            None => green,
+            Some(parent) => {
+                let replaced = parent.0.green.replace_child(self.0.index, GreenElement::Node(green));
+                parent.replace_with(Arc::new(replaced))
+            },
+        }
+    }
+}
+
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0.green, &other.0.green) && self.0.offset == other.0.offset
+    }
+}
+
+impl Eq for SyntaxNode {}
+
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?}", self.kind(), self.text_range())
+    }
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> SyntaxKind {
+        self.green.kind()
+    }
+    
+    pub fn green(&self) -> &Arc<GreenToken> {
+        &self.green
+    }
+    
+    pub fn text(&self) -> &str {
+        self.green.text()
+    }
+    
+    pub fn text_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.green.text().len()
+    }
+    
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+    
+    /// Posizione tra i figli del genitore
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl PartialEq for SyntaxToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.green, &other.green) && self.offset == other.offset
+    }
+}
+
+impl Eq for SyntaxToken {}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?} {:?}", self.kind(), self.text_range(), self.text())
+    }
+}
+
+impl SyntaxElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            SyntaxElement::Node(node) => node.kind(),
+            SyntaxElement::Token(token) => token.kind(),
+        }
+    }
+    
+    pub fn text_range(&self) -> Range<usize> {
+        match self {
+            SyntaxElement::Node(node) => node.text_range(),
+            SyntaxElement::Token(token) => token.text_range(),
+        }
+    }
+}
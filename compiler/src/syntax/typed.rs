@@ -0,0 +1,247 @@
+//! Viste tipizzate sopra i nodi del CST.
+//!
+//! Una vista avvolge un [`SyntaxNode`] di un tipo preciso e ne restituisce le
+//! parti per nome. In un sorgente con errori di sintassi le parti mancanti
+//! sono `None`. Le viste non copiano nulla: [`AstNode::syntax`] restituisce il
+//! nodo, da cui si raggiungono posizione, trivia e genitore.
+
+use crate::lexer::TokenType;
+
+use super::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+
+pub trait AstNode: Sized {
+    fn can_cast(kind: SyntaxKind) -> bool;
+    
+    fn cast(node: SyntaxNode) -> Option<Self>;
+    
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+macro_rules! ast_node {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct $name(SyntaxNode);
+        
+        impl AstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                kind == SyntaxKind::$name
+            }
+            
+            fn cast(node: SyntaxNode) -> Option<Self> {
+                Self::can_cast(node.kind()).then(|| Self(node))
+            }
+            
+            fn syntax(&self) -> &SyntaxNode {
+                &self.0
+            }
+        }
+    };
+}
+
+ast_node!(
+    /// Radice del CST
+    SourceFile
+);
+ast_node!(Block);
+ast_node!(
+    /// `fn nome(parametri): tipo` seguito dal corpo
+    FnDecl
+);
+ast_node!(ClassDecl);
+ast_node!(
+    /// `let` o `const`
+    VarDecl
+);
+ast_node!(Param);
+ast_node!(Decorator);
+ast_node!(ClassMember);
+ast_node!(CallExpr);
+ast_node!(
+    /// Uso di un nome in un'espressione
+    NameRef
+);
+
+fn child<N: AstNode>(node: &SyntaxNode) -> Option<N> {
+    node.children().find_map(N::cast)
+}
+
+fn children<'a, N: AstNode + 'a>(node: &'a SyntaxNode) -> impl Iterator<Item = N> + 'a {
+    node.children().filter_map(N::cast)
+}
+
+fn token(node: &SyntaxNode, token_type: TokenType) -> Option<SyntaxToken> {
+    node.child_tokens().find(|token| token.kind() == SyntaxKind::Token(token_type))
+}
+
+/// Primo nodo figlio che segue il token `after`
+fn node_after(node: &SyntaxNode, after: TokenType) -> Option<SyntaxNode> {
+    node.children_with_tokens()
+        .skip_while(|element| element.kind() != SyntaxKind::Token(after))
+        .find_map(|element| match element {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+}
+
+impl SourceFile {
+    /// Istruzioni e dichiarazioni del modulo
+    pub fn statements(&self) -> impl Iterator<Item = SyntaxNode> + '_ {
+        self.0.children()
+    }
+}
+
+impl Block {
+    pub fn statements(&self) -> impl Iterator<Item = SyntaxNode> + '_ {
+        self.0.children()
+    }
+}
+
+impl FnDecl {
+    pub fn decorators(&self) -> impl Iterator<Item = Decorator> + '_ {
+        children(&self.0)
+    }
+    
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, TokenType::Identifier)
+    }
+    
+    /// `fn*`
+    pub fn is_generator(&self) -> bool {
+        token(&self.0, TokenType::Star).is_some()
+    }
+    
+    pub fn params(&self) -> impl Iterator<Item = Param> + '_ {
+        children(&self.0)
+    }
+    
+    /// Tipo di ritorno, dopo i due punti
+    pub fn return_type(&self) -> Option<SyntaxNode> {
+        node_after(&self.0, TokenType::Colon).filter(|node| node.kind() == SyntaxKind::Type)
+    }
+    
+    pub fn body(&self) -> Option<Block> {
+        child(&self.0)
+    }
+}
+
+impl ClassDecl {
+    pub fn decorators(&self) -> impl Iterator<Item = Decorator> + '_ {
+        children(&self.0)
+    }
+    
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, TokenType::Identifier)
+    }
+    
+    pub fn members(&self) -> impl Iterator<Item = ClassMember> + '_ {
+        children(&self.0)
+    }
+}
+
+impl VarDecl {
+    pub fn is_const(&self) -> bool {
+        token(&self.0, TokenType::Const).is_some()
+    }
+    
+    pub fn pattern(&self) -> Option<SyntaxNode> {
+        self.0.children().find(|node| node.kind() == SyntaxKind::Pattern)
+    }
+    
+    pub fn initializer(&self) -> Option<SyntaxNode> {
+        node_after(&self.0, TokenType::Equal)
+    }
+}
+
+impl Param {
+    pub fn pattern(&self) -> Option<SyntaxNode> {
+        self.0.children().find(|node| node.kind() == SyntaxKind::Pattern)
+    }
+    
+    pub fn default_value(&self) -> Option<SyntaxNode> {
+        node_after(&self.0, TokenType::Equal)
+    }
+}
+
+impl Decorator {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, TokenType::Identifier)
+    }
+}
+
+impl ClassMember {
+    pub fn decorators(&self) -> impl Iterator<Item = Decorator> + '_ {
+        children(&self.0)
+    }
+    
+    /// Nome del campo o del metodo; `None` per il costruttore
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, TokenType::Identifier)
+    }
+}
+
+impl CallExpr {
+    pub fn callee(&self) -> Option<SyntaxNode> {
+        self.0.children().next()
+    }
+    
+    /// Argomenti, esclusi gli argomenti di tipo
+    pub fn args(&self) -> impl Iterator<Item = SyntaxNode> + '_ {
+        self.0.children().skip(1).filter(|node| node.kind() != SyntaxKind::Type)
+    }
+}
+
+impl NameRef {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, TokenType::Identifier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::SyntaxTree;
+    
+    #[test]
+    fn test_typed_views() {
+        let source = "\
+@logged
+fn* numbers(start = 1, step): Generator<number>
+  const first = start  // primo valore
+  yield first
+  print(first, step)
+";
+        let tree = SyntaxTree::parse(source);
+        let function = tree.source_file().statements().find_map(FnDecl::cast).unwrap();
+        
+        assert_eq!(function.name().unwrap().text(), "numbers");
+        assert!(function.is_generator());
+        let decorators: Vec<String> = function.decorators().map(|d| d.name().unwrap().text().to_string()).collect();
+        assert_eq!(decorators, vec!["logged"]);
+        assert_eq!(function.return_type().unwrap().text(), "Generator<number>");
+        
+        let params: Vec<Param> = function.params().collect();
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].pattern().unwrap().text(), "start");
+        assert_eq!(params[0].default_value().unwrap().text(), "1");
+        assert!(params[1].default_value().is_none());
+        
+        let body = function.body().unwrap();
+        let declaration = body.statements().find_map(VarDecl::cast).unwrap();
+        assert!(declaration.is_const());
+        assert_eq!(declaration.initializer().unwrap().text(), "start");
+        // Il commento a fine riga resta nel CST, fuori dalla dichiarazione
+        assert_eq!(declaration.syntax().text(), "const first = start");
+        
+        let call = body
+            .syntax()
+            .descendants()
+            .into_iter()
+            .find_map(CallExpr::cast)
+            .unwrap();
+        let callee = NameRef::cast(call.callee().unwrap()).unwrap();
+        assert_eq!(callee.name().unwrap().text(), "print");
+        let args: Vec<String> = call.args().map(|arg| arg.text()).collect();
+        assert_eq!(args, vec!["first", "step"]);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::error::{Error, ErrorKind, Result};
+use crate::lexer::{keyword, Lexer, Token, TokenType};
+use crate::lint::LintLevel;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Nome del file di configurazione del progetto
+pub const CONFIG_FILE: &str = "nova.config.ns";
+
+/// Valore letto dal file di configurazione
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<ConfigValue>),
+    Object(Vec<(String, ConfigValue)>),
+}
+
+impl ConfigValue {
+    /// Restituisce il valore associato a `key` se questo è un oggetto
+    pub fn get(&self, key: &str) -> Option<&ConfigValue> {
+        match self {
+            ConfigValue::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    
+    /// Restituisce il valore raggiunto seguendo le chiavi in ordine
+    pub fn get_path(&self, path: &[&str]) -> Option<&ConfigValue> {
+        path.iter().try_fold(self, |value, key| value.get(key))
+    }
+}
+
+/// Configurazione del progetto letta da `nova.config.ns`
+#[derive(Debug, Clone)]
+pub struct ProjectConfig {
+    pub path: PathBuf,
+    pub root: ConfigValue,
+}
+
+impl ProjectConfig {
+    /// Carica la configurazione da un file
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = fs::read_to_string(path).map_err(|e| Error::io(e, path))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            root: parse_config(&source).map_err(|e| e.with_path(path))?,
+        })
+    }
+    
+    /// Cerca `nova.config.ns` a partire da `start` risalendo le directory padre
+    pub fn find(start: &Path) -> Result<Option<Self>> {
+        let start = if start.is_file() { start.parent() } else { Some(start) };
+        let mut dir = start.map(Path::to_path_buf);
+        
+        while let Some(current) = dir {
+            let candidate = current.join(CONFIG_FILE);
+            if candidate.is_file() {
+                return Self::load(&candidate).map(Some);
+            }
+            dir = current.parent().map(Path::to_path_buf);
+        }
+        
+        Ok(None)
+    }
+    
+    /// Livelli delle regole del linter da `lint.rules`
+    pub fn lint_rules(&self) -> Result<Vec<(String, LintLevel)>> {
+        let Some(rules) = self.root.get_path(&["lint", "rules"]) else {
+            return Ok(Vec::new());
+        };
+        let ConfigValue::Object(entries) = rules else {
+            return Err(self.error("`lint.rules` deve essere un oggetto".to_string()));
+        };
+        
+        entries
+            .iter()
+            .map(|(name, value)| {
+                let level = match value {
+                    ConfigValue::String(level) => LintLevel::from_config(level),
+                    ConfigValue::Number(level) => LintLevel::from_config(&level.to_string()),
+                    _ => None,
+                };
+                level
+                    .map(|level| (name.clone(), level))
+                    .ok_or_else(|| self.error(format!("Livello non valido per la regola `{}`: usa off, warn o error", name)))
+            })
+            .collect()
+    }
+    
+    fn error(&self, message: String) -> Error {
+        Error::without_position(ErrorKind::SyntaxError, message).with_path(&self.path)
+    }
+}
+
+/// Legge un file di configurazione nella forma `export default { ... }`.
+/// Sono ammessi solo valori letterali: oggetti, array, stringhe, numeri,
+/// booleani e `null`.
+pub fn parse_config(source: &str) -> Result<ConfigValue> {
+    let tokens: Vec<Token> = Lexer::new(source)
+        .tokenize()?
+        .into_iter()
+        .filter(|t| !matches!(t.token_type, TokenType::Newline | TokenType::Indent | TokenType::Dedent))
+        .collect();
+    let mut reader = ConfigReader { tokens, current: 0 };
+    
+    if reader.check(TokenType::Export) {
+        reader.advance();
+        reader.expect(TokenType::Default, "`default` dopo `export`")?;
+    }
+    let value = reader.value()?;
+    reader.expect(TokenType::Eof, "la fine del file")?;
+    
+    Ok(value)
+}
+
+/// Lettore a discesa ricorsiva per i valori letterali della configurazione
+struct ConfigReader {
+    tokens: Vec<Token>,
+    current: usize,
+}
+
+impl ConfigReader {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current.min(self.tokens.len() - 1)]
+    }
+    
+    fn check(&self, token_type: TokenType) -> bool {
+        self.peek().token_type == token_type
+    }
+    
+    fn advance(&mut self) -> Token {
+        let token = self.peek().clone();
+        if token.token_type != TokenType::Eof {
+            self.current += 1;
+        }
+        token
+    }
+    
+    fn expect(&mut self, token_type: TokenType, expected: &str) -> Result<Token> {
+        if self.check(token_type) {
+            Ok(self.advance())
+        } else {
+            Err(self.unexpected(expected))
+        }
+    }
+    
+    fn unexpected(&self, expected: &str) -> Error {
+        unexpected(self.peek(), expected)
+    }
+    
+    fn value(&mut self) -> Result<ConfigValue> {
+        let token = self.advance();
+        match token.token_type {
+            TokenType::LeftBrace => self.object(),
+            TokenType::LeftBracket => self.array(),
+            TokenType::StringLiteral => Ok(ConfigValue::String(token.lexeme)),
+            TokenType::NumberLiteral => Ok(ConfigValue::Number(parse_number(&token)?)),
+            TokenType::Minus => {
+                let number = self.expect(TokenType::NumberLiteral, "un numero")?;
+                Ok(ConfigValue::Number(-parse_number(&number)?))
+            },
+            TokenType::True => Ok(ConfigValue::Bool(true)),
+            TokenType::False => Ok(ConfigValue::Bool(false)),
+            TokenType::Null => Ok(ConfigValue::Null),
+            _ => Err(unexpected(&token, "un valore")),
+        }
+    }
+    
+    fn object(&mut self) -> Result<ConfigValue> {
+        let mut entries = Vec::new();
+        
+        while !self.check(TokenType::RightBrace) {
+            let key = self.advance();
+            // Le chiavi possono essere identificatori, parole chiave o stringhe
+            let valid_key = matches!(key.token_type, TokenType::Identifier | TokenType::StringLiteral)
+                || keyword(&key.lexeme).is_some();
+            if !valid_key {
+                return Err(unexpected(&key, "una chiave"));
+            }
+            self.expect(TokenType::Colon, "':'")?;
+            entries.push((key.lexeme, self.value()?));
+            
+            if !self.check(TokenType::RightBrace) {
+                self.expect(TokenType::Comma, "',' o '}'")?;
+            }
+        }
+        self.advance();
+        
+        Ok(ConfigValue::Object(entries))
+    }
+    
+    fn array(&mut self) -> Result<ConfigValue> {
+        let mut items = Vec::new();
+        
+        while !self.check(TokenType::RightBracket) {
+            items.push(self.value()?);
+            if !self.check(TokenType::RightBracket) {
+                self.expect(TokenType::Comma, "',' o ']'")?;
+            }
+        }
+        self.advance();
+        
+        Ok(ConfigValue::Array(items))
+    }
+}
+
+fn unexpected(token: &Token, expected: &str) -> Error {
+    Error::new(
+        ErrorKind::SyntaxError,
+        format!("Atteso {} nella configurazione, trovato '{}'", expected, token.lexeme),
+        token.span,
+    )
+}
+
+fn parse_number(token: &Token) -> Result<f64> {
+    token.lexeme.parse().map_err(|_| {
+        Error::new(
+            ErrorKind::SyntaxError,
+            format!("Numero non valido: '{}'", token.lexeme),
+            token.span,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_parse_lint_rules() {
+        let source = r#"// nova.config.ns
+
+export default {
+  name: "mia-app",
+  build: {
+    optimizations: {
+      level: 3,
+    }
+  },
+  lint: {
+    rules: {
+      "no-unused-vars": "warn",
+      "no-tabs": "off",
+      "assign-in-condition": 2
+    },
+    ignoreFiles: ["dist/**/*"]
+  },
+  plugins: [
+    "nova-plugin-react",
+    ["nova-plugin-css", { modules: true }]
+  ]
+}
+"#;
+        let root = parse_config(source).unwrap();
+        assert_eq!(root.get_path(&["build", "optimizations", "level"]), Some(&ConfigValue::Number(3.0)));
+        
+        let config = ProjectConfig {
+            path: PathBuf::from(CONFIG_FILE),
+            root,
+        };
+        assert_eq!(config.lint_rules().unwrap(), vec![
+            ("no-unused-vars".to_string(), LintLevel::Warn),
+            ("no-tabs".to_string(), LintLevel::Allow),
+            ("assign-in-condition".to_string(), LintLevel::Deny),
+        ]);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::error::Error;
+use crate::lexer::Span;
+use crate::lint::{LintLevel, LintLevels};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Livello di gravità di una diagnostica
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Warning => write!(f, "avviso"),
+            Level::Error => write!(f, "errore"),
+        }
+    }
+}
+
+/// Grado di affidabilità di una correzione proposta
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Applicability {
+    /// La correzione è sicuramente corretta e può essere applicata automaticamente
+    MachineApplicable,
+    /// La correzione è probabilmente quella voluta, ma va verificata
+    MaybeIncorrect,
+    /// La sostituzione contiene segnaposto che l'utente deve completare
+    HasPlaceholders,
+}
+
+/// Correzione proposta: sostituisce il testo in `span` con `replacement`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub span: Span,
+    pub replacement: String,
+    pub message: String,
+    pub applicability: Applicability,
+}
+
+impl Suggestion {
+    /// Crea una nuova correzione proposta
+    pub fn new(span: Span, replacement: String, message: String, applicability: Applicability) -> Self {
+        Self {
+            span,
+            replacement,
+            message,
+            applicability,
+        }
+    }
+    
+    /// Verifica se la correzione può essere applicata senza intervento dell'utente
+    pub fn is_machine_applicable(&self) -> bool {
+        self.applicability == Applicability::MachineApplicable
+    }
+}
+
+/// Diagnostica emessa dal compilatore o dal linter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub level: Level,
+    /// Nome della regola che ha generato la diagnostica, se presente
+    pub code: Option<String>,
+    pub message: String,
+    pub span: Option<Span>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    /// Crea una nuova diagnostica
+    pub fn new(level: Level, message: String, span: Option<Span>) -> Self {
+        Self {
+            level,
+            code: None,
+            message,
+            span,
+            suggestions: Vec::new(),
+        }
+    }
+    
+    /// Crea una diagnostica di errore
+    pub fn error(message: String, span: Span) -> Self {
+        Self::new(Level::Error, message, Some(span))
+    }
+    
+    /// Crea una diagnostica di avviso
+    pub fn warning(message: String, span: Span) -> Self {
+        Self::new(Level::Warning, message, Some(span))
+    }
+    
+    /// Imposta il nome della regola
+    pub fn with_code(mut self, code: &str) -> Self {
+        self.code = Some(code.to_string());
+        self
+    }
+    
+    /// Aggiunge una correzione proposta
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+}
+
+/// Conversione di errori del compilatore in diagnostiche
+impl From<&Error> for Diagnostic {
+    fn from(error: &Error) -> Self {
+        Diagnostic {
+            level: Level::Error,
+            code: None,
+            message: error.message.clone(),
+            span: error.span,
+            suggestions: error.suggestions.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.level)?;
+        if let Some(code) = &self.code {
+            write!(f, "[{}]", code)?;
+        }
+        match &self.span {
+            Some(span) => write!(f, " a {}: {}", span.start, self.message)?,
+            None => write!(f, ": {}", self.message)?,
+        }
+        for suggestion in &self.suggestions {
+            write!(f, "\n  suggerimento: {}", suggestion.message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Raccolta delle diagnostiche emesse durante un'analisi.
+///
+/// Le diagnostiche con un codice di regola passano per i livelli configurati:
+/// vengono scartate, declassate ad avviso o promosse a errore in base al
+/// livello effettivo della regola nel punto in cui sono emesse.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+    levels: LintLevels,
+}
+
+impl Diagnostics {
+    /// Crea una raccolta vuota con i livelli predefiniti
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Crea una raccolta vuota che applica i livelli indicati
+    pub fn with_levels(levels: LintLevels) -> Self {
+        Self {
+            diagnostics: Vec::new(),
+            levels,
+        }
+    }
+    
+    /// Aggiunge una diagnostica, applicando il livello effettivo della sua regola
+    pub fn push(&mut self, mut diagnostic: Diagnostic) {
+        if let Some(code) = &diagnostic.code {
+            match self.levels.level_for(code, diagnostic.span) {
+                LintLevel::Allow => return,
+                LintLevel::Warn => diagnostic.level = Level::Warning,
+                LintLevel::Deny => diagnostic.level = Level::Error,
+            }
+        }
+        self.diagnostics.push(diagnostic);
+    }
+    
+    /// Verifica se è stato emesso almeno un errore
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.level == Level::Error)
+    }
+    
+    /// Numero di diagnostiche emesse
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+    
+    /// Verifica se non è stata emessa alcuna diagnostica
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+    
+    /// Itera sulle diagnostiche emesse
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter()
+    }
+    
+    /// Itera su tutte le correzioni proposte
+    pub fn suggestions(&self) -> impl Iterator<Item = &Suggestion> {
+        self.diagnostics.iter().flat_map(|d| d.suggestions.iter())
+    }
+    
+    /// Restituisce le diagnostiche ordinate per posizione
+    pub fn into_sorted_vec(mut self) -> Vec<Diagnostic> {
+        self.diagnostics
+            .sort_by_key(|d| d.span.map_or(0, |span| span.start.offset));
+        self.diagnostics
+    }
+}
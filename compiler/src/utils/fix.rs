@@ -0,0 +1,127 @@
+use crate::utils::diagnostics::Suggestion;
+
+/// Risultato dell'applicazione delle correzioni a un sorgente
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixResult {
+    /// Sorgente con le correzioni applicate
+    pub source: String,
+    /// Numero di correzioni applicate
+    pub applied: usize,
+    /// Numero di correzioni scartate perché sovrapposte ad altre
+    pub skipped: usize,
+}
+
+/// Applica al sorgente le correzioni applicabili automaticamente.
+///
+/// Le correzioni vengono applicate in ordine di posizione; quelle che si
+/// sovrappongono a una correzione già accettata vengono scartate. Dato che gli
+/// intervalli si riferiscono al sorgente originale, le correzioni scartate vanno
+/// ricalcolate con un nuovo passaggio dell'analisi sul risultato.
+pub fn apply_suggestions<'a, I>(source: &str, suggestions: I) -> FixResult
+where
+    I: IntoIterator<Item = &'a Suggestion>,
+{
+    let mut edits: Vec<&Suggestion> = suggestions
+        .into_iter()
+        .filter(|s| s.is_machine_applicable())
+        .filter(|s| is_valid_range(source, s.span.range()))
+        .collect();
+    edits.sort_by_key(|s| (s.span.start.offset, s.span.end.offset));
+    // Due diagnostiche possono proporre esattamente la stessa modifica
+    edits.dedup_by(|a, b| a.span.range() == b.span.range() && a.replacement == b.replacement);
+    
+    let mut output = String::with_capacity(source.len());
+    let mut cursor = 0;
+    let mut last: Option<std::ops::Range<usize>> = None;
+    let mut applied = 0;
+    let mut skipped = 0;
+    
+    for edit in edits {
+        let range = edit.span.range();
+        if let Some(previous) = &last {
+            // Due inserimenti nello stesso punto hanno un ordine ambiguo
+            let same_insertion_point = range.is_empty() && previous.is_empty() && range.start == previous.start;
+            if range.start < previous.end || same_insertion_point {
+                skipped += 1;
+                continue;
+            }
+        }
+        
+        output.push_str(&source[cursor..range.start]);
+        output.push_str(&edit.replacement);
+        cursor = range.end;
+        last = Some(range);
+        applied += 1;
+    }
+    output.push_str(&source[cursor..]);
+    
+    FixResult {
+        source: output,
+        applied,
+        skipped,
+    }
+}
+
+/// Verifica che l'intervallo sia interno al sorgente e cada su confini di carattere
+fn is_valid_range(source: &str, range: std::ops::Range<usize>) -> bool {
+    range.start <= range.end
+        && range.end <= source.len()
+        && source.is_char_boundary(range.start)
+        && source.is_char_boundary(range.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{Position, Span};
+    use crate::utils::diagnostics::Applicability;
+    
+    fn edit(start: usize, end: usize, replacement: &str, applicability: Applicability) -> Suggestion {
+        Suggestion::new(
+            Span::new(Position::new(1, start + 1, start), Position::new(1, end + 1, end)),
+            replacement.to_string(),
+            String::new(),
+            applicability,
+        )
+    }
+    
+    #[test]
+    fn test_apply_non_overlapping() {
+        let source = "if x = 1";
+        let fixes = vec![
+            edit(5, 6, "==", Applicability::MachineApplicable),
+            edit(0, 0, "// ", Applicability::MachineApplicable),
+        ];
+        let result = apply_suggestions(source, &fixes);
+        assert_eq!(result.source, "// if x == 1");
+        assert_eq!(result.applied, 2);
+        assert_eq!(result.skipped, 0);
+    }
+    
+    #[test]
+    fn test_overlapping_edits_are_skipped() {
+        let source = "abcdef";
+        let fixes = vec![
+            edit(1, 4, "X", Applicability::MachineApplicable),
+            edit(2, 5, "Y", Applicability::MachineApplicable),
+            edit(3, 3, "Z", Applicability::MachineApplicable),
+        ];
+        let result = apply_suggestions(source, &fixes);
+        assert_eq!(result.source, "aXef");
+        assert_eq!(result.applied, 1);
+        assert_eq!(result.skipped, 2);
+    }
+    
+    #[test]
+    fn test_only_machine_applicable() {
+        let source = "lte x";
+        let fixes = vec![
+            edit(0, 3, "let", Applicability::MaybeIncorrect),
+            edit(0, 3, "let", Applicability::MachineApplicable),
+            edit(0, 3, "let", Applicability::MachineApplicable),
+        ];
+        let result = apply_suggestions(source, &fixes);
+        assert_eq!(result.source, "let x");
+        assert_eq!(result.applied, 1);
+    }
+}
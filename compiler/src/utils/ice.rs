@@ -0,0 +1,247 @@
+use crate::error::{Error, ErrorKind, Result};
+use serde::Serialize;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::fs;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Contesto della compilazione in corso, usato per descrivere un errore interno
+#[derive(Debug, Clone, Default)]
+struct StageContext {
+    stage: Option<&'static str>,
+    file: Option<PathBuf>,
+}
+
+/// Informazioni raccolte dall'hook di panic prima dell'unwinding
+#[derive(Debug, Clone)]
+struct PanicInfo {
+    context: StageContext,
+    message: String,
+    location: Option<String>,
+    backtrace: String,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<StageContext> = RefCell::new(StageContext::default());
+    static LAST_PANIC: RefCell<Option<PanicInfo>> = const { RefCell::new(None) };
+    /// Numero di chiamate a `catch` attive sul thread
+    static CATCH_DEPTH: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+/// Report scritti dal processo, per distinguere quelli dello stesso secondo
+static REPORT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Guardia che ripristina lo stadio precedente quando viene rilasciata
+pub struct StageGuard {
+    previous: StageContext,
+}
+
+impl Drop for StageGuard {
+    fn drop(&mut self) {
+        let previous = std::mem::take(&mut self.previous);
+        CONTEXT.with(|context| *context.borrow_mut() = previous);
+    }
+}
+
+/// Registra lo stadio della pipeline e il file in elaborazione, finché la
+/// guardia restituita resta in vita
+pub fn enter_stage(stage: &'static str, file: &Path) -> StageGuard {
+    let current = StageContext {
+        stage: Some(stage),
+        file: Some(file.to_path_buf()),
+    };
+    let previous = CONTEXT.with(|context| context.replace(current));
+    StageGuard { previous }
+}
+
+/// Opzioni del gestore degli errori interni
+#[derive(Debug, Clone, Default)]
+pub struct IceOptions {
+    /// Directory in cui scrivere il report da allegare alle segnalazioni di bug
+    pub report_dir: Option<PathBuf>,
+}
+
+/// Report di un errore interno del compilatore
+#[derive(Debug, Serialize)]
+pub struct IceReport {
+    pub version: String,
+    pub flags: Vec<String>,
+    pub stage: Option<String>,
+    pub file: Option<PathBuf>,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    /// Sorgente del file in elaborazione, se ancora leggibile
+    pub source: Option<String>,
+}
+
+/// Esegue `f` convertendo un eventuale panic in un errore interno descritto
+pub fn catch<T>(options: &IceOptions, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    install_hook();
+    let _depth = CatchDepth::enter();
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(_) => Err(report(options)),
+    }
+}
+
+/// Variante di [`catch`] per i comandi asincroni
+pub async fn catch_async<T, F>(options: &IceOptions, future: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    use futures::FutureExt;
+    
+    install_hook();
+    let _depth = CatchDepth::enter();
+    match AssertUnwindSafe(future).catch_unwind().await {
+        Ok(result) => result,
+        Err(_) => Err(report(options)),
+    }
+}
+
+/// Segna il thread come protetto da `catch` finché resta in vita
+struct CatchDepth;
+
+impl CatchDepth {
+    fn enter() -> Self {
+        CATCH_DEPTH.with(|depth| depth.set(depth.get() + 1));
+        CatchDepth
+    }
+}
+
+impl Drop for CatchDepth {
+    fn drop(&mut self) {
+        CATCH_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Installa l'hook di panic che cattura stadio, file e backtrace prima che
+/// l'unwinding li rimuova. I panic fuori da `catch` usano l'hook predefinito.
+fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if CATCH_DEPTH.with(|depth| depth.get()) == 0 {
+                default_hook(info);
+                return;
+            }
+            
+            let message = info
+                .payload()
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| info.payload().downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "panic senza messaggio".to_string());
+            let panic_info = PanicInfo {
+                context: CONTEXT.with(|context| context.borrow().clone()),
+                message,
+                location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+                backtrace: Backtrace::force_capture().to_string(),
+            };
+            LAST_PANIC.with(|last| *last.borrow_mut() = Some(panic_info));
+        }));
+    });
+}
+
+/// Stampa l'errore interno e, se richiesto, scrive il report su disco
+fn report(options: &IceOptions) -> Error {
+    let info = LAST_PANIC.with(|last| last.borrow_mut().take()).unwrap_or_else(|| PanicInfo {
+        context: StageContext::default(),
+        message: "panic senza informazioni".to_string(),
+        location: None,
+        backtrace: String::new(),
+    });
+    
+    let stage = info.context.stage.unwrap_or("sconosciuto");
+    let file = info
+        .context
+        .file
+        .as_ref()
+        .map_or("nessun file".to_string(), |f| f.display().to_string());
+    eprintln!("errore interno del compilatore durante lo stadio '{}' ({}): {}", stage, file, info.message);
+    if let Some(location) = &info.location {
+        eprintln!("  in {}", location);
+    }
+    eprintln!("  questo è un bug di {}, segnalalo allegando il report di --ice-report-dir", crate::version());
+    
+    if let Some(dir) = &options.report_dir {
+        match write_report(dir, &info) {
+            Ok(path) => eprintln!("  report scritto in {}", path.display()),
+            Err(error) => eprintln!("  impossibile scrivere il report: {}", error),
+        }
+    }
+    
+    Error::without_position(
+        ErrorKind::InternalError,
+        format!("panic durante lo stadio '{}' ({}): {}", stage, file, info.message),
+    )
+}
+
+/// Scrive il report JSON con versione, flag e sorgente incriminato
+fn write_report(dir: &Path, info: &PanicInfo) -> Result<PathBuf> {
+    let report = IceReport {
+        version: crate::version(),
+        flags: std::env::args().collect(),
+        stage: info.context.stage.map(str::to_string),
+        file: info.context.file.clone(),
+        message: info.message.clone(),
+        location: info.location.clone(),
+        backtrace: info.backtrace.clone(),
+        source: info.context.file.as_ref().and_then(|f| fs::read_to_string(f).ok()),
+    };
+    
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let count = REPORT_COUNT.fetch_add(1, Ordering::Relaxed);
+    let path = dir.join(format!("nova-ice-{}-{}-{}.json", timestamp, std::process::id(), count));
+    let json = serde_json::to_string_pretty(&report).map_err(|e| {
+        Error::without_position(ErrorKind::InternalError, format!("Serializzazione del report: {}", e))
+    })?;
+    fs::create_dir_all(dir).map_err(|e| Error::io(e, dir))?;
+    fs::write(&path, json).map_err(|e| Error::io(e, &path))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_panic_becomes_internal_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let source_path = dir.path().join("main.ns");
+        fs::write(&source_path, "let x = 1\n").unwrap();
+        let options = IceOptions {
+            report_dir: Some(dir.path().join("reports")),
+        };
+        
+        let result: Result<()> = catch(&options, || {
+            let _stage = enter_stage("parser", &source_path);
+            panic!("nodo inatteso");
+        });
+        
+        let error = result.unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InternalError);
+        assert!(error.message.contains("'parser'"));
+        assert!(error.message.contains("nodo inatteso"));
+        
+        let reports: Vec<_> = fs::read_dir(dir.path().join("reports")).unwrap().collect();
+        assert_eq!(reports.len(), 1);
+        let report = fs::read_to_string(reports[0].as_ref().unwrap().path()).unwrap();
+        assert!(report.contains("let x = 1"));
+        assert!(report.contains(&crate::version()));
+        
+        // Un secondo errore nello stesso secondo non sovrascrive il primo
+        let _: Result<()> = catch(&options, || panic!("altro nodo inatteso"));
+        assert_eq!(fs::read_dir(dir.path().join("reports")).unwrap().count(), 2);
+    }
+}
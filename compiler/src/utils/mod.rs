@@ -0,0 +1,6 @@
+pub mod config;
+pub mod diagnostics;
+pub mod fix;
+pub mod ice;
+pub mod stdlib;
+pub mod suggest;
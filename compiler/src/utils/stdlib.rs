@@ -0,0 +1,100 @@
+/// Moduli della libreria standard con i nomi che esportano
+pub const STANDARD_LIBRARY: &[(&str, &[&str])] = &[
+    ("Core", &[
+        "Option", "Result", "Tuple", "Function", "println", "print", "assert", "panic",
+    ]),
+    ("Math", &[
+        "PI", "sin", "abs", "round", "E", "TAU", "PHI", "ceil", "floor", "trunc", "sign",
+        "exp", "log", "log10", "log2", "pow", "sqrt", "cos", "tan", "asin", "acos", "atan",
+        "atan2", "sinh", "cosh", "tanh", "asinh", "acosh", "atanh", "erf", "gamma", "beta",
+        "random", "randomInt", "randomNormal",
+    ]),
+    ("String", &[
+        "trim", "split", "join", "replace", "toLowerCase", "toUpperCase", "trimStart",
+        "trimEnd", "padStart", "padEnd", "startsWith", "endsWith", "includes", "indexOf",
+        "lastIndexOf", "match", "concat", "slice", "substring", "replaceAll", "format",
+        "template", "encodeURI", "decodeURI", "encodeBase64", "decodeBase64",
+    ]),
+    ("Array", &[
+        "map", "filter", "reduce", "zip", "flatMap", "flat", "fill", "reverse", "reject",
+        "compact", "unique", "reduceRight", "every", "some", "find", "findIndex", "includes",
+        "indexOf", "lastIndexOf", "concat", "zipWith", "unzip", "slice", "chunk", "take",
+        "takeWhile", "drop", "dropWhile", "sort", "sortBy",
+    ]),
+    ("Object", &[
+        "keys", "values", "entries", "merge", "hasKey", "getPath", "map", "mapKeys",
+        "mapValues", "pick", "omit", "deepMerge", "fromEntries", "toJSON", "fromJSON",
+    ]),
+    ("Date", &[
+        "now", "parse", "format", "add", "fromTimestamp", "toISOString", "toLocaleString",
+        "getYear", "getMonth", "getDate", "getHours", "getMinutes", "getSeconds", "subtract",
+        "setYear", "setMonth", "setDate", "isBefore", "isAfter", "isEqual", "diff",
+    ]),
+    ("RegExp", &[
+        "match", "test", "replace", "split", "matchAll", "replaceAll", "compile", "escape",
+    ]),
+    ("IO", &[
+        "readFile", "writeFile", "exists", "appendFile", "remove", "rename", "readDir",
+        "createDir", "removeDir", "watchDir", "joinPath", "resolvePath", "dirname", "basename",
+        "extname",
+    ]),
+    ("Collections", &[
+        "Map", "Set",
+    ]),
+    ("Http", &[
+        "get", "post", "Response", "put", "delete", "head", "options", "patch", "fetch",
+        "createRequest", "abortController",
+    ]),
+    ("Process", &[
+        "env", "args", "exit", "cwd", "pid", "kill", "spawn", "exec", "platform", "arch",
+        "uptime", "memoryUsage",
+    ]),
+    ("Path", &[
+        "join", "resolve", "dirname", "basename", "extname", "normalize", "relative",
+        "isAbsolute", "parse", "format", "sep", "delimiter", "toNamespacedPath",
+    ]),
+    ("Fs", &[
+        "readDir", "stat", "mkdir", "writeFile", "readFile", "appendFile", "copyFile",
+        "remove", "rename", "rmdir", "move", "exists", "access", "chmod", "chown",
+        "createReadStream", "createWriteStream", "pipeline", "watch", "watchFile",
+        "unwatchFile",
+    ]),
+    ("Net", &[
+        "createServer", "connect", "listen", "close", "createConnection", "write", "end",
+        "pause", "resume", "setTimeout", "on", "once", "off",
+    ]),
+    ("Url", &[
+        "parse", "format", "resolve", "URLSearchParams", "parseQuery", "formatQuery",
+        "normalize", "isAbsolute", "URL",
+    ]),
+    ("Crypto", &[
+        "hash", "hmac", "encrypt", "decrypt", "randomBytes", "pbkdf2", "createCipher",
+        "createDecipher", "randomUUID", "timingSafeEqual", "createSign", "createVerify",
+        "createDiffieHellman",
+    ]),
+    ("Zlib", &[
+        "compress", "decompress", "gzip", "gunzip", "deflate", "inflate", "brotliCompress",
+        "brotliDecompress", "createCompressStream", "createDecompressStream",
+    ]),
+    ("Events", &[
+        "EventEmitter", "on", "once", "off", "emit", "eventNames", "listenerCount",
+        "createEventTarget", "fromDOMEvents",
+    ]),
+    ("Stream", &[
+        "Readable", "Writable", "Transform", "pipeline", "Duplex", "finished", "compose",
+    ]),
+    ("Dom", &[
+        "querySelector", "createElement", "append", "querySelectorAll", "getElementById",
+        "createTextNode", "createDocumentFragment", "prepend", "remove", "replace",
+        "setAttribute", "parent", "children", "siblings", "closest", "addEventListener",
+        "removeEventListener", "dispatchEvent",
+    ]),
+];
+
+/// Restituisce i moduli della libreria standard che esportano `name`
+pub fn modules_exporting(name: &str) -> impl Iterator<Item = &'static str> + '_ {
+    STANDARD_LIBRARY
+        .iter()
+        .filter(move |(_, exports)| exports.contains(&name))
+        .map(|(module, _)| *module)
+}
@@ -0,0 +1,349 @@
+use crate::ast::{Program, StmtKind};
+use crate::error::{Error, ErrorKind};
+use crate::lexer::{Position, Span, KEYWORDS};
+use crate::utils::diagnostics::{Applicability, Suggestion};
+use crate::utils::stdlib::STANDARD_LIBRARY;
+
+/// Distanza di modifica tra due stringhe (Damerau-Levenshtein ristretta):
+/// inserimenti, cancellazioni, sostituzioni e scambi di caratteri adiacenti
+/// costano 1 ciascuno.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    
+    rows[a.len()][b.len()]
+}
+
+/// Distanza massima accettata per considerare un nome un refuso di un altro.
+/// Resta sempre minore della lunghezza del nome: altrimenti un nome di un solo
+/// carattere sarebbe il refuso di qualunque altro nome di un carattere.
+pub fn max_distance(name: &str) -> usize {
+    let length = name.chars().count();
+    (length / 3).max(1).min(length.saturating_sub(1))
+}
+
+/// Restituisce i candidati più vicini a `name`, a pari distanza minima,
+/// insieme alla distanza stessa
+pub fn closest<'a, I>(name: &str, candidates: I) -> Option<(Vec<&'a str>, usize)>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let limit = max_distance(name);
+    let mut best: Option<(Vec<&'a str>, usize)> = None;
+    
+    for candidate in candidates {
+        let distance = edit_distance(name, candidate);
+        if distance > limit {
+            continue;
+        }
+        match &mut best {
+            Some((matches, best_distance)) if distance == *best_distance => {
+                if !matches.contains(&candidate) {
+                    matches.push(candidate);
+                }
+            },
+            Some((_, best_distance)) if distance > *best_distance => {},
+            _ => best = Some((vec![candidate], distance)),
+        }
+    }
+    
+    best
+}
+
+/// Provenienza di un nome proposto come alternativa
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CandidateSource {
+    /// Nome visibile nello scope corrente
+    Scope,
+    /// Parola chiave del linguaggio
+    Keyword,
+    /// Nome esportato da un modulo non ancora importato
+    Module(String),
+}
+
+/// Nome noto che può essere proposto come alternativa
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub name: String,
+    pub source: CandidateSource,
+}
+
+/// Insieme dei nomi noti da cui proporre alternative a un nome sconosciuto
+#[derive(Debug, Default)]
+pub struct NameSuggester {
+    candidates: Vec<Candidate>,
+}
+
+impl NameSuggester {
+    /// Crea un insieme vuoto
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Crea un insieme con le parole chiave e la libreria standard
+    pub fn with_defaults() -> Self {
+        let mut suggester = Self::new();
+        suggester.add_keywords();
+        for (module, exports) in STANDARD_LIBRARY {
+            suggester.add_module(module, exports.iter().copied());
+        }
+        suggester
+    }
+    
+    /// Aggiunge i nomi visibili nello scope corrente
+    pub fn add_scope_names<'a, I>(&mut self, names: I)
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        for name in names {
+            self.push(name, CandidateSource::Scope);
+        }
+    }
+    
+    /// Aggiunge le parole chiave del linguaggio
+    pub fn add_keywords(&mut self) {
+        for (keyword, _) in KEYWORDS {
+            self.push(keyword, CandidateSource::Keyword);
+        }
+    }
+    
+    /// Aggiunge i nomi esportati da un modulo
+    pub fn add_module<'a, I>(&mut self, module: &str, exports: I)
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        for name in exports {
+            self.push(name, CandidateSource::Module(module.to_string()));
+        }
+    }
+    
+    fn push(&mut self, name: &str, source: CandidateSource) {
+        let candidate = Candidate {
+            name: name.to_string(),
+            source,
+        };
+        if !self.candidates.contains(&candidate) {
+            self.candidates.push(candidate);
+        }
+    }
+    
+    /// Verifica se il nome è già visibile nello scope
+    fn in_scope(&self, name: &str) -> bool {
+        self.candidates
+            .iter()
+            .any(|c| c.source == CandidateSource::Scope && c.name == name)
+    }
+    
+    /// Moduli che esportano esattamente `name`
+    pub fn exporting_modules(&self, name: &str) -> Vec<&str> {
+        self.candidates
+            .iter()
+            .filter(|c| c.name == name)
+            .filter_map(|c| match &c.source {
+                CandidateSource::Module(module) => Some(module.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+    
+    /// Candidati più vicini a `name`, ordinati per provenienza: prima i nomi
+    /// nello scope, poi le parole chiave, infine gli export dei moduli. Gli
+    /// export di nomi già visibili nello scope non vengono proposti.
+    pub fn similar(&self, name: &str) -> Vec<&Candidate> {
+        let names = self
+            .candidates
+            .iter()
+            .filter(|c| c.name != name)
+            .map(|c| c.name.as_str());
+        let Some((matches, _)) = closest(name, names) else {
+            return Vec::new();
+        };
+        
+        let mut similar: Vec<&Candidate> = self
+            .candidates
+            .iter()
+            .filter(|c| matches.contains(&c.name.as_str()))
+            .filter(|c| c.source == CandidateSource::Scope || !self.in_scope(&c.name))
+            .collect();
+        similar.sort_by(|a, b| a.source.cmp(&b.source));
+        similar
+    }
+    
+    /// Crea l'errore per un nome non definito, con le alternative possibili.
+    /// `import_at` è il punto in cui inserire un eventuale nuovo `import`.
+    pub fn undefined_symbol(&self, name: &str, span: Span, import_at: Position) -> Error {
+        let modules = self.exporting_modules(name);
+        if !modules.is_empty() && !self.in_scope(name) {
+            // Il nome esiste ma va importato: un solo modulo rende la correzione sicura
+            let applicability = if modules.len() == 1 {
+                Applicability::MachineApplicable
+            } else {
+                Applicability::MaybeIncorrect
+            };
+            let mut error = Error::new(
+                ErrorKind::UndefinedSymbol,
+                format!("`{}` non trovato; è esportato da {}", name, modules.join(", ")),
+                span,
+            );
+            for module in modules {
+                error = error.with_suggestion(import_suggestion(name, module, import_at, applicability));
+            }
+            return error;
+        }
+        
+        let similar = self.similar(name);
+        let Some(best) = similar.first() else {
+            return Error::new(
+                ErrorKind::UndefinedSymbol,
+                format!("`{}` non trovato", name),
+                span,
+            );
+        };
+        
+        let hint = match &best.source {
+            CandidateSource::Scope => format!("forse intendevi `{}`?", best.name),
+            CandidateSource::Keyword => format!("forse intendevi la parola chiave `{}`?", best.name),
+            CandidateSource::Module(module) => format!("forse intendevi `{}` da {}?", best.name, module),
+        };
+        let mut error = Error::new(
+            ErrorKind::UndefinedSymbol,
+            format!("`{}` non trovato; {}", name, hint),
+            span,
+        );
+        
+        for candidate in similar {
+            error = error.with_suggestion(Suggestion::new(
+                span,
+                candidate.name.clone(),
+                format!("sostituisci con `{}`", candidate.name),
+                Applicability::MaybeIncorrect,
+            ));
+            if let CandidateSource::Module(module) = &candidate.source {
+                error = error.with_suggestion(import_suggestion(
+                    &candidate.name,
+                    module,
+                    import_at,
+                    Applicability::MaybeIncorrect,
+                ));
+            }
+        }
+        
+        error
+    }
+}
+
+/// Correzione che aggiunge l'import di `name` da `module`
+fn import_suggestion(name: &str, module: &str, at: Position, applicability: Applicability) -> Suggestion {
+    Suggestion::new(
+        Span::point(at),
+        format!("import {{ {} }} from \"{}\"\n", name, module),
+        format!("importa `{}` da {}", name, module),
+        applicability,
+    )
+}
+
+/// Punto in cui inserire un nuovo `import`: prima della prima istruzione che
+/// non è un import, cioè subito dopo gli import con cui inizia il file
+pub fn import_position(program: &Program) -> Position {
+    program
+        .body
+        .iter()
+        .find(|stmt| !matches!(stmt.kind, StmtKind::Import(_)))
+        .map_or(program.span.end, |stmt| stmt.span.start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("let", "let"), 0);
+        assert_eq!(edit_distance("lte", "let"), 1);
+        assert_eq!(edit_distance("fucntion", "function"), 1);
+        assert_eq!(edit_distance("retrun", "return"), 1);
+        assert_eq!(edit_distance("whle", "while"), 1);
+        assert_eq!(edit_distance("", "fn"), 2);
+    }
+    
+    #[test]
+    fn test_closest() {
+        let candidates = ["let", "for", "fn", "while"];
+        assert_eq!(closest("lte", candidates), Some((vec!["let"], 1)));
+        assert_eq!(closest("fo", candidates), Some((vec!["for", "fn"], 1)));
+        assert_eq!(closest("xyz", candidates), None);
+    }
+    
+    #[test]
+    fn test_short_names_are_not_typos() {
+        assert_eq!(closest("x", ["s", "y", "E"]), None);
+        assert_eq!(closest("x", ["x"]), Some((vec!["x"], 0)));
+        assert_eq!(closest("xs", ["x", "ys"]), Some((vec!["x", "ys"], 1)));
+        
+        let suggester = NameSuggester::with_defaults();
+        let span = Span::point(Position::new(1, 1, 0));
+        assert_eq!(suggester.undefined_symbol("A", span, Position::new(1, 1, 0)).message, "`A` non trovato");
+    }
+    
+    #[test]
+    fn test_undefined_symbol_from_module() {
+        let suggester = NameSuggester::with_defaults();
+        let span = Span::point(Position::new(1, 1, 0));
+        let error = suggester.undefined_symbol("pritnln", span, Position::new(1, 1, 0));
+        
+        assert_eq!(error.kind, ErrorKind::UndefinedSymbol);
+        assert_eq!(error.message, "`pritnln` non trovato; forse intendevi `println` da Core?");
+        assert_eq!(error.suggestions.len(), 2);
+        assert_eq!(error.suggestions[1].replacement, "import { println } from \"Core\"\n");
+    }
+    
+    #[test]
+    fn test_undefined_symbol_prefers_scope() {
+        let mut suggester = NameSuggester::with_defaults();
+        suggester.add_scope_names(["println", "total"]);
+        let span = Span::point(Position::new(1, 1, 0));
+        
+        let error = suggester.undefined_symbol("pritnln", span, Position::new(1, 1, 0));
+        assert_eq!(error.message, "`pritnln` non trovato; forse intendevi `println`?");
+        
+        let error = suggester.undefined_symbol("retrun", span, Position::new(1, 1, 0));
+        assert_eq!(error.message, "`retrun` non trovato; forse intendevi la parola chiave `return`?");
+    }
+    
+    #[test]
+    fn test_auto_import() {
+        let source = "import { PI } from \"Math\"\nlet x = sqrt(PI)\n";
+        let tokens = crate::lexer::Lexer::new(source).tokenize().unwrap();
+        let at = import_position(&crate::parser::parse(source).unwrap());
+        assert_eq!((at.line, at.column), (2, 1));
+        
+        let suggester = NameSuggester::with_defaults();
+        let error = suggester.undefined_symbol("sqrt", tokens[10].span, at);
+        assert_eq!(error.suggestions.len(), 1);
+        assert!(error.suggestions[0].is_machine_applicable());
+        
+        let fixed = crate::utils::fix::apply_suggestions(source, &error.suggestions).source;
+        assert_eq!(fixed, "import { PI } from \"Math\"\nimport { sqrt } from \"Math\"\nlet x = sqrt(PI)\n");
+    }
+}